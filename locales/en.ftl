@@ -0,0 +1,331 @@
+# English messages of uniws-rs, the ones every other language falls back to.
+# A translation is a copy of this file in the locales folder of the config
+# directory, e.g. ~/.config/uniws/locales/de.ftl, with the values changed.
+
+language-name = English
+
+## Config bar
+
+game-dir-placeholder = Game file directory
+load-config = Load config
+add-config = Add config
+config-url-placeholder = Config URL...
+load-url = Load from URL
+check-updates = Check for updates
+roll-back = Roll back
+validate = Validate
+strict-loading = Strict loading
+theme-light = Light
+theme-dark = Dark
+theme-system = Follow system
+theme-high-contrast = High contrast
+scale = { $percent }%
+broken-themes =
+    Some themes could not be loaded:
+
+    { $errors }
+encoding-auto = Auto
+duplicates-last = Last duplicate wins
+duplicates-first = First duplicate wins
+duplicates-error = Duplicates are errors
+panel-details = Details
+panel-backups = Backups
+panel-custom = Custom patch
+panel-signature = Signature builder
+panel-hex = Hex viewer
+panel-editor = Edit game
+panel-diff = Diff
+
+## Command palette
+
+palette-placeholder = Type a game or command...
+palette-apply = Patch the game
+palette-dry-run = Dry run: preview the patch
+palette-open-backups = Open backups folder
+palette-game = Game
+palette-command = Command
+
+## Log panel
+
+log-show = Show log ({ $count })
+log-hide = Hide log
+log-empty = Nothing has happened yet
+log-config-loaded = Loaded { $count } game(s) from { $files }
+log-config-failed = Couldn't load the config: { $error }
+log-scanned = Scanned { $file }, { $count } bytes
+log-backup = Backed up { $count } file(s) to { $dir }
+log-signature = { $count } match(es) in { $file }: { $offsets }
+log-candidates = Scanned { $file } for candidates, found { $count }
+
+## Crashes
+
+crash-report =
+    uniws-rs ran into a bug and has to close. A report of what happened was saved to
+
+    { $path }
+
+    Please attach it to a bug report
+crash-no-report =
+    uniws-rs ran into a bug and has to close, and the report of what happened couldn't be saved:
+
+    { $report }
+
+## Keyboard shortcuts
+
+shortcuts = Keyboard shortcuts
+shortcut-next-field = Next field
+shortcut-previous-field = Previous field
+shortcut-game-dir = Choose the game directory
+shortcut-apply = Patch the game, when the patch button can be pressed
+shortcut-help = Show or hide this list
+shortcut-palette = Search games and commands
+shortcut-close = Close this list or the search
+close = Close
+
+## File dialogs
+
+filter-config = Config file
+filter-bundle = Undo bundle
+filter-text = Text file
+load-config-title = Load config file
+add-config-title = Add config files
+export-bundle-title = Export undo bundle
+restore-bundle-title = Restore from undo bundle
+export-diff-title = Export diff
+derive-title = Open patched copy
+check-binary-title = Open binary to check against
+
+## Updates of a downloaded config
+
+update-available = Version { $version } of the config is available
+update-available-from = Version { $version } of the config is available, you have { $from }
+update-adds = Adds: { $games }
+update-more = and { $count } more
+update = Update
+dismiss = Dismiss
+sync-up-to-date = The downloaded config is up to date at version { $version }
+sync-updated = Updated the downloaded config to version { $to }
+sync-updated-from = Updated the downloaded config from version { $from } to version { $to }
+
+## Game list and details
+
+search-placeholder = Search games...
+sort-config = Config order
+sort-alphabetical = A to Z
+sort-recent = Recently patched
+recent-game = Recent: { $name }
+all-configs = All
+built-in-config = Built in config
+detected-games = Found in this folder:
+details-source = From { $path }
+image-loading = Loading image...
+image-unsupported = Images can't be shown without the images feature
+detect-none =
+    None of the { $count } games in the config were found in { $dir }
+
+    Games are recognised by their checkfile, which must be in the game directory
+detect-no-config = Load a config to find which game is in { $dir }
+skipped-games =
+    Some games could not be loaded and were skipped:
+
+    { $games }
+skipped-lines =
+    Some lines could not be read and were skipped:
+
+    { $lines }
+confirm-merge =
+    Merge the games of the dropped config into the loaded one?
+
+    Choosing no replaces the loaded config
+merged = Merged { $count } game(s) into the config
+merged-replacing =
+    Merged { $count } game(s) into the config, replacing:
+
+    { $games }
+
+## Resolution
+
+width = Width:
+height = Height:
+width-placeholder = Width...
+height-placeholder = Height...
+lock-ratio = Lock ratio
+current-resolution = Use current resolution
+display-placeholder = Display...
+display-unavailable = Display detection unavailable
+preset-placeholder = Preset...
+side-width = width
+side-height = height
+resolution-invalid = A { $side } of { $value } isn't a real resolution
+resolution-max = This game supports a { $side } of at most { $max }
+resolution-odd = An odd { $side } may crash this engine
+resolution-swapped = Width is less than height, check they aren't swapped
+
+## Patching
+
+preview = Preview
+apply-patch = Patch { $file }
+apply-custom-patch = Apply custom patch
+patch-applied =
+    Patch applied successfully
+
+    { $report }
+patch-failed = Patch failed to apply: { $error }
+patch-failed-empty = Patch failed to apply
+patch-failed-report =
+    Patch failed to apply, no files were changed
+
+    { $report }
+preview-failed = Couldn't preview patch: { $error }
+preview-failed-report =
+    Couldn't preview patch
+
+    { $report }
+preview-site = patch { $patch } ({ $file }): match at { $offset }
+preview-written = Written to disk, old bytes on the left and new on the right
+preview-dry-run = Dry run, nothing has been written yet
+preview-export = Export diff...
+preview-empty = Nothing to write
+
+## Backups
+
+central-backups = Keep backups in user data folder
+export-bundle = Export undo bundle
+restore-bundle = Restore from bundle
+restore-everything = Restore everything
+backup-file = File
+backup-size = Size
+backup-modified = Modified
+backup-patches = Patches
+bytes = { $count } bytes
+restore = Restore
+delete = Delete
+no-backups = No backups found for this game directory
+clean-backups = Clean old backups
+retention-keep-last = Keep last { $count }
+retention-newest = Keep newest per file
+retention-prompt = Ask for each backup
+confirm-clean = Remove backup of { $game } ({ $dir })?
+cleaned = Removed { $count } old backup(s)
+backup-restored = Restored { $file }
+bundle-restored =
+    Restored:
+    { $files }
+
+## Custom patch
+
+open-share-link = Open link from clipboard
+copy-share-link = Copy share link
+
+## Signature builder
+
+signature-pattern = Pattern
+signature-file = File
+signature-parsed = { $count } byte(s): { $signature }
+signature-derived = Derived config section
+signature-no-candidates = No default resolutions found
+signature-candidates = { $count } candidate(s), pick one to start a pattern from
+signature-scan = Scan for candidates
+signature-derive = Derive from patched copy...
+signature-test = Test
+signature-save = Save to custom patch
+no-matches = No matches
+matches = { $count } match(es)
+copy = Copy
+
+## Hex viewer
+
+hex-file = File
+open = Open
+hex-goto-placeholder = Offset, e.g. 0x1000 or 50%
+hex-goto = Go
+hex-search-placeholder = Pattern, e.g. 80 02 ?? ??
+hex-find = Find
+hex-next = Next
+hex-position = { $offset } of { $len }
+hex-page-up = Page up
+hex-up = Up
+hex-down = Down
+hex-page-down = Page down
+hex-expert = Expert mode: edit bytes
+hex-byte = Byte
+hex-saved = Saved edits to { $file }
+save = Save
+
+## Game editor
+
+editor-name = Name
+editor-name-placeholder = Game name
+editor-one-match = 1 match
+editor-matches = { $count } matches
+editor-edit-set = Edit set
+editor-edit-set-numbered = Edit set { $prefix }
+editor-no-binary = No binary chosen
+editor-new = New game
+editor-edit-selected = Edit selected
+editor-add-set = Add edit set
+editor-check = Check against...
+remove = Remove
+
+## Errors
+
+error-file = File error: { $error }
+error-encoding = the text isn't valid { $encoding }
+error-syntax-at = Syntax error at line { $line }, column { $column }
+error-syntax-near = { " " }near `{ $near }`
+error-syntax-in = { " " }in [{ $section }]
+error-syntax = Syntax error: { $message }
+error-duplicate-key = { $key } in [{ $section }] is given again at line { $line }, first at line { $first_line }
+error-duplicate-section = [{ $section }] is given again at line { $line }, first at line { $first_line }
+error-missing-field = [{ $section }] is missing required field '{ $field }'
+error-invalid-field = [{ $section }] field '{ $field }' is invalid: { $message }
+error-missing-section = Section [{ $section }] is missing
+error-unknown-field = [{ $section }] has unknown field '{ $field }'
+error-context-section = [{ $section }]
+error-context-patch = patch { $patch }
+error-context-match = match { $occurrence } at { $offset }
+error-signature-not-found = signature not found
+error-too-few = found { $found } occurrence(s) of the signature, expected { $requested }
+error-too-many = found { $found } occurrence(s) of the signature, expected only { $requested }
+error-out-of-bounds = writing { $len } byte(s) at { $offset } would go past the end of the file ({ $file_len } bytes)
+error-before-start = offset { $offset } from the match at { $index } is before the start of the file
+error-expect-mismatch = found { $found } at { $offset } rather than the bytes in expect
+error-text-not-found = no line starts with "{ $find }"
+error-value-out-of-range = { $value } doesn't fit in { $len } byte(s)
+error-checksum = Checksum mismatch for { $file }
+error-backup = Backup failed, nothing was patched: { $error }
+error-verify = { $file } doesn't contain the patched data after writing
+error-invalid-link = Invalid share link: { $message }
+error-download = Download failed: { $message }
+error-no-config = No config to load
+error-no-config-dir = No folder to save configs in
+error-no-game = No game selected
+error-no-game-dir = No game directory selected
+error-missing-game-dir = Missing game dir
+error-missing-width = Missing width
+error-missing-height = Missing height
+error-missing-value = Missing { $name }
+error-no-display = Couldn't find the resolution of the display
+error-display-unavailable = Displays can't be detected on this system, enter the resolution instead
+error-not-string = must be a string
+error-theme-base = { $value } isn't dark or light
+error-theme-colour = { $value } isn't a #rrggbb colour
+error-no-locales = No folder to load languages from
+error-cli-apply = Can't patch with the values given, check the game, folder and resolution
+error-open = Couldn't open { $file }: { $error }
+error-open-link = Couldn't open { $url }: { $error }
+error-load-image = Couldn't load the image: { $error }
+error-save-game = Couldn't save game: { $error }
+error-edit-game = Couldn't open game: { $error }
+error-hex-save = Failed to save edits: { $error }
+error-export-diff = Failed to export diff: { $error }
+error-export-bundle = Failed to export undo bundle: { $error }
+error-restore-bundle = Failed to restore undo bundle: { $error }
+error-clean = Failed to clean backups: { $error }
+error-restore-backup = Failed to restore { $file }: { $error }
+error-delete-backup = Failed to delete { $file }: { $error }
+error-test-signature = Couldn't test signature: { $error }
+error-scan = Couldn't scan file: { $error }
+error-derive = Couldn't derive signature: { $error }
+error-save-signature = Couldn't save signature: { $error }
+error-share-link = Couldn't open share link: { $error }
@@ -0,0 +1,102 @@
+use std::{collections::VecDeque, time::SystemTime};
+
+use crate::model::Level;
+
+/// Most entries kept, the oldest are dropped after
+const KEPT: usize = 1000;
+
+/// Something the app did or told the user
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Entry {
+    pub time: SystemTime,
+    pub level: Level,
+    pub message: String,
+}
+impl std::fmt::Display for Entry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let level = match self.level {
+            Level::Info => "INFO",
+            Level::Warning => "WARN",
+            Level::Error => "ERROR",
+        };
+        write!(
+            f,
+            "{} {level:<5} {}",
+            humantime::format_rfc3339_seconds(self.time),
+            self.message
+        )
+    }
+}
+
+/// What's happened this session, for the log panel
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct Log {
+    entries: VecDeque<Entry>,
+}
+impl Log {
+    /// Add `message`, and write it to the log file, see [`crate::logging`]
+    pub fn push(&mut self, level: Level, message: impl Into<String>) {
+        let message = message.into();
+        match level {
+            Level::Info => tracing::info!("{message}"),
+            Level::Warning => tracing::warn!("{message}"),
+            Level::Error => tracing::error!("{message}"),
+        }
+        if self.entries.len() == KEPT {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(Entry {
+            time: SystemTime::now(),
+            level,
+            message,
+        });
+    }
+
+    /// Oldest first
+    pub fn entries(&self) -> impl DoubleEndedIterator<Item = &Entry> + ExactSizeIterator {
+        self.entries.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+/// Every entry on a line of its own, as it's copied
+impl std::fmt::Display for Log {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for entry in &self.entries {
+            writeln!(f, "{entry}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn log_test() {
+        let mut log = Log::default();
+        log.push(Level::Info, "Loaded config");
+        log.push(Level::Error, "Patch failed to apply");
+        let text = log.to_string();
+        let lines = text.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].ends_with("Z INFO  Loaded config"), "{text}");
+        assert!(
+            lines[1].ends_with("Z ERROR Patch failed to apply"),
+            "{text}"
+        );
+
+        for i in 0..KEPT {
+            log.push(Level::Info, i.to_string());
+        }
+        assert_eq!(log.len(), KEPT);
+        assert_eq!(log.entries().next().unwrap().message, "0");
+    }
+}
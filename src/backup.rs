@@ -0,0 +1,946 @@
+use std::{
+    collections::HashMap,
+    fmt::Write as _,
+    fs::File,
+    io::Read as _,
+    path::{Component, Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use sha2::{Digest, Sha256};
+
+use crate::{
+    config::{self, Items},
+    error::Error,
+    registry::{self, RawValue, RegView, RegistryBackup, RegistryPatch},
+    wine,
+};
+
+/// Where the original copies of patched files are kept
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum BackupMode {
+    /// `<modfile>.undo` next to the patched file
+    #[default]
+    GameDirectory,
+    /// Timestamped folder in the per-user data directory, described by a manifest
+    Central,
+}
+
+pub const MANIFEST_NAME: &str = "manifest.ini";
+pub const LOG_NAME: &str = "patch.log";
+/// Manifest of the registry values replaced by a game directory backup
+pub const REGISTRY_UNDO_NAME: &str = "registry.undo.ini";
+
+/// `~/.local/share/uniws/backups` (or the platform equivalent)
+pub fn backup_root() -> Option<PathBuf> {
+    dirs::data_dir().map(|x| x.join("uniws").join("backups"))
+}
+
+pub fn sha256_hex(data: &[u8]) -> String {
+    hex(&Sha256::digest(data))
+}
+
+/// `data` like `01a0ff`
+fn hex(data: &[u8]) -> String {
+    data.iter().fold(String::new(), |mut s, b| {
+        let _ = write!(s, "{b:02x}");
+        s
+    })
+}
+
+/// Strip characters that aren't valid in a directory name on some platform
+pub fn sanitize_name(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            '<' | '>' | ':' | '"' | '/' | '\\' | '|' | '?' | '*' => '_',
+            c => c,
+        })
+        .collect::<String>()
+        .trim_end_matches(['.', ' '])
+        .to_string()
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ManifestFile {
+    /// Full path of the file that was patched
+    pub source: PathBuf,
+    /// Path of the copy, relative to the backup directory
+    pub backup: String,
+    /// SHA-256 of the original (unpatched) contents
+    pub sha256: String,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Manifest {
+    pub game: String,
+    pub game_dir: PathBuf,
+    pub timestamp: u64,
+    pub width: u16,
+    pub height: u16,
+    pub files: Vec<ManifestFile>,
+    /// Registry values replaced by the patch
+    pub registry: Vec<RegistryBackup>,
+}
+impl Manifest {
+    pub fn to_ini(&self) -> String {
+        let mut out = String::new();
+
+        let _ = writeln!(out, "[Backup]");
+        let _ = writeln!(out, "game={}", config::quote(&self.game));
+        let _ = writeln!(
+            out,
+            "gamedir={}",
+            config::quote(&self.game_dir.display().to_string())
+        );
+        let _ = writeln!(out, "timestamp={}", self.timestamp);
+        let _ = writeln!(out, "width={}", self.width);
+        let _ = writeln!(out, "height={}", self.height);
+
+        for (i, file) in self.files.iter().enumerate() {
+            let _ = writeln!(out);
+            let _ = writeln!(out, "[File{i}]");
+            let _ = writeln!(
+                out,
+                "source={}",
+                config::quote(&file.source.display().to_string())
+            );
+            let _ = writeln!(out, "backup={}", config::quote(&file.backup));
+            let _ = writeln!(out, "sha256={}", file.sha256);
+        }
+
+        for (i, value) in self.registry.iter().enumerate() {
+            let _ = writeln!(out);
+            let _ = writeln!(out, "[Registry{i}]");
+            let _ = writeln!(out, "key={}", config::quote(&value.key));
+            let _ = writeln!(out, "value={}", config::quote(&value.value));
+            let _ = writeln!(out, "view={}", value.view);
+            if let Some(raw) = &value.previous {
+                let _ = writeln!(out, "type={}", raw.type_name());
+                let _ = writeln!(out, "bytes={}", hex(&raw.bytes));
+            }
+        }
+
+        out
+    }
+
+    pub fn from_ini(input: &str) -> Result<Self, Error> {
+        fn field<'a>(
+            section: &str,
+            items: &'a Items,
+            name: &'static str,
+        ) -> Result<&'a String, Error> {
+            items
+                .get(name)
+                .ok_or(Error::config_missing_field(section, name))
+        }
+
+        fn number<T: std::str::FromStr>(
+            section: &str,
+            items: &Items,
+            name: &'static str,
+        ) -> Result<T, Error> {
+            field(section, items, name)?
+                .parse()
+                .map_err(|_| Error::config_field_parse(section, name, "Invalid number"))
+        }
+
+        let raw = config::parse(input)?;
+        let backup = raw
+            .get("Backup")
+            .ok_or(Error::config_missing_section("Backup"))?;
+
+        let mut files = Vec::new();
+        while let Some(items) = raw.get(&format!("File{}", files.len())) {
+            let section = format!("File{}", files.len());
+            files.push(ManifestFile {
+                source: field(&section, items, "source")?.into(),
+                backup: field(&section, items, "backup")?.clone(),
+                sha256: field(&section, items, "sha256")?.clone(),
+            });
+        }
+
+        let mut registry = Vec::new();
+        while let Some(items) = raw.get(&format!("Registry{}", registry.len())) {
+            let section = format!("Registry{}", registry.len());
+            let invalid = |field| Error::config_field_parse(&section, field, "Invalid value");
+            // Older manifests were written by `reg.exe`, which uses the 64-bit
+            // registry, and kept the data as it printed it
+            let view = match items.get("view") {
+                Some(x) => x.parse().map_err(|e: Error| {
+                    Error::config_field_parse(&section, "view", e.to_string())
+                })?,
+                None => RegView::Bits64,
+            };
+            let previous = match (items.get("type"), items.get("bytes")) {
+                (None, _) => None,
+                (Some(regtype), Some(bytes)) => Some(RawValue {
+                    regtype: RawValue::parse_type(regtype).ok_or_else(|| invalid("type"))?,
+                    bytes: registry::parse_hex(bytes).ok_or_else(|| invalid("bytes"))?,
+                }),
+                (Some(regtype), None) => Some(
+                    RawValue::from_text(regtype, field(&section, items, "data")?)
+                        .ok_or_else(|| invalid("data"))?,
+                ),
+            };
+            registry.push(RegistryBackup {
+                key: field(&section, items, "key")?.clone(),
+                value: field(&section, items, "value")?.clone(),
+                view,
+                previous,
+            });
+        }
+
+        Ok(Self {
+            game: field("Backup", backup, "game")?.clone(),
+            game_dir: field("Backup", backup, "gamedir")?.into(),
+            timestamp: number("Backup", backup, "timestamp")?,
+            width: number("Backup", backup, "width")?,
+            height: number("Backup", backup, "height")?,
+            files,
+            registry,
+        })
+    }
+}
+
+/// The result of backing up a game's files before patching
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct BackupRecord {
+    /// Directory that [`ManifestFile::backup`] paths are relative to
+    pub dir: PathBuf,
+    pub manifest: Manifest,
+    pub log: String,
+}
+impl BackupRecord {
+    /// Attach a description of the applied patches
+    ///
+    /// Central backups also keep it on disk next to the manifest
+    pub fn set_log(&mut self, mode: BackupMode, log: String) -> Result<(), Error> {
+        if mode == BackupMode::Central {
+            std::fs::write(self.dir.join(LOG_NAME), &log)?;
+        }
+        self.log = log;
+
+        Ok(())
+    }
+
+    /// Record the registry values about to be replaced
+    ///
+    /// Like undo files, a game directory backup keeps the very first values
+    pub fn set_registry(
+        &mut self,
+        mode: BackupMode,
+        registry: Vec<RegistryBackup>,
+    ) -> Result<(), Error> {
+        self.manifest.registry = registry;
+        if self.manifest.registry.is_empty() {
+            return Ok(());
+        }
+
+        let path = match mode {
+            BackupMode::GameDirectory => self.dir.join(REGISTRY_UNDO_NAME),
+            BackupMode::Central => self.dir.join(MANIFEST_NAME),
+        };
+        if mode == BackupMode::Central || !path.exists() {
+            std::fs::write(path, self.manifest.to_ini())?;
+        }
+
+        Ok(())
+    }
+
+    /// Write the undo files, manifest and patch log into a single tar archive
+    pub fn export_bundle(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        fn append(builder: &mut tar::Builder<File>, name: &str, data: &[u8]) -> Result<(), Error> {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as u64);
+            header.set_mode(0o644);
+            header.set_mtime(unix_time());
+            builder.append_data(&mut header, name, data)?;
+            Ok(())
+        }
+
+        let mut builder = tar::Builder::new(File::create(path)?);
+        let mut manifest = self.manifest.clone();
+
+        for file in &mut manifest.files {
+            let data = std::fs::read(self.dir.join(&file.backup))?;
+            file.backup = sanitize_name(&file.backup);
+            append(&mut builder, &file.backup, &data)?;
+        }
+
+        append(&mut builder, MANIFEST_NAME, manifest.to_ini().as_bytes())?;
+        append(&mut builder, LOG_NAME, self.log.as_bytes())?;
+
+        builder.into_inner()?;
+        Ok(())
+    }
+}
+
+fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|x| x.as_secs())
+        .unwrap_or_default()
+}
+
+/// New folder in `parent` named after `timestamp`, with `-1`, `-2`... after
+/// it if there's already a backup from the same second
+fn new_dir(parent: &Path, timestamp: u64) -> std::io::Result<PathBuf> {
+    std::fs::create_dir_all(parent)?;
+    for n in 0.. {
+        let dir = match n {
+            0 => parent.join(timestamp.to_string()),
+            n => parent.join(format!("{timestamp}-{n}")),
+        };
+        match std::fs::create_dir(&dir) {
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            x => return x.map(|()| dir),
+        }
+    }
+    unreachable!()
+}
+
+/// Copy the unpatched `modfiles` out of `game_dir` before they are written to
+///
+/// In [`BackupMode::GameDirectory`] an existing undo file is left alone, so it
+/// always holds the very first original
+pub fn backup_files(
+    mode: BackupMode,
+    game: &str,
+    game_dir: &Path,
+    modfiles: &[&str],
+    width: u16,
+    height: u16,
+) -> Result<BackupRecord, Error> {
+    let timestamp = unix_time();
+
+    let (dir, files) = match mode {
+        BackupMode::GameDirectory => {
+            let files = modfiles
+                .iter()
+                .map(|modfile| {
+                    let source = wine::resolve(game_dir, modfile);
+                    let mut undo_file_path = source.clone().into_os_string();
+                    undo_file_path.push(".undo");
+                    let undo_file_path = PathBuf::from(undo_file_path);
+                    let backup = undo_file_path
+                        .strip_prefix(game_dir)
+                        .map_or(format!("{modfile}.undo"), |x| {
+                            x.to_string_lossy().into_owned()
+                        });
+
+                    if !undo_file_path.exists() {
+                        std::fs::copy(&source, &undo_file_path)?;
+                    }
+
+                    Ok(ManifestFile {
+                        source,
+                        backup,
+                        sha256: sha256_hex(&std::fs::read(&undo_file_path)?),
+                    })
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+
+            (game_dir.to_path_buf(), files)
+        }
+        BackupMode::Central => {
+            let root =
+                backup_root().ok_or(Error::state_error("Unable to find user data directory"))?;
+
+            let dir = new_dir(&root.join(sanitize_name(game)), timestamp)?;
+
+            let files = modfiles
+                .iter()
+                .map(|modfile| {
+                    let source = wine::resolve(game_dir, modfile);
+                    let data = std::fs::read(&source)?;
+                    let backup = sanitize_name(modfile);
+                    std::fs::write(dir.join(&backup), &data)?;
+
+                    Ok(ManifestFile {
+                        source,
+                        backup,
+                        sha256: sha256_hex(&data),
+                    })
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+
+            (dir, files)
+        }
+    };
+
+    let manifest = Manifest {
+        game: game.to_string(),
+        game_dir: game_dir.to_path_buf(),
+        timestamp,
+        width,
+        height,
+        files,
+        registry: vec![],
+    };
+
+    if mode == BackupMode::Central {
+        std::fs::write(dir.join(MANIFEST_NAME), manifest.to_ini())?;
+    }
+
+    Ok(BackupRecord {
+        dir,
+        manifest,
+        log: String::new(),
+    })
+}
+
+/// An undo file found on disk for a game directory
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct BackupEntry {
+    pub mode: BackupMode,
+    /// Location of the backup copy
+    pub path: PathBuf,
+    /// File the backup restores
+    pub target: PathBuf,
+    pub size: u64,
+    pub modified: Option<SystemTime>,
+    /// Expected hash of the backup, when recorded in a manifest
+    pub sha256: Option<String>,
+}
+impl BackupEntry {
+    pub fn name(&self) -> String {
+        self.path
+            .file_name()
+            .map(|x| x.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    }
+
+    pub fn modified_string(&self) -> String {
+        self.modified
+            .map(|x| humantime::format_rfc3339_seconds(x).to_string())
+            .unwrap_or_default()
+    }
+
+    fn from_path(
+        mode: BackupMode,
+        path: PathBuf,
+        target: PathBuf,
+        sha256: Option<String>,
+    ) -> Result<Self, Error> {
+        let metadata = path.metadata()?;
+        Ok(Self {
+            mode,
+            path,
+            target,
+            size: metadata.len(),
+            modified: metadata.modified().ok(),
+            sha256,
+        })
+    }
+
+    /// Copy the backup over the file it was taken from
+    pub fn restore(&self) -> Result<(), Error> {
+        let data = std::fs::read(&self.path)?;
+
+        if let Some(expected) = &self.sha256
+            && sha256_hex(&data) != *expected
+        {
+            return Err(Error::ChecksumMismatch {
+                file: self.path.display().to_string(),
+            });
+        }
+
+        std::fs::write(&self.target, data)?;
+        Ok(())
+    }
+
+    /// Remove the backup, along with its manifest entry for central backups
+    pub fn delete(&self) -> Result<(), Error> {
+        std::fs::remove_file(&self.path)?;
+
+        if self.mode == BackupMode::Central
+            && let Some(dir) = self.path.parent()
+        {
+            let manifest_path = dir.join(MANIFEST_NAME);
+            let mut manifest = Manifest::from_ini(&std::fs::read_to_string(&manifest_path)?)?;
+            manifest.files.retain(|x| dir.join(&x.backup) != self.path);
+
+            if manifest.files.is_empty() {
+                std::fs::remove_dir_all(dir)?;
+            } else {
+                std::fs::write(manifest_path, manifest.to_ini())?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Every central backup manifest on disk, with the directory it lives in
+pub fn central_manifests() -> Vec<(PathBuf, Manifest)> {
+    fn sub_dirs(dir: &Path) -> impl Iterator<Item = PathBuf> {
+        dir.read_dir()
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|x| x.path())
+            .filter(|x| x.is_dir())
+    }
+
+    let Some(root) = backup_root() else {
+        return vec![];
+    };
+
+    sub_dirs(&root)
+        .flat_map(|game| sub_dirs(&game).collect::<Vec<_>>())
+        .filter_map(|dir| {
+            let manifest = std::fs::read_to_string(dir.join(MANIFEST_NAME)).ok()?;
+            let manifest = Manifest::from_ini(&manifest).ok()?;
+            Some((dir, manifest))
+        })
+        .collect()
+}
+
+fn local_entries(dirs: &[PathBuf]) -> impl Iterator<Item = BackupEntry> {
+    dirs.iter()
+        .filter_map(|dir| dir.read_dir().ok())
+        .flatten()
+        .flatten()
+        .filter_map(|file| {
+            let path = file.path();
+            let target = path.to_str()?.strip_suffix(".undo")?.into();
+            BackupEntry::from_path(BackupMode::GameDirectory, path, target, None).ok()
+        })
+}
+
+fn central_entries(game_dir: &Path) -> impl Iterator<Item = BackupEntry> {
+    central_manifests()
+        .into_iter()
+        .filter(move |(_, manifest)| manifest.game_dir == game_dir)
+        .flat_map(|(dir, manifest)| {
+            manifest.files.into_iter().filter_map(move |file| {
+                BackupEntry::from_path(
+                    BackupMode::Central,
+                    dir.join(&file.backup),
+                    file.source,
+                    Some(file.sha256),
+                )
+                .ok()
+            })
+        })
+}
+
+/// Find undo files next to `modfiles` and central backups taken from `game_dir`
+pub fn find_backups(game_dir: &Path, modfiles: &[&str]) -> Vec<BackupEntry> {
+    let mut dirs = vec![game_dir.to_path_buf()];
+    for modfile in modfiles {
+        if let Some(parent) = wine::resolve(game_dir, modfile).parent()
+            && !dirs.iter().any(|x| x == parent)
+        {
+            dirs.push(parent.to_path_buf());
+        }
+    }
+
+    let mut entries = local_entries(&dirs)
+        .chain(central_entries(game_dir))
+        .collect::<Vec<_>>();
+    entries.sort_by_key(|x| std::cmp::Reverse(x.modified));
+    entries
+}
+
+#[derive(Debug, Default)]
+pub struct RestoreSummary {
+    pub restored: Vec<PathBuf>,
+    pub failed: Vec<(PathBuf, Error)>,
+}
+impl std::fmt::Display for RestoreSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Restored {} file(s), {} failed",
+            self.restored.len(),
+            self.failed.len()
+        )?;
+
+        for path in &self.restored {
+            writeln!(f, "  restored {}", path.display())?;
+        }
+        for (path, e) in &self.failed {
+            writeln!(f, "  failed {}: {e}", path.display())?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Restore every file under `game_dir` that has a backup, anywhere in the
+/// directory tree or in the central backup store
+///
+/// The oldest backup of each file is used, since that is the unpatched original
+pub fn restore_all(game_dir: &Path) -> RestoreSummary {
+    fn walk(dir: &Path, dirs: &mut Vec<PathBuf>) {
+        dirs.push(dir.to_path_buf());
+        for entry in dir.read_dir().into_iter().flatten().flatten() {
+            let path = entry.path();
+            if entry.file_type().is_ok_and(|x| x.is_dir()) {
+                walk(&path, dirs);
+            }
+        }
+    }
+
+    let mut dirs = vec![];
+    walk(game_dir, &mut dirs);
+
+    // Game directory undo files are never overwritten so they come first,
+    // followed by central backups from oldest to newest
+    let mut central = central_entries(game_dir).collect::<Vec<_>>();
+    central.sort_by_key(|x| x.modified);
+
+    let mut summary = RestoreSummary::default();
+    let mut seen = std::collections::HashSet::new();
+    for entry in local_entries(&dirs).chain(central) {
+        if !seen.insert(entry.target.clone()) {
+            continue;
+        }
+
+        match entry.restore() {
+            Ok(()) => summary.restored.push(entry.target),
+            Err(e) => summary.failed.push((entry.target, e)),
+        }
+    }
+
+    // Registry values in the same order, the first recorded being the original
+    let local = std::fs::read_to_string(game_dir.join(REGISTRY_UNDO_NAME))
+        .ok()
+        .and_then(|x| Manifest::from_ini(&x).ok());
+    let mut central = central_manifests()
+        .into_iter()
+        .map(|(_, manifest)| manifest)
+        .filter(|x| x.game_dir == game_dir)
+        .collect::<Vec<_>>();
+    central.sort_by_key(|x| x.timestamp);
+
+    for value in local.into_iter().chain(central).flat_map(|x| x.registry) {
+        let name = PathBuf::from(value.name());
+        if !seen.insert(name.clone()) {
+            continue;
+        }
+
+        match value.restore() {
+            Ok(()) => summary.restored.push(name),
+            Err(e) => summary.failed.push((name, e)),
+        }
+    }
+
+    summary
+}
+
+/// Put the original files stored in an exported bundle back in place
+///
+/// Files are written to `game_dir`, where they were in the game folder the
+/// bundle was made from. A bundle with files anywhere else, or going up out
+/// of it with `..`, is refused, as is one with registry values other than
+/// those `registry` writes. Returns the restored file paths
+pub fn restore_bundle(
+    bundle: impl AsRef<Path>,
+    game_dir: &Path,
+    registry: &[RegistryPatch],
+) -> Result<Vec<PathBuf>, Error> {
+    let mut archive = tar::Archive::new(File::open(bundle)?);
+
+    let mut entries = HashMap::new();
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let name = entry.path()?.to_string_lossy().into_owned();
+        let mut data = Vec::with_capacity(entry.size() as usize);
+        entry.read_to_end(&mut data)?;
+        entries.insert(name, data);
+    }
+
+    let manifest = entries
+        .get(MANIFEST_NAME)
+        .ok_or(Error::state_error("Bundle is missing its manifest"))?;
+    let manifest = Manifest::from_ini(&String::from_utf8_lossy(manifest))?;
+
+    // Check everything before touching any game files
+    let files = manifest
+        .files
+        .iter()
+        .map(|file| {
+            let data = entries.get(&file.backup).ok_or(Error::state_error(format!(
+                "Bundle is missing {}",
+                file.backup
+            )))?;
+
+            if sha256_hex(data) != file.sha256 {
+                return Err(Error::ChecksumMismatch {
+                    file: file.backup.clone(),
+                });
+            }
+
+            let relative = file
+                .source
+                .strip_prefix(&manifest.game_dir)
+                .ok()
+                .filter(|x| {
+                    x.components().next().is_some()
+                        && x.components().all(|x| matches!(x, Component::Normal(_)))
+                })
+                .ok_or_else(|| {
+                    Error::state_error(format!(
+                        "{} isn't in the game folder of the bundle",
+                        file.source.display()
+                    ))
+                })?;
+
+            Ok((game_dir.join(relative), data))
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    if let Some(value) = manifest
+        .registry
+        .iter()
+        .find(|value| !registry.iter().any(|patch| value.is_for(patch)))
+    {
+        return Err(Error::state_error(format!(
+            "{} isn't written by the game's registry patches",
+            value.name()
+        )));
+    }
+
+    let mut restored = files
+        .into_iter()
+        .map(|(target, data)| {
+            std::fs::write(&target, data)?;
+            Ok(target)
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    for value in &manifest.registry {
+        value.restore()?;
+        restored.push(value.name().into());
+    }
+
+    Ok(restored)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::RegType;
+    use crate::testing::TempDir;
+
+    #[test]
+    fn sanitize_test() {
+        assert_eq!(
+            sanitize_name("Star Wars: KOTOR (800x600 interface)"),
+            "Star Wars_ KOTOR (800x600 interface)"
+        );
+        assert_eq!(sanitize_name("Bin/DXWrap.dll"), "Bin_DXWrap.dll");
+    }
+
+    fn test_manifest() -> Manifest {
+        Manifest {
+            game: "Star Wars: KOTOR (800x600 interface)".into(),
+            game_dir: "/games/swkotor".into(),
+            timestamp: 1700000000,
+            width: 1920,
+            height: 1080,
+            files: vec![ManifestFile {
+                source: "/games/swkotor/swkotor.exe".into(),
+                backup: "swkotor.exe".into(),
+                sha256: sha256_hex(b"original"),
+            }],
+            registry: vec![
+                RegistryBackup {
+                    key: r"HKCU\Software\LucasArts\KotOR".into(),
+                    value: "Width".into(),
+                    view: RegView::Bits32,
+                    previous: RawValue::encode(RegType::Dword, "800"),
+                },
+                RegistryBackup {
+                    key: r"HKCU\Software\LucasArts\KotOR".into(),
+                    value: "Height".into(),
+                    view: RegView::Bits32,
+                    previous: None,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn manifest_round_trip() {
+        let manifest = test_manifest();
+        assert_eq!(Manifest::from_ini(&manifest.to_ini()).unwrap(), manifest);
+
+        assert!(
+            manifest
+                .to_ini()
+                .contains("type=REG_DWORD\nbytes=20030000\n")
+        );
+
+        // Written by reg.exe, as it printed the value
+        let mut older = manifest.clone();
+        for value in &mut older.registry {
+            value.view = RegView::Bits64;
+        }
+        let ini = manifest
+            .to_ini()
+            .replace("view=32\n", "")
+            .replace("type=REG_DWORD\nbytes=20030000", "type=dword\ndata=0x320");
+        assert_eq!(Manifest::from_ini(&ini).unwrap(), older);
+    }
+
+    #[test]
+    fn bundle_round_trip() {
+        let tmp = TempDir::new("bundle");
+        let game_dir = tmp.join("game");
+        std::fs::create_dir_all(&game_dir).unwrap();
+
+        let central = tmp.join("central");
+        assert_eq!(new_dir(&central, 5).unwrap(), central.join("5"));
+        assert_eq!(new_dir(&central, 5).unwrap(), central.join("5-1"));
+
+        std::fs::write(game_dir.join("swkotor.exe"), b"original").unwrap();
+        let mut record = backup_files(
+            BackupMode::GameDirectory,
+            "Star Wars: KOTOR",
+            &game_dir,
+            &["swkotor.exe"],
+            1920,
+            1080,
+        )
+        .unwrap();
+        record
+            .set_log(BackupMode::GameDirectory, "patched".into())
+            .unwrap();
+        std::fs::write(game_dir.join("swkotor.exe"), b"patched").unwrap();
+
+        let bundle = tmp.join("bundle.tar");
+        record.export_bundle(&bundle).unwrap();
+        std::fs::remove_file(game_dir.join("swkotor.exe.undo")).unwrap();
+
+        let restored = restore_bundle(&bundle, &game_dir, &[]).unwrap();
+        assert_eq!(restored, [game_dir.join("swkotor.exe")]);
+        assert_eq!(
+            std::fs::read(game_dir.join("swkotor.exe")).unwrap(),
+            b"original"
+        );
+
+        // Files outside the game folder aren't written, nor are registry
+        // values the game doesn't patch
+        let width = RegistryPatch {
+            key: r"HKCU\Software\LucasArts\KotOR".into(),
+            value: "Width".into(),
+            ..Default::default()
+        };
+        let elsewhere = [
+            ("/games/swkotor/../.bashrc", 0),
+            ("/home/user/.bashrc", 0),
+            ("/games/swkotor/swkotor.exe", 2),
+        ];
+        for (source, registry) in elsewhere {
+            let mut manifest = test_manifest();
+            manifest.registry.truncate(registry);
+            manifest.files[0].source = source.into();
+            let hostile = tmp.join("hostile.tar");
+            let mut builder = tar::Builder::new(File::create(&hostile).unwrap());
+            for (name, data) in [
+                (MANIFEST_NAME, manifest.to_ini().into_bytes()),
+                ("swkotor.exe", b"original".to_vec()),
+            ] {
+                let mut header = tar::Header::new_gnu();
+                header.set_size(data.len() as u64);
+                builder
+                    .append_data(&mut header, name, data.as_slice())
+                    .unwrap();
+            }
+            builder.finish().unwrap();
+            drop(builder);
+
+            std::fs::write(game_dir.join("swkotor.exe"), b"patched").unwrap();
+            assert!(
+                matches!(
+                    restore_bundle(&hostile, &game_dir, std::slice::from_ref(&width)),
+                    Err(Error::StateError(_))
+                ),
+                "{source}"
+            );
+            assert_eq!(
+                std::fs::read(game_dir.join("swkotor.exe")).unwrap(),
+                b"patched"
+            );
+        }
+    }
+
+    #[test]
+    fn find_local_backups() {
+        let tmp = TempDir::new("find");
+        let game_dir = tmp.join("game");
+        std::fs::create_dir_all(game_dir.join("Bin")).unwrap();
+
+        std::fs::write(game_dir.join("Bin/DXWrap.dll"), b"original").unwrap();
+        std::fs::write(game_dir.join("readme.txt"), b"").unwrap();
+        backup_files(
+            BackupMode::GameDirectory,
+            "Tiger Woods",
+            &game_dir,
+            &["Bin/DXWrap.dll"],
+            1920,
+            1080,
+        )
+        .unwrap();
+        std::fs::write(game_dir.join("Bin/DXWrap.dll"), b"patched").unwrap();
+
+        let entries = find_backups(&game_dir, &["Bin/DXWrap.dll"]);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].name(), "DXWrap.dll.undo");
+        assert_eq!(entries[0].target, game_dir.join("Bin/DXWrap.dll"));
+        assert_eq!(entries[0].size, 8);
+
+        entries[0].restore().unwrap();
+        assert_eq!(
+            std::fs::read(game_dir.join("Bin/DXWrap.dll")).unwrap(),
+            b"original"
+        );
+
+        entries[0].delete().unwrap();
+        assert!(find_backups(&game_dir, &["Bin/DXWrap.dll"]).is_empty());
+    }
+
+    #[test]
+    fn restore_everything() {
+        let tmp = TempDir::new("restore-all");
+        let game_dir = tmp.join("game");
+        std::fs::create_dir_all(game_dir.join("Bin")).unwrap();
+
+        std::fs::write(game_dir.join("game.exe"), b"original exe").unwrap();
+        std::fs::write(game_dir.join("Bin/DXWrap.dll"), b"original dll").unwrap();
+        backup_files(
+            BackupMode::GameDirectory,
+            "Game",
+            &game_dir,
+            &["Bin/DXWrap.dll", "game.exe"],
+            1920,
+            1080,
+        )
+        .unwrap();
+        std::fs::write(game_dir.join("game.exe"), b"patched").unwrap();
+        std::fs::write(game_dir.join("Bin/DXWrap.dll"), b"patched").unwrap();
+
+        let summary = restore_all(&game_dir);
+        assert_eq!(summary.restored.len(), 2);
+        assert!(summary.failed.is_empty());
+        assert_eq!(
+            std::fs::read(game_dir.join("game.exe")).unwrap(),
+            b"original exe"
+        );
+        assert_eq!(
+            std::fs::read(game_dir.join("Bin/DXWrap.dll")).unwrap(),
+            b"original dll"
+        );
+    }
+
+    #[test]
+    fn hash_test() {
+        assert_eq!(
+            sha256_hex(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+}
@@ -0,0 +1,609 @@
+use std::{
+    io::{BufRead as _, Write as _},
+    path::PathBuf,
+    process::ExitCode,
+};
+
+use crate::{
+    config::{Config, Format, LoadOptions},
+    derive,
+    document::Document,
+    encoding::{self, Encoding},
+    error::Error,
+    model::Model,
+    patch_info::PatchStatus,
+    retention::{self, RetentionPolicy},
+    share, testdb, validate,
+};
+
+pub const USAGE: &str = "\
+Usage:
+  uniws-rs [uniws://patch?...] [--config DB_INI]... [--dir GAME_DIR] [--app NAME]
+           [--width W] [--height H] [--apply]
+                                Start the GUI, filled in with a shared patch or the
+                                values given. Each --config is merged into the ones
+                                before it. With --apply, patch once it's up
+  uniws-rs clean-backups [--keep N | --newest-per-file | --prompt] [--dir GAME_DIR]
+                                Remove old backups from the user data folder
+  uniws-rs test-db --config DB_INI --samples DIR [--json]
+                                Check every signature in a database against sample
+                                files stored as DIR/<game name>/<modfile>
+  uniws-rs patch --config DB_INI --dir GAME_DIR --app NAME --width W --height H
+                [--enable-option LABEL]... [--value NAME=VALUE]...
+                                Patch a game without the GUI, including the optional
+                                patches of each enabled option and the value slots
+                                given
+  uniws-rs derive-sig ORIGINAL PATCHED [--name NAME] [--res WIDTHxHEIGHT]
+                                Print a config section that makes the same changes
+                                as PATCHED, a patched copy of ORIGINAL. With --res,
+                                writes of that resolution become xoffset/yoffset
+  uniws-rs validate --config DB_INI
+                                Check every game of a database for bad signatures,
+                                offsets outside them and games missing from [Apps]
+                                or listed without a section
+  uniws-rs convert INPUT OUTPUT
+                                Rewrite a config as INI, TOML or JSON, picked by the
+                                extension of each file
+
+Any command also takes --verbose, which logs every step in detail, file hashes
+included, and prints the log to the terminal. Logs are kept for a week in the
+user data folder (~/.local/share/uniws/logs on Linux).
+
+Exit codes: 1 general, 2 bad config, 3 file error, 4 signature or text not found,
+5 offset out of bounds, 6 checksum mismatch, 7 backup failed, 8 write verify failed,
+9 expected bytes not found";
+
+/// What the GUI is filled in with on startup
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct GuiArgs {
+    /// Share link to open
+    pub link: Option<String>,
+    /// Configs to merge, in order
+    pub configs: Vec<PathBuf>,
+    pub game_dir: Option<PathBuf>,
+    pub app: Option<String>,
+    pub width: Option<u16>,
+    pub height: Option<u16>,
+    /// Patch the game once everything is filled in
+    pub apply: bool,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Command {
+    Gui(GuiArgs),
+    Help,
+    CleanBackups {
+        policy: RetentionPolicy,
+        game_dir: Option<PathBuf>,
+    },
+    TestDb {
+        config: PathBuf,
+        samples: PathBuf,
+        json: bool,
+    },
+    Patch {
+        config: PathBuf,
+        game_dir: PathBuf,
+        app: String,
+        width: u16,
+        height: u16,
+        /// Labels of the optional patches to apply
+        options: Vec<String>,
+        /// Input of each value slot, by slot name
+        values: Vec<(String, String)>,
+    },
+    DeriveSig {
+        original: PathBuf,
+        patched: PathBuf,
+        /// Section name, defaults to the file name of `original`
+        name: Option<String>,
+        resolution: Option<(u16, u16)>,
+    },
+    Validate {
+        config: PathBuf,
+    },
+    Convert {
+        input: PathBuf,
+        output: PathBuf,
+    },
+}
+
+fn parse_resolution(s: &str) -> Option<(u16, u16)> {
+    let (width, height) = s.split_once(['x', 'X'])?;
+    Some((width.parse().ok()?, height.parse().ok()?))
+}
+
+fn value(args: &mut impl Iterator<Item = String>, flag: &str) -> Result<String, String> {
+    args.next()
+        .ok_or_else(|| format!("Missing value for {flag}"))
+}
+
+/// Whether `--verbose` is in `args`, which any command takes anywhere, and
+/// the other arguments
+pub fn take_verbose(args: impl IntoIterator<Item = String>) -> (bool, Vec<String>) {
+    let (verbose, rest): (Vec<_>, _) = args.into_iter().partition(|x| x == "--verbose");
+    (!verbose.is_empty(), rest)
+}
+
+pub fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Command, String> {
+    let mut args = args.into_iter();
+
+    let Some(command) = args.next() else {
+        return Ok(Command::Gui(GuiArgs::default()));
+    };
+
+    match command.as_str() {
+        "-h" | "--help" | "help" => Ok(Command::Help),
+        x if x.starts_with(share::PREFIX) || x.starts_with("--") => {
+            let mut gui = GuiArgs::default();
+            let dimension = |flag: &str, x: String| {
+                x.parse::<u16>()
+                    .map_err(|_| format!("Invalid value for {flag}: {x}"))
+            };
+
+            let mut args = std::iter::once(command).chain(args);
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "--config" => gui.configs.push(value(&mut args, "--config")?.into()),
+                    "--dir" => gui.game_dir = Some(value(&mut args, "--dir")?.into()),
+                    "--app" => gui.app = Some(value(&mut args, "--app")?),
+                    "--width" => {
+                        gui.width = Some(dimension("--width", value(&mut args, "--width")?)?)
+                    }
+                    "--height" => {
+                        gui.height = Some(dimension("--height", value(&mut args, "--height")?)?)
+                    }
+                    "--apply" => gui.apply = true,
+                    x if x.starts_with(share::PREFIX) => gui.link = Some(arg),
+                    x => return Err(format!("Unknown argument: {x}")),
+                }
+            }
+
+            Ok(Command::Gui(gui))
+        }
+        "clean-backups" => {
+            let mut policy = RetentionPolicy::default();
+            let mut game_dir = None;
+
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "--keep" => {
+                        let n = value(&mut args, "--keep")?;
+                        policy = RetentionPolicy::KeepLast(
+                            n.parse()
+                                .map_err(|_| format!("Invalid count for --keep: {n}"))?,
+                        );
+                    }
+                    "--newest-per-file" => policy = RetentionPolicy::KeepNewestPerFile,
+                    "--prompt" => policy = RetentionPolicy::Prompt,
+                    "--dir" => game_dir = Some(value(&mut args, "--dir")?.into()),
+                    x => return Err(format!("Unknown argument: {x}")),
+                }
+            }
+
+            Ok(Command::CleanBackups { policy, game_dir })
+        }
+        "test-db" => {
+            let mut config = None;
+            let mut samples = None;
+            let mut json = false;
+
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "--config" => config = Some(value(&mut args, "--config")?.into()),
+                    "--samples" => samples = Some(value(&mut args, "--samples")?.into()),
+                    "--json" => json = true,
+                    x => return Err(format!("Unknown argument: {x}")),
+                }
+            }
+
+            Ok(Command::TestDb {
+                config: config.ok_or("Missing --config")?,
+                samples: samples.ok_or("Missing --samples")?,
+                json,
+            })
+        }
+        "patch" => {
+            let mut config = None;
+            let mut game_dir = None;
+            let mut app = None;
+            let mut width = None;
+            let mut height = None;
+            let mut options = Vec::new();
+            let mut values = Vec::new();
+
+            let dimension = |flag: &str, x: String| {
+                x.parse::<u16>()
+                    .map_err(|_| format!("Invalid value for {flag}: {x}"))
+            };
+
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "--config" => config = Some(value(&mut args, "--config")?.into()),
+                    "--dir" => game_dir = Some(value(&mut args, "--dir")?.into()),
+                    "--app" => app = Some(value(&mut args, "--app")?),
+                    "--width" => width = Some(dimension("--width", value(&mut args, "--width")?)?),
+                    "--height" => {
+                        height = Some(dimension("--height", value(&mut args, "--height")?)?)
+                    }
+                    "--enable-option" => options.push(value(&mut args, "--enable-option")?),
+                    "--value" => {
+                        let x = value(&mut args, "--value")?;
+                        let (name, input) = x
+                            .split_once('=')
+                            .ok_or_else(|| format!("Expected NAME=VALUE for --value: {x}"))?;
+                        values.push((name.to_string(), input.to_string()));
+                    }
+                    x => return Err(format!("Unknown argument: {x}")),
+                }
+            }
+
+            Ok(Command::Patch {
+                config: config.ok_or("Missing --config")?,
+                game_dir: game_dir.ok_or("Missing --dir")?,
+                app: app.ok_or("Missing --app")?,
+                width: width.ok_or("Missing --width")?,
+                height: height.ok_or("Missing --height")?,
+                options,
+                values,
+            })
+        }
+        "derive-sig" => {
+            let mut files = Vec::new();
+            let mut name = None;
+            let mut resolution = None;
+
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "--name" => name = Some(value(&mut args, "--name")?),
+                    "--res" => {
+                        let res = value(&mut args, "--res")?;
+                        resolution = Some(
+                            parse_resolution(&res)
+                                .ok_or_else(|| format!("Invalid resolution for --res: {res}"))?,
+                        );
+                    }
+                    x if x.starts_with("--") => return Err(format!("Unknown argument: {x}")),
+                    _ => files.push(PathBuf::from(arg)),
+                }
+            }
+
+            let [original, patched] = <[PathBuf; 2]>::try_from(files)
+                .map_err(|_| "Expected an original and a patched file")?;
+            Ok(Command::DeriveSig {
+                original,
+                patched,
+                name,
+                resolution,
+            })
+        }
+        "validate" => {
+            let mut config = None;
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "--config" => config = Some(value(&mut args, "--config")?.into()),
+                    x => return Err(format!("Unknown argument: {x}")),
+                }
+            }
+
+            Ok(Command::Validate {
+                config: config.ok_or("Missing --config")?,
+            })
+        }
+        "convert" => {
+            let files: Vec<PathBuf> = args.map(PathBuf::from).collect();
+            let [input, output] = <[PathBuf; 2]>::try_from(files)
+                .map_err(|_| "Expected an input and an output file")?;
+            Ok(Command::Convert { input, output })
+        }
+        x => Err(format!("Unknown command: {x}")),
+    }
+}
+
+fn confirm(question: &str) -> bool {
+    print!("{question} [y/N] ");
+    let _ = std::io::stdout().flush();
+
+    let mut answer = String::new();
+    let _ = std::io::stdin().lock().read_line(&mut answer);
+    matches!(answer.trim(), "y" | "Y" | "yes")
+}
+
+/// Run a non-GUI command
+pub fn run(command: Command) -> ExitCode {
+    match command {
+        Command::Gui(_) => ExitCode::SUCCESS,
+        Command::Help => {
+            println!("{USAGE}");
+            ExitCode::SUCCESS
+        }
+        Command::CleanBackups { policy, game_dir } => {
+            let result = retention::clean(policy, game_dir.as_deref(), |dir, manifest| {
+                confirm(&format!(
+                    "Remove backup of {} ({})?",
+                    manifest.game,
+                    dir.display()
+                ))
+            });
+
+            match result {
+                Ok(removed) => {
+                    for dir in &removed {
+                        println!("Removed {}", dir.display());
+                    }
+                    println!("Removed {} backup(s)", removed.len());
+                    ExitCode::SUCCESS
+                }
+                Err(e) => {
+                    eprintln!("Failed to clean backups: {e}");
+                    ExitCode::FAILURE
+                }
+            }
+        }
+        Command::TestDb {
+            config,
+            samples,
+            json,
+        } => {
+            let config = match Config::load_file(&config, LoadOptions::default()) {
+                Ok(config) => config,
+                Err(e) => {
+                    if json {
+                        println!("{}", e.to_json());
+                    } else {
+                        eprintln!("Failed to load {}: {e}", config.display());
+                    }
+                    return ExitCode::from(e.exit_code());
+                }
+            };
+
+            let reports = testdb::verify(&config, &samples);
+            if json {
+                println!("{}", testdb::json(&config, &reports));
+            } else {
+                print!("{}", testdb::matrix(&config, &reports));
+            }
+
+            match testdb::first_error(&config, &reports) {
+                Some(e) => ExitCode::from(e.exit_code()),
+                None => ExitCode::SUCCESS,
+            }
+        }
+        Command::Patch {
+            config,
+            game_dir,
+            app,
+            width,
+            height,
+            options,
+            values,
+        } => {
+            let result = Config::load_file(&config, LoadOptions::default()).and_then(|config| {
+                let section = config
+                    .sections
+                    .iter()
+                    .find(|x| x.name == app)
+                    .cloned()
+                    .ok_or_else(|| Error::config_missing_section(app))?;
+
+                let mut model = Model::default();
+                model.game_dir = Some(game_dir.to_string_lossy().into_owned());
+                model.width = Some(width);
+                model.height = Some(height);
+                model.enabled_options = options.into_iter().collect();
+                model.values = values.into_iter().collect();
+                model.apply_patches(&section, false)
+            });
+
+            match result {
+                Ok(report) => {
+                    tracing::info!("Patched from the command line\n{report}");
+                    print!("{report}");
+                    let failed = report.patches.iter().find_map(|x| match &x.status {
+                        PatchStatus::Failed(e) => Some(e),
+                        _ => None,
+                    });
+                    match (failed, report.succeeded()) {
+                        (Some(e), _) => ExitCode::from(e.exit_code()),
+                        (None, true) => ExitCode::SUCCESS,
+                        (None, false) => ExitCode::FAILURE,
+                    }
+                }
+                Err(e) => {
+                    tracing::error!("Failed to patch from the command line: {e}");
+                    eprintln!("Failed to patch: {e}");
+                    ExitCode::from(e.exit_code())
+                }
+            }
+        }
+        Command::DeriveSig {
+            original,
+            patched,
+            name,
+            resolution,
+        } => {
+            let modfile = original
+                .file_name()
+                .map(|x| x.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let name = name.unwrap_or_else(|| modfile.clone());
+
+            let result = (|| -> Result<String, Error> {
+                let original = std::fs::read(&original)?;
+                let patched = std::fs::read(&patched)?;
+                derive::derive(&name, &modfile, &original, &patched, resolution)
+            })();
+
+            match result {
+                Ok(section) => {
+                    print!("{section}");
+                    ExitCode::SUCCESS
+                }
+                Err(e) => {
+                    eprintln!("Failed to derive signature: {e}");
+                    ExitCode::from(e.exit_code())
+                }
+            }
+        }
+        Command::Validate { config } => {
+            match validate::validate_file(&config, LoadOptions::default()) {
+                Ok(issues) => {
+                    print!("{}", validate::report(&issues));
+                    match issues
+                        .iter()
+                        .any(|x| x.severity == validate::Severity::Error)
+                    {
+                        // The exit code of a bad config
+                        true => ExitCode::from(2),
+                        false => ExitCode::SUCCESS,
+                    }
+                }
+                Err(e) => {
+                    eprintln!("Failed to load {}: {e}", config.display());
+                    ExitCode::from(e.exit_code())
+                }
+            }
+        }
+        Command::Convert { input, output } => {
+            let result = encoding::read(&input, Encoding::Auto)
+                .and_then(|x| Document::parse_as(&x, Format::from_path(&input)))
+                .and_then(|document| {
+                    let text = document.to_string_as(Format::from_path(&output));
+                    std::fs::write(&output, text).map_err(Into::into)
+                });
+
+            match result {
+                Ok(()) => ExitCode::SUCCESS,
+                Err(e) => {
+                    eprintln!("Failed to convert {}: {e}", input.display());
+                    ExitCode::from(e.exit_code())
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(x: &str) -> Vec<String> {
+        x.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn parse_test() {
+        assert_eq!(parse_args(args("")), Ok(Command::Gui(GuiArgs::default())));
+        assert_eq!(
+            parse_args(args("uniws://patch?sig=80")),
+            Ok(Command::Gui(GuiArgs {
+                link: Some("uniws://patch?sig=80".into()),
+                ..Default::default()
+            }))
+        );
+        assert_eq!(
+            parse_args(args(
+                "--config db.ini --config mine.ini --dir /games/kotor --app KOTOR --width 2560 \
+                 --height 1440 --apply"
+            )),
+            Ok(Command::Gui(GuiArgs {
+                link: None,
+                configs: vec!["db.ini".into(), "mine.ini".into()],
+                game_dir: Some("/games/kotor".into()),
+                app: Some("KOTOR".into()),
+                width: Some(2560),
+                height: Some(1440),
+                apply: true,
+            }))
+        );
+        assert!(parse_args(args("--width wide")).is_err());
+        assert!(parse_args(args("--apply --samples x")).is_err());
+        assert_eq!(parse_args(args("--help")), Ok(Command::Help));
+        assert_eq!(
+            take_verbose(args("validate --verbose --config db.ini")),
+            (true, args("validate --config db.ini"))
+        );
+        assert_eq!(take_verbose(args("validate")), (false, args("validate")));
+        assert_eq!(
+            parse_args(args("clean-backups")),
+            Ok(Command::CleanBackups {
+                policy: RetentionPolicy::default(),
+                game_dir: None
+            })
+        );
+        assert_eq!(
+            parse_args(args("clean-backups --keep 2 --dir /games/kotor")),
+            Ok(Command::CleanBackups {
+                policy: RetentionPolicy::KeepLast(2),
+                game_dir: Some("/games/kotor".into())
+            })
+        );
+        assert_eq!(
+            parse_args(args("clean-backups --newest-per-file")),
+            Ok(Command::CleanBackups {
+                policy: RetentionPolicy::KeepNewestPerFile,
+                game_dir: None
+            })
+        );
+        assert!(parse_args(args("clean-backups --keep")).is_err());
+        assert!(parse_args(args("clean-backups --keep x")).is_err());
+        assert_eq!(
+            parse_args(args("test-db --samples samples --config db.ini")),
+            Ok(Command::TestDb {
+                config: "db.ini".into(),
+                samples: "samples".into(),
+                json: false
+            })
+        );
+        assert!(parse_args(args("test-db --config db.ini")).is_err());
+        assert_eq!(
+            parse_args(args("derive-sig game.exe patched.exe --res 1920x1080")),
+            Ok(Command::DeriveSig {
+                original: "game.exe".into(),
+                patched: "patched.exe".into(),
+                name: None,
+                resolution: Some((1920, 1080))
+            })
+        );
+        assert!(parse_args(args("derive-sig game.exe")).is_err());
+        assert_eq!(
+            parse_args(args(
+                "patch --config db.ini --dir /games/kotor --app KOTOR --width 1920 --height 1080 \
+                 --enable-option Letterbox --enable-option HUD --value FOV=90"
+            )),
+            Ok(Command::Patch {
+                config: "db.ini".into(),
+                game_dir: "/games/kotor".into(),
+                app: "KOTOR".into(),
+                width: 1920,
+                height: 1080,
+                options: vec!["Letterbox".into(), "HUD".into()],
+                values: vec![("FOV".into(), "90".into())],
+            })
+        );
+        assert!(parse_args(args("patch --value FOV")).is_err());
+        assert!(
+            parse_args(args(
+                "patch --config db.ini --dir . --app KOTOR --width 1920"
+            ))
+            .is_err()
+        );
+        assert!(parse_args(args("derive-sig a b --res 1920")).is_err());
+        assert_eq!(
+            parse_args(args("convert patches.ini patches.toml")),
+            Ok(Command::Convert {
+                input: "patches.ini".into(),
+                output: "patches.toml".into(),
+            })
+        );
+        assert!(parse_args(args("convert patches.ini")).is_err());
+        assert_eq!(
+            parse_args(args("validate --config db.ini")),
+            Ok(Command::Validate {
+                config: "db.ini".into()
+            })
+        );
+        assert!(parse_args(args("validate db.ini")).is_err());
+        assert!(parse_args(args("frobnicate")).is_err());
+    }
+}
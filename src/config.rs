@@ -1,19 +1,35 @@
 use crate::{
-    error::{ConfigError, Error},
+    encoding::{self, Encoding},
+    error::{Cause, Error},
+    i18n::tr,
     patch_info::PatchInfo,
+    registry::RegistryPatch,
+    structured,
+    text_patch::TextPatch,
+    value::ValueType,
+    wine,
+};
+use indexmap::IndexMap;
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
 };
-use std::collections::HashMap;
 use winnow::{
-    ascii::{alphanumeric1, line_ending, multispace1, space0, till_line_ending},
-    combinator::{alt, delimited, opt, repeat, terminated},
+    ascii::{line_ending, multispace1, space0, till_line_ending},
+    combinator::{alt, eof, fail, opt, peek, repeat, terminated},
+    error::{ContextError, ErrMode, StrContext},
     prelude::*,
-    token::{one_of, take_till, take_until},
+    token::{any, one_of, take_till, take_while},
 };
 
 fn header(input: &mut &str) -> ModalResult<String> {
     '['.parse_next(input)?;
-    let res = take_until(1.., ']').parse_next(input)?;
-    ']'.parse_next(input)?;
+    // A name can't run on to the next line
+    let res = take_till(1.., [']', '\r', '\n'])
+        .context(StrContext::Label("section name"))
+        .parse_next(input)?;
+    ']'.context(StrContext::Expected(']'.into()))
+        .parse_next(input)?;
 
     Ok(res.to_string())
 }
@@ -21,7 +37,7 @@ fn header(input: &mut &str) -> ModalResult<String> {
 fn comment(input: &mut &str) -> ModalResult<()> {
     one_of((';', '#')).void().parse_next(input)?;
     till_line_ending.void().parse_next(input)?;
-    line_ending.void().parse_next(input)
+    alt((line_ending, eof)).void().parse_next(input)
 }
 
 fn whitespace_and_comments(input: &mut &str) -> ModalResult<()> {
@@ -36,45 +52,293 @@ fn whitespace_and_comments(input: &mut &str) -> ModalResult<()> {
     opt(repeat_ws).void().parse_next(input)
 }
 
-pub fn kv_pair(input: &mut &str) -> ModalResult<(String, String)> {
-    let key = alphanumeric1.parse_next(input)?;
+/// Letters, digits and `_`, `.` or `-`, e.g. `p1_xoffset` or `check.file`
+pub fn is_key_char(x: char) -> bool {
+    x.is_ascii_alphanumeric() || matches!(x, '_' | '.' | '-')
+}
+
+/// A value up to a comment or the end of the line. A line ending in `\`
+/// goes on to the next, without the spaces it starts with
+fn unquoted(input: &mut &str) -> ModalResult<String> {
+    let mut value = String::new();
+    loop {
+        let line = take_till(0.., (';', '#', '\r', '\n')).parse_next(input)?;
+        let line = line.trim_end();
+        match line.strip_suffix('\\') {
+            Some(part) if opt(line_ending).parse_next(input)?.is_some() => {
+                value.push_str(part);
+                space0.parse_next(input)?;
+            }
+            _ => {
+                value.push_str(line);
+                break;
+            }
+        }
+    }
+
+    match value.is_empty() {
+        true => fail.parse_next(input),
+        false => Ok(value),
+    }
+}
+
+/// A value in quotes, where `\"` is a quote and `\\` a backslash. Any other
+/// `\` is kept, so Windows paths can be written as they are
+pub fn quoted(input: &mut &str) -> ModalResult<String> {
+    '"'.parse_next(input)?;
+    let mut value = String::new();
+    loop {
+        value.push_str(take_till(0.., ('"', '\\', '\r', '\n')).parse_next(input)?);
+        match any.parse_next(input)? {
+            '"' => return Ok(value),
+            '\\' => value.push(opt(one_of(('"', '\\'))).parse_next(input)?.unwrap_or('\\')),
+            _ => return fail.parse_next(input),
+        }
+    }
+}
+
+/// `value` in quotes, escaped so that [`quoted`] reads it back the same
+pub fn quote(value: &str) -> String {
+    let mut out = String::from('"');
+    let mut chars = value.chars().peekable();
+    while let Some(x) = chars.next() {
+        match x {
+            '"' => out.push_str("\\\""),
+            '\\' if matches!(chars.peek(), None | Some('"' | '\\')) => out.push_str("\\\\"),
+            x => out.push(x),
+        }
+    }
+    out.push('"');
+    out
+}
 
-    (space0, '=', space0).void().parse_next(input)?;
+pub fn kv_pair(input: &mut &str) -> ModalResult<(String, String)> {
+    let key = take_while(1.., is_key_char)
+        .context(StrContext::Label("key"))
+        .parse_next(input)?;
 
-    let quoted = (delimited('"', take_until(0.., '"'), '"'), line_ending).map(|(a, _)| a);
+    (
+        space0,
+        '='.context(StrContext::Expected('='.into())),
+        space0,
+    )
+        .void()
+        .parse_next(input)?;
 
-    let unquoted = take_till(1.., (';', '#', '\r', '\n')).map(|x: &str| x.trim_end());
+    // Only the end of the line or a comment can follow the closing quote, so
+    // a `;` or `#` inside one is part of the value
+    let end = alt((one_of((';', '#')).void(), line_ending.void(), eof.void()));
+    let quoted = terminated(quoted, (space0, peek(end)));
 
     let value = alt((quoted, unquoted)).parse_next(input)?;
 
-    Ok((key.to_string(), value.to_string()))
+    Ok((key.to_string(), value))
 }
 
-#[derive(Debug, PartialEq, Eq)]
-pub struct Section {
-    pub name: String,
-    pub items: HashMap<String, String>,
+/// Keys of a section, in the order they're first given
+pub type Items = IndexMap<String, String>;
+/// Sections of a config, in the order they're first given
+pub type RawConfig = IndexMap<String, Items>;
+
+/// Line `rest` starts on, counting from 1, where it's the end of `input`
+fn line_of(input: &str, rest: &str) -> usize {
+    input[..input.len() - rest.len()].matches('\n').count() + 1
+}
+
+/// Error for `rest`, the part of `input` that couldn't be parsed, within
+/// `section` if one had started, and why the parser stopped there
+fn syntax_error(
+    input: &str,
+    rest: &str,
+    section: Option<&str>,
+    cause: Option<ErrMode<ContextError>>,
+) -> Error {
+    let before = &input[..input.len() - rest.len()];
+    let line_start = before.rfind('\n').map_or(0, |x| x + 1);
+    let near = input[line_start..]
+        .lines()
+        .next()
+        .and_then(|x| x.split(['=', ';', '#']).next())
+        .map(str::trim)
+        .filter(|x| !x.is_empty());
+
+    Error::ParseError {
+        line: line_of(input, rest),
+        column: before[line_start..].chars().count() + 1,
+        near: near.map(String::from),
+        section: section.map(String::from),
+        cause: cause.and_then(|x| x.into_inner().ok()).map(Cause::from),
+    }
+}
+
+/// `rest` from the start of its next line
+fn next_line(rest: &str) -> &str {
+    rest.find('\n').map_or("", |x| &rest[x + 1..])
+}
+
+/// How mistakes in a config are handled
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum ParseMode {
+    /// Skip lines that can't be read and games that don't load, collecting
+    /// why in [`Config::skipped`] and [`Config::broken`]
+    #[default]
+    Lenient,
+    /// Fail on the first line or game that doesn't load
+    Strict,
+}
+
+/// What's kept when a section or a key of one is given more than once. A
+/// section given again goes on from where it was left, so its keys are
+/// added to the ones before
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum DuplicatePolicy {
+    /// The last value of a key given
+    #[default]
+    LastWins,
+    /// The first value of a key given, later ones are ignored
+    FirstWins,
+    /// Anything given again is a mistake, skipped like a line that can't be
+    /// read in [`ParseMode::Lenient`]
+    Error,
 }
+impl DuplicatePolicy {
+    pub const ALL: [Self; 3] = [Self::LastWins, Self::FirstWins, Self::Error];
 
-pub type Items = HashMap<String, String>;
-type RawConfig = HashMap<String, Items>;
+    /// Name as it's saved in the settings
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::LastWins => "last",
+            Self::FirstWins => "first",
+            Self::Error => "error",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|x| x.name() == name)
+    }
+}
+impl std::fmt::Display for DuplicatePolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::LastWins => f.write_str(&tr!("duplicates-last")),
+            Self::FirstWins => f.write_str(&tr!("duplicates-first")),
+            Self::Error => f.write_str(&tr!("duplicates-error")),
+        }
+    }
+}
 
-fn parse(mut input: &str) -> ModalResult<RawConfig> {
-    let input = &mut input;
+/// How config files are read, see [`crate::model::Model::load_configs`]
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub struct LoadOptions {
+    pub mode: ParseMode,
+    pub encoding: Encoding,
+    pub duplicates: DuplicatePolicy,
+}
+
+/// [`parse`], skipping lines that can't be read in [`ParseMode::Lenient`].
+/// A bad header skips the whole section under it, as does a section given
+/// again with [`DuplicatePolicy::Error`]
+pub fn parse_with(input: &str, options: LoadOptions) -> Result<(RawConfig, Vec<Error>), Error> {
+    let mut skipped = vec![];
+    let mut skip = |error: Error| match options.mode {
+        ParseMode::Lenient => {
+            skipped.push(error);
+            Ok(())
+        }
+        ParseMode::Strict => Err(error),
+    };
+
+    let start = input
+        .find('[')
+        .ok_or_else(|| syntax_error(input, "", None, None))?;
+    let mut rest = &input[start..];
 
-    take_until(0.., '[').void().parse_next(input)?;
+    let skip_section = |rest: &mut &str| {
+        *rest = next_line(rest);
+        let _ = whitespace_and_comments.parse_next(rest);
+        while !rest.is_empty() && !rest.starts_with('[') {
+            *rest = next_line(rest);
+            let _ = whitespace_and_comments.parse_next(rest);
+        }
+    };
 
-    fn parse_section(input: &mut &str) -> ModalResult<(String, Items)> {
-        let header = header.parse_next(input)?;
-        whitespace_and_comments.parse_next(input)?;
+    let mut raw = RawConfig::new();
+    // Line each section and key was first given on
+    let mut first: HashMap<(String, Option<String>), usize> = HashMap::new();
+    while !rest.is_empty() {
+        let mut probe = rest;
+        let name = match header.parse_next(&mut probe) {
+            Ok(name) => name,
+            Err(e) => {
+                skip(syntax_error(input, rest, None, Some(e)))?;
+                skip_section(&mut rest);
+                continue;
+            }
+        };
+        let line = line_of(input, rest);
+        if let Some(&first_line) = first.get(&(name.clone(), None))
+            && options.duplicates == DuplicatePolicy::Error
+        {
+            skip(Error::Duplicate {
+                line,
+                first_line,
+                section: name,
+                key: None,
+            })?;
+            skip_section(&mut rest);
+            continue;
+        }
+        first.entry((name.clone(), None)).or_insert(line);
+        rest = probe;
+        let _ = whitespace_and_comments.parse_next(&mut rest);
 
-        let items: HashMap<String, String> =
-            repeat(1.., terminated(kv_pair, whitespace_and_comments)).parse_next(input)?;
+        let items = raw.entry(name.clone()).or_default();
+        while !rest.is_empty() && !rest.starts_with('[') {
+            let mut probe = rest;
+            let (key, value) = match kv_pair.parse_next(&mut probe) {
+                Ok(pair) => pair,
+                Err(e) => {
+                    // Anything else is a line that isn't a key, header or comment
+                    skip(syntax_error(input, probe, Some(&name), Some(e)))?;
+                    rest = next_line(rest);
+                    let _ = whitespace_and_comments.parse_next(&mut rest);
+                    continue;
+                }
+            };
+            let line = line_of(input, rest);
+            rest = probe;
+            let _ = whitespace_and_comments.parse_next(&mut rest);
 
-        Ok((header, items))
+            match first.get(&(name.clone(), Some(key.clone()))) {
+                None => {
+                    first.insert((name.clone(), Some(key.clone())), line);
+                    items.insert(key, value);
+                }
+                Some(_) if options.duplicates == DuplicatePolicy::LastWins => {
+                    items.insert(key, value);
+                }
+                Some(_) if options.duplicates == DuplicatePolicy::FirstWins => {}
+                Some(&first_line) => skip(Error::Duplicate {
+                    line,
+                    first_line,
+                    section: name.clone(),
+                    key: Some(key),
+                })?,
+            }
+        }
     }
 
-    repeat(1.., parse_section).parse_next(input)
+    Ok((raw, skipped))
+}
+
+/// Fails on the first line that can't be read, the last of any key given
+/// twice wins
+pub fn parse(input: &str) -> Result<RawConfig, Error> {
+    let options = LoadOptions {
+        mode: ParseMode::Strict,
+        ..Default::default()
+    };
+    parse_with(input, options).map(|x| x.0)
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -88,64 +352,237 @@ const LINE_ENDING: &str = "\r\n";
 #[cfg(not(windows))]
 const LINE_ENDING: &str = "\n";
 
-#[derive(Debug, PartialEq, Eq, Clone)]
+/// Value entered by the user besides the resolution, from `value1name`,
+/// `value1type` and `value1default`
+#[derive(Debug, PartialEq, Clone)]
+pub struct ValueSlot {
+    pub name: String,
+    /// Whether the value must be a whole number
+    pub valuetype: ValueType,
+    pub default: Option<f64>,
+}
+impl ValueSlot {
+    /// Parse what the user typed, only whole numbers for `int` slots
+    pub fn parse(&self, input: &str) -> Result<f64, Error> {
+        let input = input.trim();
+        let value = match self.valuetype {
+            ValueType::Int => input.parse::<u64>().ok().map(|x| x as f64),
+            _ => input.parse::<f64>().ok().filter(|x| x.is_finite()),
+        };
+
+        value.ok_or_else(|| Error::value_error(format!("Invalid {}: {input}", self.name)))
+    }
+
+    fn from_items(section: &str, items: &Items, n: usize) -> Result<Option<Self>, Error> {
+        let Some(name) = items.get(&format!("value{n}name")) else {
+            return Ok(None);
+        };
+
+        let valuetype = match items.get(&format!("value{n}type")) {
+            Some(x) => x
+                .parse::<ValueType>()
+                .map_err(|e| Error::config_field_parse(section, "valuetype", e.to_string()))?,
+            None => ValueType::Int,
+        };
+
+        let mut slot = Self {
+            name: name.clone(),
+            valuetype,
+            default: None,
+        };
+        if let Some(default) = items.get(&format!("value{n}default")) {
+            slot.default =
+                Some(slot.parse(default).map_err(|e| {
+                    Error::config_field_parse(section, "valuedefault", e.to_string())
+                })?);
+        }
+
+        Ok(Some(slot))
+    }
+}
+
+/// How a game's `details` are shown, from its `detailsformat`
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum DetailsFormat {
+    /// As they're written, with `\013\010` for a new line
+    #[default]
+    Text,
+    /// Headings, lists and emphasis, see [`crate::markdown::parse`]
+    Markdown,
+}
+
+#[derive(Debug, PartialEq, Clone)]
 pub struct AppSection {
     pub name: String,
     pub details: String,
+    pub details_format: DetailsFormat,
+    /// Cover or screenshot shown with the details, a file or an address,
+    /// see [`crate::cover::Source`]
+    pub image: Option<String>,
     pub checkfile: String,
+    /// Epic Games Launcher app or display name, to find the install folder
+    pub egsname: Option<String>,
     pub patches: Vec<PatchInfo>,
+    /// Edit sets with a `textfile`, reported after `patches`
+    pub text_patches: Vec<TextPatch>,
+    /// Edit sets with a `regkey`, reported after `text_patches`
+    pub registry_patches: Vec<RegistryPatch>,
+    /// Largest resolution the game is known to handle
+    pub maxwidth: Option<u16>,
+    pub maxheight: Option<u16>,
+    /// Extra values patches can write with `valueslot`
+    pub values: Vec<ValueSlot>,
 }
 impl AppSection {
-    fn from_items(name: impl Into<String>, items: &Items) -> Result<Self, Error> {
+    /// Label of every option of the optional patches, in config order
+    pub fn options(&self) -> Vec<&str> {
+        let mut options = Vec::new();
+        for label in self
+            .patches
+            .iter()
+            .filter(|x| x.optional)
+            .flat_map(|x| &x.label)
+        {
+            if !options.contains(&label.as_str()) {
+                options.push(label.as_str());
+            }
+        }
+        options
+    }
+
+    /// Every file patched, in edit set order
+    pub fn files(&self) -> Vec<&str> {
+        self.patches
+            .iter()
+            .map(|x| x.modfile.as_str())
+            .chain(self.text_patches.iter().map(|x| x.textfile.as_str()))
+            .collect()
+    }
+
+    pub fn from_items(name: impl Into<String>, items: &Items) -> Result<Self, Error> {
         let name: String = name.into();
         let details = items
             .get("details")
             .cloned()
             .map(|details| details.replace(r#"\013\010"#, LINE_ENDING))
             .ok_or(Error::config_missing_field(name.clone(), "details"))?;
+        let details_format = match items.get("detailsformat").map(|x| x.trim()) {
+            None => DetailsFormat::Text,
+            Some(x) if x.eq_ignore_ascii_case("text") => DetailsFormat::Text,
+            Some(x) if x.eq_ignore_ascii_case("markdown") => DetailsFormat::Markdown,
+            Some(x) => {
+                return Err(Error::config_field_parse(
+                    name,
+                    "detailsformat",
+                    format!("Invalid details format '{x}', expected text or markdown"),
+                ));
+            }
+        };
+        let image = items
+            .get("image")
+            .map(|x| x.trim().to_string())
+            .filter(|x| !x.is_empty());
         let checkfile = items
             .get("checkfile")
-            .cloned()
+            .map(|x| wine::config_path(x))
             .ok_or(Error::config_missing_field(name.clone(), "checkfile"))?;
+        let egsname = items.get("egsname").cloned();
+        let limit = |field: &'static str| {
+            items
+                .get(field)
+                .map(|x| {
+                    x.trim()
+                        .parse::<u16>()
+                        .map_err(|e| Error::config_field_parse(name.clone(), field, e.to_string()))
+                })
+                .transpose()
+        };
+        let maxwidth = limit("maxwidth")?;
+        let maxheight = limit("maxheight")?;
 
-        let first = PatchInfo::from_items(&name, items, None)?;
-        let mut patches = vec![first];
-
-        let mut idx = 1;
-        loop {
-            match PatchInfo::from_items(&name, items, Some(idx)) {
-                Ok(next) => {
-                    patches.push(next);
-                }
-                Err(Error::ConfigError(ConfigError::MissingRequiredField { .. })) => break,
-                Err(e) => return Err(e),
+        let mut patches = Vec::new();
+        let mut text_patches = Vec::new();
+        let mut registry_patches = Vec::new();
+        for idx in std::iter::once(None).chain((1..=u8::MAX).map(Some)) {
+            let prefix = idx.map_or(String::new(), |x| format!("p{x}"));
+            // The first edit set is required, the rest end at the first without
+            // a file or key to edit. A set that has one but is missing another
+            // field breaks the game, rather than dropping it and every later set
+            if items.contains_key(&format!("{prefix}textfile")) {
+                text_patches.push(TextPatch::from_items(&name, items, idx)?);
+            } else if items.contains_key(&format!("{prefix}regkey")) {
+                registry_patches.push(RegistryPatch::from_items(&name, items, idx)?);
+            } else if idx.is_none() || items.contains_key(&format!("{prefix}modfile")) {
+                patches.push(PatchInfo::from_items(&name, items, idx)?);
+            } else {
+                break;
             }
-            idx += 1;
+        }
+
+        let mut values = Vec::new();
+        while let Some(slot) = ValueSlot::from_items(&name, items, values.len() + 1)? {
+            values.push(slot);
+        }
+
+        if let Some(slot) = patches
+            .iter()
+            .filter_map(|x| x.valueslot)
+            .find(|&x| x.get() > values.len())
+        {
+            return Err(Error::config_field_parse(
+                name,
+                "valueslot",
+                format!("No value{slot}name for value slot {slot}"),
+            ));
         }
 
         Ok(Self {
             name,
             details,
+            details_format,
+            image,
             checkfile,
+            egsname,
             patches,
+            text_patches,
+            registry_patches,
+            maxwidth,
+            maxheight,
+            values,
         })
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone)]
+/// A game entry that failed to load
+#[derive(Debug, PartialEq, Clone)]
+pub struct BrokenSection {
+    pub name: String,
+    pub error: Error,
+}
+
+#[derive(Debug, PartialEq, Clone)]
 pub struct Config {
     pub apps: Apps,
     pub sections: Vec<AppSection>,
+    /// Entries skipped because they couldn't be loaded; they are not listed in `apps`
+    pub broken: Vec<BrokenSection>,
+    /// File each game was loaded from, by name; empty for the built in config
+    pub sources: HashMap<String, PathBuf>,
+    /// Why each line skipped in [`ParseMode::Lenient`] couldn't be read
+    pub skipped: Vec<Error>,
+    /// Files listed in `[Include]` as `file0`, `file1`.. Once loaded by
+    /// [`Self::load_file`] it's every file merged in, wherever it's from
+    pub includes: Vec<PathBuf>,
 }
 impl Config {
     fn get_apps(raw_config: &RawConfig) -> Result<Apps, Error> {
         let apps = raw_config
             .get("Apps")
-            .ok_or(Error::config_error("Missing 'Apps' Section"))?;
+            .ok_or(Error::config_missing_section("Apps"))?;
 
         let version = apps
             .get("version")
-            .ok_or(Error::config_error("Missing 'Apps.version'"))?;
+            .ok_or(Error::config_missing_field("Apps", "version"))?;
 
         let mut apps = apps
             .iter()
@@ -168,31 +605,271 @@ impl Config {
         })
     }
 
+    /// Load every valid game entry, collecting broken ones in [`Self::broken`]
+    /// and lines that can't be read in [`Self::skipped`]
+    ///
+    /// Only a file with no sections or a bad `[Apps]` section is an error
+    #[cfg(test)]
     pub fn new(input: &str) -> Result<Self, Error> {
-        let raw_config: RawConfig = parse(input)?;
-        let apps = Self::get_apps(&raw_config)?;
+        Self::load(input, Format::Ini, LoadOptions::default())
+    }
+
+    /// A config written in `format`, where any mistake is an error in
+    /// [`ParseMode::Strict`]. TOML and JSON files are always read strictly,
+    /// only their games can be skipped
+    pub fn load(input: &str, format: Format, options: LoadOptions) -> Result<Self, Error> {
+        let config = match format {
+            Format::Ini => {
+                let (raw, skipped) = parse_with(input, options)?;
+                Self {
+                    skipped,
+                    ..Self::from_raw(&raw)?
+                }
+            }
+            Format::Toml => Self::from_raw(&structured::parse_toml(input)?.to_raw())?,
+            Format::Json => Self::from_raw(&structured::parse_json(input)?.to_raw())?,
+        };
+
+        match config.broken.first() {
+            Some(broken) if options.mode == ParseMode::Strict => Err(broken.error.clone()),
+            _ => Ok(config),
+        }
+    }
+
+    fn from_raw(raw_config: &RawConfig) -> Result<Self, Error> {
+        let mut apps = Self::get_apps(raw_config)?;
 
         let mut sections = Vec::with_capacity(apps.apps.len());
+        let mut broken = vec![];
         for header in &apps.apps {
             let section = raw_config
                 .get(header)
-                .ok_or(Error::config_error(format!("Missing section {header}")))?;
+                .ok_or(Error::config_missing_section(header))
+                .and_then(|section| AppSection::from_items(header, section));
+
+            match section {
+                Ok(section) => sections.push(section),
+                Err(error) => broken.push(BrokenSection {
+                    name: header.clone(),
+                    error,
+                }),
+            }
+        }
+
+        apps.apps.retain(|x| !broken.iter().any(|b| b.name == *x));
+
+        Ok(Self {
+            apps,
+            sections,
+            broken,
+            sources: HashMap::new(),
+            skipped: vec![],
+            includes: Self::get_includes(raw_config),
+        })
+    }
+
+    fn get_includes(raw_config: &RawConfig) -> Vec<PathBuf> {
+        let Some(items) = raw_config.get("Include") else {
+            return vec![];
+        };
+        (0..)
+            .map_while(|i| items.get(&format!("file{i}")))
+            .map(PathBuf::from)
+            .collect()
+    }
+
+    /// The config at `path` with the files it includes merged in before it,
+    /// so its own games win. In [`ParseMode::Lenient`] an include that
+    /// doesn't load is skipped like a line that can't be read
+    pub fn load_file(path: &Path, options: LoadOptions) -> Result<Self, Error> {
+        Self::load_included(path, options, &mut vec![])
+    }
+
+    /// [`Self::load_file`], where `chain` is the files that included this
+    /// one, to stop one including itself
+    fn load_included(
+        path: &Path,
+        options: LoadOptions,
+        chain: &mut Vec<PathBuf>,
+    ) -> Result<Self, Error> {
+        let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        if let Some(i) = chain.iter().position(|x| *x == canonical) {
+            let cycle = chain[i..]
+                .iter()
+                .chain([&canonical])
+                .map(|x| x.display().to_string())
+                .collect::<Vec<_>>()
+                .join(" -> ");
+            return Err(Error::config_field_parse(
+                "Include",
+                "file",
+                format!("the config includes itself: {cycle}"),
+            ));
+        }
+
+        let text = encoding::read(path, options.encoding)?;
+        let mut config = Self::load(&text, Format::from_path(path), options)?;
+        config.set_source(path);
+        config.skipped = std::mem::take(&mut config.skipped)
+            .into_iter()
+            .map(|e| Error::in_config_file(path.display().to_string(), e))
+            .collect();
+
+        let dir = path.parent().unwrap_or(Path::new(""));
+        let includes: Vec<_> = config.includes.iter().map(|x| dir.join(x)).collect();
+        config.includes = vec![];
+
+        chain.push(canonical);
+        let mut merged: Option<Config> = None;
+        for include in includes {
+            let included = Self::load_included(&include, options, chain)
+                .map_err(|e| Error::in_config_file(include.display().to_string(), e));
+            match included {
+                Ok(mut included) => {
+                    included.includes.insert(0, include);
+                    match &mut merged {
+                        Some(merged) => {
+                            merged.merge(included);
+                        }
+                        None => merged = Some(included),
+                    }
+                }
+                Err(e) if options.mode == ParseMode::Lenient => config.skipped.push(e),
+                Err(e) => return Err(e),
+            }
+        }
+        chain.pop();
+
+        Ok(match merged {
+            Some(mut merged) => {
+                merged.apps.version = config.apps.version.clone();
+                merged.merge(config);
+                merged
+            }
+            None => config,
+        })
+    }
 
-            let section = AppSection::from_items(header, section)?;
-            sections.push(section);
+    /// Mark every game as loaded from `path`
+    pub fn set_source(&mut self, path: &Path) {
+        self.sources = self
+            .apps
+            .apps
+            .iter()
+            .map(|x| (x.clone(), path.to_path_buf()))
+            .collect();
+    }
+
+    /// Add the games of `other`, which replace any with the same name, keeping
+    /// their place in the list
+    ///
+    /// Returns the names of the games replaced
+    pub fn merge(&mut self, other: Config) -> Vec<String> {
+        let mut replaced = vec![];
+        for section in other.sections {
+            match self.sections.iter_mut().find(|x| x.name == section.name) {
+                Some(existing) => {
+                    replaced.push(section.name.clone());
+                    *existing = section;
+                }
+                None => {
+                    self.apps.apps.push(section.name.clone());
+                    self.sections.push(section);
+                }
+            }
+        }
+
+        self.broken.extend(other.broken);
+        self.broken.retain(|x| !self.apps.apps.contains(&x.name));
+        self.sources.extend(other.sources);
+        self.skipped.extend(other.skipped);
+        self.includes.extend(other.includes);
+
+        replaced
+    }
+}
+
+/// How a config file is written
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum Format {
+    /// The patches.ini of the original UniWS
+    #[default]
+    Ini,
+    Toml,
+    Json,
+}
+impl Format {
+    /// Extensions of config files, for file dialogs
+    pub const EXTENSIONS: [&str; 3] = ["ini", "toml", "json"];
+
+    /// Told apart by extension, anything unknown is read as INI
+    pub fn from_path(path: &Path) -> Self {
+        let extension = path.extension().and_then(|x| x.to_str()).unwrap_or("");
+        match extension.to_ascii_lowercase().as_str() {
+            "toml" => Self::Toml,
+            "json" => Self::Json,
+            _ => Self::Ini,
+        }
+    }
+
+    /// Extension a file in this format is given
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Ini => "ini",
+            Self::Toml => "toml",
+            Self::Json => "json",
         }
+    }
+}
+
+/// Name of the config looked for on startup
+pub const FILE_NAME: &str = "patches.ini";
 
-        Ok(Self { apps, sections })
+/// Built into the app, used when no [`FILE_NAME`] is found
+pub const DEFAULT: &str = include_str!("../patches.ini");
+
+/// Where [`FILE_NAME`] is looked for, in order: the working folder, the
+/// folder of the executable, then the user's config folder
+/// (`~/.config/uniws` or `%APPDATA%\uniws`)
+pub fn search_paths() -> Vec<PathBuf> {
+    let exe_dir = std::env::current_exe()
+        .ok()
+        .and_then(|x| x.parent().map(Path::to_path_buf));
+    let config_dir = dirs::config_dir().map(|x| x.join("uniws"));
+
+    let mut paths = Vec::new();
+    for dir in [Some(PathBuf::new()), exe_dir, config_dir]
+        .into_iter()
+        .flatten()
+    {
+        let path = dir.join(FILE_NAME);
+        if !paths.contains(&path) {
+            paths.push(path);
+        }
     }
+    paths
+}
+
+/// The first config in [`search_paths`] that exists
+pub fn find() -> Option<PathBuf> {
+    search_paths().into_iter().find(|x| x.is_file())
+}
+
+/// `~/.config/uniws/personal.ini` (or the platform equivalent), where games
+/// written in the editor are saved
+pub fn personal_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|x| x.join("uniws").join("personal.ini"))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::document::Document;
+    use crate::error::ConfigError;
+    use crate::testing::TempDir;
     use pretty_assertions::assert_eq;
-    use std::collections::HashMap;
 
-    fn items_map<T, U>(items: T) -> HashMap<String, String>
+    fn items_map<T, U>(items: T) -> Items
     where
         T: IntoIterator<Item = (U, U)>,
         U: Into<String>,
@@ -236,18 +913,617 @@ mod tests {
             a0 = One
             a1=Two
             a2="Three"
-        "#;
+            a3="Four; with a semicolon" ; Quoted
+            a4 = "Five" # Hash comment
+            a5=Six#Seven
+            occur = 1 ; patch menu code"#;
 
-        let expected = HashMap::from_iter([(
+        let expected = RawConfig::from_iter([(
             "Apps".to_string(),
             items_map([
                 ("version", "1.0"),
                 ("a0", "One"),
                 ("a1", "Two"),
                 ("a2", "Three"),
+                ("a3", "Four; with a semicolon"),
+                ("a4", "Five"),
+                ("a5", "Six"),
+                ("occur", "1"),
             ]),
         )]);
 
-        assert_eq!(parse(file), Ok(expected))
+        assert_eq!(parse(file), Ok(expected));
+
+        // Sections and keys come out in the order they're written
+        let raw = parse("[Zeta]\nb=1\na=2\n\n[Apps]\nversion=1\n\n[Middle]\nc=3\n").unwrap();
+        assert_eq!(
+            raw.keys().map(String::as_str).collect::<Vec<_>>(),
+            ["Zeta", "Apps", "Middle"]
+        );
+        assert_eq!(
+            raw["Zeta"].keys().map(String::as_str).collect::<Vec<_>>(),
+            ["b", "a"]
+        );
+    }
+
+    #[test]
+    fn escape_test() {
+        let file = r#"
+[Test Game]
+details = "He said \"widescreen\"" ; quoted
+path="C:\Games\Test\\"
+share="\\\server\share"
+"#;
+        let raw = parse(file).unwrap();
+        let items = &raw["Test Game"];
+        assert_eq!(items["details"], r#"He said "widescreen""#);
+        assert_eq!(items["path"], r"C:\Games\Test\");
+        assert_eq!(items["share"], r"\\server\share");
+
+        for value in [
+            r#"He said "widescreen""#,
+            r"C:\Games\",
+            r"\\server\share",
+            r#"\""#,
+        ] {
+            let written = quote(value);
+            assert_eq!(quoted.parse(written.as_str()), Ok(value.to_string()));
+
+            let mut document = Document::parse("[A]\n");
+            document.set("A", "b", value);
+            assert_eq!(parse(&document.to_string()).unwrap()["A"]["b"], value);
+            assert_eq!(
+                Document::parse(&document.to_string()).get("A", "b"),
+                Some(value)
+            );
+        }
+    }
+
+    #[test]
+    fn continuation_test() {
+        let file = "\
+[Test Game]
+details=Point to the folder \\
+    containing game.exe ; the launcher
+sig=80020000\\
+    C701E001\\
+    0000
+occur=1
+";
+        let raw = parse(file).unwrap();
+        let items = &raw["Test Game"];
+        assert_eq!(items["details"], "Point to the folder containing game.exe");
+        assert_eq!(items["sig"], "80020000C701E0010000");
+        assert_eq!(items["occur"], "1");
+
+        let document = Document::parse(file);
+        assert_eq!(document.to_string(), file);
+        assert_eq!(document.to_raw(), raw);
+        assert_eq!(document.line("Test Game", Some("occur")), Some(7));
+    }
+
+    #[test]
+    fn key_chars_test() {
+        // Keys other tools write alongside the ones uniws reads
+        let file = r#"
+[Apps]
+version=1.03
+a0=Test Game
+
+[Test Game]
+details=Test
+checkfile=game.exe
+check.file=game.exe
+modfile=game.exe
+sig=80020000
+sigwild=0000
+xoffset=0
+p1_xoffset=2
+last-updated=2009-03-14
+occur=1
+"#;
+        let raw = parse(file).unwrap();
+        let items = &raw["Test Game"];
+        assert_eq!(items["check.file"], "game.exe");
+        assert_eq!(items["p1_xoffset"], "2");
+        assert_eq!(items["last-updated"], "2009-03-14");
+
+        // Unknown keys are left alone by the loader
+        let config = Config::new(file).unwrap();
+        assert_eq!(config.sections[0].patches.len(), 1);
+        assert!(config.broken.is_empty());
+    }
+
+    #[test]
+    fn parse_error_test() {
+        let file =
+            "[Apps]\nversion=1.0\na0=Test Game\n\n[Test Game]\ndetails=Test\np1sig wild=00\n";
+        let error = parse(file).unwrap_err();
+        let Error::ParseError {
+            line,
+            column,
+            near,
+            section,
+            cause,
+        } = &error
+        else {
+            panic!("{error:?}");
+        };
+        assert_eq!(
+            (*line, *column, near.as_deref(), section.as_deref()),
+            (7, 7, Some("p1sig wild"), Some("Test Game"))
+        );
+        assert_eq!(
+            error.to_string(),
+            "Syntax error at line 7, column 7 near `p1sig wild` in [Test Game]"
+        );
+        // What the parser expected is kept as the source, not the message
+        assert_eq!(cause.as_ref().unwrap().to_string(), "expected `=`");
+
+        let error = parse("[Apps]\nversion=1.0\n[Test Game\n").unwrap_err();
+        assert!(matches!(
+            error,
+            Error::ParseError {
+                line: 3,
+                column: 1,
+                section: None,
+                ..
+            }
+        ));
+        let source = std::error::Error::source(&error).unwrap();
+        assert_eq!(source.to_string(), "expected `]`");
+        assert!(matches!(
+            parse("; nothing here\n"),
+            Err(Error::ParseError { line: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn broken_sections_test() {
+        let file = r#"
+            [Apps]
+            version=1.0
+            a0=Good
+            a1=Typo
+            a2=Missing
+
+            [Good]
+            details=Fine
+            checkfile=game.exe
+            modfile=game.exe
+            sig=8002
+            sigwild=00
+            occur=1
+
+            [Typo]
+            details=Broken
+            checkfile=game.exe
+            modfile=game.exe
+            sig=8002
+            sigwild=00
+            occur=one
+        "#;
+
+        let config = Config::new(file).unwrap();
+        assert_eq!(config.apps.apps, ["Good"]);
+        assert_eq!(config.sections.len(), 1);
+
+        let broken = config
+            .broken
+            .iter()
+            .map(|x| x.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(broken, ["Typo", "Missing"]);
+        assert!(matches!(
+            config.broken[0].error,
+            Error::ConfigError(ConfigError::FieldParseError { field: "occur", .. })
+        ));
+
+        let strict = LoadOptions {
+            mode: ParseMode::Strict,
+            ..Default::default()
+        };
+        assert!(matches!(
+            Config::load(file, Format::Ini, strict),
+            Err(Error::ConfigError(ConfigError::FieldParseError {
+                field: "occur",
+                ..
+            }))
+        ));
+    }
+
+    #[test]
+    fn parse_mode_test() {
+        let file = "\
+[Apps]
+version=1.0
+a0=Test Game
+not a key
+
+[Test Game
+details=Skipped
+
+[Test Game]
+details=Test
+p1sig wild=00
+checkfile=game.exe
+";
+        let strict = LoadOptions {
+            mode: ParseMode::Strict,
+            ..Default::default()
+        };
+        let (raw, skipped) = parse_with(file, LoadOptions::default()).unwrap();
+        assert_eq!(raw["Apps"].len(), 2);
+        assert_eq!(raw["Test Game"]["checkfile"], "game.exe");
+        assert_eq!(raw["Test Game"]["details"], "Test");
+        let lines = skipped
+            .iter()
+            .map(|x| match x {
+                Error::ParseError { line, .. } => *line,
+                _ => 0,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(lines, [4, 6, 11]);
+
+        assert!(matches!(
+            parse_with(file, strict),
+            Err(Error::ParseError { line: 4, .. })
+        ));
+        assert!(Config::load(file, Format::Ini, strict).is_err());
+        let config = Config::load(file, Format::Ini, LoadOptions::default()).unwrap();
+        assert_eq!(config.skipped.len(), 3);
+    }
+
+    #[test]
+    fn duplicates_test() {
+        let file = "\
+[Apps]
+version=1.0
+a0=Test Game
+
+[Test Game]
+details=First
+checkfile=game.exe
+
+[Test Game]
+details=Second
+modfile=game.exe
+";
+        let (raw, skipped) = parse_with(file, LoadOptions::default()).unwrap();
+        assert_eq!(raw["Test Game"]["details"], "Second");
+        assert_eq!(raw["Test Game"]["checkfile"], "game.exe");
+        assert_eq!(raw["Test Game"]["modfile"], "game.exe");
+        assert!(skipped.is_empty());
+
+        let first = LoadOptions {
+            duplicates: DuplicatePolicy::FirstWins,
+            ..Default::default()
+        };
+        let (raw, _) = parse_with(file, first).unwrap();
+        assert_eq!(raw["Test Game"]["details"], "First");
+        assert_eq!(raw["Test Game"]["modfile"], "game.exe");
+
+        let error = LoadOptions {
+            duplicates: DuplicatePolicy::Error,
+            ..Default::default()
+        };
+        let (raw, skipped) = parse_with(file, error).unwrap();
+        assert!(!raw["Test Game"].contains_key("modfile"));
+        assert_eq!(
+            skipped,
+            [Error::Duplicate {
+                line: 9,
+                first_line: 5,
+                section: "Test Game".into(),
+                key: None,
+            }]
+        );
+        assert_eq!(
+            parse_with(
+                "[Apps]\nversion=1.0\nversion=1.1\n",
+                LoadOptions {
+                    mode: ParseMode::Strict,
+                    ..error
+                }
+            )
+            .unwrap_err()
+            .to_string(),
+            "version in [Apps] is given again at line 3, first at line 2"
+        );
+
+        for policy in DuplicatePolicy::ALL {
+            assert_eq!(DuplicatePolicy::from_name(policy.name()), Some(policy));
+        }
+    }
+
+    #[test]
+    fn include_test() {
+        let tmp = TempDir::new("include");
+        std::fs::create_dir_all(tmp.join("racing")).unwrap();
+        let game = |name: &str, details: &str| {
+            format!(
+                "[{name}]\ndetails={details}\ncheckfile=game.exe\nmodfile=game.exe\n\
+                 sig=8002\nsigwild=00\noccur=1\n"
+            )
+        };
+        let (main, racing) = (tmp.join("patches.ini"), tmp.join("racing/racing.ini"));
+        std::fs::write(
+            &main,
+            format!(
+                "[Apps]\nversion=2.0\na0=Main Game\n\n[Include]\nfile0=racing/racing.ini\n\n{}",
+                game("Main Game", "Main")
+            ),
+        )
+        .unwrap();
+        std::fs::write(
+            &racing,
+            format!(
+                "[Apps]\nversion=1.0\na0=Race Game\na1=Main Game\n\n{}\n{}",
+                game("Race Game", "Race"),
+                game("Main Game", "Included")
+            ),
+        )
+        .unwrap();
+
+        let config = Config::load_file(&main, LoadOptions::default()).unwrap();
+        assert_eq!(config.apps.version, "2.0");
+        assert_eq!(config.apps.apps, ["Race Game", "Main Game"]);
+        // The including file's own games win
+        assert_eq!(config.sections[1].details, "Main");
+        assert_eq!(config.sources["Race Game"], tmp.join("racing/racing.ini"));
+        assert_eq!(config.sources["Main Game"], main);
+        assert_eq!(config.includes, [tmp.join("racing/racing.ini")]);
+
+        // Including a file that includes it back
+        let cycle =
+            std::fs::read_to_string(&racing).unwrap() + "\n[Include]\nfile0=../patches.ini\n";
+        std::fs::write(&racing, cycle).unwrap();
+        let config = Config::load_file(&main, LoadOptions::default()).unwrap();
+        assert_eq!(config.apps.apps, ["Race Game", "Main Game"]);
+        assert!(config.skipped[0].to_string().contains("includes itself"));
+        let strict = LoadOptions {
+            mode: ParseMode::Strict,
+            ..Default::default()
+        };
+        assert!(Config::load_file(&main, strict).is_err());
+    }
+
+    #[test]
+    fn merge_test() {
+        let section = |name: &str, details: &str| {
+            format!(
+                "[{name}]\ndetails={details}\ncheckfile=game.exe\nmodfile=game.exe\n\
+                 sig=8002\nsigwild=00\noccur=1\n"
+            )
+        };
+        let mut config = Config::new(&format!(
+            "[Apps]\nversion=1.0\na0=One\na1=Two\na2=Broken\n{}{}",
+            section("One", "Old"),
+            section("Two", "Kept"),
+        ))
+        .unwrap();
+        let mut other = Config::new(&format!(
+            "[Apps]\nversion=2.0\na0=Three\na1=One\na2=Broken\na3=Missing\n{}{}{}",
+            section("Three", "New"),
+            section("One", "Replaced"),
+            section("Broken", "Fixed"),
+        ))
+        .unwrap();
+        config.set_source(Path::new("one.ini"));
+        other.set_source(Path::new("two.ini"));
+
+        assert_eq!(config.merge(other), ["One"]);
+        assert_eq!(config.apps.version, "1.0");
+        assert_eq!(config.apps.apps, ["One", "Two", "Three", "Broken"]);
+        let details = config
+            .sections
+            .iter()
+            .map(|x| x.details.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(details, ["Replaced", "Kept", "New", "Fixed"]);
+        let broken = config
+            .broken
+            .iter()
+            .map(|x| x.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(broken, ["Missing"]);
+        let sources = config
+            .apps
+            .apps
+            .iter()
+            .map(|x| config.sources[x].to_str().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(sources, ["two.ini", "one.ini", "two.ini", "two.ini"]);
+    }
+
+    #[test]
+    fn default_config_test() {
+        let config = Config::new(DEFAULT).unwrap();
+        assert_eq!(config.sections.len(), config.apps.apps.len());
+        assert!(!config.sections.is_empty());
+        assert!(config.broken.is_empty());
+
+        let paths = search_paths();
+        assert_eq!(paths[0], Path::new(FILE_NAME));
+        assert!(paths.iter().all(|x| x.ends_with(FILE_NAME)));
+    }
+
+    #[test]
+    fn details_format_test() {
+        let items = |format: &str| {
+            items_map([
+                ("details", r"# Fix\013\010- **HUD**"),
+                ("detailsformat", format),
+                ("checkfile", "game.exe"),
+                ("modfile", "game.exe"),
+                ("sig", "8002"),
+                ("sigwild", "00"),
+                ("occur", "1"),
+            ])
+        };
+        let section = AppSection::from_items("Game", &items(" Markdown ")).unwrap();
+        assert_eq!(section.details_format, DetailsFormat::Markdown);
+        assert_eq!(section.details, format!("# Fix{LINE_ENDING}- **HUD**"));
+        let mut plain = items("");
+        plain.shift_remove("detailsformat");
+        let section = AppSection::from_items("Game", &plain).unwrap();
+        assert_eq!(section.details_format, DetailsFormat::Text);
+        assert!(matches!(
+            AppSection::from_items("Game", &items("html")),
+            Err(Error::ConfigError(ConfigError::FieldParseError {
+                field: "detailsformat",
+                ..
+            }))
+        ));
+    }
+
+    #[test]
+    fn value_slots_test() {
+        let file = r#"
+            [Apps]
+            version=1.0
+            a0=Game
+            a1=Missing slot
+
+            [Game]
+            details=FOV
+            checkfile=game.exe
+            value1name=FOV
+            value1type=f32
+            value1default=75
+            value2name=Frame limit
+            modfile=game.exe
+            sig=0000B442
+            sigwild=0000
+            xoffset=0
+            valuetype=f32
+            valueslot=1
+            occur=1
+
+            [Missing slot]
+            details=FOV
+            checkfile=game.exe
+            value1name=FOV
+            modfile=game.exe
+            sig=8002
+            sigwild=00
+            xoffset=0
+            valueslot=2
+            occur=1
+        "#;
+
+        let config = Config::new(file).unwrap();
+        let values = &config.sections[0].values;
+        assert_eq!(
+            values,
+            &[
+                ValueSlot {
+                    name: "FOV".into(),
+                    valuetype: ValueType::F32,
+                    default: Some(75.0),
+                },
+                ValueSlot {
+                    name: "Frame limit".into(),
+                    valuetype: ValueType::Int,
+                    default: None,
+                },
+            ]
+        );
+        assert_eq!(values[0].parse(" 90.5 ").unwrap(), 90.5);
+        assert!(values[1].parse("60.5").is_err());
+        assert!(matches!(
+            config.broken[0].error,
+            Error::ConfigError(ConfigError::FieldParseError {
+                field: "valueslot",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn edit_sets_test() {
+        let file = r#"
+            [Apps]
+            version=1.0
+            a0=Game
+            a1=Half written
+            a2=No operand
+            a3=No replace
+
+            [Game]
+            details=Two sets
+            checkfile=game.exe
+            modfile=game.exe
+            sig=8002
+            sigwild=00
+            occur=1
+            p1modfile=game.exe
+            p1sig=8003
+            p1sigwild=00
+            p1occur=1
+            p3modfile=game.exe
+
+            [Half written]
+            details=Missing sigaltwild
+            checkfile=game.exe
+            modfile=game.exe
+            sig=8002
+            sigwild=00
+            occur=1
+            p1modfile=game.exe
+            p1sig=8003
+            p1sigwild=00
+            p1sigalt1=8004
+            p1occur=1
+            p2modfile=game.exe
+            p2sig=8005
+            p2sigwild=00
+            p2occur=1
+
+            [No operand]
+            details=Missing operand
+            checkfile=game.exe
+            modfile=game.exe
+            sig=8002
+            sigwild=00
+            occur=1
+            p1modfile=game.exe
+            p1sig=8003
+            p1sigwild=00
+            p1op=or
+            p1opoffset=1
+            p1occur=1
+
+            [No replace]
+            details=Text set missing replace
+            checkfile=game.exe
+            modfile=game.exe
+            sig=8002
+            sigwild=00
+            occur=1
+            p1textfile=Game.ini
+            p1find=ResX=
+            p2textfile=Game.ini
+            p2find=ResY=
+            p2replace=ResY={height}
+        "#;
+
+        let config = Config::new(file).unwrap();
+        // The list ends at p2, which has no modfile
+        assert_eq!(config.sections[0].patches.len(), 2);
+        assert_eq!(config.broken.len(), 3);
+        assert_eq!(config.broken[0].name, "Half written");
+        assert_eq!(
+            config.broken[0].error,
+            Error::config_missing_field("Half written", "sigaltwild")
+        );
+        assert_eq!(
+            config.broken[1].error,
+            Error::config_missing_field("No operand", "operand")
+        );
+        assert_eq!(
+            config.broken[2].error,
+            Error::config_missing_field("No replace", "replace")
+        );
     }
 }
@@ -0,0 +1,78 @@
+use std::path::{Path, PathBuf};
+
+use crate::{error::Error, remote, wine};
+
+/// Where a game's `image` is loaded from
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub enum Source {
+    Url(String),
+    File(PathBuf),
+}
+impl Source {
+    /// `image` as it's written in the config at `config`: an `https://`
+    /// address, or a file relative to the config. Files of the built in
+    /// config are relative to the working folder
+    pub fn locate(image: &str, config: Option<&Path>) -> Self {
+        if image.starts_with("https://") || image.starts_with("http://") {
+            return Self::Url(image.to_string());
+        }
+        let dir = config.and_then(Path::parent).unwrap_or(Path::new(""));
+        Self::File(wine::resolve(dir, image))
+    }
+}
+
+/// An image ready to be shown. Builds without the `images` feature can't
+/// decode one, so they keep the bytes
+#[cfg(feature = "images")]
+pub type Picture = iced::widget::image::Handle;
+#[cfg(not(feature = "images"))]
+pub type Picture = Vec<u8>;
+
+/// Where loading the image of a [`Source`] is up to
+#[derive(Debug, Clone)]
+pub enum State {
+    Loading,
+    Loaded(Picture),
+    Failed(Error),
+}
+
+/// `bytes` of an image file, in any format iced can decode, ready to be
+/// shown
+pub fn picture(bytes: Vec<u8>) -> Picture {
+    #[cfg(feature = "images")]
+    let picture = iced::widget::image::Handle::from_bytes(bytes);
+    #[cfg(not(feature = "images"))]
+    let picture = bytes;
+    picture
+}
+
+/// Read or download the image at `source`
+pub async fn load(source: Source) -> Result<Picture, Error> {
+    let bytes = match source {
+        Source::Url(url) => remote::fetch_bytes(&url).await?,
+        Source::File(path) => tokio::fs::read(path).await?,
+    };
+    Ok(picture(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locate_test() {
+        let config = Path::new("configs").join("patches.ini");
+        assert_eq!(
+            Source::locate("https://example.com/cover.png", Some(&config)),
+            Source::Url("https://example.com/cover.png".into())
+        );
+        assert_eq!(
+            Source::locate("images/cover.png", Some(&config)),
+            Source::File(Path::new("configs").join("images").join("cover.png"))
+        );
+        assert_eq!(
+            Source::locate("cover.png", None),
+            Source::File("cover.png".into())
+        );
+    }
+}
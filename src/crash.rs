@@ -0,0 +1,118 @@
+use std::{
+    backtrace::Backtrace,
+    panic::Location,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::SystemTime,
+};
+
+use crate::i18n::tr;
+
+/// Files the loaded config was read from, for the report
+static CONFIG: Mutex<String> = Mutex::new(String::new());
+
+/// `~/.local/share/uniws/crashes` (or the platform equivalent)
+pub fn dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|x| x.join("uniws").join("crashes"))
+}
+
+/// Remember the config that's loaded, to name it if the app crashes
+pub fn set_config(name: &str) {
+    *CONFIG.lock().unwrap_or_else(|e| e.into_inner()) = name.to_string();
+}
+
+/// On a panic, write a report to [`dir`] and tell the user where it is, in
+/// a dialog if `gui`, rather than the window just disappearing
+pub fn install(gui: bool) {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        previous(info);
+
+        let config = CONFIG.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        let report = report(
+            info.payload_as_str().unwrap_or("Box<dyn Any>"),
+            info.location(),
+            &config,
+            &Backtrace::force_capture(),
+        );
+        tracing::error!("{report}");
+        let message = match dir()
+            .ok_or(())
+            .and_then(|x| write(&x, &report).map_err(|_| ()))
+        {
+            Ok(path) => tr!("crash-report", path = path.display()),
+            Err(()) => tr!("crash-no-report", report = report),
+        };
+        match gui {
+            true => {
+                rfd::MessageDialog::new()
+                    .set_level(rfd::MessageLevel::Error)
+                    .set_buttons(rfd::MessageButtons::Ok)
+                    .set_description(message)
+                    .show();
+            }
+            false => eprintln!("{message}"),
+        }
+    }));
+}
+
+/// What's written to the crash report of a panic with `message` at `location`
+fn report(
+    message: &str,
+    location: Option<&Location>,
+    config: &str,
+    backtrace: &Backtrace,
+) -> String {
+    let location = location
+        .map(|x| format!("{}:{}:{}", x.file(), x.line(), x.column()))
+        .unwrap_or_default();
+    let config = match config.is_empty() {
+        true => "none",
+        false => config,
+    };
+    format!(
+        "uniws-rs {}\nConfig: {config}\nPanicked at {location}: {message}\nThread: {}\n\n{backtrace}\n",
+        env!("CARGO_PKG_VERSION"),
+        std::thread::current().name().unwrap_or("unnamed"),
+    )
+}
+
+/// Write `report` to a new file in `dir`, named after the time
+fn write(dir: &Path, report: &str) -> std::io::Result<PathBuf> {
+    std::fs::create_dir_all(dir)?;
+    let time = humantime::format_rfc3339_seconds(SystemTime::now())
+        .to_string()
+        .replace(':', "-");
+    let path = dir.join(format!("crash-{time}.txt"));
+    std::fs::write(&path, report)?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+
+    #[test]
+    fn report_test() {
+        let report = report(
+            "Out of bounds",
+            Some(Location::caller()),
+            "patches.ini",
+            &Backtrace::disabled(),
+        );
+        assert!(
+            report.starts_with(&format!(
+                "uniws-rs {}\nConfig: patches.ini\nPanicked at src/crash.rs:",
+                env!("CARGO_PKG_VERSION")
+            )),
+            "{report}"
+        );
+        assert!(report.contains(": Out of bounds\nThread: "), "{report}");
+        assert!(report.ends_with("\n\ndisabled backtrace\n"), "{report}");
+
+        let dir = TempDir::new("crash");
+        let path = write(&dir, &report).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), report);
+    }
+}
@@ -0,0 +1,251 @@
+use std::{fmt::Write as _, ops::Range};
+
+use crate::{
+    error::Error,
+    signature::Signature,
+    value::{Endian, ValueType, WordSize},
+};
+
+/// Bytes kept either side of a change to start with
+pub const CONTEXT: usize = 8;
+
+/// Changes this close together become one edit set
+pub const MERGE_GAP: usize = 4;
+
+/// Longest sig grown while looking for a unique one
+pub const MAX_SIG: usize = 64;
+
+/// Ranges of bytes that differ, with nearby changes merged
+pub fn changed_regions(original: &[u8], patched: &[u8]) -> Vec<Range<usize>> {
+    let mut regions: Vec<Range<usize>> = Vec::new();
+
+    for i in (0..original.len()).filter(|&i| original[i] != patched[i]) {
+        match regions.last_mut() {
+            Some(last) if i - last.end <= MERGE_GAP => last.end = i + 1,
+            _ => regions.push(i..i + 1),
+        }
+    }
+
+    regions
+}
+
+/// Settings of one edit set, in the order they're written
+type Fields = Vec<(&'static str, String)>;
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|x| format!("{x:02X}")).collect()
+}
+
+/// Smallest sig around `region` that's unique in `original`, or the longest
+/// tried if none is. Changed bytes are wildcards so the sig still matches once
+/// patched. Returns the start of the sig and its fields
+fn derive_sig(original: &[u8], patched: &[u8], region: &Range<usize>) -> (usize, Fields) {
+    let mut context = CONTEXT;
+    loop {
+        let start = region.start.saturating_sub(context);
+        let end = (region.end + context).min(original.len());
+
+        let sig = &original[start..end];
+        let sigwild = (start..end)
+            .map(|i| if original[i] == patched[i] { '0' } else { '1' })
+            .collect::<String>();
+
+        let signature = Signature::from_string("", &hex(sig), &sigwild)
+            .expect("sig and sigwild are built the same length");
+        let matches = signature.find_iter(original).collect::<Vec<_>>();
+
+        let grown = end - start >= MAX_SIG || (start == 0 && end == original.len());
+        if matches.len() == 1 || grown {
+            let mut fields = vec![("sig", hex(sig)), ("sigwild", sigwild)];
+            match matches.iter().position(|&x| x == start) {
+                Some(0) | None => {}
+                Some(i) => fields.push(("occurfirst", (i + 1).to_string())),
+            }
+            fields.push(("occur", "1".into()));
+            return (start, fields);
+        }
+
+        context += CONTEXT;
+    }
+}
+
+/// Width and height writes covering every change in `region`, if there are any
+fn resolution_fields(
+    patched: &[u8],
+    sig_start: usize,
+    region: &Range<usize>,
+    changed: impl Fn(usize) -> bool,
+    (width, height): (u16, u16),
+) -> Option<Fields> {
+    [ValueType::Int, ValueType::F32]
+        .into_iter()
+        .find_map(|valuetype| {
+            let size = WordSize::Word;
+            let len = valuetype.len(size);
+            let encode = |value: u16| {
+                let mut bytes = vec![0; len];
+                valuetype.write(size, &mut bytes, 0, value.into(), Endian::Little);
+                bytes
+            };
+            let (width, height) = (encode(width), encode(height));
+
+            // Values overlapping the region
+            let first = region.start.saturating_sub(len - 1);
+            let last = region.end.min(patched.len().saturating_sub(len - 1));
+            let find = |value: &[u8]| {
+                (first..last)
+                    .filter(|&i| patched[i..i + len] == *value)
+                    .collect::<Vec<_>>()
+            };
+            let (xs, ys) = (find(&width), find(&height));
+
+            let covered = |i: usize| xs.iter().chain(&ys).any(|&x| (x..x + len).contains(&i));
+            if xs.is_empty() && ys.is_empty() || !region.clone().all(|i| !changed(i) || covered(i))
+            {
+                return None;
+            }
+
+            let offsets = |list: &[usize]| {
+                list.iter()
+                    .map(|x| (x - sig_start).to_string())
+                    .collect::<Vec<_>>()
+                    .join(",")
+            };
+
+            let mut fields = Fields::new();
+            if !xs.is_empty() {
+                fields.push(("xoffset", offsets(&xs)));
+            }
+            if !ys.is_empty() {
+                fields.push(("yoffset", offsets(&ys)));
+            }
+            if valuetype == ValueType::F32 {
+                fields.push(("valuetype", "f32".into()));
+            }
+            Some(fields)
+        })
+}
+
+/// Diff `original` against `patched` and write a config section that makes
+/// the same changes. With the `resolution` the patched copy was made for,
+/// changes to it become `xoffset`/`yoffset` rather than fixed bytes
+pub fn derive(
+    name: &str,
+    modfile: &str,
+    original: &[u8],
+    patched: &[u8],
+    resolution: Option<(u16, u16)>,
+) -> Result<String, Error> {
+    if original.len() != patched.len() {
+        return Err(Error::value_error(format!(
+            "Files are different sizes ({} and {} bytes)",
+            original.len(),
+            patched.len()
+        )));
+    }
+
+    let regions = changed_regions(original, patched);
+    if regions.is_empty() {
+        return Err(Error::value_error("Files are identical"));
+    }
+
+    let mut out = String::new();
+    let _ = writeln!(out, "[{name}]");
+    let _ = writeln!(out, "   details=Derived from a patched copy of {modfile}");
+    let _ = writeln!(out, "   checkfile={modfile}");
+
+    for (i, region) in regions.iter().enumerate() {
+        let prefix = match i {
+            0 => String::new(),
+            i => format!("p{i}"),
+        };
+
+        let (sig_start, sig_fields) = derive_sig(original, patched, region);
+        let changed = |i: usize| original[i] != patched[i];
+
+        let writes = resolution
+            .and_then(|res| resolution_fields(patched, sig_start, region, changed, res))
+            .unwrap_or_else(|| {
+                vec![
+                    (
+                        "patchbytes",
+                        region
+                            .clone()
+                            .map(|i| match changed(i) {
+                                true => format!("{:02X}", patched[i]),
+                                false => "..".into(),
+                            })
+                            .collect(),
+                    ),
+                    ("offset", (region.start - sig_start).to_string()),
+                ]
+            });
+
+        let fields = std::iter::once(("modfile", modfile.to_string()))
+            .chain(sig_fields)
+            .chain(writes);
+        for (field, value) in fields {
+            let _ = writeln!(out, "   {prefix}{field}={value}");
+        }
+    }
+
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn derive_test() {
+        let mut original = (0..64).map(|x| x as u8 ^ 0x5A).collect::<Vec<_>>();
+        original[10..12].copy_from_slice(&800u16.to_le_bytes());
+        original[14..16].copy_from_slice(&600u16.to_le_bytes());
+
+        let mut patched = original.clone();
+        patched[10..12].copy_from_slice(&1920u16.to_le_bytes());
+        patched[14..16].copy_from_slice(&1080u16.to_le_bytes());
+        // Unrelated NOP-out
+        patched[40] = 0x90;
+        patched[41] = 0x90;
+
+        assert_eq!(changed_regions(&original, &patched), [10..16, 40..42]);
+
+        let section = derive("Game", "game.exe", &original, &patched, Some((1920, 1080))).unwrap();
+        assert_eq!(
+            section,
+            "[Game]
+   details=Derived from a patched copy of game.exe
+   checkfile=game.exe
+   modfile=game.exe
+   sig=58595E5F5C5D52532003565758024A4B48494E4F4C4D
+   sigwild=0000000011001100000000
+   occur=1
+   xoffset=8
+   yoffset=12
+   p1modfile=game.exe
+   p1sig=7A7B78797E7F7C7D72737071767774756A6B
+   p1sigwild=000000001100000000
+   p1occur=1
+   p1patchbytes=9090
+   p1offset=8
+"
+        );
+
+        let config = Config::new(&format!("[Apps]\nversion=1.0\na0=Game\n\n{section}")).unwrap();
+        let patches = &config.sections[0].patches;
+        assert_eq!(patches.len(), 2);
+        assert_eq!(
+            patches[0].find_patch_offsets(&original, 0).unwrap()[0].index,
+            2
+        );
+        assert_eq!(
+            patches[1].find_patch_offsets(&patched, 1).unwrap()[0].index,
+            32
+        );
+
+        assert!(derive("Game", "game.exe", &original, &original, None).is_err());
+        assert!(derive("Game", "game.exe", &original, &patched[1..], None).is_err());
+    }
+}
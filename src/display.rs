@@ -0,0 +1,313 @@
+#[cfg(not(windows))]
+use std::path::Path;
+
+/// A connected monitor and the resolution it's running at
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct Display {
+    pub name: String,
+    pub width: u16,
+    pub height: u16,
+    pub primary: bool,
+}
+impl std::fmt::Display for Display {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({}x{})", self.name, self.width, self.height)
+    }
+}
+
+/// Common resolution, offered next to the width and height inputs
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Preset {
+    /// Marketed ratio, which isn't always exact: 3440x1440 is sold as 21:9
+    pub aspect: &'static str,
+    pub width: u16,
+    pub height: u16,
+}
+impl std::fmt::Display for Preset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}x{}", self.aspect, self.width, self.height)
+    }
+}
+
+/// Grouped by aspect ratio, narrowest first
+pub const PRESETS: [Preset; 21] = {
+    const fn preset(aspect: &'static str, width: u16, height: u16) -> Preset {
+        Preset {
+            aspect,
+            width,
+            height,
+        }
+    }
+    [
+        preset("4:3", 1024, 768),
+        preset("4:3", 1280, 960),
+        preset("4:3", 1600, 1200),
+        preset("16:10", 1280, 800),
+        preset("16:10", 1440, 900),
+        preset("16:10", 1680, 1050),
+        preset("16:10", 1920, 1200),
+        preset("16:10", 2560, 1600),
+        preset("16:9", 1280, 720),
+        preset("16:9", 1366, 768),
+        preset("16:9", 1600, 900),
+        preset("16:9", 1920, 1080),
+        preset("16:9", 2560, 1440),
+        preset("16:9", 3840, 2160),
+        preset("21:9", 2560, 1080),
+        preset("21:9", 3440, 1440),
+        preset("21:9", 3840, 1600),
+        preset("21:9", 5120, 2160),
+        preset("32:9", 3840, 1080),
+        preset("32:9", 5120, 1440),
+        preset("32:9", 7680, 2160),
+    ]
+};
+
+fn gcd(a: u16, b: u16) -> u16 {
+    match b {
+        0 => a,
+        _ => gcd(b, a % b),
+    }
+}
+
+/// `width:height` in lowest terms, `0:0` for a zero size
+pub fn reduce(width: u16, height: u16) -> (u16, u16) {
+    match gcd(width, height) {
+        0 => (0, 0),
+        x => (width / x, height / x),
+    }
+}
+
+/// Readable aspect ratio: `16:9` for 1920x1080, `2.39:1` for 3440x1440
+pub fn aspect_ratio(width: u16, height: u16) -> String {
+    match reduce(width, height) {
+        (0, _) | (_, 0) => "-".into(),
+        (w, h) if w <= 32 && h <= 32 => format!("{w}:{h}"),
+        (w, h) => format!("{:.2}:1", f64::from(w) / f64::from(h)),
+    }
+}
+
+/// `value` scaled by `to / from`, rounded, e.g. the height for a width at a ratio
+pub fn scale(value: u16, from: u16, to: u16) -> u16 {
+    let scaled = (u32::from(value) * u32::from(to) + u32::from(from) / 2) / u32::from(from.max(1));
+    scaled.min(u16::MAX.into()) as u16
+}
+
+/// Largest width or height accepted
+pub const MAX_DIMENSION: u16 = 16384;
+
+/// `2560x1440`, as printed by most tools
+pub fn parse_mode(mode: &str) -> Option<(u16, u16)> {
+    let (width, height) = mode.trim().split_once(['x', 'X'])?;
+    let height = height.trim_end_matches(|x: char| !x.is_ascii_digit());
+    Some((width.trim().parse().ok()?, height.trim().parse().ok()?))
+}
+
+/// Connected outputs in `xrandr --query`, e.g.
+/// `DP-1 connected primary 2560x1440+0+0 (normal left inverted) 597mm x 336mm`
+#[cfg(not(windows))]
+fn parse_xrandr(output: &str) -> Vec<Display> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut words = line.split_whitespace();
+            let name = words.next()?;
+            if words.next()? != "connected" {
+                return None;
+            }
+
+            let mut primary = false;
+            let geometry = words.find(|&x| {
+                primary |= x == "primary";
+                x != "primary"
+            })?;
+            let (width, height) = parse_mode(geometry.split('+').next()?)?;
+
+            Some(Display {
+                name: name.into(),
+                width,
+                height,
+                primary,
+            })
+        })
+        .collect()
+}
+
+/// Preferred mode of each connected output the kernel knows of, for when
+/// `xrandr` isn't available such as under Wayland. `None` if `dir` can't be
+/// read, like anywhere but Linux
+#[cfg(not(windows))]
+fn drm_displays(dir: &Path) -> Option<Vec<Display>> {
+    let mut connectors = dir
+        .read_dir()
+        .ok()?
+        .flatten()
+        .map(|x| x.path())
+        .filter(|x| {
+            std::fs::read_to_string(x.join("status")).is_ok_and(|x| x.trim() == "connected")
+        })
+        .collect::<Vec<_>>();
+    connectors.sort();
+
+    let displays = connectors
+        .into_iter()
+        .filter_map(|path| {
+            let modes = std::fs::read_to_string(path.join("modes")).ok()?;
+            let (width, height) = parse_mode(modes.lines().next()?)?;
+
+            // card0-DP-1
+            let name = path.file_name()?.to_string_lossy();
+            let name = name.split_once('-').map_or(&*name, |x| x.1);
+
+            Some(Display {
+                name: name.into(),
+                width,
+                height,
+                primary: false,
+            })
+        })
+        .collect();
+    Some(displays)
+}
+
+/// Every connected display, `None` if they can't be detected on this
+/// system: without `xrandr` or the kernel's list of outputs, as on macOS
+#[cfg(not(windows))]
+pub fn displays() -> Option<Vec<Display>> {
+    let output = std::process::Command::new("xrandr")
+        .arg("--query")
+        .output()
+        .ok()
+        .filter(|x| x.status.success());
+    let xrandr = output.map(|x| parse_xrandr(&String::from_utf8_lossy(&x.stdout)));
+    match xrandr {
+        Some(displays) if !displays.is_empty() => Some(displays),
+        _ => drm_displays(Path::new("/sys/class/drm")).or(xrandr),
+    }
+}
+
+/// Every display attached to the desktop, at the resolution it's running at
+/// rather than scaled by the DPI setting
+#[cfg(windows)]
+pub fn displays() -> Option<Vec<Display>> {
+    use windows_sys::Win32::Graphics::Gdi::{
+        DEVMODEW, DISPLAY_DEVICE_ATTACHED_TO_DESKTOP, DISPLAY_DEVICE_PRIMARY_DEVICE,
+        DISPLAY_DEVICEW, ENUM_CURRENT_SETTINGS, EnumDisplayDevicesW, EnumDisplaySettingsW,
+    };
+
+    let mut displays = Vec::new();
+    for index in 0.. {
+        // Both are plain C structs, for which all zeroes is valid
+        let mut device: DISPLAY_DEVICEW = unsafe { std::mem::zeroed() };
+        device.cb = size_of::<DISPLAY_DEVICEW>() as u32;
+        if unsafe { EnumDisplayDevicesW(std::ptr::null(), index, &mut device, 0) } == 0 {
+            break;
+        }
+        if device.StateFlags & DISPLAY_DEVICE_ATTACHED_TO_DESKTOP == 0 {
+            continue;
+        }
+
+        let mut mode: DEVMODEW = unsafe { std::mem::zeroed() };
+        mode.dmSize = size_of::<DEVMODEW>() as u16;
+        let name = device.DeviceName.as_ptr();
+        if unsafe { EnumDisplaySettingsW(name, ENUM_CURRENT_SETTINGS, &mut mode) } == 0 {
+            continue;
+        }
+
+        // \\.\DISPLAY1
+        let name = &device.DeviceName;
+        let name = String::from_utf16_lossy(
+            &name[..name.iter().position(|&x| x == 0).unwrap_or(name.len())],
+        );
+        displays.push(Display {
+            name: name.trim_start_matches(r"\\.\").into(),
+            width: mode.dmPelsWidth.try_into().unwrap_or(u16::MAX),
+            height: mode.dmPelsHeight.try_into().unwrap_or(u16::MAX),
+            primary: device.StateFlags & DISPLAY_DEVICE_PRIMARY_DEVICE != 0,
+        });
+    }
+    Some(displays)
+}
+
+/// [`displays`] without blocking the UI while `xrandr` runs
+pub async fn query() -> Option<Vec<Display>> {
+    tokio::task::spawn_blocking(displays).await.ok().flatten()
+}
+
+/// The main display, or the first if none is marked as such
+pub fn primary(displays: &[Display]) -> Option<&Display> {
+    displays
+        .iter()
+        .find(|x| x.primary)
+        .or_else(|| displays.first())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_test() {
+        assert_eq!(aspect_ratio(1920, 1080), "16:9");
+        assert_eq!(aspect_ratio(1920, 1200), "8:5");
+        assert_eq!(aspect_ratio(1366, 768), "1.78:1");
+        assert_eq!(aspect_ratio(3440, 1440), "2.39:1");
+        assert_eq!(aspect_ratio(0, 1440), "-");
+        assert_eq!(scale(2560, 16, 9), 1440);
+        assert_eq!(scale(1366, 16, 9), 768);
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn detect_test() {
+        use crate::testing::TempDir;
+
+        let xrandr = "\
+Screen 0: minimum 320 x 200, current 4480 x 1440, maximum 16384 x 16384
+DP-1 connected 1920x1080+2560+0 (normal left inverted right x axis y axis) 527mm x 296mm
+   1920x1080     60.00*+
+HDMI-1 connected primary 2560x1440+0+0 (normal left inverted right x axis y axis) 597mm x 336mm
+   2560x1440     59.95*+
+DP-2 connected (normal left inverted right x axis y axis)
+DP-3 disconnected (normal left inverted right x axis y axis)
+";
+        let displays = parse_xrandr(xrandr);
+        assert_eq!(
+            displays,
+            [
+                Display {
+                    name: "DP-1".into(),
+                    width: 1920,
+                    height: 1080,
+                    primary: false,
+                },
+                Display {
+                    name: "HDMI-1".into(),
+                    width: 2560,
+                    height: 1440,
+                    primary: true,
+                },
+            ]
+        );
+        assert_eq!(primary(&displays), Some(&displays[1]));
+        assert_eq!(primary(&displays[..1]), Some(&displays[0]));
+
+        let tmp = TempDir::new("drm");
+        for (connector, status, modes) in [
+            ("card0-eDP-1", "connected", "2880x1800\n1920x1200\n"),
+            ("card0-HDMI-A-1", "disconnected", ""),
+            ("card0-DP-2", "connected\n", "1920x1080i\n"),
+        ] {
+            std::fs::create_dir_all(tmp.join(connector)).unwrap();
+            std::fs::write(tmp.join(connector).join("status"), status).unwrap();
+            std::fs::write(tmp.join(connector).join("modes"), modes).unwrap();
+        }
+        let names = drm_displays(&tmp)
+            .unwrap()
+            .iter()
+            .map(|x| x.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["DP-2 (1920x1080)", "eDP-1 (2880x1800)"]);
+        assert_eq!(drm_displays(&tmp.join("missing")), None);
+    }
+}
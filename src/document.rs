@@ -0,0 +1,515 @@
+use winnow::Parser;
+
+use crate::{
+    config::{self, Format, Items, RawConfig},
+    error::Error,
+    structured,
+};
+
+/// One line of a patches.ini, kept as written until it's changed
+#[derive(Debug, PartialEq, Eq, Clone)]
+enum Line {
+    Header {
+        name: String,
+        raw: String,
+    },
+    Pair {
+        key: String,
+        value: String,
+        /// Whitespace before the key
+        indent: String,
+        /// Comment after the value, with the space before it
+        comment: String,
+        raw: String,
+    },
+    /// Blank lines, comments and anything else
+    Other(String),
+}
+impl Line {
+    fn parse(raw: &str) -> Self {
+        let trimmed = raw.trim();
+        if let Some(name) = trimmed.strip_prefix('[').and_then(|x| x.split_once(']')) {
+            return Self::Header {
+                name: name.0.to_string(),
+                raw: raw.to_string(),
+            };
+        }
+
+        let Some((key, rest)) = raw.split_once('=') else {
+            return Self::Other(raw.to_string());
+        };
+        let indent = &key[..key.len() - key.trim_start().len()];
+        let key = key.trim();
+        if key.is_empty() || !key.chars().all(config::is_key_char) {
+            return Self::Other(raw.to_string());
+        }
+
+        let rest = rest.trim_start();
+        // Quoted only if the line ends or a comment starts after the closing
+        // quote, the same as the loader
+        let quoted = config::quoted
+            .parse_peek(rest)
+            .ok()
+            .filter(|x| matches!(x.0.trim_start().chars().next(), None | Some(';' | '#')));
+        let (value, comment) = match quoted {
+            Some((comment, value)) => (value, comment),
+            None => {
+                let end = rest.find([';', '#']).unwrap_or(rest.len());
+                let value = rest[..end].trim_end();
+
+                // Lines ending in `\` were joined by `Document::parse`
+                let mut parts = value.split('\n').map(|x| x.trim_end());
+                let mut joined = parts.next().unwrap_or_default().to_string();
+                for part in parts {
+                    joined.pop();
+                    joined.push_str(part.trim_start());
+                }
+                (joined, &rest[value.len()..])
+            }
+        };
+
+        Self::Pair {
+            key: key.to_string(),
+            value,
+            indent: indent.to_string(),
+            comment: comment.trim_end().to_string(),
+            raw: raw.to_string(),
+        }
+    }
+
+    fn pair(indent: &str, key: &str, value: &str, comment: &str) -> Self {
+        let quoted = value.is_empty()
+            || value.trim() != value
+            || value.contains([';', '#'])
+            || value.starts_with('"')
+            || value.ends_with('\\');
+        let written = match quoted {
+            true => config::quote(value),
+            false => value.to_string(),
+        };
+
+        Self::Pair {
+            key: key.to_string(),
+            value: value.to_string(),
+            indent: indent.to_string(),
+            comment: comment.to_string(),
+            raw: format!("{indent}{key}={written}{comment}"),
+        }
+    }
+
+    /// Whether the value of this pair goes on to the next line, see
+    /// [`config::kv_pair`]
+    fn continues(&self) -> bool {
+        matches!(self, Self::Pair { comment, raw, .. } if comment.is_empty() && raw.trim_end().ends_with('\\'))
+    }
+
+    fn raw(&self) -> &str {
+        match self {
+            Self::Header { raw, .. } | Self::Pair { raw, .. } | Self::Other(raw) => raw,
+        }
+    }
+}
+
+/// A config as written, for changing some of it and saving it again without
+/// losing the order, comments and layout of the rest
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Document {
+    lines: Vec<Line>,
+    /// `\r\n` when the file was written that way
+    line_ending: &'static str,
+    /// Whether the last line ended in a line break
+    trailing_newline: bool,
+}
+impl Default for Document {
+    fn default() -> Self {
+        Self {
+            lines: vec![],
+            line_ending: "\n",
+            trailing_newline: true,
+        }
+    }
+}
+impl Document {
+    pub fn parse(input: &str) -> Self {
+        let line_ending = match input.contains("\r\n") {
+            true => "\r\n",
+            false => "\n",
+        };
+        let mut lines = vec![];
+        let mut physical = input.lines().map(|x| x.strip_suffix('\r').unwrap_or(x));
+        while let Some(first) = physical.next() {
+            let mut line = Line::parse(first);
+            while line.continues()
+                && let Some(next) = physical.next()
+            {
+                line = Line::parse(&format!("{}{line_ending}{next}", line.raw()));
+            }
+            lines.push(line);
+        }
+
+        Self {
+            lines,
+            line_ending,
+            trailing_newline: input.ends_with('\n') || input.is_empty(),
+        }
+    }
+
+    /// A config in any format, only TOML and JSON can fail to load
+    pub fn parse_as(input: &str, format: Format) -> Result<Self, Error> {
+        match format {
+            Format::Ini => Ok(Self::parse(input)),
+            Format::Toml => structured::parse_toml(input),
+            Format::Json => structured::parse_json(input),
+        }
+    }
+
+    /// Every section's keys and values, as the config parser gives them
+    pub fn to_raw(&self) -> RawConfig {
+        self.sections()
+            .into_iter()
+            .map(|section| {
+                let items = self.items(section).into_iter();
+                let items: Items = items.map(|(k, v)| (k.into(), v.into())).collect();
+                (section.to_string(), items)
+            })
+            .collect()
+    }
+
+    /// Lines of `section`, from its header to the next one
+    fn section_range(&self, section: &str) -> Option<std::ops::Range<usize>> {
+        let start = self
+            .lines
+            .iter()
+            .position(|x| matches!(x, Line::Header { name, .. } if name == section))?;
+        let end = self.lines[start + 1..]
+            .iter()
+            .position(|x| matches!(x, Line::Header { .. }))
+            .map_or(self.lines.len(), |x| start + 1 + x);
+        Some(start..end)
+    }
+
+    /// Line number of `key` in `section`, counting from 1, or of the
+    /// section's header without a key
+    pub fn line(&self, section: &str, key: Option<&str>) -> Option<usize> {
+        let range = self.section_range(section)?;
+        let Some(key) = key else {
+            return Some(self.line_number(range.start));
+        };
+        let i = self.lines[range.clone()]
+            .iter()
+            .position(|x| matches!(x, Line::Pair { key: k, .. } if k == key))?;
+        Some(self.line_number(range.start + i))
+    }
+
+    /// Line of the file `self.lines[i]` starts on, counting from 1
+    fn line_number(&self, i: usize) -> usize {
+        let before = self.lines[..i]
+            .iter()
+            .map(|x| x.raw().matches('\n').count() + 1);
+        before.sum::<usize>() + 1
+    }
+
+    /// Names of the sections, in file order
+    pub fn sections(&self) -> Vec<&str> {
+        self.lines
+            .iter()
+            .filter_map(|x| match x {
+                Line::Header { name, .. } => Some(name.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Every key and value of `section`, in file order
+    pub fn items(&self, section: &str) -> Vec<(&str, &str)> {
+        let Some(range) = self.section_range(section) else {
+            return vec![];
+        };
+        self.lines[range]
+            .iter()
+            .filter_map(|x| match x {
+                Line::Pair { key, value, .. } => Some((key.as_str(), value.as_str())),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn get(&self, section: &str, key: &str) -> Option<&str> {
+        self.items(section)
+            .into_iter()
+            .find(|x| x.0 == key)
+            .map(|x| x.1)
+    }
+
+    /// Change the value of `key`, keeping its place and comment, or add it
+    /// after the last key of `section`. The section is added if it's missing
+    pub fn set(&mut self, section: &str, key: &str, value: &str) {
+        let range = match self.section_range(section) {
+            Some(range) => range,
+            None => {
+                self.push_section(section);
+                self.section_range(section).expect("section was just added")
+            }
+        };
+
+        let existing = self.lines[range.clone()]
+            .iter()
+            .position(|x| matches!(x, Line::Pair { key: k, .. } if k == key));
+        if let Some(i) = existing {
+            let Line::Pair {
+                indent, comment, ..
+            } = &self.lines[range.start + i]
+            else {
+                unreachable!()
+            };
+            self.lines[range.start + i] = Line::pair(indent, key, value, comment);
+            return;
+        }
+
+        let last = self.last_pair(range.clone());
+        let indent = match last.map(|x| &self.lines[x]) {
+            Some(Line::Pair { indent, .. }) => indent.clone(),
+            _ => String::new(),
+        };
+        let at = last.map_or(range.start + 1, |x| x + 1);
+        self.lines.insert(at, Line::pair(&indent, key, value, ""));
+    }
+
+    /// Take `key` out of `section`, returning whether it was there
+    pub fn remove(&mut self, section: &str, key: &str) -> bool {
+        let Some(range) = self.section_range(section) else {
+            return false;
+        };
+        let existing = self.lines[range.clone()]
+            .iter()
+            .position(|x| matches!(x, Line::Pair { key: k, .. } if k == key));
+        existing
+            .map(|i| self.lines.remove(range.start + i))
+            .is_some()
+    }
+
+    /// Replace every key of `section` with `items`, written where its first key
+    /// was so comments around it stay, or add the section at the end
+    pub fn set_section(&mut self, section: &str, items: &[(String, String)]) {
+        let Some(range) = self.section_range(section) else {
+            self.push_section(section);
+            for (key, value) in items {
+                self.set(section, key, value);
+            }
+            return;
+        };
+
+        let first = self.lines[range.clone()]
+            .iter()
+            .position(|x| matches!(x, Line::Pair { .. }))
+            .map(|x| range.start + x);
+        let indent = match first.map(|x| &self.lines[x]) {
+            Some(Line::Pair { indent, .. }) => indent.clone(),
+            _ => String::new(),
+        };
+        let at = first.unwrap_or(range.start + 1);
+
+        let mut i = range.start;
+        let mut end = range.end;
+        while i < end {
+            if matches!(self.lines[i], Line::Pair { .. }) {
+                self.lines.remove(i);
+                end -= 1;
+            } else {
+                i += 1;
+            }
+        }
+
+        let pairs = items
+            .iter()
+            .map(|(key, value)| Line::pair(&indent, key, value, ""));
+        self.lines.splice(at..at, pairs);
+    }
+
+    /// Take `section` out of the file, and out of the game list
+    pub fn remove_section(&mut self, section: &str) -> bool {
+        let Some(range) = self.section_range(section) else {
+            return false;
+        };
+        self.lines.drain(range);
+
+        let listed = self
+            .items("Apps")
+            .into_iter()
+            .find(|&(key, value)| is_app_key(key) && value == section)
+            .map(|x| x.0.to_string());
+        if let Some(key) = listed {
+            self.remove("Apps", &key);
+        }
+        true
+    }
+
+    /// Add `name` to the end of the game list in `[Apps]`, if it isn't there
+    pub fn add_app(&mut self, name: &str) {
+        let apps = self.items("Apps");
+        if apps
+            .iter()
+            .any(|&(key, value)| is_app_key(key) && value == name)
+        {
+            return;
+        }
+
+        let next = apps
+            .iter()
+            .filter(|x| is_app_key(x.0))
+            .filter_map(|x| x.0[1..].parse::<usize>().ok())
+            .max()
+            .map_or(0, |x| x + 1);
+        self.set("Apps", &format!("a{next}"), name);
+    }
+
+    /// Games of the `[Apps]` list in order, like [`crate::config::Config`] reads them
+    pub fn apps(&self) -> Vec<&str> {
+        let mut apps = self
+            .items("Apps")
+            .into_iter()
+            .filter(|x| is_app_key(x.0))
+            .filter_map(|(key, value)| Some((key[1..].parse::<usize>().ok()?, value)))
+            .collect::<Vec<_>>();
+        apps.sort_by_key(|x| x.0);
+        apps.into_iter().map(|x| x.1).collect()
+    }
+
+    fn push_section(&mut self, section: &str) {
+        if self
+            .lines
+            .last()
+            .is_some_and(|x| !x.raw().trim().is_empty())
+        {
+            self.lines.push(Line::Other(String::new()));
+        }
+        self.lines.push(Line::Header {
+            name: section.to_string(),
+            raw: format!("[{section}]"),
+        });
+    }
+
+    fn last_pair(&self, range: std::ops::Range<usize>) -> Option<usize> {
+        range
+            .rev()
+            .find(|&x| matches!(self.lines[x], Line::Pair { .. }))
+    }
+
+    /// The document written out as `format`. Only INI keeps comments and
+    /// layout, the others are rebuilt from the keys
+    pub fn to_string_as(&self, format: Format) -> String {
+        match format {
+            Format::Ini => self.to_string(),
+            Format::Toml => structured::to_toml(self),
+            Format::Json => structured::to_json(self),
+        }
+    }
+}
+impl std::fmt::Display for Document {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, line) in self.lines.iter().enumerate() {
+            f.write_str(line.raw())?;
+            if i + 1 < self.lines.len() || self.trailing_newline {
+                f.write_str(self.line_ending)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `a0`, `a1` and so on, the keys of the game list
+pub fn is_app_key(key: &str) -> bool {
+    key.strip_prefix('a')
+        .is_some_and(|x| !x.is_empty() && x.chars().all(|x| x.is_ascii_digit()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{Config, DEFAULT, LoadOptions};
+
+    #[test]
+    fn document_test() {
+        let input = "\
+; Games
+[Apps]
+version=1.03
+a0=Test Game
+
+[Test Game]
+   details=\"Test; with a semicolon\"
+   checkfile=game.exe ; the launcher
+   modfile=game.exe
+   sig=8002
+   sigwild=00
+   occur=1
+";
+        let mut document = Document::parse(input);
+        assert_eq!(document.to_string(), input);
+        assert_eq!(Document::parse(DEFAULT).to_string(), DEFAULT);
+        assert_eq!(Document::parse("[A]\r\nb=1").to_string(), "[A]\r\nb=1");
+
+        assert_eq!(document.sections(), ["Apps", "Test Game"]);
+        assert_eq!(
+            document.get("Test Game", "details"),
+            Some("Test; with a semicolon")
+        );
+        assert_eq!(document.get("Test Game", "checkfile"), Some("game.exe"));
+        assert_eq!(
+            Document::parse("[A]\np1_x.y-z=1\n").get("A", "p1_x.y-z"),
+            Some("1")
+        );
+
+        document.set("Test Game", "checkfile", "GAME.EXE");
+        document.set("Test Game", "xoffset", "4");
+        document.set_section(
+            "Other Game",
+            &[
+                ("details".into(), "Other".into()),
+                ("checkfile".into(), " spaced ".into()),
+            ],
+        );
+        document.add_app("Other Game");
+        document.add_app("Test Game");
+        assert_eq!(
+            document.to_string(),
+            "\
+; Games
+[Apps]
+version=1.03
+a0=Test Game
+a1=Other Game
+
+[Test Game]
+   details=\"Test; with a semicolon\"
+   checkfile=GAME.EXE ; the launcher
+   modfile=game.exe
+   sig=8002
+   sigwild=00
+   occur=1
+   xoffset=4
+
+[Other Game]
+details=Other
+checkfile=\" spaced \"
+"
+        );
+        assert_eq!(document.apps(), ["Test Game", "Other Game"]);
+
+        document.set_section("Test Game", &[("details".into(), "New".into())]);
+        assert_eq!(document.items("Test Game"), [("details", "New")]);
+
+        // Written out in another format, it loads as the same config
+        let document = Document::parse(DEFAULT);
+        let config = Config::new(DEFAULT).unwrap();
+        for format in [Format::Toml, Format::Json] {
+            let written = document.to_string_as(format);
+            assert_eq!(
+                Config::load(&written, format, LoadOptions::default()).unwrap(),
+                config
+            );
+            let ini = Document::parse_as(&written, format).unwrap().to_string();
+            assert_eq!(Config::new(&ini).unwrap(), config);
+        }
+    }
+}
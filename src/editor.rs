@@ -0,0 +1,278 @@
+use std::path::PathBuf;
+
+use crate::{
+    config::{AppSection, Items},
+    error::Error,
+    patch_info::PatchInfo,
+    structured,
+};
+
+/// Fields of the game the form has an input for
+pub const GAME_FIELDS: [&str; 2] = ["details", "checkfile"];
+
+/// Fields of an edit set the form has an input for, any others it has are
+/// kept as they are
+pub const PATCH_FIELDS: [&str; 8] = [
+    "modfile", "sig", "sigwild", "xoffset", "yoffset", "occur", "optional", "label",
+];
+
+/// A game entry written or changed by hand, checked as it's typed
+#[derive(Debug, Default, Clone)]
+pub struct Editor {
+    /// Name of the game that was opened, `None` for a new one
+    pub original: Option<String>,
+    pub name: String,
+    /// Keys of the game itself, like `details` and the value slots, in order
+    pub game: Vec<(String, String)>,
+    /// Keys of each edit set without their `p1`, `p2`.. prefix, in order
+    pub patches: Vec<Vec<(String, String)>>,
+    /// File the signatures are searched for in
+    pub binary: Option<PathBuf>,
+    pub data: Vec<u8>,
+    /// Why the form doesn't load as a game, if it doesn't
+    pub error: Option<Error>,
+    /// Matches of each edit set's signature in `data`, `None` when there's
+    /// nothing to search
+    pub matches: Vec<Result<Option<usize>, Error>>,
+}
+impl Editor {
+    /// Blank the form for a new game, keeping the binary already chosen
+    pub fn clear(&mut self) {
+        *self = Self {
+            patches: vec![vec![]],
+            binary: self.binary.take(),
+            data: std::mem::take(&mut self.data),
+            ..Default::default()
+        };
+        self.validate();
+    }
+
+    /// The form filled in with the keys of the game `name`, keeping the
+    /// binary already chosen
+    pub fn open(&mut self, name: &str, items: &[(&str, &str)]) {
+        let mut game = vec![];
+        let mut patches: Vec<Vec<(String, String)>> = vec![vec![]];
+        for &(key, value) in items {
+            if structured::GAME_FIELDS.contains(&key)
+                || structured::numbered(key, "value").is_some()
+            {
+                game.push((key.into(), value.into()));
+                continue;
+            }
+            let (n, field) = structured::numbered(key, "p").unwrap_or((0, key));
+
+            if patches.len() <= n {
+                patches.resize_with(n + 1, Vec::new);
+            }
+            patches[n].push((field.into(), value.into()));
+        }
+
+        *self = Self {
+            original: Some(name.into()),
+            name: name.into(),
+            game,
+            patches,
+            binary: self.binary.take(),
+            data: std::mem::take(&mut self.data),
+            ..Default::default()
+        };
+        self.validate();
+    }
+
+    /// Value of `field` of the game, or of edit set `patch`
+    pub fn field(&self, patch: Option<usize>, field: &str) -> &str {
+        let fields = match patch {
+            Some(i) => &self.patches[i],
+            None => &self.game,
+        };
+        fields
+            .iter()
+            .find(|x| x.0 == field)
+            .map_or("", |x| x.1.as_str())
+    }
+
+    /// Set `field` of the game, or of edit set `patch`. An empty value takes
+    /// the field out
+    pub fn set_field(&mut self, patch: Option<usize>, field: &str, value: String) {
+        let fields = match patch {
+            Some(i) if i < self.patches.len() => &mut self.patches[i],
+            Some(_) => return,
+            None => &mut self.game,
+        };
+        match fields.iter().position(|x| x.0 == field) {
+            Some(i) if value.is_empty() => {
+                fields.remove(i);
+            }
+            Some(i) => fields[i].1 = value,
+            None if value.is_empty() => {}
+            None => fields.push((field.into(), value)),
+        }
+        self.validate();
+    }
+
+    pub fn set_name(&mut self, name: String) {
+        self.name = name;
+        self.validate();
+    }
+
+    pub fn add_patch(&mut self) {
+        self.patches.push(vec![]);
+        self.validate();
+    }
+
+    /// Take out edit set `patch`, the ones after it move up a number
+    pub fn remove_patch(&mut self, patch: usize) {
+        if patch < self.patches.len() && self.patches.len() > 1 {
+            self.patches.remove(patch);
+            self.validate();
+        }
+    }
+
+    /// Search `data`, read from `path`, for every signature from now on
+    pub fn set_binary(&mut self, path: PathBuf, data: Vec<u8>) {
+        self.binary = Some(path);
+        self.data = data;
+        self.validate();
+    }
+
+    /// Keys of the section as they're saved, the game's first then each
+    /// edit set's
+    pub fn items(&self) -> Vec<(String, String)> {
+        let patches = self.patches.iter().enumerate().flat_map(|(i, fields)| {
+            let prefix = match i {
+                0 => String::new(),
+                i => format!("p{i}"),
+            };
+            fields
+                .iter()
+                .map(move |(key, value)| (format!("{prefix}{key}"), value.clone()))
+        });
+        self.game.iter().cloned().chain(patches).collect()
+    }
+
+    /// The game as the config would load it
+    pub fn section(&self) -> Result<AppSection, Error> {
+        if self.name.trim().is_empty() {
+            return Err(Error::state_error("The game needs a name"));
+        }
+        if self.name.contains(']') {
+            return Err(Error::state_error(format!(
+                "A game name can't contain ']': {}",
+                self.name
+            )));
+        }
+        AppSection::from_items(&self.name, &self.items().into_iter().collect())
+    }
+
+    fn validate(&mut self) {
+        self.error = self.section().err();
+
+        let items: Items = self.items().into_iter().collect();
+        self.matches = (0..self.patches.len())
+            .map(|i| {
+                let other = ["textfile", "regkey"];
+                if self.patches[i]
+                    .iter()
+                    .any(|x| other.contains(&x.0.as_str()))
+                {
+                    return Ok(None);
+                }
+
+                let index = match i {
+                    0 => None,
+                    i => Some(u8::try_from(i).map_err(|_| {
+                        Error::state_error("A game can't have more than 255 edit sets")
+                    })?),
+                };
+                let patch = PatchInfo::from_items(&self.name, &items, index)?;
+                Ok((!self.data.is_empty()).then(|| patch.count_matches(&self.data)))
+            })
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn editor_test() {
+        let mut editor = Editor::default();
+        editor.clear();
+        assert!(editor.error.is_some());
+        assert!(editor.matches[0].is_err());
+
+        editor.set_name("Test Game".into());
+        editor.set_field(None, "details", "Test".into());
+        editor.set_field(None, "checkfile", "game.exe".into());
+        for (field, value) in [
+            ("modfile", "game.exe"),
+            ("sig", "8002E001"),
+            ("sigwild", "0000"),
+            ("xoffset", "0"),
+            ("occur", "0"),
+        ] {
+            editor.set_field(Some(0), field, value.into());
+        }
+        assert_eq!(editor.error, None);
+        assert!(matches!(editor.matches[..], [Ok(None)]));
+
+        editor.set_binary(
+            "game.exe".into(),
+            vec![0x80, 0x02, 0xE0, 0x01, 0x00, 0x80, 0x02, 0xE0, 0x01],
+        );
+        assert!(matches!(editor.matches[..], [Ok(Some(2))]));
+
+        editor.add_patch();
+        assert!(editor.matches[1].is_err());
+        editor.set_field(Some(1), "modfile", "game.exe".into());
+        editor.set_field(Some(1), "sig", "FFFF".into());
+        editor.set_field(Some(1), "sigwild", "00".into());
+        editor.set_field(Some(1), "occur", "1".into());
+        assert!(matches!(editor.matches[..], [Ok(Some(2)), Ok(Some(0))]));
+        editor.set_field(Some(1), "sigwild", "0".into());
+        assert!(editor.error.is_some());
+        editor.remove_patch(1);
+        assert_eq!(editor.error, None);
+
+        editor.set_field(Some(0), "label", "Fix HUD".into());
+        editor.set_field(Some(0), "label", String::new());
+        assert_eq!(editor.field(Some(0), "label"), "");
+        assert_eq!(
+            editor.items(),
+            [
+                ("details", "Test"),
+                ("checkfile", "game.exe"),
+                ("modfile", "game.exe"),
+                ("sig", "8002E001"),
+                ("sigwild", "0000"),
+                ("xoffset", "0"),
+                ("occur", "0"),
+            ]
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+        );
+
+        editor.open(
+            "Other Game",
+            &[
+                ("details", "Other"),
+                ("checkfile", "other.exe"),
+                ("modfile", "other.exe"),
+                ("sig", "8002"),
+                ("sigwild", "00"),
+                ("occur", "0"),
+                ("p1modfile", "other.exe"),
+                ("p1sig", "E001"),
+                ("p1sigwild", "00"),
+                ("p1occur", "0"),
+                ("value1name", "FOV"),
+            ],
+        );
+        assert_eq!(editor.original.as_deref(), Some("Other Game"));
+        assert_eq!(editor.patches.len(), 2);
+        assert_eq!(editor.field(Some(1), "sig"), "E001");
+        assert_eq!(editor.field(None, "value1name"), "FOV");
+        assert!(matches!(editor.matches[..], [Ok(Some(2)), Ok(Some(2))]));
+        assert_eq!(editor.section().unwrap().values.len(), 1);
+    }
+}
@@ -0,0 +1,164 @@
+use std::{io::ErrorKind, path::Path};
+
+use crate::{error::Error, i18n::tr};
+
+/// How the bytes of a config file are read as text
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum Encoding {
+    /// Whatever [`Encoding::detect`] finds
+    #[default]
+    Auto,
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    Windows1252,
+}
+impl Encoding {
+    pub const ALL: [Self; 5] = [
+        Self::Auto,
+        Self::Utf8,
+        Self::Utf16Le,
+        Self::Utf16Be,
+        Self::Windows1252,
+    ];
+
+    /// Name as it's saved in the settings
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Auto => "auto",
+            Self::Utf8 => "utf-8",
+            Self::Utf16Le => "utf-16le",
+            Self::Utf16Be => "utf-16be",
+            Self::Windows1252 => "windows-1252",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|x| x.name().eq_ignore_ascii_case(name))
+    }
+
+    /// What `bytes` look to be written in: the one its byte order mark is
+    /// for, UTF-16 if the first characters are mostly ASCII with a zero byte
+    /// beside each, UTF-8 if it's valid UTF-8, and Windows-1252 like the
+    /// configs written for the original uniws otherwise
+    pub fn detect(bytes: &[u8]) -> Self {
+        match bytes {
+            [0xEF, 0xBB, 0xBF, ..] => return Self::Utf8,
+            [0xFF, 0xFE, ..] => return Self::Utf16Le,
+            [0xFE, 0xFF, ..] => return Self::Utf16Be,
+            _ => {}
+        }
+
+        let start = &bytes[..bytes.len().min(512) & !1];
+        let zeros = |i: usize| start.iter().skip(i).step_by(2).filter(|&&x| x == 0).count();
+        let half = start.len() / 4;
+        match (zeros(0), zeros(1)) {
+            (even, odd) if odd > half && even == 0 => Self::Utf16Le,
+            (even, odd) if even > half && odd == 0 => Self::Utf16Be,
+            _ if std::str::from_utf8(bytes).is_ok() => Self::Utf8,
+            _ => Self::Windows1252,
+        }
+    }
+
+    /// `bytes` as text. UTF-8 and UTF-16 fail on bytes that aren't valid
+    /// in them
+    pub fn decode(self, bytes: &[u8]) -> Result<String, Error> {
+        let strict = |encoding: &'static encoding_rs::Encoding| match encoding
+            .decode_with_bom_removal(bytes)
+        {
+            (text, false) => Ok(text.into_owned()),
+            (_, true) => Err(std::io::Error::new(
+                ErrorKind::InvalidData,
+                tr!("error-encoding", encoding = self),
+            )
+            .into()),
+        };
+        match self {
+            Self::Auto => Self::detect(bytes).decode(bytes),
+            Self::Utf8 => strict(encoding_rs::UTF_8),
+            Self::Utf16Le => strict(encoding_rs::UTF_16LE),
+            Self::Utf16Be => strict(encoding_rs::UTF_16BE),
+            Self::Windows1252 => Ok(encoding_rs::WINDOWS_1252
+                .decode_without_bom_handling(bytes)
+                .0
+                .into_owned()),
+        }
+    }
+}
+impl std::fmt::Display for Encoding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Auto => f.write_str(&tr!("encoding-auto")),
+            Self::Utf8 => f.write_str("UTF-8"),
+            Self::Utf16Le => f.write_str("UTF-16 LE"),
+            Self::Utf16Be => f.write_str("UTF-16 BE"),
+            Self::Windows1252 => f.write_str("Windows-1252"),
+        }
+    }
+}
+
+/// The file at `path` as text
+pub fn read(path: &Path, encoding: Encoding) -> Result<String, Error> {
+    encoding.decode(&std::fs::read(path)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encoding_test() {
+        // "Café" in Windows-1252
+        let legacy = b"details=Caf\xE9";
+        assert_eq!(Encoding::Auto.decode(legacy).unwrap(), "details=Café");
+        assert_eq!(
+            Encoding::Windows1252.decode(legacy).unwrap(),
+            "details=Café"
+        );
+        let error = Encoding::Utf8.decode(legacy).unwrap_err();
+        assert!(matches!(
+            error,
+            Error::ReadError {
+                kind: ErrorKind::InvalidData,
+                ..
+            }
+        ));
+        assert_eq!(error.to_string(), "File error: the text isn't valid UTF-8");
+
+        let utf8 = "details=Café".as_bytes();
+        assert_eq!(Encoding::Auto.decode(utf8).unwrap(), "details=Café");
+        assert_eq!(
+            Encoding::Auto.decode(b"\xEF\xBB\xBF[Apps]").unwrap(),
+            "[Apps]"
+        );
+
+        // As Notepad saves "Unicode", with and without the byte order mark
+        let text = "[Apps]\r\nversion=1.03\r\n; Café\r\n";
+        let le: Vec<u8> = text.encode_utf16().flat_map(u16::to_le_bytes).collect();
+        let be: Vec<u8> = text.encode_utf16().flat_map(u16::to_be_bytes).collect();
+        for (bytes, encoding) in [
+            (
+                [0xFF, 0xFE].iter().chain(&le).copied().collect(),
+                Encoding::Utf16Le,
+            ),
+            (
+                [0xFE, 0xFF].iter().chain(&be).copied().collect(),
+                Encoding::Utf16Be,
+            ),
+            (le, Encoding::Utf16Le),
+            (be, Encoding::Utf16Be),
+        ] {
+            assert_eq!(Encoding::detect(&bytes), encoding);
+            assert_eq!(Encoding::Auto.decode(&bytes).unwrap(), text);
+        }
+        assert_eq!(Encoding::detect(legacy), Encoding::Windows1252);
+        assert_eq!(Encoding::detect(utf8), Encoding::Utf8);
+        assert_eq!(Encoding::detect(b""), Encoding::Utf8);
+
+        for encoding in Encoding::ALL {
+            assert_eq!(Encoding::from_name(encoding.name()), Some(encoding));
+        }
+    }
+}
@@ -1,39 +1,280 @@
-use winnow::error::{ContextError, ErrMode};
+use std::sync::Arc;
 
-type WinnowError = ErrMode<ContextError>;
+use serde_json::{Map, Value, json};
 
+use crate::i18n::tr;
+
+/// An error of std or a dependency behind one of ours, its [`source`](std::error::Error::source).
+/// Shared so [`Error`] can be cloned, and compared by message
 #[derive(Debug, Clone)]
-#[allow(dead_code)]
+pub struct Cause(Arc<dyn std::error::Error + Send + Sync>);
+impl PartialEq for Cause {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.to_string() == other.0.to_string()
+    }
+}
+impl std::fmt::Display for Cause {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+impl std::error::Error for Cause {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.0.source()
+    }
+}
+impl From<std::io::Error> for Cause {
+    fn from(value: std::io::Error) -> Self {
+        Self(Arc::new(value))
+    }
+}
+/// What a parser expected, when it's been labelled with a [`winnow::error::StrContext`]
+impl From<winnow::error::ContextError> for Cause {
+    fn from(value: winnow::error::ContextError) -> Self {
+        Self(Arc::from(Box::<dyn std::error::Error + Send + Sync>::from(
+            value.to_string(),
+        )))
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, thiserror::Error)]
 pub enum ConfigError {
-    MissingRequiredField{ section: String, field: &'static str },
-    FieldParseError { section: String, field: &'static str, msg: String },
-    Other(String),
+    #[error("{}", tr!("error-missing-field", section = .section, field = .field))]
+    MissingRequiredField {
+        section: String,
+        field: &'static str,
+    },
+    #[error("{}", tr!("error-invalid-field", section = .section, field = .field, message = .msg))]
+    FieldParseError {
+        section: String,
+        field: &'static str,
+        msg: String,
+    },
+    /// Section listed or required but not present
+    #[error("{}", tr!("error-missing-section", section = .0))]
+    MissingSection(String),
+    #[error("{}", tr!("error-unknown-field", section = .section, field = .field))]
+    UnknownField { section: String, field: String },
+}
+
+#[derive(Debug, PartialEq, Clone, thiserror::Error)]
+pub enum PatchErrorKind {
+    #[error("{}", tr!("error-signature-not-found"))]
+    SignatureNotFound,
+    #[error("{}", tr!("error-too-few", found = .found, requested = .requested))]
+    TooFewOccurrences { found: usize, requested: u32 },
+    /// The signature is in a sample more often than `occur` says, so it may patch the wrong place
+    #[error("{}", tr!("error-too-many", found = .found, requested = .requested))]
+    TooManyOccurrences { found: usize, requested: u32 },
+    /// A write of `len` bytes at `offset` would run past the end of the file
+    #[error("{}", tr!("error-out-of-bounds", len = .len, offset = format!("0x{:X}", .offset), file_len = .file_len))]
+    OffsetOutOfBounds {
+        offset: usize,
+        len: usize,
+        file_len: usize,
+    },
+    /// A negative `offset` from the match at `index` would land before the start of the file
+    #[error("{}", tr!("error-before-start", offset = .offset, index = format!("0x{:X}", .index)))]
+    OffsetBeforeStart { index: usize, offset: i64 },
+    /// The bytes at `offset` aren't the ones `expect` says should be there
+    #[error("{}", tr!("error-expect-mismatch", offset = format!("0x{:X}", .offset), found = .found))]
+    ExpectMismatch { offset: usize, found: String },
+    /// No line of a text file starts with `find`
+    #[error("{}", tr!("error-text-not-found", find = .find))]
+    TextNotFound { find: String },
+    /// `value` can't be written in `len` bytes without being truncated
+    #[error("{}", tr!("error-value-out-of-range", value = .value, len = .len))]
+    ValueOutOfRange { value: f64, len: usize },
+}
+
+/// Where patching failed, as far as it's known: each step of the pipeline fills in what it knows
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct PatchContext {
+    pub section: Option<String>,
+    /// Position of the patch in its section
+    pub patch: Option<usize>,
+    /// File being patched, as the config gives it
+    pub file: Option<String>,
+    /// Start of the signature match being patched
+    pub offset: Option<usize>,
+    /// Which match of the signature it is, counting from 1
+    pub occurrence: Option<usize>,
+}
+impl PatchContext {
+    pub fn section(section: impl Into<String>) -> Self {
+        Self {
+            section: Some(section.into()),
+            ..Default::default()
+        }
+    }
+
+    pub fn file(file: impl Into<String>) -> Self {
+        Self {
+            file: Some(file.into()),
+            ..Default::default()
+        }
+    }
+
+    pub fn patch(patch: usize, file: impl Into<String>) -> Self {
+        Self {
+            patch: Some(patch),
+            ..Self::file(file)
+        }
+    }
+
+    /// At the `occurrence`th match, starting at `offset`
+    pub fn at(self, offset: usize, occurrence: usize) -> Self {
+        Self {
+            offset: Some(offset),
+            occurrence: Some(occurrence),
+            ..self
+        }
+    }
+
+    /// `self`, with what it's missing taken from `other`
+    fn or(self, other: Self) -> Self {
+        Self {
+            section: self.section.or(other.section),
+            patch: self.patch.or(other.patch),
+            file: self.file.or(other.file),
+            offset: self.offset.or(other.offset),
+            occurrence: self.occurrence.or(other.occurrence),
+        }
+    }
+
+    fn json_fields(&self, fields: &mut Map<String, Value>) {
+        if let Some(section) = &self.section {
+            fields.insert("section".into(), json!(section));
+        }
+        if let Some(patch) = self.patch {
+            fields.insert("patch".into(), json!(patch));
+        }
+        if let Some(file) = &self.file {
+            fields.insert("file".into(), json!(file));
+        }
+        if let Some(offset) = self.offset {
+            fields.insert("match_offset".into(), json!(offset));
+        }
+        if let Some(occurrence) = self.occurrence {
+            fields.insert("occurrence".into(), json!(occurrence));
+        }
+    }
+}
+/// What's known of it, e.g. "[Game], patch 2, game.exe, match 1 at 0x1F00"
+impl std::fmt::Display for PatchContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let parts = [
+            self.section
+                .as_ref()
+                .map(|x| tr!("error-context-section", section = x)),
+            self.patch.map(|x| tr!("error-context-patch", patch = x)),
+            self.file.clone(),
+            self.offset
+                .zip(self.occurrence)
+                .map(|(offset, occurrence)| {
+                    tr!(
+                        "error-context-match",
+                        occurrence = occurrence,
+                        offset = format!("0x{offset:X}")
+                    )
+                }),
+        ];
+        f.write_str(&parts.into_iter().flatten().collect::<Vec<_>>().join(", "))
+    }
 }
 
 #[allow(clippy::enum_variant_names)]
-#[allow(dead_code)]
-#[derive(Debug, Clone)]
+#[derive(Debug, PartialEq, Clone, thiserror::Error)]
 pub enum Error {
-    ReadError(std::io::ErrorKind),
-    ParseError(WinnowError),
+    /// Reading or writing a file failed, `cause` saying why like the OS did
+    #[error("{}", tr!("error-file", error = .cause))]
+    ReadError {
+        kind: std::io::ErrorKind,
+        #[source]
+        cause: Cause,
+    },
+    /// An INI file that can't be read, at a line and column counting from 1, with what the
+    /// parser expected there as the `cause`
+    #[error("{}", parse_message(*.line, *.column, .near, .section))]
+    ParseError {
+        line: usize,
+        column: usize,
+        near: Option<String>,
+        section: Option<String>,
+        #[source]
+        cause: Option<Cause>,
+    },
+    /// A TOML or JSON config that can't be read
+    #[error("{}", tr!("error-syntax", message = .0))]
+    SyntaxError(String),
+    /// A section, or a key of one when `key` is given, that's already been given at `first_line`
+    #[error("{}", match .key {
+        Some(key) => tr!("error-duplicate-key", key = key, section = .section, line = .line, first_line = .first_line),
+        None => tr!("error-duplicate-section", section = .section, line = .line, first_line = .first_line),
+    })]
+    Duplicate {
+        line: usize,
+        first_line: usize,
+        section: String,
+        key: Option<String>,
+    },
+    #[error(transparent)]
     ConfigError(ConfigError),
+    #[error("{0}")]
     StateError(String),
-    PatchError { iteration: usize },
+    #[error("{0}")]
+    ValueError(String),
+    #[error("{context}: {kind}")]
+    PatchError {
+        context: Box<PatchContext>,
+        kind: PatchErrorKind,
+    },
+    /// Data doesn't match the hash recorded for it
+    #[error("{}", tr!("error-checksum", file = .file))]
+    ChecksumMismatch { file: String },
+    /// Taking a backup failed, so nothing was patched
+    #[error("{}", tr!("error-backup", error = .0))]
+    BackupFailed(#[source] Box<Error>),
+    /// Reading a file back after patching didn't return what was written
+    #[error("{}", tr!("error-verify", file = .file))]
+    WriteVerifyFailed { file: String },
+    #[error("{}", tr!("error-invalid-link", message = .0))]
+    InvalidLink(String),
+    /// Fetching a file over the network failed
+    #[error("{}", tr!("error-download", message = .0))]
+    DownloadError(String),
+    /// Error loading one of several config files
+    #[error("{file}: {cause}")]
+    InConfigFile {
+        file: String,
+        #[source]
+        cause: Box<Error>,
+    },
+    /// Any other error that stopped a patch, see [`Error::in_patch`]
+    #[error("{context}: {cause}")]
+    InPatch {
+        context: Box<PatchContext>,
+        #[source]
+        cause: Box<Error>,
+    },
 }
 impl Error {
     pub fn config_missing_field(section: impl Into<String>, field: &'static str) -> Self {
-        Self::ConfigError( ConfigError::MissingRequiredField{ section: section.into(), field })
+        Self::ConfigError(ConfigError::MissingRequiredField {
+            section: section.into(),
+            field,
+        })
     }
 
-    pub fn config_error<T>(x: T) -> Self
-    where
-        T: Into<String>,
-    {
-        Self::ConfigError(ConfigError::Other(x.into()))
+    pub fn config_missing_section(section: impl Into<String>) -> Self {
+        Self::ConfigError(ConfigError::MissingSection(section.into()))
     }
 
-    pub fn config_field_parse(section: impl Into<String>, field: &'static str, msg: impl Into<String>) -> Self
-    {
+    pub fn config_field_parse(
+        section: impl Into<String>,
+        field: &'static str,
+        msg: impl Into<String>,
+    ) -> Self {
         Self::ConfigError(ConfigError::FieldParseError {
             section: section.into(),
             field,
@@ -41,23 +282,342 @@ impl Error {
         })
     }
 
+    pub fn in_config_file(file: impl Into<String>, cause: Error) -> Self {
+        Self::InConfigFile {
+            file: file.into(),
+            cause: Box::new(cause),
+        }
+    }
+
+    /// `self` as a failure of patching at `context`, which fills in what it didn't know yet
+    pub fn in_patch(self, context: PatchContext) -> Self {
+        match self {
+            Self::PatchError {
+                context: known,
+                kind,
+            } => Self::PatchError {
+                context: Box::new((*known).or(context)),
+                kind,
+            },
+            Self::InPatch {
+                context: known,
+                cause,
+            } => Self::InPatch {
+                context: Box::new((*known).or(context)),
+                cause,
+            },
+            cause => Self::InPatch {
+                context: Box::new(context),
+                cause: Box::new(cause),
+            },
+        }
+    }
+
     pub fn state_error(msg: impl Into<String>) -> Self {
         Self::StateError(msg.into())
     }
+
+    pub fn value_error(msg: impl Into<String>) -> Self {
+        Self::ValueError(msg.into())
+    }
+
+    /// Stable identifier for scripts and JSON output
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::ReadError { .. } => "io_error",
+            Self::ParseError { .. } | Self::SyntaxError(_) => "syntax_error",
+            Self::Duplicate { .. } => "duplicate",
+            Self::ConfigError(ConfigError::MissingRequiredField { .. }) => "missing_field",
+            Self::ConfigError(ConfigError::FieldParseError { .. }) => "invalid_field",
+            Self::ConfigError(ConfigError::MissingSection(_)) => "missing_section",
+            Self::ConfigError(ConfigError::UnknownField { .. }) => "unknown_field",
+            Self::StateError(_) => "state_error",
+            Self::ValueError(_) => "value_error",
+            Self::PatchError {
+                kind: PatchErrorKind::SignatureNotFound,
+                ..
+            } => "signature_not_found",
+            Self::PatchError {
+                kind: PatchErrorKind::TooFewOccurrences { .. },
+                ..
+            } => "too_few_occurrences",
+            Self::PatchError {
+                kind: PatchErrorKind::TooManyOccurrences { .. },
+                ..
+            } => "too_many_occurrences",
+            Self::PatchError {
+                kind: PatchErrorKind::TextNotFound { .. },
+                ..
+            } => "text_not_found",
+            Self::PatchError {
+                kind: PatchErrorKind::ValueOutOfRange { .. },
+                ..
+            } => "value_out_of_range",
+            Self::PatchError {
+                kind: PatchErrorKind::ExpectMismatch { .. },
+                ..
+            } => "expect_mismatch",
+            Self::PatchError {
+                kind:
+                    PatchErrorKind::OffsetOutOfBounds { .. } | PatchErrorKind::OffsetBeforeStart { .. },
+                ..
+            } => "offset_out_of_bounds",
+            Self::ChecksumMismatch { .. } => "checksum_mismatch",
+            Self::BackupFailed(_) => "backup_failed",
+            Self::WriteVerifyFailed { .. } => "write_verify_failed",
+            Self::InvalidLink(_) => "invalid_link",
+            Self::DownloadError(_) => "download_error",
+            Self::InConfigFile { cause, .. } | Self::InPatch { cause, .. } => cause.code(),
+        }
+    }
+
+    /// Process exit code for command line use
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Self::StateError(_)
+            | Self::ValueError(_)
+            | Self::DownloadError(_)
+            | Self::PatchError {
+                kind: PatchErrorKind::ValueOutOfRange { .. },
+                ..
+            } => 1,
+            Self::ParseError { .. }
+            | Self::SyntaxError(_)
+            | Self::Duplicate { .. }
+            | Self::ConfigError(_)
+            | Self::InvalidLink(_) => 2,
+            Self::ReadError { .. } => 3,
+            Self::PatchError {
+                kind:
+                    PatchErrorKind::SignatureNotFound
+                    | PatchErrorKind::TooFewOccurrences { .. }
+                    | PatchErrorKind::TooManyOccurrences { .. }
+                    | PatchErrorKind::TextNotFound { .. },
+                ..
+            } => 4,
+            Self::PatchError {
+                kind:
+                    PatchErrorKind::OffsetOutOfBounds { .. } | PatchErrorKind::OffsetBeforeStart { .. },
+                ..
+            } => 5,
+            Self::ChecksumMismatch { .. } => 6,
+            Self::BackupFailed(_) => 7,
+            Self::WriteVerifyFailed { .. } => 8,
+            Self::PatchError {
+                kind: PatchErrorKind::ExpectMismatch { .. },
+                ..
+            } => 9,
+            Self::InConfigFile { cause, .. } | Self::InPatch { cause, .. } => cause.exit_code(),
+        }
+    }
+
+    /// JSON object with the error's code, message and context
+    pub fn to_json(&self) -> Value {
+        let mut fields = Map::new();
+        fields.insert("code".into(), json!(self.code()));
+        fields.insert("message".into(), json!(self.to_string()));
+        match self {
+            Self::ConfigError(ConfigError::MissingRequiredField { section, field }) => {
+                fields.insert("section".into(), json!(section));
+                fields.insert("field".into(), json!(field));
+            }
+            Self::ConfigError(ConfigError::FieldParseError { section, field, .. }) => {
+                fields.insert("section".into(), json!(section));
+                fields.insert("field".into(), json!(field));
+            }
+            Self::ConfigError(ConfigError::UnknownField { section, field }) => {
+                fields.insert("section".into(), json!(section));
+                fields.insert("field".into(), json!(field));
+            }
+            Self::ConfigError(ConfigError::MissingSection(section)) => {
+                fields.insert("section".into(), json!(section));
+            }
+            Self::ParseError {
+                line,
+                column,
+                section,
+                ..
+            } => {
+                fields.insert("line".into(), json!(line));
+                fields.insert("column".into(), json!(column));
+                if let Some(section) = section {
+                    fields.insert("section".into(), json!(section));
+                }
+            }
+            Self::Duplicate {
+                line,
+                first_line,
+                section,
+                key,
+            } => {
+                fields.insert("line".into(), json!(line));
+                fields.insert("first_line".into(), json!(first_line));
+                fields.insert("section".into(), json!(section));
+                if let Some(key) = key {
+                    fields.insert("key".into(), json!(key));
+                }
+            }
+            Self::PatchError { context, kind } => {
+                context.json_fields(&mut fields);
+                match kind {
+                    PatchErrorKind::SignatureNotFound => {}
+                    PatchErrorKind::TooFewOccurrences { found, requested }
+                    | PatchErrorKind::TooManyOccurrences { found, requested } => {
+                        fields.insert("found".into(), json!(found));
+                        fields.insert("requested".into(), json!(requested));
+                    }
+                    PatchErrorKind::OffsetOutOfBounds {
+                        offset,
+                        len,
+                        file_len,
+                    } => {
+                        fields.insert("offset".into(), json!(offset));
+                        fields.insert("len".into(), json!(len));
+                        fields.insert("file_len".into(), json!(file_len));
+                    }
+                    PatchErrorKind::OffsetBeforeStart { index, offset } => {
+                        fields.insert("index".into(), json!(index));
+                        fields.insert("offset".into(), json!(offset));
+                    }
+                    PatchErrorKind::ExpectMismatch { offset, found } => {
+                        fields.insert("offset".into(), json!(offset));
+                        fields.insert("found".into(), json!(found));
+                    }
+                    PatchErrorKind::TextNotFound { find } => {
+                        fields.insert("find".into(), json!(find));
+                    }
+                    PatchErrorKind::ValueOutOfRange { value, len } => {
+                        fields.insert("value".into(), json!(value));
+                        fields.insert("len".into(), json!(len));
+                    }
+                }
+            }
+            Self::ChecksumMismatch { file } | Self::WriteVerifyFailed { file } => {
+                fields.insert("file".into(), json!(file));
+            }
+            Self::BackupFailed(e) => {
+                fields.insert("cause".into(), e.to_json());
+            }
+            Self::InConfigFile { file, cause } => {
+                fields.insert("file".into(), json!(file));
+                fields.insert("cause".into(), cause.to_json());
+            }
+            Self::InPatch { context, cause } => {
+                context.json_fields(&mut fields);
+                fields.insert("cause".into(), cause.to_json());
+            }
+            _ => {}
+        }
+
+        Value::Object(fields)
+    }
 }
-impl std::fmt::Display for Error {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{self:?}")
+/// "Syntax error at line 1, column 2", then where if it's known
+fn parse_message(
+    line: usize,
+    column: usize,
+    near: &Option<String>,
+    section: &Option<String>,
+) -> String {
+    let mut message = tr!("error-syntax-at", line = line, column = column);
+    if let Some(near) = near {
+        message.push_str(&tr!("error-syntax-near", near = near));
+    }
+    if let Some(section) = section {
+        message.push_str(&tr!("error-syntax-in", section = section));
+    }
+    message
+}
+/// Found without knowing where, see [`Error::in_patch`]
+impl From<PatchErrorKind> for Error {
+    fn from(value: PatchErrorKind) -> Self {
+        Self::PatchError {
+            context: Box::default(),
+            kind: value,
+        }
     }
 }
 impl From<std::io::Error> for Error {
     fn from(value: std::io::Error) -> Self {
-        let kind = value.kind();
-        Self::ReadError(kind)
+        Self::ReadError {
+            kind: value.kind(),
+            cause: value.into(),
+        }
     }
 }
-impl From<WinnowError> for Error {
-    fn from(value: WinnowError) -> Self {
-        Self::ParseError(value)
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_test() {
+        let error = Error::from(PatchErrorKind::TooFewOccurrences {
+            found: 1,
+            requested: 3,
+        })
+        .in_patch(PatchContext::patch(2, "C:\\game.exe"))
+        .in_patch(PatchContext::section("Game"));
+
+        assert_eq!(error.code(), "too_few_occurrences");
+        assert_eq!(error.exit_code(), 4);
+        assert_eq!(
+            error.to_json().to_string(),
+            r#"{"code":"too_few_occurrences","message":"[Game], patch 2, C:\\game.exe: found 1 occurrence(s) of the signature, expected 3","section":"Game","patch":2,"file":"C:\\game.exe","found":1,"requested":3}"#
+        );
+
+        let error = Error::from(PatchErrorKind::OffsetOutOfBounds {
+            offset: 0x20,
+            len: 4,
+            file_len: 0x21,
+        })
+        .in_patch(PatchContext::patch(0, "game.exe").at(0x1F, 2));
+        assert_eq!(
+            error.to_string(),
+            "patch 0, game.exe, match 2 at 0x1F: writing 4 byte(s) at 0x20 would go past the end of the file (33 bytes)"
+        );
+
+        let error = Error::WriteVerifyFailed {
+            file: "game.exe".into(),
+        }
+        .in_patch(PatchContext::section("Game"));
+        assert_eq!(error.code(), "write_verify_failed");
+        assert_eq!(
+            error.to_json().to_string(),
+            r#"{"code":"write_verify_failed","message":"[Game]: game.exe doesn't contain the patched data after writing","section":"Game","cause":{"code":"write_verify_failed","message":"game.exe doesn't contain the patched data after writing","file":"game.exe"}}"#
+        );
+
+        // Numbers JSON can't hold are null
+        let error = Error::from(PatchErrorKind::ValueOutOfRange {
+            value: f64::INFINITY,
+            len: 4,
+        });
+        assert_eq!(error.to_json()["value"], Value::Null);
+        assert_eq!(error.to_json()["len"], 4);
+        let error = Error::state_error("Line\n\"quoted\"\u{1}");
+        assert_eq!(
+            serde_json::from_str::<Value>(&error.to_json().to_string()).unwrap()["message"],
+            "Line\n\"quoted\"\u{1}"
+        );
+    }
+
+    #[test]
+    fn source_test() {
+        let io = std::io::Error::new(
+            std::io::ErrorKind::PermissionDenied,
+            "game.exe is read only",
+        );
+        let error = Error::BackupFailed(Box::new(io.into()));
+        assert_eq!(
+            error.to_string(),
+            "Backup failed, nothing was patched: File error: game.exe is read only"
+        );
+
+        let source = std::error::Error::source(&error).unwrap();
+        assert_eq!(source.to_string(), "File error: game.exe is read only");
+        assert_eq!(
+            source.source().unwrap().to_string(),
+            "game.exe is read only"
+        );
     }
 }
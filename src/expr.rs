@@ -0,0 +1,193 @@
+use crate::error::Error;
+use winnow::{
+    ascii::{alpha1, hex_digit1, multispace0},
+    combinator::{alt, delimited, eof, opt, preceded, terminated},
+    prelude::*,
+    token::{one_of, take_while},
+};
+
+/// Values an expression can refer to by name
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct Vars {
+    pub width: f64,
+    pub height: f64,
+}
+impl Vars {
+    pub fn new(width: u16, height: u16) -> Self {
+        Self {
+            width: width.into(),
+            height: height.into(),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Var {
+    Width,
+    Height,
+    /// `width / height`
+    Aspect,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+/// Arithmetic over numbers and [`Var`]s, e.g. `width / height / (4 / 3)`
+#[derive(Debug, PartialEq, Clone)]
+pub enum Expr {
+    Number(f64),
+    Var(Var),
+    Neg(Box<Expr>),
+    Binary(Box<Expr>, BinOp, Box<Expr>),
+}
+impl Expr {
+    pub fn parse(input: &str) -> Result<Self, Error> {
+        let mut rest = input;
+        terminated(expr, eof).parse_next(&mut rest).map_err(|_| {
+            let column = input[..input.len() - rest.len()].chars().count() + 1;
+            Error::SyntaxError(format!("{input:?} can't be read past column {column}"))
+        })
+    }
+
+    /// Value of an expression without any [`Var`], known before the
+    /// resolution is
+    pub fn constant(&self) -> Option<f64> {
+        match self {
+            Self::Number(x) => Some(*x),
+            Self::Var(_) => None,
+            Self::Neg(x) => x.constant().map(|x| -x),
+            Self::Binary(a, op, b) => {
+                let (a, b) = (a.constant()?, b.constant()?);
+                Some(match op {
+                    BinOp::Add => a + b,
+                    BinOp::Sub => a - b,
+                    BinOp::Mul => a * b,
+                    BinOp::Div => a / b,
+                })
+            }
+        }
+    }
+
+    pub fn eval(&self, vars: &Vars) -> f64 {
+        match self {
+            Self::Number(x) => *x,
+            Self::Var(Var::Width) => vars.width,
+            Self::Var(Var::Height) => vars.height,
+            Self::Var(Var::Aspect) => vars.width / vars.height,
+            Self::Neg(x) => -x.eval(vars),
+            Self::Binary(a, op, b) => {
+                let (a, b) = (a.eval(vars), b.eval(vars));
+                match op {
+                    BinOp::Add => a + b,
+                    BinOp::Sub => a - b,
+                    BinOp::Mul => a * b,
+                    BinOp::Div => a / b,
+                }
+            }
+        }
+    }
+}
+
+fn ws<'a, O>(
+    inner: impl Parser<&'a str, O, winnow::error::ErrMode<winnow::error::ContextError>>,
+) -> impl Parser<&'a str, O, winnow::error::ErrMode<winnow::error::ContextError>> {
+    delimited(multispace0, inner, multispace0)
+}
+
+fn number(input: &mut &str) -> ModalResult<Expr> {
+    let hex = preceded(alt(("0x", "0X")), hex_digit1)
+        .try_map(|x| u64::from_str_radix(x, 16).map(|x| x as f64));
+    let decimal = take_while(1.., ('0'..='9', '.')).try_map(str::parse::<f64>);
+
+    alt((hex, decimal)).map(Expr::Number).parse_next(input)
+}
+
+fn var(input: &mut &str) -> ModalResult<Expr> {
+    alpha1
+        .verify_map(|x: &str| match x.to_ascii_lowercase().as_str() {
+            "width" => Some(Var::Width),
+            "height" => Some(Var::Height),
+            "aspect" => Some(Var::Aspect),
+            _ => None,
+        })
+        .map(Expr::Var)
+        .parse_next(input)
+}
+
+fn atom(input: &mut &str) -> ModalResult<Expr> {
+    let neg = preceded('-', atom).map(|x| Expr::Neg(Box::new(x)));
+    let parens = delimited('(', expr, ')');
+
+    ws(alt((number, var, neg, parens))).parse_next(input)
+}
+
+fn binary(
+    input: &mut &str,
+    operand: fn(&mut &str) -> ModalResult<Expr>,
+    ops: [char; 2],
+) -> ModalResult<Expr> {
+    let mut acc = operand(input)?;
+
+    while let Some((op, rhs)) = opt((one_of(ops), operand)).parse_next(input)? {
+        let op = match op {
+            '+' => BinOp::Add,
+            '-' => BinOp::Sub,
+            '*' => BinOp::Mul,
+            _ => BinOp::Div,
+        };
+        acc = Expr::Binary(Box::new(acc), op, Box::new(rhs));
+    }
+
+    Ok(acc)
+}
+
+fn term(input: &mut &str) -> ModalResult<Expr> {
+    binary(input, atom, ['*', '/'])
+}
+
+fn expr(input: &mut &str) -> ModalResult<Expr> {
+    binary(input, term, ['+', '-'])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(x: &str) -> f64 {
+        Expr::parse(x).unwrap().eval(&Vars::new(1920, 1080))
+    }
+
+    #[test]
+    fn eval_test() {
+        assert_eq!(eval("2"), 2.0);
+        assert_eq!(eval("0x10"), 16.0);
+        assert_eq!(eval("1.5 * 2"), 3.0);
+        assert_eq!(eval("1 + 2 * 3"), 7.0);
+        assert_eq!(eval("(1 + 2) * 3"), 9.0);
+        assert_eq!(eval("10 - 2 - 3"), 5.0);
+        assert_eq!(eval("-width"), -1920.0);
+        assert_eq!(
+            eval("width / height / (4 / 3)"),
+            1920.0 / 1080.0 / (4.0 / 3.0)
+        );
+        assert_eq!(eval("ASPECT"), 1920.0 / 1080.0);
+
+        let constant = |x| Expr::parse(x).unwrap().constant();
+        assert_eq!(constant("-(0x10 - 1) * 2"), Some(-30.0));
+        assert_eq!(constant("1 + width"), None);
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(Expr::parse("").is_err());
+        assert!(Expr::parse("fov").is_err());
+        assert!(Expr::parse("1 +").is_err());
+        assert!(Expr::parse("(1").is_err());
+        assert!(Expr::parse("1 2").is_err());
+    }
+}
@@ -0,0 +1,209 @@
+use crate::{error::Error, preview::ROW, signature::Signature, value::FilePosition};
+
+/// Rows shown at once
+pub const PAGE_ROWS: usize = 24;
+
+/// Rows kept above a byte that's jumped to
+const LEAD_ROWS: usize = 4;
+
+/// Read-only view of a whole file, editable in expert mode
+#[derive(Debug, Default, Clone)]
+pub struct HexView {
+    /// Relative to the game directory
+    pub modfile: String,
+    /// Modfile `data` was read from, which edits are saved to
+    pub loaded: String,
+    pub data: Vec<u8>,
+    /// File offset of the first row shown, always a multiple of [`ROW`]
+    pub top: usize,
+    /// Byte from the last jump, search hit or click
+    pub cursor: Option<usize>,
+    /// Offset to jump to, anything a [`FilePosition`] accepts
+    pub goto: String,
+    /// Pattern to search for, e.g. `80 02 ?? ??`
+    pub search: String,
+    /// Start of every match of `search`
+    pub hits: Vec<usize>,
+    /// Length of the searched pattern
+    pub hit_len: usize,
+    /// Allow editing bytes
+    pub expert: bool,
+    /// Hex value to write at the cursor
+    pub byte: String,
+    /// Whether `data` has edits that haven't been saved
+    pub dirty: bool,
+}
+impl HexView {
+    pub const SECTION: &str = "Hex viewer";
+
+    /// Replace the file being viewed, dropping anything from the last one
+    pub fn open(&mut self, modfile: String, data: Vec<u8>) {
+        *self = Self {
+            loaded: modfile.clone(),
+            modfile,
+            data,
+            goto: std::mem::take(&mut self.goto),
+            search: std::mem::take(&mut self.search),
+            expert: self.expert,
+            ..Default::default()
+        };
+    }
+
+    fn last_top(&self) -> usize {
+        self.data.len().saturating_sub(1) / ROW * ROW
+    }
+
+    /// Move the cursor to `offset` and scroll it into view
+    pub fn jump(&mut self, offset: usize) {
+        let offset = offset.min(self.data.len().saturating_sub(1));
+        self.cursor = Some(offset);
+
+        let shown = self.top..self.top + PAGE_ROWS * ROW;
+        if !shown.contains(&offset) {
+            self.top = (offset / ROW).saturating_sub(LEAD_ROWS) * ROW;
+        }
+    }
+
+    pub fn scroll(&mut self, rows: isize) {
+        let top = self
+            .top
+            .saturating_add_signed(rows.saturating_mul(ROW as isize));
+        self.top = top.min(self.last_top());
+    }
+
+    /// Jump to the offset in `goto`
+    pub fn goto(&mut self) -> Result<(), Error> {
+        let position = self
+            .goto
+            .parse::<FilePosition>()
+            .map_err(|e| Error::value_error(e.to_string()))?;
+
+        // 100% is the end of the file, so show the last byte
+        let offset = match position {
+            FilePosition::Offset(x) if x >= self.data.len() => {
+                return Err(Error::value_error(format!(
+                    "0x{x:X} is past the end of the file"
+                )));
+            }
+            x => x.resolve(self.data.len()),
+        };
+        self.jump(offset);
+        Ok(())
+    }
+
+    /// Find every match of `search` and jump to the first one after the cursor
+    pub fn find(&mut self) -> Result<(), Error> {
+        let signature = Signature::from_pattern_str(Self::SECTION, &self.search)?;
+        self.hits = signature.find_iter(&self.data).collect();
+        self.hit_len = signature.pattern.len();
+        self.next_hit();
+        Ok(())
+    }
+
+    /// Jump to the next hit after the cursor, wrapping back to the first
+    pub fn next_hit(&mut self) {
+        let after = |&&x: &&usize| self.cursor.is_none_or(|cursor| x > cursor);
+        if let Some(&hit) = self.hits.iter().find(after).or(self.hits.first()) {
+            self.jump(hit);
+        }
+    }
+
+    /// Show `hits` found elsewhere, e.g. by the signature builder
+    pub fn show_hits(&mut self, search: String, hits: Vec<usize>, hit_len: usize) {
+        self.search = search;
+        self.hits = hits;
+        self.hit_len = hit_len;
+    }
+
+    pub fn is_hit(&self, offset: usize) -> bool {
+        let first = self.hits.partition_point(|&x| x + self.hit_len <= offset);
+        self.hits.get(first).is_some_and(|&x| x <= offset)
+    }
+
+    /// Overwrite the byte at the cursor and move on to the next, only in expert mode
+    pub fn edit(&mut self, value: &str) -> Result<(), Error> {
+        let (true, Some(cursor)) = (self.expert, self.cursor) else {
+            return Ok(());
+        };
+        if self.data.is_empty() {
+            return Ok(());
+        }
+
+        let value = u8::from_str_radix(value.trim(), 16)
+            .map_err(|_| Error::value_error(format!("Invalid byte: {value}")))?;
+        if self.data[cursor] != value {
+            self.data[cursor] = value;
+            self.dirty = true;
+        }
+        self.jump(cursor + 1);
+        Ok(())
+    }
+
+    /// File offset and bytes of each row shown
+    pub fn rows(&self) -> impl Iterator<Item = (usize, &[u8])> {
+        self.data
+            .get(self.top..)
+            .unwrap_or_default()
+            .chunks(ROW)
+            .take(PAGE_ROWS)
+            .enumerate()
+            .map(|(i, x)| (self.top + i * ROW, x))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hexview_test() {
+        let mut view = HexView::default();
+        let mut data = vec![0x00; 0x1000];
+        data[0x10..0x12].copy_from_slice(&[0x80, 0x02]);
+        data[0x800..0x802].copy_from_slice(&[0x80, 0x02]);
+        view.open("game.exe".into(), data);
+
+        view.goto = "0x400".into();
+        view.goto().unwrap();
+        assert_eq!((view.cursor, view.top), (Some(0x400), 0x3C0));
+        assert_eq!(view.rows().next().unwrap().0, 0x3C0);
+        assert_eq!(view.rows().count(), PAGE_ROWS);
+
+        view.goto = "50%".into();
+        view.goto().unwrap();
+        assert_eq!(view.cursor, Some(0x800));
+        view.goto = "0x1000".into();
+        assert!(view.goto().is_err());
+
+        view.search = "80 02".into();
+        view.find().unwrap();
+        assert_eq!(view.hits, [0x10, 0x800]);
+        assert_eq!(view.cursor, Some(0x10));
+        assert!(view.is_hit(0x11) && !view.is_hit(0x12) && view.is_hit(0x800));
+        view.next_hit();
+        assert_eq!(view.cursor, Some(0x800));
+        view.next_hit();
+        assert_eq!(view.cursor, Some(0x10));
+
+        view.edit("FF").unwrap();
+        assert!(!view.dirty);
+        view.expert = true;
+        view.edit("FF").unwrap();
+        assert!(view.dirty);
+        assert_eq!((view.data[0x10], view.cursor), (0xFF, Some(0x11)));
+        assert!(view.edit("XYZ").is_err());
+
+        view.modfile = "other.exe".into();
+        assert_eq!(view.loaded, "game.exe");
+
+        view.scroll(isize::MAX);
+        assert_eq!(view.top, 0xFF0);
+        view.scroll(-1);
+        assert_eq!(view.top, 0xFE0);
+
+        view.open("empty.dat".into(), Vec::new());
+        view.jump(0);
+        view.edit("FF").unwrap();
+        assert!(view.data.is_empty() && !view.dirty);
+    }
+}
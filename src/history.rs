@@ -0,0 +1,59 @@
+use std::path::{Path, PathBuf};
+
+use crate::error::Error;
+
+/// Most games kept in the history
+pub const LIMIT: usize = 50;
+
+/// `~/.local/share/uniws/recent.txt` (or the platform equivalent)
+pub fn path() -> Option<PathBuf> {
+    dirs::data_dir().map(|x| x.join("uniws").join("recent.txt"))
+}
+
+/// Names of the games patched from the GUI, most recent first
+pub fn load(path: &Path) -> Vec<String> {
+    std::fs::read_to_string(path)
+        .map(|x| {
+            x.lines()
+                .map(str::trim)
+                .filter(|x| !x.is_empty())
+                .map(String::from)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+pub fn save(path: &Path, recent: &[String]) -> Result<(), Error> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, recent.join("\n"))?;
+    Ok(())
+}
+
+/// Move `game` to the front of `recent`
+pub fn touch(recent: &mut Vec<String>, game: &str) {
+    recent.retain(|x| x != game);
+    recent.insert(0, game.to_string());
+    recent.truncate(LIMIT);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+
+    #[test]
+    fn history_test() {
+        let mut recent = vec!["One".to_string(), "Two".into(), "Three".into()];
+        touch(&mut recent, "Three");
+        touch(&mut recent, "Four");
+        assert_eq!(recent, ["Four", "Three", "One", "Two"]);
+
+        let tmp = TempDir::new("history");
+        let path = tmp.join("recent.txt");
+        assert!(load(&path).is_empty());
+        save(&path, &recent).unwrap();
+        assert_eq!(load(&path), recent);
+    }
+}
@@ -0,0 +1,355 @@
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    path::{Path, PathBuf},
+    sync::{LazyLock, RwLock},
+};
+
+use crate::error::Error;
+
+/// Code of the language every message is written in first
+pub const ENGLISH: &str = "en";
+
+/// The English messages, built in so there's always one to show
+static BASELINE: LazyLock<Bundle> = LazyLock::new(|| {
+    Bundle::parse(ENGLISH, include_str!("../locales/en.ftl")).expect("built in locale is valid")
+});
+
+/// Language picked, `None` for English
+static CURRENT: RwLock<Option<Bundle>> = RwLock::new(None);
+
+/// The messages of one language, read from a Fluent (`.ftl`) file
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct Bundle {
+    /// File name the language is loaded from, e.g. `de` for `de.ftl`
+    pub code: String,
+    messages: HashMap<String, String>,
+}
+impl Bundle {
+    /// Read the subset of Fluent the app needs: `key = value` messages,
+    /// continued on indented lines, with `{ $name }` in place of arguments
+    /// and `{ "{" }` for a brace. `#` lines are comments. Selectors, terms,
+    /// attributes and any other `{ .. }` aren't supported, and are errors
+    /// rather than shown as they're written
+    pub fn parse(code: &str, input: &str) -> Result<Self, Error> {
+        let mut messages = HashMap::new();
+        let mut current: Option<(String, String)> = None;
+        // Blank lines seen inside a message, kept if it goes on after them
+        let mut blank = 0;
+
+        for (i, line) in input.lines().enumerate() {
+            if line.trim().is_empty() {
+                blank += 1;
+                continue;
+            }
+            if line.starts_with([' ', '\t']) {
+                let Some((_, value)) = &mut current else {
+                    return Err(ftl_error(i, line, 1));
+                };
+                let text = line.trim();
+                // `.attribute = ..` or a `[variant] ..` of a selector
+                if text.starts_with(['.', '[', '*']) {
+                    return Err(ftl_error(i, line, 1));
+                }
+                check(i, line, line.len() - line.trim_start().len(), text)?;
+                if !value.is_empty() {
+                    value.push_str(&"\n".repeat(blank + 1));
+                }
+                value.push_str(text);
+                blank = 0;
+                continue;
+            }
+
+            blank = 0;
+            messages.extend(current.take());
+            if line.starts_with('#') {
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(ftl_error(i, line, 1));
+            };
+            let key = key.trim();
+            // Terms, `-brand = ..`, start with a dash
+            if !key.starts_with(char::is_alphabetic)
+                || !key.chars().all(|x| x.is_alphanumeric() || "-_".contains(x))
+            {
+                return Err(ftl_error(i, line, 1));
+            }
+            let start = line.len() - value.trim_start().len();
+            let value = value.trim();
+            check(i, line, start, value)?;
+            current = Some((key.to_string(), value.to_string()));
+        }
+        messages.extend(current);
+
+        Ok(Self {
+            code: code.to_string(),
+            messages,
+        })
+    }
+
+    /// The pattern of `key`, as it's written in the file
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.messages.get(key).map(String::as_str)
+    }
+
+    /// What the language calls itself, its `language-name`
+    pub fn name(&self) -> &str {
+        self.get("language-name").unwrap_or(&self.code)
+    }
+}
+
+/// Fail if a `{ .. }` in `text`, which is at `start` of `line`, isn't an
+/// argument or a string, see [`unsupported`]
+fn check(index: usize, line: &str, start: usize, text: &str) -> Result<(), Error> {
+    match unsupported(text) {
+        Some(offset) => {
+            let column = line[..start + offset].chars().count() + 1;
+            Err(ftl_error(index, line, column))
+        }
+        None => Ok(()),
+    }
+}
+
+/// Offset of the first `{ .. }` in `text` that isn't `{ $name }` or
+/// `{ "literal" }`, or isn't closed on the same line
+fn unsupported(text: &str) -> Option<usize> {
+    let mut rest = text;
+    while let Some(start) = rest.find('{') {
+        let inner = rest[start + 1..].trim_start();
+        let after = match inner.strip_prefix('"') {
+            Some(x) => x.find('"').map(|end| &x[end + 1..]),
+            None => inner.strip_prefix('$').and_then(|x| {
+                let end = x
+                    .find(|x: char| !x.is_alphanumeric() && !"-_".contains(x))
+                    .unwrap_or(x.len());
+                (end > 0).then(|| &x[end..])
+            }),
+        };
+        match after.and_then(|x| x.trim_start().strip_prefix('}')) {
+            Some(x) => rest = x,
+            None => return Some(text.len() - rest.len() + start),
+        }
+    }
+    None
+}
+
+fn ftl_error(index: usize, line: &str, column: usize) -> Error {
+    Error::ParseError {
+        line: index + 1,
+        column,
+        near: Some(line.trim().to_string()),
+        section: None,
+        cause: None,
+    }
+}
+
+/// `pattern` with each `{ $name }` in it replaced by the argument of that
+/// name. Unknown arguments are left as `{$name}`, like Fluent does
+pub fn format(pattern: &str, args: &[(&str, &dyn Display)]) -> String {
+    let mut out = String::new();
+    let mut rest = pattern;
+    while let Some(start) = rest.find('{') {
+        // A `}` in a string literal doesn't end it
+        let after = rest[start + 1..].trim_start();
+        let from = match after.strip_prefix('"') {
+            Some(x) => x
+                .find('"')
+                .map_or(rest.len(), |x| rest.len() - after.len() + x + 2),
+            None => start,
+        };
+        let Some(end) = rest.get(from..).and_then(|x| x.find('}')).map(|x| from + x) else {
+            break;
+        };
+        out.push_str(&rest[..start]);
+        let inner = rest[start + 1..end].trim();
+        if let Some(name) = inner.strip_prefix('$') {
+            match args.iter().find(|(x, _)| *x == name) {
+                Some((_, value)) => out.push_str(&value.to_string()),
+                None => out.push_str(&format!("{{${name}}}")),
+            }
+        } else if let Some(literal) = inner.strip_prefix('"').and_then(|x| x.strip_suffix('"')) {
+            out.push_str(literal);
+        } else {
+            out.push_str(&rest[start..=end]);
+        }
+        rest = &rest[end + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// The message `key` in the language picked, or in English if it hasn't
+/// been translated. See [`tr`]
+pub fn message(key: &str, args: &[(&str, &dyn Display)]) -> String {
+    let current = CURRENT.read().unwrap_or_else(|e| e.into_inner());
+    let pattern = current
+        .as_ref()
+        .and_then(|x| x.get(key))
+        .or_else(|| BASELINE.get(key))
+        .unwrap_or(key);
+    format(pattern, args)
+}
+
+/// A message of the app in the language picked, with its arguments by
+/// name: `tr!("patch-failed", error = e)`
+macro_rules! tr {
+    ($key:literal $(, $name:ident = $value:expr)* $(,)?) => {
+        $crate::i18n::message($key, &[$((stringify!($name), &$value as &dyn std::fmt::Display)),*])
+    };
+}
+pub(crate) use tr;
+
+/// A language that can be picked
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Language {
+    pub code: String,
+    pub name: String,
+}
+impl Display for Language {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+/// `~/.config/uniws/locales` (or the platform equivalent), where a
+/// `de.ftl` adds German
+pub fn dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|x| x.join("uniws").join("locales"))
+}
+
+/// English and the languages in `dir`, by code
+pub fn languages(dir: Option<&Path>) -> Vec<Language> {
+    let language = |x: &Bundle| Language {
+        code: x.code.clone(),
+        name: x.name().to_string(),
+    };
+    let mut found: Vec<Language> = dir
+        .and_then(|x| std::fs::read_dir(x).ok())
+        .into_iter()
+        .flatten()
+        .filter_map(|x| Some(x.ok()?.path()))
+        .filter(|x| x.extension().is_some_and(|x| x == "ftl"))
+        .filter_map(|x| {
+            load(&x)
+                .inspect_err(|e| tracing::warn!("Skipped {}: {e}", x.display()))
+                .ok()
+        })
+        .filter(|x| x.code != ENGLISH)
+        .map(|x| language(&x))
+        .collect();
+    found.sort_by(|a, b| a.code.cmp(&b.code));
+    found.insert(0, language(&BASELINE));
+    found
+}
+
+/// The bundle in the file at `path`, named after the file
+fn load(path: &Path) -> Result<Bundle, Error> {
+    let code = path
+        .file_stem()
+        .and_then(|x| x.to_str())
+        .unwrap_or_default();
+    Bundle::parse(code, &std::fs::read_to_string(path)?)
+}
+
+/// Show messages in the language `code` from here on, one of
+/// [`languages`] in `dir`. English doesn't need a file
+pub fn select(dir: Option<&Path>, code: &str) -> Result<(), Error> {
+    let bundle = match code {
+        ENGLISH => None,
+        _ => {
+            let dir = dir.ok_or(Error::state_error(tr!("error-no-locales")))?;
+            Some(load(&dir.join(format!("{code}.ftl")))?)
+        }
+    };
+    *CURRENT.write().unwrap_or_else(|e| e.into_inner()) = bundle;
+    Ok(())
+}
+
+/// Code of the language messages are shown in
+pub fn current() -> String {
+    let current = CURRENT.read().unwrap_or_else(|e| e.into_inner());
+    current.as_ref().map_or(ENGLISH.into(), |x| x.code.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundle_test() {
+        let bundle = Bundle::parse(
+            "de",
+            "# Deutsch\nlanguage-name = Deutsch\n\npatch-applied =\n    Patch erfolgreich angewendet\n\n    { $report }\nbraces = { \"{\" }x{ \"}\" } { $missing }\n",
+        )
+        .unwrap();
+        assert_eq!(bundle.name(), "Deutsch");
+        assert_eq!(
+            format(
+                bundle.get("patch-applied").unwrap(),
+                &[("report", &"1 file")]
+            ),
+            "Patch erfolgreich angewendet\n\n1 file"
+        );
+        assert_eq!(format(bundle.get("braces").unwrap(), &[]), "{x} {$missing}");
+        assert!(matches!(
+            Bundle::parse("de", "a = 1\nnot a message\n"),
+            Err(Error::ParseError { line: 2, .. })
+        ));
+
+        // Fluent that isn't supported is an error, not shown as it's written
+        for (input, line, column) in [
+            (
+                "files = { $n ->\n    [one] One file\n   *[other] { $n } files\n}\n",
+                1,
+                9,
+            ),
+            ("-brand = UniWS\n", 1, 1),
+            ("about = About { -brand }\n", 1, 15),
+            ("title = Title\n    .tooltip = Tip\n", 2, 1),
+            ("count = { NUMBER($n) }\n", 1, 9),
+            ("other = See\n    { about }\n", 2, 5),
+            ("open = { $n\n", 1, 8),
+        ] {
+            assert!(
+                matches!(
+                    Bundle::parse("de", input),
+                    Err(Error::ParseError { line: l, column: c, .. }) if (l, c) == (line, column)
+                ),
+                "{input}"
+            );
+        }
+
+        assert_eq!(
+            tr!("patch-failed", error = "oops"),
+            "Patch failed to apply: oops"
+        );
+        assert_eq!(message("no-such-message", &[]), "no-such-message");
+    }
+
+    /// Every message used in the source is in the English file
+    #[test]
+    fn baseline_test() {
+        let src = Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+        for entry in std::fs::read_dir(src).unwrap() {
+            let path = entry.unwrap().path();
+            let source = std::fs::read_to_string(&path).unwrap();
+            for (i, _) in source.match_indices("tr!(") {
+                // Not the end of another macro, like `include_str!`, or this
+                // string
+                if source[..i].ends_with(|x: char| x.is_alphanumeric() || "_\"".contains(x)) {
+                    continue;
+                }
+                let Some(key) = source[i + 4..].trim_start().strip_prefix('"') else {
+                    continue;
+                };
+                let key = &key[..key.find('"').unwrap()];
+                assert!(
+                    BASELINE.get(key).is_some(),
+                    "{key} of {} isn't in en.ftl",
+                    path.display()
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,229 @@
+use std::path::PathBuf;
+
+use crate::{cover, error::Error};
+
+/// Resource types of an executable
+const RT_ICON: u32 = 3;
+const RT_GROUP_ICON: u32 = 14;
+
+fn u16_at(data: &[u8], offset: usize) -> Option<u16> {
+    let bytes = data.get(offset..offset + 2)?;
+    Some(u16::from_le_bytes([bytes[0], bytes[1]]))
+}
+
+fn u32_at(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset + 4)?;
+    Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+/// The resource section of a PE file
+struct Resources<'a> {
+    data: &'a [u8],
+    /// Offset of the root directory in the file
+    base: usize,
+    /// Virtual address, virtual size, file offset and file size of each section
+    sections: Vec<[usize; 4]>,
+}
+impl<'a> Resources<'a> {
+    fn new(data: &'a [u8]) -> Option<Self> {
+        let pe = u32_at(data, 0x3C)? as usize;
+        if data.get(pe..pe + 4)? != b"PE\0\0" {
+            return None;
+        }
+        let coff = pe + 4;
+        let count = u16_at(data, coff + 2)? as usize;
+        let optional = coff + 20;
+        let directories = match u16_at(data, optional)? {
+            0x10B => optional + 96,
+            0x20B => optional + 112,
+            _ => return None,
+        };
+        // The resource directory is the third
+        let rva = u32_at(data, directories + 16)? as usize;
+
+        let table = optional + u16_at(data, coff + 16)? as usize;
+        let sections = (0..count)
+            .map(|i| {
+                let field = |x| u32_at(data, table + 40 * i + x).map(|x| x as usize);
+                Some([field(12)?, field(8)?, field(20)?, field(16)?])
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        let mut resources = Self {
+            data,
+            base: 0,
+            sections,
+        };
+        resources.base = resources.offset(rva)?;
+        Some(resources)
+    }
+
+    /// Where the virtual address `rva` is in the file
+    fn offset(&self, rva: usize) -> Option<usize> {
+        self.sections
+            .iter()
+            .find(|[address, size, _, raw_size]| {
+                (*address..address + size.max(raw_size)).contains(&rva)
+            })
+            .map(|[address, _, raw, _]| rva - address + raw)
+    }
+
+    /// Offset of the entry `id` of the directory at `dir`, or of its first
+    /// entry
+    fn entry(&self, dir: usize, id: Option<u32>) -> Option<u32> {
+        let dir = self.base + dir;
+        let count = u16_at(self.data, dir + 12)? as usize + u16_at(self.data, dir + 14)? as usize;
+        (0..count)
+            .map(|i| dir + 16 + 8 * i)
+            .find(|&x| id.is_none() || u32_at(self.data, x) == id)
+            .and_then(|x| u32_at(self.data, x + 4))
+    }
+
+    /// Data of the resource at `offset`, the first of each directory on the
+    /// way, e.g. its first language. Resources are only ever 3 directories
+    /// deep, so a file that goes deeper, maybe round in a loop, has none
+    fn data(&self, mut offset: u32) -> Option<&'a [u8]> {
+        for _ in 0..3 {
+            if offset & 0x8000_0000 == 0 {
+                break;
+            }
+            offset = self.entry((offset & 0x7FFF_FFFF) as usize, None)?;
+        }
+        if offset & 0x8000_0000 != 0 {
+            return None;
+        }
+        let entry = self.base + offset as usize;
+        let start = self.offset(u32_at(self.data, entry)? as usize)?;
+        let size = u32_at(self.data, entry + 4)? as usize;
+        self.data.get(start..start + size)
+    }
+}
+
+/// The first icon of the executable `data` as an `.ico` file, every size of
+/// it. `None` if it doesn't have one or isn't an executable
+pub fn extract(data: &[u8]) -> Option<Vec<u8>> {
+    let resources = Resources::new(data)?;
+    let group = resources.data(resources.entry(0, Some(RT_GROUP_ICON))?)?;
+    let icons = resources.entry(0, Some(RT_ICON))? & 0x7FFF_FFFF;
+
+    // Entries of the group are an .ico's, with an id in place of the offset
+    let images = (0..u16_at(group, 4)? as usize)
+        .filter_map(|i| {
+            let entry = group.get(6 + 14 * i..6 + 14 * (i + 1))?;
+            let id = u16_at(entry, 12)?;
+            let image = resources.data(resources.entry(icons as usize, Some(id.into()))?)?;
+            Some((&entry[..8], image))
+        })
+        .collect::<Vec<_>>();
+    if images.is_empty() {
+        return None;
+    }
+
+    let mut ico = vec![0, 0, 1, 0];
+    ico.extend((images.len() as u16).to_le_bytes());
+    let mut offset = 6 + 16 * images.len();
+    for (entry, image) in &images {
+        ico.extend_from_slice(entry);
+        ico.extend((image.len() as u32).to_le_bytes());
+        ico.extend((offset as u32).to_le_bytes());
+        offset += image.len();
+    }
+    for (_, image) in images {
+        ico.extend_from_slice(image);
+    }
+    Some(ico)
+}
+
+/// Icon of the executable at `path`
+pub async fn load(path: PathBuf) -> Result<cover::Picture, Error> {
+    let data = tokio::fs::read(&path).await?;
+    let ico = extract(&data)
+        .ok_or_else(|| Error::state_error(format!("{} has no icon", path.display())))?;
+    Ok(cover::picture(ico))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A PE32 file with one icon, `image`, in the resource section
+    fn executable(image: &[u8]) -> Vec<u8> {
+        let mut data = vec![0; 0x200];
+        let mut put = |offset: usize, bytes: &[u8]| {
+            if data.len() < offset + bytes.len() {
+                data.resize(offset + bytes.len(), 0);
+            }
+            data[offset..offset + bytes.len()].copy_from_slice(bytes);
+        };
+        let (va, raw) = (0x1000u32, 0x200usize);
+
+        put(0x3C, &0x40u32.to_le_bytes());
+        put(0x40, b"PE\0\0");
+        put(0x44 + 2, &1u16.to_le_bytes());
+        put(0x44 + 16, &224u16.to_le_bytes());
+        put(0x58, &0x10Bu16.to_le_bytes());
+        put(0x58 + 96 + 16, &va.to_le_bytes());
+        let section = 0x58 + 224;
+        put(section, b".rsrc\0\0\0");
+        for (field, value) in [(8, 0x1000), (12, va), (16, 0x1000), (20, raw as u32)] {
+            put(section + field, &value.to_le_bytes());
+        }
+
+        // A directory with one entry, `id`, pointing at `target`
+        let mut dir = |offset: usize, id: u32, target: u32| {
+            put(raw + offset + 14, &1u16.to_le_bytes());
+            put(raw + offset + 16, &id.to_le_bytes());
+            put(raw + offset + 20, &target.to_le_bytes());
+        };
+        let subdir = |x: u32| 0x8000_0000 | x;
+        dir(32, 1, subdir(56));
+        dir(56, 0x409, 128);
+        dir(80, 101, subdir(104));
+        dir(104, 0x409, 144);
+        put(raw + 14, &2u16.to_le_bytes());
+        put(raw + 16, &RT_ICON.to_le_bytes());
+        put(raw + 20, &subdir(32).to_le_bytes());
+        put(raw + 24, &RT_GROUP_ICON.to_le_bytes());
+        put(raw + 28, &subdir(80).to_le_bytes());
+
+        let group = [
+            &[0, 0, 1, 0, 1, 0][..],
+            &[16, 16, 0, 0, 1, 0, 32, 0],
+            &(image.len() as u32).to_le_bytes(),
+            &1u16.to_le_bytes(),
+        ]
+        .concat();
+        put(raw + 128, &(va + 160).to_le_bytes());
+        put(raw + 132, &(image.len() as u32).to_le_bytes());
+        put(raw + 144, &(va + 160 + image.len() as u32).to_le_bytes());
+        put(raw + 148, &(group.len() as u32).to_le_bytes());
+        put(raw + 160, image);
+        put(raw + 160 + image.len(), &group);
+        data
+    }
+
+    #[test]
+    fn extract_test() {
+        let image = b"\x89PNG....";
+        let ico = extract(&executable(image)).unwrap();
+        assert_eq!(
+            ico,
+            [
+                &[0, 0, 1, 0, 1, 0][..],
+                &[16, 16, 0, 0, 1, 0, 32, 0],
+                &8u32.to_le_bytes(),
+                &22u32.to_le_bytes(),
+                image,
+            ]
+            .concat()
+        );
+
+        assert_eq!(extract(b"MZ"), None);
+        assert_eq!(extract(&[0; 0x400]), None);
+
+        // The group's language directory pointing back at itself
+        let mut looped = executable(image);
+        looped[0x200 + 104 + 20..][..4].copy_from_slice(&(0x8000_0000u32 | 104).to_le_bytes());
+        assert_eq!(extract(&looped), None);
+    }
+}
@@ -0,0 +1,196 @@
+use std::path::PathBuf;
+
+use crate::wine::{self, Prefix};
+
+/// A game installed through the Epic Games Launcher, from its `.item` manifest
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct EpicInstall {
+    /// Internal name, what `egsname` usually holds
+    pub app_name: String,
+    pub display_name: String,
+    pub install_location: PathBuf,
+}
+impl EpicInstall {
+    /// Read the fields we need out of a `.item` manifest, which is JSON
+    pub fn parse(manifest: &str) -> Option<Self> {
+        Some(Self {
+            app_name: json_field(manifest, "AppName")?,
+            display_name: json_field(manifest, "DisplayName").unwrap_or_default(),
+            install_location: json_field(manifest, "InstallLocation")?.into(),
+        })
+    }
+
+    /// Whether `egsname` names this game, by app or display name
+    pub fn matches(&self, egsname: &str) -> bool {
+        let egsname = egsname.trim();
+        self.app_name.eq_ignore_ascii_case(egsname)
+            || self.display_name.eq_ignore_ascii_case(egsname)
+    }
+}
+
+/// Value of the first string field called `name` in a JSON document
+///
+/// Launcher manifests are flat objects, so this avoids a full JSON parser
+fn json_field(json: &str, name: &str) -> Option<String> {
+    let key = format!("\"{name}\"");
+    let mut rest = json;
+    loop {
+        let start = rest.find(&key)? + key.len();
+        rest = &rest[start..];
+
+        let Some(value) = rest.trim_start().strip_prefix(':') else {
+            continue;
+        };
+        let Some(value) = value.trim_start().strip_prefix('"') else {
+            continue;
+        };
+
+        let mut out = String::new();
+        let mut chars = value.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => return Some(out),
+                '\\' => match chars.next()? {
+                    'n' => out.push('\n'),
+                    't' => out.push('\t'),
+                    'r' => out.push('\r'),
+                    'u' => {
+                        let hex = chars.by_ref().take(4).collect::<String>();
+                        out.push(char::from_u32(u32::from_str_radix(&hex, 16).ok()?)?);
+                    }
+                    c => out.push(c),
+                },
+                c => out.push(c),
+            }
+        }
+        return None;
+    }
+}
+
+/// Where the Epic Games Launcher keeps its install manifests, including
+/// launchers installed in Wine prefixes
+pub fn epic_manifest_dirs() -> Vec<PathBuf> {
+    let program_data = std::env::var_os("PROGRAMDATA")
+        .map(PathBuf::from)
+        .filter(|_| cfg!(windows));
+    let prefixes = wine::prefixes()
+        .into_iter()
+        .map(|x| x.drive_c().join("ProgramData"));
+
+    program_data
+        .into_iter()
+        .chain(prefixes)
+        .map(|x| x.join("Epic/EpicGamesLauncher/Data/Manifests"))
+        .filter(|x| x.is_dir())
+        .collect()
+}
+
+/// Every game with a readable manifest in `dirs`
+///
+/// Install locations of manifests in a Wine prefix are translated to where
+/// they are in the prefix
+pub fn epic_installs(dirs: &[PathBuf]) -> Vec<EpicInstall> {
+    dirs.iter()
+        .flat_map(|dir| {
+            let prefix = Prefix::containing(dir);
+            let manifests = dir.read_dir().into_iter().flatten().flatten();
+
+            manifests
+                .map(|x| x.path())
+                .filter(|x| {
+                    x.extension()
+                        .is_some_and(|x| x.eq_ignore_ascii_case("item"))
+                })
+                .filter_map(|x| std::fs::read_to_string(x).ok())
+                .filter_map(|x| EpicInstall::parse(&x))
+                .map(move |mut install| {
+                    if let Some(prefix) = &prefix
+                        && let Some(native) = install
+                            .install_location
+                            .to_str()
+                            .and_then(|x| prefix.to_native(x))
+                    {
+                        install.install_location = native;
+                    }
+                    install
+                })
+        })
+        .collect()
+}
+
+/// Install folder of the game called `egsname`, if it's still there
+pub fn find_epic(dirs: &[PathBuf], egsname: &str) -> Option<PathBuf> {
+    epic_installs(dirs)
+        .into_iter()
+        .find(|x| x.matches(egsname))
+        .map(|x| x.install_location)
+        .filter(|x| x.is_dir())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+
+    #[test]
+    fn epic_test() {
+        let tmp = TempDir::new("epic");
+        let manifests = tmp.join("Manifests");
+        let game_dir = tmp.join("Games").join("Hello \"World\"");
+        std::fs::create_dir_all(&manifests).unwrap();
+        std::fs::create_dir_all(&game_dir).unwrap();
+
+        let location = game_dir
+            .to_string_lossy()
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"");
+        let manifest = format!(
+            r#"{{
+	"FormatVersion": 0,
+	"bIsIncompleteInstall": false,
+	"LaunchExecutable": "Game.exe",
+	"DisplayName": "Hello World",
+	"InstallTags": [ "AppName" ],
+	"AppName": "Sunflower",
+	"InstallLocation": "{location}"
+}}"#
+        );
+        std::fs::write(manifests.join("0A1B.item"), &manifest).unwrap();
+        std::fs::write(manifests.join("broken.item"), "{}").unwrap();
+
+        let installs = epic_installs(std::slice::from_ref(&manifests));
+        assert_eq!(
+            installs,
+            [EpicInstall {
+                app_name: "Sunflower".into(),
+                display_name: "Hello World".into(),
+                install_location: game_dir.clone(),
+            }]
+        );
+        assert_eq!(
+            find_epic(std::slice::from_ref(&manifests), "sunflower"),
+            Some(game_dir.clone())
+        );
+        assert_eq!(
+            find_epic(std::slice::from_ref(&manifests), "Hello World"),
+            Some(game_dir)
+        );
+        assert_eq!(find_epic(&[manifests], "Other"), None);
+
+        // Launcher running in a Wine prefix
+        let root = tmp.join("pfx");
+        let manifests = root.join("drive_c/ProgramData/Epic/EpicGamesLauncher/Data/Manifests");
+        std::fs::create_dir_all(&manifests).unwrap();
+        std::fs::create_dir_all(root.join("dosdevices")).unwrap();
+        std::fs::create_dir_all(root.join("drive_c/Games/Sunflower")).unwrap();
+        std::fs::write(
+            manifests.join("0A1B.item"),
+            r#"{"AppName": "Sunflower", "InstallLocation": "C:\\Games\\Sunflower"}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            find_epic(&[manifests], "Sunflower"),
+            Some(root.join("drive_c/Games/Sunflower"))
+        );
+    }
+}
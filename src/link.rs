@@ -0,0 +1,102 @@
+use crate::error::Error;
+
+/// Part of some text, and whether it's a web address
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Segment<'a> {
+    Text(&'a str),
+    Url(&'a str),
+}
+
+/// Length of the address `text` starts with. It ends at whitespace, without
+/// the punctuation of the sentence around it or a `)` that wasn't opened in
+/// it, like in `(see https://www.wsgf.org/dr/game)`
+fn url_len(text: &str) -> usize {
+    let end = text.find(char::is_whitespace).unwrap_or(text.len());
+    let mut url = &text[..end];
+    loop {
+        let trimmed = url.trim_end_matches(['.', ',', ';', ':', '!', '?', '\'', '"', '*']);
+        let trimmed = match trimmed.strip_suffix(')') {
+            Some(x) if x.matches('(').count() < trimmed.matches(')').count() => x,
+            _ => trimmed,
+        };
+        if trimmed.len() == url.len() {
+            return url.len();
+        }
+        url = trimmed;
+    }
+}
+
+/// `text` split into the `http://` and `https://` addresses in it and the
+/// text between them
+pub fn segments(text: &str) -> Vec<Segment<'_>> {
+    let mut segments = vec![];
+    // Start of the text not in a segment yet, and of where to look next
+    let (mut done, mut from) = (0, 0);
+    while let Some(start) = ["http://", "https://"]
+        .iter()
+        .filter_map(|x| text[from..].find(x))
+        .min()
+        .map(|x| from + x)
+    {
+        let len = url_len(&text[start..]);
+        let scheme = text[start..].find("//").unwrap_or(0) + 2;
+        from = start + scheme;
+        if len <= scheme {
+            // Nothing after the scheme, so it's only text
+            continue;
+        }
+        if start > done {
+            segments.push(Segment::Text(&text[done..start]));
+        }
+        segments.push(Segment::Url(&text[start..start + len]));
+        (done, from) = (start + len, start + len);
+    }
+    if done < text.len() {
+        segments.push(Segment::Text(&text[done..]));
+    }
+    segments
+}
+
+/// Open `url` in the default browser, or a folder in the file manager
+pub fn open(url: &str) -> Result<(), Error> {
+    let mut command = if cfg!(windows) {
+        // `start` would read the `&` of a query as the end of the command
+        let mut command = std::process::Command::new("rundll32");
+        command.arg("url.dll,FileProtocolHandler");
+        command
+    } else if cfg!(target_os = "macos") {
+        std::process::Command::new("open")
+    } else {
+        std::process::Command::new("xdg-open")
+    };
+    command.arg(url).spawn()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn segments_test() {
+        assert_eq!(
+            segments("See https://www.wsgf.org/dr/game. Or the forum"),
+            [
+                Segment::Text("See "),
+                Segment::Url("https://www.wsgf.org/dr/game"),
+                Segment::Text(". Or the forum"),
+            ]
+        );
+        assert_eq!(
+            segments("(http://example.com/a_(b)?c=1&d=2), http:// and http://x.org"),
+            [
+                Segment::Text("("),
+                Segment::Url("http://example.com/a_(b)?c=1&d=2"),
+                Segment::Text("), http:// and "),
+                Segment::Url("http://x.org"),
+            ]
+        );
+        assert_eq!(segments("No links"), [Segment::Text("No links")]);
+        assert_eq!(segments(""), []);
+    }
+}
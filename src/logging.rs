@@ -0,0 +1,89 @@
+use std::path::{Path, PathBuf};
+
+use tracing::Subscriber;
+use tracing_appender::rolling::{InitError, RollingFileAppender, Rotation};
+use tracing_subscriber::{EnvFilter, fmt, layer::SubscriberExt as _};
+
+/// Days of logs kept, older files are removed when a new one is started
+const KEPT: usize = 7;
+
+/// `~/.local/share/uniws/logs` (or the platform equivalent), with a file
+/// per day
+pub fn dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|x| x.join("uniws").join("logs"))
+}
+
+/// Write what's logged to a file per day in `dir`. `verbose` adds debug
+/// detail, like the hashes of the files patched, and prints it all to the
+/// terminal too
+pub fn init(dir: Option<PathBuf>, verbose: bool) {
+    // Only fails if one has been set already
+    let _ = tracing::subscriber::set_global_default(subscriber(dir.as_deref(), verbose));
+}
+
+/// `uniws.<day>.log` files in `dir`, keeping the newest [`KEPT`]
+fn appender(dir: &Path) -> Result<RollingFileAppender, InitError> {
+    RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix("uniws")
+        .filename_suffix("log")
+        .max_log_files(KEPT)
+        .build(dir)
+}
+
+/// Logs of this crate at info level, or debug if `verbose`. Other crates
+/// only get in with warnings
+fn subscriber(dir: Option<&Path>, verbose: bool) -> impl Subscriber + Send + Sync {
+    let level = match verbose {
+        true => "debug",
+        false => "info",
+    };
+    let filter = EnvFilter::new(format!("warn,{}={level}", env!("CARGO_CRATE_NAME")));
+
+    let file = dir
+        .and_then(|x| appender(x).ok())
+        .map(|x| fmt::layer().with_writer(x).with_ansi(false));
+    let terminal = verbose.then(|| fmt::layer().with_writer(std::io::stderr));
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(file)
+        .with(terminal)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+
+    #[test]
+    fn file_log_test() {
+        let dir = TempDir::new("logging");
+        for day in 1..=KEPT + 2 {
+            std::fs::write(dir.join(format!("uniws.2020-01-{day:02}.log")), "").unwrap();
+        }
+        std::fs::write(dir.join("other.txt"), "").unwrap();
+
+        tracing::subscriber::with_default(subscriber(Some(&dir), false), || {
+            tracing::info!(file = "game.exe", bytes = 12, "Scanned");
+            tracing::debug!("Only with --verbose");
+        });
+
+        let mut files = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|x| x.unwrap().file_name().into_string().unwrap())
+            .collect::<Vec<_>>();
+        files.sort();
+        assert_eq!(files.len(), KEPT + 1);
+        assert_eq!(files[0], "other.txt");
+
+        let today = files.last().unwrap();
+        assert!(!today.starts_with("uniws.2020"));
+        let log = std::fs::read_to_string(dir.join(today)).unwrap();
+        assert!(
+            log.ends_with("Z  INFO uniws_rs::logging::tests: Scanned file=\"game.exe\" bytes=12\n"),
+            "{log}"
+        );
+        assert_eq!(log.lines().count(), 1);
+    }
+}
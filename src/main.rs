@@ -1,27 +1,78 @@
+mod activity;
+mod backup;
+mod cli;
 mod config;
+mod cover;
+mod crash;
+mod derive;
+mod display;
+mod document;
+mod editor;
+mod encoding;
 mod error;
+mod expr;
+mod hexview;
+mod history;
+mod i18n;
+mod icon;
+mod installs;
+mod link;
+mod logging;
+mod markdown;
+mod model;
+mod palette;
 mod patch_info;
+mod preview;
+mod registry;
+mod remote;
+mod retention;
+mod scan;
+mod settings;
+mod share;
+mod shortcut;
 mod signature;
+mod steam;
+mod structured;
+mod template;
+mod testdb;
+#[cfg(test)]
+mod testing;
+mod text_patch;
+mod theme;
+mod validate;
+mod value;
+mod watch;
+mod wine;
 
 use std::{
-    collections::{HashMap, hash_map::Entry},
-    fs::File,
-    io::Read,
-    path::Path,
+    collections::HashMap,
+    path::{Path, PathBuf},
+    process::ExitCode,
 };
 
 use crate::{
-    config::{AppSection, Config},
+    backup::BackupMode,
+    cli::{Command, GuiArgs},
+    config::{AppSection, Config, DetailsFormat, DuplicatePolicy, Format, LoadOptions, ParseMode},
+    encoding::Encoding,
     error::Error,
-    patch_info::PatchStrategy,
+    i18n::tr,
+    model::{Action, AppEntry, ConfigState, Effect, Level, Model, Panel, SortOrder},
+    palette::{Entry, Palette},
+    retention::RetentionPolicy,
+    settings::Settings,
+    shortcut::Shortcut,
+    theme::{ThemeMode, button},
 };
 use iced::{
     Length, Task, Theme,
     keyboard::{Event as KeyboardEvent, Key, key::Named},
     padding::Padding,
     widget::{
-        button, column, container, horizontal_space, pick_list, row, text, text_input,
-        vertical_rule, vertical_space,
+        button::{self},
+        center, checkbox, column, container, horizontal_space, mouse_area, opaque, pick_list,
+        rich_text, row, scrollable, slider, span, stack, text, text_input, vertical_rule,
+        vertical_space,
     },
 };
 
@@ -30,20 +81,123 @@ enum Message {
     Event(iced::Event),
     SelectGameDir,
     LoadConfig,
-    ConfigLoaded(Result<Config, Error>),
+    /// Add configs to merge into the loaded ones
+    AddConfig,
+    ConfigUrlChanged(String),
+    /// Download the config at [`Model::config_url`]
+    LoadUrl,
+    /// Address downloaded from, and where the config was saved
+    UrlLoaded(String, Result<PathBuf, Error>),
+    /// Download the config at [`Model::sync_url`] if there's a newer version
+    CheckForUpdates,
+    Synced(Result<remote::Synced, Error>),
+    /// Go back to the version of the downloaded config before the last update
+    RollBack,
+    /// Result of looking for a newer downloaded config on startup
+    UpdateChecked(Result<Option<remote::Available>, Error>),
+    UpdateAccepted,
+    UpdateDismissed,
+    /// Web address clicked in a game's details
+    LinkClicked(String),
+    ImageLoaded(cover::Source, Result<cover::Picture, Error>),
+    /// Icon of the executable at the path
+    IconLoaded(PathBuf, Result<cover::Picture, Error>),
+    /// Files merged into the config, none for the built in one
+    ConfigLoaded(Vec<PathBuf>, Result<Config, Error>),
+    /// The loaded config was changed on disk
+    ConfigChanged(PathBuf),
+    ConfigDropped(PathBuf, Result<Config, Error>),
     AppSelected(String),
+    FilterChanged(String),
+    SortSelected(SortOrder),
+    SourceSelected(Option<PathBuf>),
     GameDirChanged(String),
+    DisplaysFound(Option<Vec<display::Display>>),
+    UseCurrentResolution,
+    DisplaySelected(display::Display),
+    PresetSelected(display::Preset),
+    AspectLockToggled(bool),
     WidthCHanged(String),
     HeightChanged(String),
+    WidthStepped(i16),
+    HeightStepped(i16),
+    StrictToggled(bool),
+    EncodingSelected(Encoding),
+    DuplicatesSelected(DuplicatePolicy),
+    LanguageSelected(i18n::Language),
+    ThemeSelected(ThemeMode),
+    /// The scale slider moved, applied once it's let go
+    ScaleDragged(u16),
+    ScaleReleased,
+    /// Show or hide the keyboard shortcuts
+    ShowShortcuts(bool),
+    /// Open or close the command palette
+    ShowPalette(bool),
+    PaletteChanged(String),
+    /// Move the palette's selection up or down
+    PaletteMoved(isize),
+    /// Run the palette's selected match
+    PaletteSubmitted,
+    PaletteRun(Entry),
+    OpenBackupsFolder,
+    /// Open or collapse the log at the bottom of the window
+    ToggleLog,
+    CopyLog,
+    CentralBackupToggled(bool),
+    OptionToggled(String, bool),
+    ValueChanged(String, String),
     ApplyPatch,
-}
-
-#[derive(Debug, Default, Clone)]
-enum ConfigState {
-    #[default]
-    NotLoaded,
-    Loaded(Config),
-    Error(Error),
+    PreviewPatch,
+    ExportDiff,
+    ExportBundle,
+    RestoreBundle,
+    TogglePanel(Panel),
+    RestoreEverything,
+    RetentionSelected(RetentionPolicy),
+    CleanBackups,
+    RestoreBackup(usize),
+    DeleteBackup(usize),
+    CustomFieldChanged(&'static str, String),
+    PasteShareLink,
+    ShareLinkOpened(Option<String>),
+    /// Fill in the values given on the command line, once the config is loaded
+    Launched,
+    CopyShareLink,
+    ApplyCustomPatch,
+    PreviewCustomPatch,
+    SigPatternChanged(String),
+    SigFileChanged(String),
+    TestSignature,
+    SaveSignature,
+    ScanCandidates,
+    UseCandidate(usize),
+    DeriveSignature,
+    CopyDerived,
+    ShowInHexView(usize),
+    HexFileChanged(String),
+    HexLoad,
+    HexGotoChanged(String),
+    HexGoto,
+    HexSearchChanged(String),
+    HexSearch,
+    HexNextHit,
+    HexScroll(isize),
+    HexSelect(usize),
+    HexExpertToggled(bool),
+    HexByteChanged(String),
+    HexEdit,
+    HexSave,
+    EditSelected,
+    EditNew,
+    EditorNameChanged(String),
+    EditorFieldChanged(Option<usize>, &'static str, String),
+    EditorAddPatch,
+    EditorRemovePatch(usize),
+    /// Pick a file to search for the editor's signatures in
+    EditorChooseBinary,
+    /// Save the editor's game to the personal config
+    EditorSave,
+    ValidateConfig,
 }
 
 type Element<'a> = iced::Element<'a, Message>;
@@ -62,300 +216,1869 @@ fn bordered_container<'a>(
     })
 }
 
+/// Sizes the interface can be scaled to, in percent
+const SCALES: std::ops::RangeInclusive<u16> = 50..=300;
+
+/// Height a game's image is shown at, and of the box shown until it loads
+const IMAGE_HEIGHT: f32 = 240.0;
+
+/// The box shown where a game's image goes when it can't be yet
+fn image_placeholder<'a>(message: impl text::IntoFragment<'a>) -> Element<'a> {
+    bordered_container(text(message).size(14), 1.0)
+        .center_x(Length::Fill)
+        .center_y(IMAGE_HEIGHT)
+        .into()
+}
+
+#[cfg(feature = "images")]
+fn game_image(picture: &cover::Picture) -> Element<'_> {
+    iced::widget::image::<cover::Picture>(picture.clone())
+        .height(IMAGE_HEIGHT)
+        .width(Length::Fill)
+        .into()
+}
+
+#[cfg(not(feature = "images"))]
+fn game_image(_: &cover::Picture) -> Element<'_> {
+    image_placeholder(tr!("image-unsupported"))
+}
+
+/// An executable's icon, `size` pixels square. Nothing without the images
+/// feature, since it's only there to recognise the game by
+#[cfg(feature = "images")]
+fn game_icon(picture: &cover::Picture, size: f32) -> Option<Element<'_>> {
+    Some(
+        iced::widget::image::<cover::Picture>(picture.clone())
+            .width(size)
+            .height(size)
+            .into(),
+    )
+}
+
+#[cfg(not(feature = "images"))]
+fn game_icon(_: &cover::Picture, _: f32) -> Option<Element<'_>> {
+    None
+}
+
+/// A game's details as `format` lays them out, with links underlined in
+/// `color` and opened when clicked
+fn details_text<'a>(details: &'a str, format: DetailsFormat, color: iced::Color) -> Element<'a> {
+    const SIZE: u16 = 20;
+
+    let spans = |inlines: &[markdown::Inline<'a>], bold: bool| -> Vec<_> {
+        inlines
+            .iter()
+            .map(|x| {
+                let font = iced::Font {
+                    weight: match x.strong || bold {
+                        true => iced::font::Weight::Bold,
+                        false => iced::font::Weight::Normal,
+                    },
+                    style: match x.emphasis {
+                        true => iced::font::Style::Italic,
+                        false => iced::font::Style::Normal,
+                    },
+                    ..match x.code {
+                        true => iced::Font::MONOSPACE,
+                        false => iced::Font::DEFAULT,
+                    }
+                };
+                let span = span(x.text).font(font);
+                match x.link {
+                    Some(url) => span
+                        .color(color)
+                        .underline(true)
+                        .link(Message::LinkClicked(url.into())),
+                    None => span,
+                }
+            })
+            .collect()
+    };
+
+    if format == DetailsFormat::Text {
+        let inlines: Vec<_> = link::segments(details)
+            .into_iter()
+            .map(|x| match x {
+                link::Segment::Text(text) => markdown::Inline {
+                    text,
+                    ..Default::default()
+                },
+                link::Segment::Url(url) => markdown::Inline {
+                    text: url,
+                    link: Some(url),
+                    ..Default::default()
+                },
+            })
+            .collect();
+        return rich_text(spans(&inlines, false)).size(SIZE).into();
+    }
+
+    let blocks = markdown::parse(details)
+        .into_iter()
+        .map(|block| match block {
+            markdown::Block::Heading(level, content) => rich_text(spans(&content, true))
+                .size(match level {
+                    1 => 32,
+                    2 => 28,
+                    3 => 24,
+                    _ => SIZE,
+                })
+                .into(),
+            markdown::Block::Paragraph(content) => {
+                rich_text(spans(&content, false)).size(SIZE).into()
+            }
+            markdown::Block::Item {
+                depth,
+                marker,
+                content,
+            } => {
+                let marker = match marker {
+                    markdown::Marker::Bullet => "•".to_string(),
+                    markdown::Marker::Number(n) => format!("{n}."),
+                };
+                row![
+                    text(marker).size(SIZE),
+                    rich_text(spans(&content, false)).size(SIZE)
+                ]
+                .spacing(8)
+                .padding(Padding::ZERO.left(depth as f32 * 24.0))
+                .into()
+            }
+        });
+    column(blocks).spacing(8).into()
+}
+
+/// Offer a newer version of the downloaded config, listing the games it adds
+fn update_banner(update: &remote::Available) -> Element<'_> {
+    const LISTED: usize = 10;
+
+    let version = &update.fetched.config.apps.version;
+    let mut message = match &update.from {
+        Some(from) => tr!("update-available-from", version = version, from = from),
+        None => tr!("update-available", version = version),
+    };
+    if !update.added.is_empty() {
+        let mut added = update
+            .added
+            .iter()
+            .take(LISTED)
+            .cloned()
+            .collect::<Vec<_>>();
+        if update.added.len() > LISTED {
+            added.push(tr!("update-more", count = update.added.len() - LISTED));
+        }
+        message += &format!("\n{}", tr!("update-adds", games = added.join(", ")));
+    }
+
+    let content = row![
+        text(message).width(Length::Fill),
+        button(text(tr!("update"))).on_press(Message::UpdateAccepted),
+        button(text(tr!("dismiss")))
+            .style(theme::secondary)
+            .on_press(Message::UpdateDismissed),
+    ]
+    .align_y(iced::alignment::Vertical::Center)
+    .spacing(8);
+    bordered_container(content, 1.0)
+        .padding(8)
+        .width(Length::Fill)
+        .into()
+}
+
+/// Id of the command palette's field, focused when it opens
+const PALETTE_INPUT: &str = "palette";
+
+/// `content` in a box over the rest of the window. Clicking beside it sends
+/// `close` rather than reaching what's under it
+fn overlay<'a>(content: impl Into<Element<'a>>, close: Message) -> Element<'a> {
+    let card = container(content)
+        .padding(16)
+        .style(|theme: &Theme| container::Style {
+            background: Some(theme.palette().background.into()),
+            border: iced::Border {
+                color: theme.palette().text,
+                width: 2.0,
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+    let backdrop = center(opaque(card)).style(|_| container::Style {
+        background: Some(iced::Color::BLACK.scale_alpha(0.6).into()),
+        ..Default::default()
+    });
+    opaque(mouse_area(backdrop).on_press(close))
+}
+
+/// Every [`Shortcut`] and its keys, over the rest of the window
+fn shortcuts_overlay<'a>() -> Element<'a> {
+    let shortcuts = Shortcut::ALL.into_iter().map(|x| {
+        row![
+            text(x.keys()).width(Length::Fixed(120.0)),
+            text(x.to_string())
+        ]
+        .spacing(8)
+        .into()
+    });
+    let content = column![text(tr!("shortcuts")).size(20)]
+        .extend(shortcuts)
+        .push(row![
+            horizontal_space(),
+            button(text(tr!("close"))).on_press(Message::ShowShortcuts(false))
+        ])
+        .spacing(8)
+        .width(Length::Fixed(420.0));
+    overlay(content, Message::ShowShortcuts(false))
+}
+
+/// Keys taken by a focused field that still reach the palette, so it can be
+/// opened while typing and its matches picked from its own field. A text
+/// field lets the arrows through, so those also come as a [`Message::Event`]
+fn captured_key(
+    event: iced::Event,
+    status: iced::event::Status,
+    _: iced::window::Id,
+) -> Option<Message> {
+    let iced::Event::Keyboard(KeyboardEvent::KeyPressed { key, modifiers, .. }) = event else {
+        return None;
+    };
+    if status != iced::event::Status::Captured {
+        return None;
+    }
+    match (key.as_ref(), Shortcut::from_key(&key, modifiers)) {
+        (Key::Named(Named::ArrowUp), _) => Some(Message::PaletteMoved(-1)),
+        (Key::Named(Named::ArrowDown), _) => Some(Message::PaletteMoved(1)),
+        (_, Some(Shortcut::Palette)) => Some(Message::ShowPalette(true)),
+        (_, Some(Shortcut::Close)) => Some(Message::ShowPalette(false)),
+        _ => None,
+    }
+}
+
+/// Load and merge `paths`, see [`Model::config_paths`]
+fn load_configs(paths: Vec<PathBuf>, options: LoadOptions) -> Task<Message> {
+    Task::perform(Model::load_configs(paths.clone(), options), move |x| {
+        Message::ConfigLoaded(paths.clone(), x)
+    })
+}
+
+/// The iced front end; all patching state lives in [`Model`]
 #[derive(Debug, Default)]
 struct App {
-    config: ConfigState,
-    selected_section: Option<String>,
-    game_dir: Option<String>,
-    width: Option<u16>,
-    height: Option<u16>,
+    model: Model,
+    theme_mode: ThemeMode,
+    /// What `theme_mode` stands for, kept so the desktop isn't asked every
+    /// frame
+    theme: Theme,
+    /// Where the session is saved on close, if anywhere
+    settings_file: Option<PathBuf>,
+    window_size: Option<iced::Size>,
+    /// Size of the interface in percent, see [`SCALES`]
+    scale: u16,
+    /// Where the scale slider is while it's dragged
+    scale_dragged: Option<u16>,
+    window_position: Option<iced::Point>,
+    /// Command line values still to fill in
+    args: GuiArgs,
+    /// Newer downloaded config offered in a banner
+    update: Option<remote::Available>,
+    /// Images of the games shown so far, by where they're from
+    images: HashMap<cover::Source, cover::State>,
+    /// Icons of the games' executables, by path
+    icons: HashMap<PathBuf, cover::State>,
+    /// Themes of the user's, see [`theme::load`]
+    custom_themes: Vec<theme::Custom>,
+    /// Theme files that couldn't be read, reported once the window is up
+    broken_themes: Vec<Error>,
+    /// English and the translations found, see [`i18n::languages`]
+    languages: Vec<i18n::Language>,
+    /// Whether the keyboard shortcuts are listed over the window
+    shortcuts: bool,
+    /// The command palette, while it's open
+    palette: Option<Palette>,
+    /// Whether the log's entries are shown, rather than only its latest
+    log_shown: bool,
 }
 impl App {
-    async fn load_config(path: impl AsRef<Path>) -> Result<Config, Error> {
-        tokio::fs::read_to_string(path)
-            .await
-            .map_err(Error::from)
-            .and_then(|x: String| Config::new(&x))
+    /// Pick up the settings and history of the last run
+    fn new() -> Self {
+        let mut app = Self {
+            settings_file: settings::path(),
+            languages: i18n::languages(i18n::dir().as_deref()),
+            scale: 100,
+            ..Default::default()
+        };
+        (app.custom_themes, app.broken_themes) = theme::load(theme::dir().as_deref());
+        app.model.history_file = history::path();
+        app.model.personal_config = config::personal_path();
+        if let Some(path) = &app.model.history_file {
+            app.model.recent = history::load(path);
+        }
+
+        if let Some(path) = &app.settings_file {
+            let settings = settings::load(path);
+            app.theme_mode = settings
+                .theme
+                .as_deref()
+                .and_then(|x| ThemeMode::from_name(x, &app.custom_themes))
+                .unwrap_or_default();
+            // A language that's gone stays in English
+            if let Some(code) = &settings.language {
+                let _ = i18n::select(i18n::dir().as_deref(), code);
+            }
+            app.window_size = settings
+                .window_size
+                .map(|(w, h)| iced::Size::new(w as f32, h as f32));
+            app.scale = settings.scale.unwrap_or(100);
+            app.window_position = settings
+                .window_position
+                .map(|(x, y)| iced::Point::new(x as f32, y as f32));
+            app.model.apply_settings(settings);
+        }
+        app.theme = app.theme_mode.resolve();
+        app
     }
 
-    fn read_game_data(path: impl AsRef<Path>) -> Result<Vec<u8>, Error> {
-        let mut file = File::open(path)?;
-        let capacity = file.metadata().map(|m| m.len()).unwrap_or_default();
-        let mut buf = Vec::with_capacity(capacity as usize);
-        file.read_to_end(&mut buf)?;
-        Ok(buf)
+    fn subscription(&self) -> iced::Subscription<Message> {
+        let events = iced::event::listen().map(Message::Event);
+        let includes = match &self.model.config {
+            ConfigState::Loaded(config) => config.includes.as_slice(),
+            _ => &[],
+        };
+        let configs = self.model.config_paths.iter().chain(includes).map(|path| {
+            iced::Subscription::run_with_id(path.clone(), watch::changes(path.clone()))
+                .map(Message::ConfigChanged)
+        });
+
+        let captured = iced::event::listen_with(captured_key);
+
+        iced::Subscription::batch([events, captured].into_iter().chain(configs))
     }
 
-    fn apply_patches(&self, section: &AppSection) -> Result<bool, Error> {
-        if let Some(dir) = self.game_dir.as_deref() {
-            let game_path = Path::new(dir);
+    /// Show `description` in a dialog, and keep it in the log
+    fn notify(&mut self, level: Level, description: impl Into<String>) {
+        let description = description.into();
+        self.model.log.push(level, description.clone());
+        let level = match level {
+            Level::Info => rfd::MessageLevel::Info,
+            Level::Warning => rfd::MessageLevel::Warning,
+            Level::Error => rfd::MessageLevel::Error,
+        };
+        rfd::MessageDialog::new()
+            .set_level(level)
+            .set_buttons(rfd::MessageButtons::Ok)
+            .set_description(description)
+            .show();
+    }
 
-            let mut game_data_library = HashMap::new();
+    /// Run `action` through the model and present whatever it asks for
+    fn dispatch(&mut self, action: Action) {
+        let mut effect = self.model.update(action);
+        loop {
+            effect = match effect {
+                Effect::None => break,
+                Effect::Notify(level, description) => {
+                    self.notify(level, description);
+                    break;
+                }
+                Effect::Confirm(question) => {
+                    let answer = rfd::MessageDialog::new()
+                        .set_level(rfd::MessageLevel::Warning)
+                        .set_buttons(rfd::MessageButtons::YesNo)
+                        .set_description(question)
+                        .show();
 
-            let patch_strategies = section
-                .patches
-                .iter()
-                .enumerate()
-                .map(|(i, p)| -> Result<PatchStrategy, Error> {
-                    let data = match game_data_library.entry(p.modfile.as_str()) {
-                        Entry::Vacant(vacant) => {
-                            let path = game_path.join(&p.modfile);
-                            let data = Self::read_game_data(path)?;
-                            vacant.insert(data)
-                        }
-                        Entry::Occupied(o) => o.into_mut(),
-                    };
+                    self.model
+                        .update(Action::Confirmed(answer == rfd::MessageDialogResult::Yes))
+                }
+            };
+        }
+    }
 
-                    let offsets = p.find_patch_offsets(data, i)?;
-                    Ok(PatchStrategy {
-                        base_directory: game_path,
-                        modfile: &p.modfile,
-                        offsets,
-                    })
-                })
-                .collect::<Result<Vec<_>, _>>()?;
+    /// [`Self::handle`] `msg`, then start loading the image and icon of the
+    /// game selected if they haven't been already
+    fn update(&mut self, msg: Message) -> Task<Message> {
+        let mut tasks = vec![self.handle(msg)];
+        if let Some(source) = self.selected_image()
+            && !self.images.contains_key(&source)
+        {
+            self.images.insert(source.clone(), cover::State::Loading);
+            tasks.push(Task::perform(cover::load(source.clone()), move |x| {
+                Message::ImageLoaded(source.clone(), x)
+            }));
+        }
+        // Icons can't be shown without the images feature, see `game_icon`
+        if cfg!(feature = "images")
+            && let Some(path) = self.model.checkfile_path()
+            && !self.icons.contains_key(&path)
+        {
+            self.icons.insert(path.clone(), cover::State::Loading);
+            tasks.push(Task::perform(icon::load(path.clone()), move |x| {
+                Message::IconLoaded(path.clone(), x)
+            }));
+        }
+        Task::batch(tasks)
+    }
 
-            let width = self.width.ok_or(Error::state_error("Missing width"))?;
-            let height = self.height.ok_or(Error::state_error("Missing height"))?;
+    /// Icon of the selected game's executable, once it's loaded
+    fn selected_icon(&self, size: f32) -> Option<Element<'_>> {
+        match self.icons.get(&self.model.checkfile_path()?)? {
+            cover::State::Loaded(picture) => game_icon(picture, size),
+            _ => None,
+        }
+    }
 
-            for s in patch_strategies {
-                let file_data = game_data_library
-                    .get_mut(s.modfile)
-                    .expect("Missing game data?");
+    /// Where the image of the selected game is, if it has one
+    fn selected_image(&self) -> Option<cover::Source> {
+        let image = self.model.get_selected_app_section()?.image.as_deref()?;
+        Some(cover::Source::locate(image, self.model.selected_source()))
+    }
 
-                s.apply(file_data, width, height)?
-            }
+    /// Whether the patch button is the custom patch's rather than the
+    /// selected game's
+    fn custom_patching(&self) -> bool {
+        self.model.panel == Panel::CustomPatch
+            || (self.model.panel == Panel::Preview && self.model.preview.custom)
+    }
 
-            Ok(true)
-        } else {
-            Err(Error::state_error("Missing game dir"))
+    /// Whether the patch button can be pressed
+    fn can_apply(&self) -> bool {
+        match self.custom_patching() {
+            true => self
+                .model
+                .custom_section()
+                .is_ok_and(|s| self.model.can_patch(&s)),
+            false => self
+                .model
+                .get_selected_app_section()
+                .is_some_and(|s| self.model.can_patch(s)),
         }
     }
 
-    fn subscription(&self) -> iced::Subscription<Message> {
-        iced::event::listen().map(Message::Event)
+    /// Do what `shortcut` does, if it can be done now. Only closing it
+    /// works while the palette is open
+    fn shortcut(&self, shortcut: Shortcut) -> Task<Message> {
+        if self.palette.is_some()
+            && !matches!(
+                shortcut,
+                Shortcut::NextField | Shortcut::PreviousField | Shortcut::Close
+            )
+        {
+            return Task::none();
+        }
+        let message = match shortcut {
+            Shortcut::NextField => return iced::widget::focus_next(),
+            Shortcut::PreviousField => return iced::widget::focus_previous(),
+            Shortcut::LoadConfig => Some(Message::LoadConfig),
+            Shortcut::SelectGameDir => Some(Message::SelectGameDir),
+            Shortcut::Apply => self.can_apply().then(|| match self.custom_patching() {
+                true => Message::ApplyCustomPatch,
+                false => Message::ApplyPatch,
+            }),
+            Shortcut::Restore => self
+                .model
+                .game_dir
+                .is_some()
+                .then_some(Message::RestoreEverything),
+            Shortcut::Help => Some(Message::ShowShortcuts(!self.shortcuts)),
+            Shortcut::Palette => Some(Message::ShowPalette(true)),
+            Shortcut::Close if self.palette.is_some() => Some(Message::ShowPalette(false)),
+            Shortcut::Close => self.shortcuts.then_some(Message::ShowShortcuts(false)),
+        };
+        message.map_or_else(Task::none, Task::done)
     }
 
-    fn update(&mut self, msg: Message) -> Task<Message> {
-        match msg {
-            Message::Event(e) => match e {
-                iced::Event::Keyboard(KeyboardEvent::KeyPressed {
-                    key: Key::Named(Named::Tab),
-                    modifiers,
-                    ..
-                }) => {
-                    if modifiers.shift() {
-                        iced::widget::focus_previous()
-                    } else {
-                        iced::widget::focus_next()
+    /// Every game of the config, for the palette to search
+    fn games(&self) -> &[String] {
+        match &self.model.config {
+            ConfigState::Loaded(config) => &config.apps.apps,
+            _ => &[],
+        }
+    }
+
+    /// Do what `command` of the palette does, if it can be done now
+    fn run_command(&self, command: palette::Command) -> Task<Message> {
+        let panel = |panel| (self.model.panel != panel).then_some(Message::TogglePanel(panel));
+        let message = match command {
+            palette::Command::LoadConfig => Some(Message::LoadConfig),
+            palette::Command::AddConfig => Some(Message::AddConfig),
+            palette::Command::SelectGameDir => Some(Message::SelectGameDir),
+            palette::Command::Apply => return self.shortcut(Shortcut::Apply),
+            palette::Command::DryRun => self.can_apply().then(|| match self.custom_patching() {
+                true => Message::PreviewCustomPatch,
+                false => Message::PreviewPatch,
+            }),
+            palette::Command::Restore => return self.shortcut(Shortcut::Restore),
+            palette::Command::OpenBackups => Some(Message::OpenBackupsFolder),
+            palette::Command::Backups => panel(Panel::Backups),
+            palette::Command::CustomPatch => panel(Panel::CustomPatch),
+            palette::Command::SignatureBuilder => panel(Panel::SignatureBuilder),
+            palette::Command::HexView => panel(Panel::HexView),
+            palette::Command::Editor => panel(Panel::Editor),
+            palette::Command::Validate => Some(Message::ValidateConfig),
+            palette::Command::CheckForUpdates => self
+                .model
+                .sync_url
+                .is_some()
+                .then_some(Message::CheckForUpdates),
+            palette::Command::Shortcuts => Some(Message::ShowShortcuts(true)),
+        };
+        message.map_or_else(Task::none, Task::done)
+    }
+
+    fn handle(&mut self, msg: Message) -> Task<Message> {
+        let action = match msg {
+            Message::Event(e) => {
+                return match e {
+                    // The palette's own field doesn't take the arrows
+                    iced::Event::Keyboard(KeyboardEvent::KeyPressed {
+                        key: Key::Named(arrow @ (Named::ArrowUp | Named::ArrowDown)),
+                        ..
+                    }) if self.palette.is_some() => {
+                        let delta = match arrow {
+                            Named::ArrowUp => -1,
+                            _ => 1,
+                        };
+                        self.handle(Message::PaletteMoved(delta))
                     }
-                }
-                _ => Task::none(),
-            },
+                    iced::Event::Keyboard(KeyboardEvent::KeyPressed { key, modifiers, .. }) => {
+                        match Shortcut::from_key(&key, modifiers) {
+                            Some(shortcut) => self.shortcut(shortcut),
+                            None => Task::none(),
+                        }
+                    }
+                    // Minimizing on Windows resizes to nothing and moves off to -32000
+                    iced::Event::Window(iced::window::Event::Resized(size)) => {
+                        // Reported in scaled units, but restored in unscaled ones
+                        let size = size * self.scale_factor() as f32;
+                        if size.width >= 200.0 && size.height >= 200.0 {
+                            self.window_size = Some(size);
+                        }
+                        Task::none()
+                    }
+                    iced::Event::Window(iced::window::Event::Moved(position)) => {
+                        if position.x > -30000.0 && position.y > -30000.0 {
+                            self.window_position = Some(position);
+                        }
+                        Task::none()
+                    }
+                    // The desktop may have switched between light and dark
+                    iced::Event::Window(iced::window::Event::Focused)
+                        if self.theme_mode == ThemeMode::System =>
+                    {
+                        self.theme = self.theme_mode.resolve();
+                        Task::none()
+                    }
+                    iced::Event::Window(iced::window::Event::CloseRequested) => {
+                        self.save_settings();
+                        iced::window::get_oldest().and_then(iced::window::close)
+                    }
+                    iced::Event::Window(iced::window::Event::FileDropped(path))
+                        if path.extension().is_some_and(|x| {
+                            Format::EXTENSIONS.iter().any(|e| x.eq_ignore_ascii_case(e))
+                        }) =>
+                    {
+                        Task::perform(
+                            Model::load_config(path.clone(), self.model.load_options),
+                            move |x| Message::ConfigDropped(path.clone(), x),
+                        )
+                    }
+                    iced::Event::Window(iced::window::Event::FileDropped(path)) => {
+                        self.dispatch(Action::PathDropped(path));
+                        Task::none()
+                    }
+                    _ => Task::none(),
+                };
+            }
             Message::SelectGameDir => {
-                let dir = rfd::FileDialog::new().pick_folder();
-                self.game_dir = dir.as_ref().map(|x| x.to_string_lossy().into_owned());
+                // Steam folders are hard to find by hand on Linux, especially
+                // under Flatpak, so start in the games or the Proton prefixes
+                let start = self
+                    .model
+                    .game_dir
+                    .as_ref()
+                    .map(PathBuf::from)
+                    .or_else(|| steam::common_dirs().into_iter().next())
+                    .or_else(|| steam::compatdata_dirs().into_iter().next());
+                let dialog = match start {
+                    Some(dir) => rfd::FileDialog::new().set_directory(dir),
+                    None => rfd::FileDialog::new(),
+                };
+                let dir = dialog.pick_folder();
+                Action::GameDirChanged(dir.map(|x| x.to_string_lossy().into_owned()))
+            }
+            Message::LoadConfig => {
+                let file = rfd::FileDialog::new()
+                    .add_filter(tr!("filter-config"), &Format::EXTENSIONS)
+                    .set_title(tr!("load-config-title"))
+                    .pick_file();
 
-                Task::none()
+                return match file {
+                    Some(file) => load_configs(vec![file], self.model.load_options),
+                    None => Task::none(),
+                };
             }
-            Message::GameDirChanged(dir) => {
-                self.game_dir = Some(dir);
-                Task::none()
+            Message::AddConfig => {
+                let files = rfd::FileDialog::new()
+                    .add_filter(tr!("filter-config"), &Format::EXTENSIONS)
+                    .set_title(tr!("add-config-title"))
+                    .pick_files()
+                    .unwrap_or_default();
+                if files.is_empty() {
+                    return Task::none();
+                }
+
+                // Added again moves it last, so its games win
+                let mut paths = self.model.config_paths.clone();
+                paths.retain(|x| !files.contains(x));
+                paths.extend(files);
+                return load_configs(paths, self.model.load_options);
             }
-            Message::AppSelected(app) => {
-                self.selected_section = Some(app);
-                Task::none()
+            Message::ExportBundle => {
+                let Some(record) = &self.model.last_backup else {
+                    return Task::none();
+                };
+
+                let file = rfd::FileDialog::new()
+                    .add_filter(tr!("filter-bundle"), &["tar"])
+                    .set_title(tr!("export-bundle-title"))
+                    .set_file_name(format!("{}.tar", record.manifest.timestamp))
+                    .save_file();
+
+                match file {
+                    Some(file) => Action::ExportBundle(file),
+                    None => return Task::none(),
+                }
             }
-            Message::LoadConfig => {
+            Message::RestoreBundle => {
                 let file = rfd::FileDialog::new()
-                    .add_filter("Config file", &["ini"])
-                    .set_title("Load config file")
+                    .add_filter(tr!("filter-bundle"), &["tar"])
+                    .set_title(tr!("restore-bundle-title"))
                     .pick_file();
 
                 match file {
-                    Some(file) => Task::perform(Self::load_config(file), Message::ConfigLoaded),
+                    Some(file) => Action::RestoreBundle(file),
+                    None => return Task::none(),
+                }
+            }
+            Message::ConfigChanged(path) => {
+                // Every file is loaded again to merge them in order
+                let included = match &self.model.config {
+                    ConfigState::Loaded(config) => config.includes.contains(&path),
+                    _ => false,
+                };
+                if !self.model.config_paths.contains(&path) && !included {
+                    return Task::none();
+                }
+                return load_configs(self.model.config_paths.clone(), self.model.load_options);
+            }
+            Message::ConfigLoaded(paths, config) => {
+                if config.is_ok() {
+                    self.model.config_paths = paths;
+                }
+                Action::ConfigLoaded(config)
+            }
+            Message::ConfigDropped(path, config) => Action::ConfigDropped(path, config),
+            Message::AppSelected(app) => Action::AppSelected(app),
+            Message::FilterChanged(filter) => Action::FilterChanged(filter),
+            Message::ConfigUrlChanged(url) => Action::ConfigUrlChanged(url),
+            Message::LoadUrl => {
+                let url = self.model.config_url.trim().to_string();
+                return Task::perform(remote::download_config(url.clone()), move |x| {
+                    Message::UrlLoaded(url.clone(), x)
+                });
+            }
+            Message::UrlLoaded(url, Ok(path)) => {
+                self.model.sync_url = Some(url);
+                return load_configs(vec![path], self.model.load_options);
+            }
+            Message::UrlLoaded(_, Err(e)) | Message::Synced(Err(e)) => {
+                self.notify(Level::Error, e.to_string());
+                return Task::none();
+            }
+            Message::CheckForUpdates => {
+                return match self.model.sync_url.clone() {
+                    Some(url) => Task::perform(remote::sync(url), Message::Synced),
                     None => Task::none(),
+                };
+            }
+            Message::Synced(Ok(remote::Synced::UpToDate(version))) => {
+                self.notify(Level::Info, tr!("sync-up-to-date", version = version));
+                return Task::none();
+            }
+            Message::Synced(Ok(remote::Synced::Updated { path, from, to })) => {
+                let message = match from {
+                    Some(from) => tr!("sync-updated-from", from = from, to = to),
+                    None => tr!("sync-updated", to = to),
+                };
+                self.notify(Level::Info, message);
+                return self.reload_downloaded(path);
+            }
+            Message::UpdateChecked(result) => {
+                // Only worth a mention when there's something new
+                self.update = result.ok().flatten();
+                return Task::none();
+            }
+            Message::UpdateAccepted => {
+                let (Some(update), Some(dir)) = (self.update.take(), remote::cache_dir()) else {
+                    return Task::none();
+                };
+                return match remote::save(&dir, &update.fetched) {
+                    Ok(path) => self.reload_downloaded(path),
+                    Err(e) => {
+                        self.notify(Level::Error, e.to_string());
+                        Task::none()
+                    }
+                };
+            }
+            Message::UpdateDismissed => {
+                self.update = None;
+                return Task::none();
+            }
+            Message::ImageLoaded(source, result) => {
+                let state = match result {
+                    Ok(picture) => cover::State::Loaded(picture),
+                    Err(e) => cover::State::Failed(e),
+                };
+                self.images.insert(source, state);
+                return Task::none();
+            }
+            Message::IconLoaded(path, result) => {
+                let state = match result {
+                    Ok(picture) => cover::State::Loaded(picture),
+                    Err(e) => cover::State::Failed(e),
+                };
+                self.icons.insert(path, state);
+                return Task::none();
+            }
+            Message::LinkClicked(url) => {
+                if let Err(e) = link::open(&url) {
+                    self.notify(Level::Error, tr!("error-open-link", url = url, error = e));
                 }
+                return Task::none();
             }
-            Message::ConfigLoaded(config) => {
-                self.config = match config {
-                    Ok(config) => {
-                        self.selected_section = config.apps.apps.first().cloned();
-                        ConfigState::Loaded(config)
+            Message::RollBack => {
+                let rolled_back = match (remote::cache_dir(), &self.model.sync_url) {
+                    (Some(dir), Some(url)) => remote::rollback(&dir, url),
+                    _ => return Task::none(),
+                };
+                return match rolled_back {
+                    Ok(path) => self.reload_downloaded(path),
+                    Err(e) => {
+                        self.notify(Level::Error, e.to_string());
+                        Task::none()
                     }
-                    Err(e) => ConfigState::Error(e),
                 };
-
-                Task::none()
             }
-            Message::WidthCHanged(width) => {
-                self.width = if width.is_empty() {
-                    None
-                } else if let Ok(value) = width.parse() {
-                    Some(value)
-                } else {
-                    self.width
+            Message::SortSelected(sort) => Action::SortSelected(sort),
+            Message::SourceSelected(source) => Action::SourceSelected(source),
+            Message::GameDirChanged(dir) => Action::GameDirChanged(Some(dir)),
+            Message::DisplaysFound(displays) => Action::DisplaysFound(displays),
+            Message::UseCurrentResolution => Action::UseCurrentResolution,
+            Message::DisplaySelected(display) => Action::DisplaySelected(display),
+            Message::PresetSelected(preset) => Action::PresetSelected(preset),
+            Message::AspectLockToggled(locked) => Action::AspectLockToggled(locked),
+            Message::WidthCHanged(width) => Action::WidthChanged(width),
+            Message::HeightChanged(height) => Action::HeightChanged(height),
+            Message::WidthStepped(step) => Action::WidthStepped(step),
+            Message::HeightStepped(step) => Action::HeightStepped(step),
+            Message::StrictToggled(strict) => {
+                self.dispatch(Action::StrictToggled(strict));
+                return self.reload_configs();
+            }
+            Message::EncodingSelected(encoding) => {
+                self.dispatch(Action::EncodingSelected(encoding));
+                return self.reload_configs();
+            }
+            Message::DuplicatesSelected(policy) => {
+                self.dispatch(Action::DuplicatesSelected(policy));
+                return self.reload_configs();
+            }
+            Message::LanguageSelected(language) => {
+                if let Err(e) = i18n::select(i18n::dir().as_deref(), &language.code) {
+                    self.notify(Level::Error, e.to_string());
+                }
+                return Task::none();
+            }
+            Message::ScaleDragged(scale) => {
+                self.scale_dragged = Some(scale);
+                return Task::none();
+            }
+            Message::ScaleReleased => {
+                self.scale = self.scale_dragged.take().unwrap_or(self.scale);
+                return Task::none();
+            }
+            Message::ShowShortcuts(shown) => {
+                self.shortcuts = shown;
+                return Task::none();
+            }
+            Message::ShowPalette(shown) => {
+                self.palette = shown.then(Palette::default);
+                return match shown {
+                    true => text_input::focus(text_input::Id::new(PALETTE_INPUT)),
+                    false => Task::none(),
                 };
-
-                Task::none()
             }
-            Message::HeightChanged(height) => {
-                self.height = if height.is_empty() {
-                    None
-                } else if let Ok(value) = height.parse() {
-                    Some(value)
-                } else {
-                    self.height
+            Message::PaletteChanged(query) => {
+                if let Some(palette) = &mut self.palette {
+                    palette.query = query;
+                    palette.selected = 0;
+                }
+                return Task::none();
+            }
+            Message::PaletteMoved(delta) => {
+                let count = self
+                    .palette
+                    .as_ref()
+                    .map_or(0, |x| x.matches(self.games()).len());
+                if let Some(palette) = &mut self.palette {
+                    palette.select(delta, count);
+                }
+                return Task::none();
+            }
+            Message::PaletteSubmitted => {
+                let entry = self
+                    .palette
+                    .as_ref()
+                    .and_then(|x| x.matches(self.games()).into_iter().nth(x.selected));
+                return entry.map_or_else(Task::none, |x| Task::done(Message::PaletteRun(x)));
+            }
+            Message::PaletteRun(entry) => {
+                self.palette = None;
+                return match entry {
+                    Entry::Game(name) => Task::done(Message::AppSelected(name)),
+                    Entry::Command(command) => self.run_command(command),
                 };
-
-                Task::none()
             }
-            Message::ApplyPatch => {
-                let result = match self.get_selected_app_section() {
-                    Some(section) => self.apply_patches(section),
-                    None => Ok(false),
+            Message::OpenBackupsFolder => {
+                let dir = match self.model.backup_mode {
+                    BackupMode::Central => backup::backup_root(),
+                    BackupMode::GameDirectory => self.model.game_dir.as_ref().map(PathBuf::from),
+                };
+                let Some(dir) = dir else {
+                    self.notify(Level::Error, tr!("error-no-game-dir"));
+                    return Task::none();
                 };
+                // Nothing may have been backed up there yet
+                let opened = match self.model.backup_mode {
+                    BackupMode::Central => std::fs::create_dir_all(&dir).map_err(Error::from),
+                    BackupMode::GameDirectory => Ok(()),
+                }
+                .and_then(|()| link::open(&dir.to_string_lossy()));
+                if let Err(e) = opened {
+                    self.notify(
+                        Level::Error,
+                        tr!("error-open", file = dir.display(), error = e),
+                    );
+                }
+                return Task::none();
+            }
+            Message::ThemeSelected(mode) => {
+                self.theme = mode.resolve();
+                self.theme_mode = mode;
+                return Task::none();
+            }
+            Message::CentralBackupToggled(enabled) => Action::CentralBackupToggled(enabled),
+            Message::OptionToggled(label, enabled) => Action::OptionToggled(label, enabled),
+            Message::ValueChanged(name, input) => Action::ValueChanged(name, input),
+            Message::ApplyPatch => Action::ApplyPatch,
+            Message::PreviewPatch => Action::PreviewPatch,
+            Message::ExportDiff => {
+                let file = rfd::FileDialog::new()
+                    .add_filter(tr!("filter-text"), &["txt"])
+                    .set_title(tr!("export-diff-title"))
+                    .set_file_name("diff.txt")
+                    .save_file();
 
-                match result {
-                    Ok(true) => {
-                        rfd::MessageDialog::new()
-                            .set_level(rfd::MessageLevel::Info)
-                            .set_buttons(rfd::MessageButtons::Ok)
-                            .set_description("Patch applied successfully")
-                            .show();
-                    }
-                    Ok(false) => {
-                        rfd::MessageDialog::new()
-                            .set_level(rfd::MessageLevel::Error)
-                            .set_buttons(rfd::MessageButtons::Ok)
-                            .set_description("Patch failed to apply")
-                            .show();
+                match file {
+                    Some(file) => Action::ExportDiff(file),
+                    None => return Task::none(),
+                }
+            }
+            Message::TogglePanel(panel) => Action::TogglePanel(panel),
+            Message::RestoreEverything => Action::RestoreEverything,
+            Message::RetentionSelected(policy) => Action::RetentionSelected(policy),
+            Message::CleanBackups => Action::CleanBackups,
+            Message::RestoreBackup(index) => Action::RestoreBackup(index),
+            Message::DeleteBackup(index) => Action::DeleteBackup(index),
+            Message::CustomFieldChanged(field, value) => Action::CustomFieldChanged(field, value),
+            Message::PasteShareLink => {
+                return iced::clipboard::read().map(Message::ShareLinkOpened);
+            }
+            Message::ShareLinkOpened(Some(link)) => Action::OpenShareLink(link),
+            Message::ShareLinkOpened(None) => return Task::none(),
+            Message::Launched => {
+                self.launch();
+                return Task::none();
+            }
+            Message::CopyShareLink => return iced::clipboard::write(self.model.share_link()),
+            Message::ToggleLog => {
+                self.log_shown = !self.log_shown;
+                return Task::none();
+            }
+            Message::CopyLog => return iced::clipboard::write(self.model.log.to_string()),
+            Message::ApplyCustomPatch => Action::ApplyCustomPatch,
+            Message::PreviewCustomPatch => Action::PreviewCustomPatch,
+            Message::SigPatternChanged(pattern) => Action::SigPatternChanged(pattern),
+            Message::SigFileChanged(file) => Action::SigFileChanged(file),
+            Message::TestSignature => Action::TestSignature,
+            Message::SaveSignature => Action::SaveSignature,
+            Message::ScanCandidates => Action::ScanCandidates,
+            Message::UseCandidate(index) => Action::UseCandidate(index),
+            Message::DeriveSignature => {
+                let file = rfd::FileDialog::new()
+                    .set_title(tr!("derive-title"))
+                    .pick_file();
+
+                match file {
+                    Some(file) => Action::DeriveSignature(file),
+                    None => return Task::none(),
+                }
+            }
+            Message::ShowInHexView(offset) => Action::ShowInHexView(offset),
+            Message::HexFileChanged(file) => Action::HexFileChanged(file),
+            Message::HexLoad => Action::HexLoad,
+            Message::HexGotoChanged(goto) => Action::HexGotoChanged(goto),
+            Message::HexGoto => Action::HexGoto,
+            Message::HexSearchChanged(search) => Action::HexSearchChanged(search),
+            Message::HexSearch => Action::HexSearch,
+            Message::HexNextHit => Action::HexNextHit,
+            Message::HexScroll(rows) => Action::HexScroll(rows),
+            Message::HexSelect(offset) => Action::HexSelect(offset),
+            Message::HexExpertToggled(expert) => Action::HexExpertToggled(expert),
+            Message::HexByteChanged(byte) => Action::HexByteChanged(byte),
+            Message::HexEdit => Action::HexEdit,
+            Message::HexSave => Action::HexSave,
+            Message::ValidateConfig => Action::ValidateConfig,
+            Message::EditSelected => Action::EditSelected,
+            Message::EditNew => Action::EditNew,
+            Message::EditorNameChanged(name) => Action::EditorNameChanged(name),
+            Message::EditorFieldChanged(patch, field, value) => {
+                Action::EditorFieldChanged(patch, field, value)
+            }
+            Message::EditorAddPatch => Action::EditorAddPatch,
+            Message::EditorRemovePatch(patch) => Action::EditorRemovePatch(patch),
+            Message::EditorChooseBinary => {
+                let mut dialog = rfd::FileDialog::new().set_title(tr!("check-binary-title"));
+                if let Some(dir) = &self.model.game_dir {
+                    dialog = dialog.set_directory(dir);
+                }
+
+                match dialog.pick_file() {
+                    Some(file) => Action::EditorBinaryChosen(file),
+                    None => return Task::none(),
+                }
+            }
+            Message::EditorSave => {
+                return match self.model.save_editor() {
+                    Ok(path) => {
+                        let mut paths = self.model.config_paths.clone();
+                        if !paths.contains(&path) {
+                            paths.push(path);
+                        }
+                        load_configs(paths, self.model.load_options)
                     }
                     Err(e) => {
-                        rfd::MessageDialog::new()
-                            .set_level(rfd::MessageLevel::Error)
-                            .set_buttons(rfd::MessageButtons::Ok)
-                            .set_description(format!("Patch failed to apply: {e}"))
-                            .show();
+                        self.notify(Level::Error, tr!("error-save-game", error = e));
+                        Task::none()
                     }
-                }
-
-                Task::none()
+                };
             }
-        }
+            Message::CopyDerived => {
+                return match &self.model.sig_builder.derived {
+                    Some(section) => iced::clipboard::write(section.clone()),
+                    None => Task::none(),
+                };
+            }
+        };
+
+        self.dispatch(action);
+        Task::none()
     }
 
-    fn can_patch(&self, selected_section: &AppSection) -> bool {
-        let game_dir = self
-            .game_dir
-            .as_deref()
-            .map(Path::new)
-            .filter(|x| x.exists());
-
-        let has_checkfile = game_dir
-            .and_then(|x| x.read_dir().ok())
-            .map(|mut dir| {
-                dir.any(|x| {
-                    if let Ok(file) = x {
-                        file.file_name()
-                            .eq_ignore_ascii_case(&selected_section.checkfile)
-                    } else {
-                        false
-                    }
+    fn backup_panel(&self, section: Option<&AppSection>) -> Element<'_> {
+        let game_dir = Path::new(self.model.game_dir.as_deref().unwrap_or_default());
+
+        let header = row![
+            text(tr!("backup-file")).width(Length::FillPortion(3)),
+            text(tr!("backup-size")).width(Length::FillPortion(2)),
+            text(tr!("backup-modified")).width(Length::FillPortion(3)),
+            text(tr!("backup-patches")).width(Length::FillPortion(2)),
+            horizontal_space().width(Length::FillPortion(3)),
+        ]
+        .spacing(8);
+
+        let entries = self.model.backups.iter().enumerate().map(|(i, entry)| {
+            let patches = section
+                .map(|s| {
+                    s.files()
+                        .into_iter()
+                        .enumerate()
+                        .filter(|(_, x)| wine::resolve(game_dir, x) == entry.target)
+                        .map(|(i, _)| i.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ")
                 })
-            })
-            .unwrap_or(false);
+                .unwrap_or_default();
+
+            row![
+                text(entry.name()).width(Length::FillPortion(3)),
+                text(tr!("bytes", count = entry.size)).width(Length::FillPortion(2)),
+                text(entry.modified_string()).width(Length::FillPortion(3)),
+                text(patches).width(Length::FillPortion(2)),
+                row![
+                    button(text(tr!("restore"))).on_press(Message::RestoreBackup(i)),
+                    button(text(tr!("delete"))).on_press(Message::DeleteBackup(i)),
+                ]
+                .spacing(8)
+                .width(Length::FillPortion(3)),
+            ]
+            .align_y(iced::alignment::Vertical::Center)
+            .spacing(8)
+            .into()
+        });
+
+        let body: Element = if self.model.backups.is_empty() {
+            text(tr!("no-backups")).into()
+        } else {
+            scrollable(column(entries).spacing(4)).into()
+        };
 
-        has_checkfile && self.width.is_some() && self.height.is_some()
+        let retention_row = row![
+            horizontal_space(),
+            pick_list(
+                RetentionPolicy::PRESETS,
+                Some(self.model.retention),
+                Message::RetentionSelected
+            ),
+            button(text(tr!("clean-backups"))).on_press(Message::CleanBackups),
+        ]
+        .align_y(iced::alignment::Vertical::Center)
+        .spacing(8);
+
+        column![header, body, retention_row].spacing(8).into()
     }
 
-    fn get_selected_app_section(&self) -> Option<&AppSection> {
-        match &self.config {
-            ConfigState::Loaded(config) => self
-                .selected_section
-                .as_deref()
-                .and_then(|selected| config.sections.iter().find(|x| x.name == selected)),
-            _ => None,
-        }
+    fn custom_panel(&self) -> Element<'_> {
+        let fields = share::FIELDS.iter().map(|&field| {
+            let value = self.model.custom.get(field).map(String::as_str);
+
+            row![
+                text(field).width(Length::Fixed(100.0)),
+                text_input(field, value.unwrap_or_default())
+                    .on_input(move |x| Message::CustomFieldChanged(field, x)),
+            ]
+            .align_y(iced::alignment::Vertical::Center)
+            .spacing(8)
+            .into()
+        });
+
+        let link_row = row![
+            horizontal_space(),
+            button(text(tr!("open-share-link"))).on_press(Message::PasteShareLink),
+            button(text(tr!("copy-share-link"))).on_press(Message::CopyShareLink),
+        ]
+        .spacing(8);
+
+        column![
+            scrollable(column(fields).spacing(4)).height(Length::Fill),
+            link_row
+        ]
+        .spacing(8)
+        .into()
+    }
+
+    fn editor_panel(&self) -> Element<'_> {
+        let editor = &self.model.editor;
+        let field = |patch: Option<usize>, field: &'static str| {
+            row![
+                text(field).width(Length::Fixed(100.0)),
+                text_input(field, editor.field(patch, field))
+                    .on_input(move |x| Message::EditorFieldChanged(patch, field, x)),
+            ]
+            .align_y(iced::alignment::Vertical::Center)
+            .spacing(8)
+        };
+        let red = theme::colors(&self.theme()).error;
+
+        let game = column![
+            row![
+                text(tr!("editor-name")).width(Length::Fixed(100.0)),
+                text_input(&tr!("editor-name-placeholder"), &editor.name)
+                    .on_input(Message::EditorNameChanged),
+            ]
+            .align_y(iced::alignment::Vertical::Center)
+            .spacing(8)
+        ]
+        .extend(editor::GAME_FIELDS.map(|x| field(None, x).into()))
+        .spacing(4);
+
+        let patches = editor.matches.iter().enumerate().map(|(i, matches)| {
+            let status = match matches {
+                Ok(Some(1)) => text(tr!("editor-one-match")),
+                Ok(Some(n)) => text(tr!("editor-matches", count = n)),
+                Ok(None) => text(""),
+                Err(e) => text(e.to_string()).color(red),
+            };
+            let header = row![
+                text(match i {
+                    0 => tr!("editor-edit-set"),
+                    i => tr!("editor-edit-set-numbered", prefix = format!("p{i}")),
+                }),
+                status,
+                horizontal_space(),
+                button(text(tr!("remove")))
+                    .style(theme::secondary)
+                    .on_press_maybe(
+                        (editor.patches.len() > 1).then_some(Message::EditorRemovePatch(i))
+                    ),
+            ]
+            .align_y(iced::alignment::Vertical::Center)
+            .spacing(8);
+
+            column![header]
+                .extend(editor::PATCH_FIELDS.map(|x| field(Some(i), x).into()))
+                .spacing(4)
+                .into()
+        });
+
+        let binary = editor
+            .binary
+            .as_ref()
+            .map(|x| x.display().to_string())
+            .unwrap_or(tr!("editor-no-binary"));
+        let error = editor
+            .error
+            .as_ref()
+            .map(|e| text(e.to_string()).color(red));
+        let actions = row![
+            button(text(tr!("editor-new"))).on_press(Message::EditNew),
+            button(text(tr!("editor-edit-selected"))).on_press_maybe(
+                self.model
+                    .selected_section
+                    .is_some()
+                    .then_some(Message::EditSelected)
+            ),
+            button(text(tr!("editor-add-set"))).on_press(Message::EditorAddPatch),
+            button(text(tr!("editor-check"))).on_press(Message::EditorChooseBinary),
+            text(binary).size(14),
+            horizontal_space(),
+            button(text(tr!("save")))
+                .on_press_maybe(editor.error.is_none().then_some(Message::EditorSave)),
+        ]
+        .align_y(iced::alignment::Vertical::Center)
+        .spacing(8);
+
+        column![
+            scrollable(column![game].extend(patches).spacing(16)).height(Length::Fill),
+            column![].push_maybe(error),
+            actions,
+        ]
+        .spacing(8)
+        .into()
+    }
+
+    fn signature_panel(&self) -> Element<'_> {
+        let builder = &self.model.sig_builder;
+
+        let inputs = column![
+            row![
+                text(tr!("signature-pattern")).width(Length::Fixed(100.0)),
+                text_input("80 02 ?? ?? C7 01 E0 01", &builder.pattern)
+                    .on_input(Message::SigPatternChanged),
+            ]
+            .align_y(iced::alignment::Vertical::Center)
+            .spacing(8),
+            row![
+                text(tr!("signature-file")).width(Length::Fixed(100.0)),
+                text_input("game.exe", &builder.modfile).on_input(Message::SigFileChanged),
+            ]
+            .align_y(iced::alignment::Vertical::Center)
+            .spacing(8),
+        ]
+        .spacing(4);
+
+        let signature = builder.signature();
+        let parsed: Element = match &signature {
+            Ok(sig) => text(tr!(
+                "signature-parsed",
+                count = sig.pattern.len(),
+                signature = sig
+            ))
+            .into(),
+            Err(_) if builder.pattern.trim().is_empty() => text("").into(),
+            Err(e) => text(e.to_string())
+                .color(theme::colors(&self.theme()).error)
+                .into(),
+        };
+
+        let matches: Element = match (&builder.matches, &builder.candidates) {
+            _ if let Some(section) = &builder.derived => column![
+                row![
+                    text(tr!("signature-derived")),
+                    horizontal_space(),
+                    button(text(tr!("copy"))).on_press(Message::CopyDerived),
+                ]
+                .align_y(iced::alignment::Vertical::Center),
+                scrollable(text(section).font(iced::Font::MONOSPACE)).height(Length::Fill)
+            ]
+            .spacing(4)
+            .into(),
+            (None, Some(candidates)) if candidates.is_empty() => {
+                text(tr!("signature-no-candidates")).into()
+            }
+            (None, Some(candidates)) => {
+                let header = text(tr!("signature-candidates", count = candidates.len()));
+                let rows = candidates.iter().enumerate().map(|(i, x)| {
+                    button(text(x.to_string()).font(iced::Font::MONOSPACE))
+                        .style(button::text)
+                        .on_press(Message::UseCandidate(i))
+                        .into()
+                });
+                column![
+                    header,
+                    scrollable(column(rows).spacing(2)).height(Length::Fill)
+                ]
+                .spacing(4)
+                .into()
+            }
+            (None, None) => vertical_space().into(),
+            (Some(matches), _) if matches.is_empty() => text(tr!("no-matches")).into(),
+            (Some(matches), _) => {
+                let header = text(tr!("matches", count = matches.len()));
+                let offsets = matches.iter().map(|&x| {
+                    button(text(format!("0x{x:X}")))
+                        .style(button::text)
+                        .on_press(Message::ShowInHexView(x))
+                        .into()
+                });
+                column![
+                    header,
+                    scrollable(column(offsets).spacing(2)).height(Length::Fill)
+                ]
+                .spacing(4)
+                .into()
+            }
+        };
+
+        let can_test = signature.is_ok() && self.model.game_dir.is_some();
+        let can_derive = self.model.game_dir.is_some() && !builder.modfile.is_empty();
+        let buttons = row![
+            button(text(tr!("signature-scan"))).on_press_maybe(
+                self.model
+                    .game_dir
+                    .is_some()
+                    .then_some(Message::ScanCandidates)
+            ),
+            button(text(tr!("signature-derive")))
+                .on_press_maybe(can_derive.then_some(Message::DeriveSignature)),
+            horizontal_space(),
+            button(text(tr!("signature-test")))
+                .on_press_maybe(can_test.then_some(Message::TestSignature)),
+            button(text(tr!("signature-save")))
+                .on_press_maybe(signature.is_ok().then_some(Message::SaveSignature)),
+        ]
+        .spacing(8);
+
+        column![
+            inputs,
+            parsed,
+            container(matches).height(Length::Fill),
+            buttons
+        ]
+        .spacing(8)
+        .into()
     }
 
-    fn view(&self) -> Element {
+    fn preview_panel(&self) -> Element<'_> {
+        let preview = &self.model.preview;
+        let colors = theme::colors(&self.theme());
+
+        let byte = |x: u8, color: Option<iced::Color>| {
+            text(format!("{x:02X}"))
+                .font(iced::Font::MONOSPACE)
+                .color_maybe(color)
+                .into()
+        };
+
+        let dumps = preview.sites.iter().map(|site| {
+            let rows = site.rows().map(|(offset, before, after)| {
+                let written = |i: usize| site.is_written(offset + i);
+                let bytes = |data: &[u8], color: iced::Color| -> Element {
+                    let bytes = data
+                        .iter()
+                        .enumerate()
+                        .map(|(i, &x)| byte(x, written(i).then_some(color)));
+                    row(bytes).spacing(6).into()
+                };
+
+                row![
+                    text(format!("{offset:08X}"))
+                        .font(iced::Font::MONOSPACE)
+                        .width(Length::Fixed(100.0)),
+                    bytes(before, colors.removed),
+                    vertical_rule(16),
+                    bytes(after, colors.added),
+                ]
+                .spacing(12)
+                .into()
+            });
+
+            column![
+                text(tr!(
+                    "preview-site",
+                    patch = site.iteration,
+                    file = site.modfile,
+                    offset = format!("0x{:X}", site.index)
+                )),
+                column(rows).spacing(4),
+            ]
+            .spacing(4)
+            .into()
+        });
+
+        let header = row![
+            text(match preview.applied {
+                true => tr!("preview-written"),
+                false => tr!("preview-dry-run"),
+            }),
+            horizontal_space(),
+            button(text(tr!("preview-export")))
+                .on_press_maybe((!preview.sites.is_empty()).then_some(Message::ExportDiff)),
+        ]
+        .align_y(iced::alignment::Vertical::Center);
+
+        let body: Element = if preview.sites.is_empty() {
+            text(tr!("preview-empty")).into()
+        } else {
+            scrollable(column(dumps).spacing(12)).into()
+        };
+
+        column![header, body].spacing(8).into()
+    }
+
+    fn hex_panel(&self) -> Element<'_> {
+        let view = &self.model.hex_view;
+        let colors = theme::colors(&self.theme());
+        let mono = |s: String| text(s).font(iced::Font::MONOSPACE);
+
+        let file_row = row![
+            text(tr!("hex-file")).width(Length::Fixed(100.0)),
+            text_input("game.exe", &view.modfile)
+                .on_input(Message::HexFileChanged)
+                .on_submit(Message::HexLoad),
+            button(text(tr!("open"))).on_press_maybe(
+                (self.model.game_dir.is_some() && !view.modfile.is_empty())
+                    .then_some(Message::HexLoad)
+            ),
+        ]
+        .align_y(iced::alignment::Vertical::Center)
+        .spacing(8);
+
+        let search_row = row![
+            text_input(&tr!("hex-goto-placeholder"), &view.goto)
+                .on_input(Message::HexGotoChanged)
+                .on_submit(Message::HexGoto),
+            button(text(tr!("hex-goto"))).on_press(Message::HexGoto),
+            vertical_rule(16),
+            text_input(&tr!("hex-search-placeholder"), &view.search)
+                .on_input(Message::HexSearchChanged)
+                .on_submit(Message::HexSearch),
+            button(text(tr!("hex-find"))).on_press(Message::HexSearch),
+            button(text(tr!("hex-next")))
+                .on_press_maybe((!view.hits.is_empty()).then_some(Message::HexNextHit)),
+        ]
+        .align_y(iced::alignment::Vertical::Center)
+        .spacing(8);
+
+        let rows = view.rows().map(|(offset, bytes)| {
+            let hex = bytes.iter().enumerate().map(|(i, &x)| {
+                let offset = offset + i;
+                let color = if view.cursor == Some(offset) {
+                    Some(colors.cursor)
+                } else if view.is_hit(offset) {
+                    Some(colors.hit)
+                } else {
+                    None
+                };
+
+                button(mono(format!("{x:02X}")).color_maybe(color))
+                    .style(button::text)
+                    .padding(0)
+                    .on_press(Message::HexSelect(offset))
+                    .into()
+            });
+            let ascii = bytes
+                .iter()
+                .map(|&x| match x {
+                    0x20..0x7F => x as char,
+                    _ => '.',
+                })
+                .collect::<String>();
+
+            row![
+                mono(format!("{offset:08X}")).width(Length::Fixed(100.0)),
+                row(hex).spacing(6),
+                mono(ascii),
+            ]
+            .spacing(12)
+            .into()
+        });
+
+        let page = hexview::PAGE_ROWS as isize;
+        let status = match (view.cursor, view.data.is_empty()) {
+            (_, true) => String::new(),
+            (Some(cursor), _) => tr!(
+                "hex-position",
+                offset = format!("0x{cursor:X}"),
+                len = format!("0x{:X}", view.data.len())
+            ),
+            (None, _) => tr!("bytes", count = format!("0x{:X}", view.data.len())),
+        };
+        let nav_row = row![
+            button(text(tr!("hex-page-up"))).on_press(Message::HexScroll(-page)),
+            button(text(tr!("hex-up"))).on_press(Message::HexScroll(-1)),
+            button(text(tr!("hex-down"))).on_press(Message::HexScroll(1)),
+            button(text(tr!("hex-page-down"))).on_press(Message::HexScroll(page)),
+            text(status),
+            horizontal_space(),
+            text(match view.hits.len() {
+                0 => String::new(),
+                n => tr!("matches", count = n),
+            }),
+        ]
+        .align_y(iced::alignment::Vertical::Center)
+        .spacing(8);
+
+        let edit_row = row![
+            checkbox(tr!("hex-expert"), view.expert).on_toggle(Message::HexExpertToggled),
+            horizontal_space(),
+        ]
+        .push_maybe(view.expert.then(|| {
+            text_input(&tr!("hex-byte"), &view.byte)
+                .on_input(Message::HexByteChanged)
+                .on_submit(Message::HexEdit)
+                .width(Length::Fixed(60.0))
+        }))
+        .push_maybe(view.expert.then(|| {
+            button(text(tr!("save"))).on_press_maybe(view.dirty.then_some(Message::HexSave))
+        }))
+        .align_y(iced::alignment::Vertical::Center)
+        .spacing(8);
+
+        column![
+            file_row,
+            search_row,
+            column(rows).spacing(2).height(Length::Fill),
+            nav_row,
+            edit_row,
+        ]
+        .spacing(8)
+        .into()
+    }
+
+    fn view(&self) -> Element<'_> {
         let config_bar = row![
             text_input(
-                "Game file directory",
-                self.game_dir.as_deref().unwrap_or("")
+                &tr!("game-dir-placeholder"),
+                self.model.game_dir.as_deref().unwrap_or("")
             )
             .on_input(Message::GameDirChanged),
             button("...").on_press(Message::SelectGameDir),
             vertical_rule(16),
-            button("Load config").on_press(Message::LoadConfig)
+            button(text(tr!("load-config"))).on_press(Message::LoadConfig),
+            button(text(tr!("add-config"))).on_press(Message::AddConfig),
+            text_input(&tr!("config-url-placeholder"), &self.model.config_url)
+                .on_input(Message::ConfigUrlChanged)
+                .on_submit(Message::LoadUrl)
+                .width(Length::Fixed(240.0)),
+            button(text(tr!("load-url"))).on_press_maybe(
+                (!self.model.config_url.trim().is_empty()).then_some(Message::LoadUrl)
+            ),
+            button(text(tr!("check-updates"))).on_press_maybe(
+                self.model
+                    .sync_url
+                    .is_some()
+                    .then_some(Message::CheckForUpdates)
+            ),
+            button(text(tr!("roll-back")))
+                .on_press_maybe(self.model.sync_url.is_some().then_some(Message::RollBack)),
+            button(text(tr!("validate"))).on_press(Message::ValidateConfig),
+            checkbox(
+                tr!("strict-loading"),
+                self.model.load_options.mode == ParseMode::Strict
+            )
+            .on_toggle(Message::StrictToggled),
+            pick_list(
+                Encoding::ALL,
+                Some(self.model.load_options.encoding),
+                Message::EncodingSelected
+            ),
+            pick_list(
+                DuplicatePolicy::ALL,
+                Some(self.model.load_options.duplicates),
+                Message::DuplicatesSelected
+            ),
+            pick_list(
+                self.languages.as_slice(),
+                self.languages
+                    .iter()
+                    .find(|x| x.code == i18n::current())
+                    .cloned(),
+                Message::LanguageSelected
+            ),
+            pick_list(
+                ThemeMode::BUILT_IN
+                    .into_iter()
+                    .chain(self.custom_themes.iter().cloned().map(ThemeMode::Custom))
+                    .collect::<Vec<_>>(),
+                Some(self.theme_mode.clone()),
+                Message::ThemeSelected
+            ),
+            text(tr!(
+                "scale",
+                percent = self.scale_dragged.unwrap_or(self.scale)
+            )),
+            slider(
+                SCALES,
+                self.scale_dragged.unwrap_or(self.scale),
+                Message::ScaleDragged
+            )
+            .on_release(Message::ScaleReleased)
+            .step(10u16)
+            .width(Length::Fixed(80.0)),
+            button(text(if self.model.panel == Panel::Backups {
+                tr!("panel-details")
+            } else {
+                tr!("panel-backups")
+            }))
+            .on_press(Message::TogglePanel(Panel::Backups)),
+            button(text(if self.model.panel == Panel::CustomPatch {
+                tr!("panel-details")
+            } else {
+                tr!("panel-custom")
+            }))
+            .on_press(Message::TogglePanel(Panel::CustomPatch)),
+            button(text(if self.model.panel == Panel::SignatureBuilder {
+                tr!("panel-details")
+            } else {
+                tr!("panel-signature")
+            }))
+            .on_press(Message::TogglePanel(Panel::SignatureBuilder)),
+            button(text(if self.model.panel == Panel::HexView {
+                tr!("panel-details")
+            } else {
+                tr!("panel-hex")
+            }))
+            .on_press(Message::TogglePanel(Panel::HexView)),
+            button(text(if self.model.panel == Panel::Editor {
+                tr!("panel-details")
+            } else {
+                tr!("panel-editor")
+            }))
+            .on_press(Message::TogglePanel(Panel::Editor)),
+            button(text(if self.model.panel == Panel::Preview {
+                tr!("panel-details")
+            } else {
+                tr!("panel-diff")
+            }))
+            .on_press_maybe(
+                (self.model.panel == Panel::Preview || !self.model.preview.sites.is_empty())
+                    .then_some(Message::TogglePanel(Panel::Preview))
+            ),
+            button("?").on_press(Message::ShowShortcuts(!self.shortcuts)),
         ]
         .height(Length::Shrink)
+        .align_y(iced::alignment::Vertical::Center)
         .spacing(8)
         .padding(8);
 
-        let body: Element = match &self.config {
-            ConfigState::NotLoaded => vertical_space().into(),
-            ConfigState::Loaded(config) => {
-                let options = config.apps.apps.as_slice();
-                let picker =
-                    pick_list(options, self.selected_section.clone(), Message::AppSelected)
-                        .width(Length::Fill);
+        let custom = self.custom_patching();
+        // Panels that work without a config
+        let standalone = custom
+            || matches!(
+                self.model.panel,
+                Panel::SignatureBuilder | Panel::HexView | Panel::Editor
+            );
 
-                let selected = self
-                    .selected_section
-                    .as_deref()
-                    .and_then(|selected| config.sections.iter().find(|x| x.name == selected));
+        let body: Element = match &self.model.config {
+            ConfigState::NotLoaded if !standalone => vertical_space().into(),
+            ConfigState::Error(e) if !standalone => text(e.to_string())
+                .color(theme::colors(&self.theme()).error)
+                .into(),
+            config => {
+                let picker = match config {
+                    ConfigState::Loaded(_) => Some(
+                        row![
+                            text_input(&tr!("search-placeholder"), &self.model.filter)
+                                .on_input(Message::FilterChanged)
+                                .width(Length::FillPortion(1)),
+                        ]
+                        .push_maybe(self.selected_icon(28.0))
+                        .push(
+                            pick_list(
+                                self.model.app_entries(),
+                                self.model.selected_section.clone().map(|name| AppEntry {
+                                    name,
+                                    recent: false,
+                                }),
+                                |x| Message::AppSelected(x.name),
+                            )
+                            .width(Length::FillPortion(3)),
+                        )
+                        .push(pick_list(
+                            SortOrder::ALL,
+                            Some(self.model.sort),
+                            Message::SortSelected,
+                        ))
+                        .align_y(iced::alignment::Vertical::Center)
+                        .spacing(8),
+                    ),
+                    _ => None,
+                };
+
+                // A tab per config file when several are merged
+                let loaded = matches!(config, ConfigState::Loaded(_));
+                let tabs = (loaded && self.model.config_paths.len() > 1).then(|| {
+                    let tab = |label: String, source: Option<PathBuf>| {
+                        let style = match self.model.source == source {
+                            true => theme::primary,
+                            false => theme::secondary,
+                        };
+                        button(text(label))
+                            .style(style)
+                            .on_press(Message::SourceSelected(source))
+                            .into()
+                    };
+                    let files = self.model.config_paths.iter().map(|path| {
+                        let name = path.file_name().unwrap_or(path.as_os_str());
+                        tab(name.to_string_lossy().into_owned(), Some(path.clone()))
+                    });
+                    row(std::iter::once(tab(tr!("all-configs"), None)).chain(files)).spacing(4)
+                });
+
+                let detected = (!standalone && !self.model.detected.is_empty()).then(|| {
+                    let games = self.model.detected.iter().map(|name| {
+                        button(text(name))
+                            .on_press(Message::AppSelected(name.clone()))
+                            .into()
+                    });
+                    row![text(tr!("detected-games"))]
+                        .extend(games)
+                        .align_y(iced::alignment::Vertical::Center)
+                        .spacing(8)
+                });
+
+                let selected = self.model.get_selected_app_section();
 
                 let content = {
-                    let content = selected.map(|x| x.details.clone()).unwrap_or_default();
-                    let t = text(content).size(20);
-                    bordered_container(t, 2.0)
+                    let content: Element = match self.model.panel {
+                        Panel::Details => {
+                            let content = selected.map_or("", |x| x.details.as_str());
+                            let format = selected.map(|x| x.details_format).unwrap_or_default();
+                            let source = self
+                                .model
+                                .selected_source()
+                                .map(|x| text(tr!("details-source", path = x.display())).size(14));
+                            let image = self.selected_image().and_then(|x| self.images.get(&x));
+                            let image = image.map(|x| match x {
+                                cover::State::Loading => image_placeholder(tr!("image-loading")),
+                                cover::State::Loaded(picture) => game_image(picture),
+                                cover::State::Failed(e) => {
+                                    image_placeholder(tr!("error-load-image", error = e))
+                                }
+                            });
+                            let options = selected.iter().flat_map(|x| x.options()).map(|label| {
+                                checkbox(label, self.model.enabled_options.contains(label))
+                                    .on_toggle(|x| Message::OptionToggled(label.to_string(), x))
+                                    .into()
+                            });
+
+                            let header = self.selected_icon(48.0).zip(selected).map(|(icon, x)| {
+                                row![icon, text(&x.name).size(24)]
+                                    .align_y(iced::alignment::Vertical::Center)
+                                    .spacing(8)
+                            });
+                            column![]
+                                .push_maybe(header)
+                                .push_maybe(image)
+                                .push(
+                                    scrollable(details_text(
+                                        content,
+                                        format,
+                                        self.theme().palette().primary,
+                                    ))
+                                    .height(Length::Fill),
+                                )
+                                .push(column(options).spacing(4))
+                                .push_maybe(source)
+                                .spacing(8)
+                                .into()
+                        }
+                        Panel::Backups => self.backup_panel(selected),
+                        Panel::CustomPatch => self.custom_panel(),
+                        Panel::SignatureBuilder => self.signature_panel(),
+                        Panel::Preview => self.preview_panel(),
+                        Panel::HexView => self.hex_panel(),
+                        Panel::Editor => self.editor_panel(),
+                    };
+                    bordered_container(content, 2.0)
                         .padding(8)
                         .width(Length::Fill)
                         .height(Length::Fill)
                 };
 
                 let settings_row = row![
-                    text("Width:"),
+                    text(tr!("width")),
                     text_input(
-                        "Width...",
-                        &self.width.map(|x| x.to_string()).unwrap_or_default()
+                        &tr!("width-placeholder"),
+                        &self.model.width.map(|x| x.to_string()).unwrap_or_default()
                     )
                     .on_input(Message::WidthCHanged),
+                    button("-").on_press(Message::WidthStepped(-1)),
+                    button("+").on_press(Message::WidthStepped(1)),
                     horizontal_space(),
-                    text("Height:"),
+                    text(tr!("height")),
                     text_input(
-                        "Height...",
-                        &self.height.map(|x| x.to_string()).unwrap_or_default()
+                        &tr!("height-placeholder"),
+                        &self.model.height.map(|x| x.to_string()).unwrap_or_default()
                     )
                     .on_input(Message::HeightChanged),
+                    button("-").on_press(Message::HeightStepped(-1)),
+                    button("+").on_press(Message::HeightStepped(1)),
+                    text(match (self.model.width, self.model.height) {
+                        (Some(width), Some(height)) => display::aspect_ratio(width, height),
+                        _ => "-".into(),
+                    }),
+                    checkbox(tr!("lock-ratio"), self.model.aspect_lock.is_some()).on_toggle_maybe(
+                        (self.model.width.is_some() && self.model.height.is_some())
+                            .then_some(Message::AspectLockToggled)
+                    ),
+                    button(text(tr!("current-resolution"))).on_press_maybe(
+                        (!self.model.displays.is_empty()).then_some(Message::UseCurrentResolution)
+                    ),
+                    pick_list(
+                        self.model.displays.as_slice(),
+                        self.model.selected_display().cloned(),
+                        Message::DisplaySelected,
+                    )
+                    .placeholder(if self.model.displays_unavailable {
+                        tr!("display-unavailable")
+                    } else {
+                        tr!("display-placeholder")
+                    }),
+                    pick_list(
+                        display::PRESETS,
+                        self.model.selected_preset(),
+                        Message::PresetSelected,
+                    )
+                    .placeholder(tr!("preset-placeholder")),
+                ]
+                .extend(
+                    selected
+                        .filter(|_| !custom)
+                        .iter()
+                        .flat_map(|x| &x.values)
+                        .flat_map(|slot| {
+                            let placeholder = match slot.default {
+                                Some(x) => x.to_string(),
+                                None => format!("{}...", slot.name),
+                            };
+                            let input = self
+                                .model
+                                .values
+                                .get(&slot.name)
+                                .map(String::as_str)
+                                .unwrap_or_default();
+                            let name = slot.name.clone();
+                            [
+                                horizontal_space().into(),
+                                text!("{}:", slot.name).into(),
+                                text_input(&placeholder, input)
+                                    .on_input(move |x| Message::ValueChanged(name.clone(), x))
+                                    .into(),
+                            ]
+                        }),
+                )
+                .align_y(iced::alignment::Vertical::Center)
+                .spacing(8);
+
+                let issues = self
+                    .model
+                    .resolution_issues(selected.filter(|_| !custom))
+                    .into_iter()
+                    .map(|(level, issue)| {
+                        let colors = theme::colors(&self.theme());
+                        let color = match level {
+                            Level::Error => colors.error,
+                            _ => colors.warning,
+                        };
+                        text(issue).color(color).into()
+                    });
+                let issues = column(issues).spacing(4);
+
+                let backup_row = row![
+                    checkbox(
+                        tr!("central-backups"),
+                        self.model.backup_mode == BackupMode::Central,
+                    )
+                    .on_toggle(Message::CentralBackupToggled),
+                    horizontal_space(),
+                    button(text(tr!("export-bundle"))).on_press_maybe(
+                        self.model
+                            .last_backup
+                            .is_some()
+                            .then_some(Message::ExportBundle)
+                    ),
+                    button(text(tr!("restore-bundle"))).on_press(Message::RestoreBundle),
+                    button(text(tr!("restore-everything"))).on_press_maybe(
+                        self.model
+                            .game_dir
+                            .is_some()
+                            .then_some(Message::RestoreEverything)
+                    ),
                 ]
                 .align_y(iced::alignment::Vertical::Center)
                 .spacing(8);
 
                 let patch_button = {
-                    let exe_name = selected.map(|x| x.checkfile.as_str()).unwrap_or_default();
-                    let content = row![
-                        horizontal_space(),
-                        text(format!("Patch {}", exe_name)),
-                        horizontal_space(),
-                    ];
+                    let enabled = self.can_apply();
+                    let (label, message, preview) = if custom {
+                        (
+                            tr!("apply-custom-patch"),
+                            Message::ApplyCustomPatch,
+                            Message::PreviewCustomPatch,
+                        )
+                    } else {
+                        let exe_name = selected.map(|x| x.checkfile.as_str()).unwrap_or_default();
+                        (
+                            tr!("apply-patch", file = exe_name),
+                            Message::ApplyPatch,
+                            Message::PreviewPatch,
+                        )
+                    };
 
-                    let patch_button_enabled = selected.map(|s| self.can_patch(s)).unwrap_or(false);
+                    let content = row![horizontal_space(), text(label), horizontal_space()];
 
-                    button(content)
-                        .width(Length::Fill)
-                        .on_press_maybe(patch_button_enabled.then_some(Message::ApplyPatch))
+                    row![
+                        button(text(tr!("preview"))).on_press_maybe(enabled.then_some(preview)),
+                        button(content)
+                            .width(Length::Fill)
+                            .on_press_maybe(enabled.then_some(message)),
+                    ]
+                    .spacing(8)
                 };
 
-                column![picker, content, settings_row, patch_button]
+                column![]
+                    .push_maybe(tabs)
+                    .push_maybe(picker)
+                    .push_maybe(detected)
+                    .push(content)
+                    .push(settings_row)
+                    .push(issues)
+                    .push(backup_row)
+                    .push(patch_button)
                     .spacing(8)
                     .into()
             }
-            ConfigState::Error(e) => text(e.to_string())
-                .color(iced::Color::from_rgb(1.0, 0.0, 0.0))
-                .into(),
         };
 
         let body = container(body).padding(8);
@@ -375,38 +2098,269 @@ impl App {
             .width(Length::Fill)
             .height(Length::Fill);
 
-        column![config_bar, body]
+        let window = column![config_bar]
+            .push_maybe(self.update.as_ref().map(update_banner))
+            .push(body)
+            .push(self.log_panel())
             .padding(8)
             .width(Length::Fill)
-            .height(Length::Fill)
+            .height(Length::Fill);
+        let overlay = match &self.palette {
+            Some(palette) => Some(self.palette_overlay(palette)),
+            None => self.shortcuts.then(shortcuts_overlay),
+        };
+        match overlay {
+            Some(overlay) => stack![window, overlay].into(),
+            None => window.into(),
+        }
+    }
+
+    /// A bar with the latest entry of the log, or every entry when it's
+    /// open, and a button to copy them
+    fn log_panel(&self) -> Element<'_> {
+        let log = &self.model.log;
+        let toggle = match self.log_shown {
+            true => tr!("log-hide"),
+            false => tr!("log-show", count = log.len()),
+        };
+        let latest = log
+            .entries()
+            .next_back()
+            .filter(|_| !self.log_shown)
+            .map(|x| {
+                let line = x.to_string();
+                text(line.lines().next().unwrap_or_default().to_string()).size(14)
+            });
+        let bar = row![
+            button(text(toggle))
+                .style(theme::secondary)
+                .on_press(Message::ToggleLog)
+        ]
+        .push_maybe(latest)
+        .push(horizontal_space())
+        .push(
+            button(text(tr!("copy"))).on_press_maybe((!log.is_empty()).then_some(Message::CopyLog)),
+        )
+        .align_y(iced::alignment::Vertical::Center)
+        .spacing(8);
+        if !self.log_shown {
+            return bar.into();
+        }
+
+        let colors = theme::colors(&self.theme());
+        let entries: Element = match log.is_empty() {
+            true => text(tr!("log-empty")).into(),
+            false => scrollable(
+                column(log.entries().map(|x| {
+                    let color = match x.level {
+                        Level::Info => None,
+                        Level::Warning => Some(colors.warning),
+                        Level::Error => Some(colors.error),
+                    };
+                    text(x.to_string())
+                        .size(14)
+                        .font(iced::Font::MONOSPACE)
+                        .color_maybe(color)
+                        .into()
+                }))
+                .spacing(2)
+                .width(Length::Fill),
+            )
+            .anchor_bottom()
+            .into(),
+        };
+        column![bar, container(entries).height(Length::Fixed(160.0))]
+            .spacing(4)
             .into()
     }
 
+    /// The palette's field and its matches, the one Enter runs highlighted
+    fn palette_overlay(&self, palette: &Palette) -> Element<'_> {
+        let input = text_input(&tr!("palette-placeholder"), &palette.query)
+            .id(text_input::Id::new(PALETTE_INPUT))
+            .on_input(Message::PaletteChanged)
+            .on_submit(Message::PaletteSubmitted);
+        let matches = palette
+            .matches(self.games())
+            .into_iter()
+            .enumerate()
+            .map(|(i, entry)| {
+                let kind = match entry {
+                    Entry::Game(_) => tr!("palette-game"),
+                    Entry::Command(_) => tr!("palette-command"),
+                };
+                let style = match i == palette.selected {
+                    true => theme::primary,
+                    false => theme::secondary,
+                };
+                button(row![
+                    text(entry.to_string()),
+                    horizontal_space(),
+                    text(kind).size(12)
+                ])
+                .style(style)
+                .width(Length::Fill)
+                .on_press(Message::PaletteRun(entry))
+                .into()
+            });
+        let content = column![input]
+            .extend(matches)
+            .spacing(4)
+            .width(Length::Fixed(480.0));
+        overlay(content, Message::ShowPalette(false))
+    }
+
     fn theme(&self) -> Theme {
-        Theme::Dark
+        self.theme.clone()
     }
 
-    pub fn run(self) -> iced::Result {
-        let task = {
-            let path = Path::new("patches.ini");
-            if path.exists() {
-                Task::perform(App::load_config(path), Message::ConfigLoaded)
-            } else {
-                Task::none()
+    fn scale_factor(&self) -> f64 {
+        self.scale.clamp(*SCALES.start(), *SCALES.end()) as f64 / 100.0
+    }
+
+    /// Load the configs again, or the built in one if there are none, after
+    /// [`Model::load_options`] changed
+    fn reload_configs(&self) -> Task<Message> {
+        match self.model.config_paths.is_empty() {
+            false => load_configs(self.model.config_paths.clone(), self.model.load_options),
+            true => Task::done(Message::ConfigLoaded(
+                vec![],
+                Config::load(config::DEFAULT, Format::Ini, self.model.load_options),
+            )),
+        }
+    }
+
+    /// Load the downloaded config at `path` again after it changed, along
+    /// with the others if it's merged with them
+    fn reload_downloaded(&self, path: PathBuf) -> Task<Message> {
+        match self.model.config_paths.contains(&path) {
+            true => load_configs(self.model.config_paths.clone(), self.model.load_options),
+            false => load_configs(vec![path], self.model.load_options),
+        }
+    }
+
+    /// Fill in [`Self::args`] and patch if asked to
+    fn launch(&mut self) {
+        let args = std::mem::take(&mut self.args);
+
+        if !self.broken_themes.is_empty() {
+            let errors = self.broken_themes.iter().map(ToString::to_string);
+            let errors = errors.collect::<Vec<_>>().join("\n");
+            self.notify(Level::Warning, tr!("broken-themes", errors = errors));
+        }
+
+        // The folder first so an explicit --app wins over the one detected in it
+        if let Some(dir) = args.game_dir {
+            self.dispatch(Action::GameDirChanged(Some(
+                dir.to_string_lossy().into_owned(),
+            )));
+        }
+        if let Some(app) = args.app {
+            self.dispatch(Action::AppSelected(app));
+        }
+        if let Some(width) = args.width {
+            self.dispatch(Action::WidthChanged(width.to_string()));
+        }
+        if let Some(height) = args.height {
+            self.dispatch(Action::HeightChanged(height.to_string()));
+        }
+
+        if args.apply {
+            match self.model.get_selected_app_section() {
+                Some(section) if self.model.can_patch(section) => self.dispatch(Action::ApplyPatch),
+                _ => self.notify(Level::Error, tr!("error-cli-apply")),
             }
+        }
+    }
+
+    /// Remember the session for the next run
+    fn save_settings(&self) {
+        if let Some(path) = &self.settings_file {
+            let settings = Settings {
+                theme: Some(self.theme_mode.name().into()),
+                language: Some(i18n::current()),
+                scale: Some(self.scale),
+                window_size: self
+                    .window_size
+                    .map(|x| (x.width.round() as u32, x.height.round() as u32)),
+                window_position: self
+                    .window_position
+                    .map(|x| (x.x.round() as i32, x.y.round() as i32)),
+                ..self.model.settings()
+            };
+            // Nothing to tell the user about on the way out
+            let _ = settings::save(path, &settings);
+        }
+    }
+
+    /// `args` are filled in once the window is up
+    pub fn run(mut self, mut args: GuiArgs) -> iced::Result {
+        // The config given, the one from last time, one found in the usual
+        // places or the built in one. One given that doesn't exist is still
+        // loaded to show the error
+        let mut paths = std::mem::take(&mut args.configs);
+        if paths.is_empty() {
+            paths = self.model.config_paths.clone();
+            paths.retain(|x| x.exists());
+        }
+        if paths.is_empty() {
+            paths.extend(config::find());
+        }
+        let task = match paths.is_empty() {
+            false => load_configs(paths, self.model.load_options),
+            true => Task::done(Message::ConfigLoaded(
+                vec![],
+                Config::load(config::DEFAULT, Format::Ini, self.model.load_options),
+            )),
+        };
+        let update = match self.model.sync_url.clone() {
+            Some(url) => Task::perform(remote::check(url), Message::UpdateChecked),
+            None => Task::none(),
+        };
+        let task = Task::batch([
+            task.chain(Task::done(Message::ShareLinkOpened(args.link.take())))
+                .chain(Task::done(Message::Launched)),
+            Task::perform(display::query(), Message::DisplaysFound),
+            update,
+        ]);
+
+        let position = match self.window_position {
+            Some(point) => iced::window::Position::Specific(point),
+            None => iced::window::Position::Centered,
         };
+        let size = self.window_size.unwrap_or(iced::Size::new(1280.0, 720.0));
+        self.args = args;
 
         iced::application(env!("CARGO_BIN_NAME"), Self::update, Self::view)
             .subscription(Self::subscription)
-            .centered()
-            .window_size((1280.0, 720.0))
+            .position(position)
+            .window_size(size)
             .theme(Self::theme)
-            .exit_on_close_request(true)
+            .scale_factor(Self::scale_factor)
+            .exit_on_close_request(false)
             .run_with(|| (self, task))
     }
 }
 
-fn main() {
-    let app = App::default();
-    app.run().expect("Failed to run app");
+fn main() -> ExitCode {
+    let (verbose, args) = cli::take_verbose(std::env::args().skip(1));
+    logging::init(logging::dir(), verbose);
+    tracing::info!(version = env!("CARGO_PKG_VERSION"), ?args, "Started");
+
+    match cli::parse_args(args) {
+        Ok(Command::Gui(args)) => {
+            crash::install(true);
+            let app = App::new();
+            app.run(args).expect("Failed to run app");
+            ExitCode::SUCCESS
+        }
+        Ok(command) => {
+            crash::install(false);
+            cli::run(command)
+        }
+        Err(e) => {
+            eprintln!("{e}\n\n{}", cli::USAGE);
+            ExitCode::FAILURE
+        }
+    }
 }
@@ -0,0 +1,318 @@
+use crate::link;
+
+/// Text in one style, part of a [`Block`]
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub struct Inline<'a> {
+    pub text: &'a str,
+    /// `**bold**`
+    pub strong: bool,
+    /// `*italic*`
+    pub emphasis: bool,
+    /// `` `code` ``
+    pub code: bool,
+    /// Address of a `[link](https://..)`, or of the address itself
+    pub link: Option<&'a str>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Marker {
+    /// `- `, `* ` or `+ `
+    Bullet,
+    /// `1. `
+    Number(u32),
+}
+
+/// A line or more of the details as they're laid out
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Block<'a> {
+    /// `# Heading`, from level 1 to 6
+    Heading(usize, Vec<Inline<'a>>),
+    /// Lines up to a blank one, joined with spaces
+    Paragraph(Vec<Inline<'a>>),
+    /// List item, `depth` counting up every 2 spaces it's indented
+    Item {
+        depth: usize,
+        marker: Marker,
+        content: Vec<Inline<'a>>,
+    },
+}
+
+/// The `**`/`__` or `*`/`_` at `pos` of `line` if it can end a span, or
+/// start one when `opening`: text follows it and it's closed later in the
+/// line. An `_` inside a word never counts
+fn emphasis_marker(line: &str, pos: usize, opening: bool) -> Option<&str> {
+    let rest = &line[pos..];
+    let marker = ["**", "__", "*", "_"]
+        .into_iter()
+        .find(|x| rest.starts_with(x))?;
+    let before = line[..pos].chars().next_back();
+    let after = rest[marker.len()..].chars().next();
+    let word = |x: Option<char>| x.is_some_and(char::is_alphanumeric);
+    if marker.starts_with('_') && word(before) && word(after) {
+        return None;
+    }
+    match opening {
+        true => (!after.is_some_and(char::is_whitespace) && rest[marker.len()..].contains(marker))
+            .then_some(marker),
+        false => Some(marker),
+    }
+}
+
+/// `[text](url)` at the start of `rest`, and its length. Only web addresses
+/// are links, like in [`link::segments`], so a config can't open a local file
+fn link_at(rest: &str) -> Option<(&str, &str, usize)> {
+    let close = rest.find("](")?;
+    let text = &rest[1..close];
+    let end = rest[close + 2..].find(')')? + close + 2;
+    let url = &rest[close + 2..end];
+    let web = url.starts_with("http://") || url.starts_with("https://");
+    (web && !text.contains(']') && !url.contains(char::is_whitespace)).then_some((
+        text,
+        url,
+        end + 1,
+    ))
+}
+
+/// `text` in the style given, with the addresses in it as links
+fn push<'a>(out: &mut Vec<Inline<'a>>, text: &'a str, strong: bool, emphasis: bool) {
+    for segment in link::segments(text) {
+        let (text, link) = match segment {
+            link::Segment::Text(x) => (x, None),
+            link::Segment::Url(x) => (x, Some(x)),
+        };
+        out.push(Inline {
+            text,
+            strong,
+            emphasis,
+            code: false,
+            link,
+        });
+    }
+}
+
+/// Spans of one line of text
+fn inlines(line: &str) -> Vec<Inline<'_>> {
+    let mut out = vec![];
+    let (mut strong, mut emphasis) = (false, false);
+
+    // Addresses written out are kept as they are
+    let mut urls = vec![];
+    let mut end = 0;
+    for segment in link::segments(line) {
+        let (link::Segment::Text(x) | link::Segment::Url(x)) = segment;
+        if let link::Segment::Url(_) = segment {
+            urls.push(end..end + x.len());
+        }
+        end += x.len();
+    }
+
+    let mut start = 0;
+    let mut pos = 0;
+    while let Some(offset) = line[pos..].find(['\\', '`', '[', '*', '_']) {
+        pos += offset;
+        if let Some(url) = urls.iter().find(|x| x.contains(&pos)) {
+            pos = url.end;
+            continue;
+        }
+        let rest = &line[pos..];
+        let special = match rest.as_bytes()[0] {
+            b'\\' => rest[1..]
+                .chars()
+                .next()
+                .filter(char::is_ascii_punctuation)
+                .map(|_| (Inline::default(), 1, 2)),
+            b'`' => rest[1..].find('`').map(|end| {
+                let code = Inline {
+                    text: &rest[1..end + 1],
+                    code: true,
+                    ..Default::default()
+                };
+                (code, 0, end + 2)
+            }),
+            b'[' => link_at(rest).map(|(text, url, len)| {
+                let link = Inline {
+                    text,
+                    link: Some(url),
+                    ..Default::default()
+                };
+                (link, 0, len)
+            }),
+            _ => emphasis_marker(line, pos, false)
+                .filter(|x| {
+                    let started = if x.len() == 2 { strong } else { emphasis };
+                    started || emphasis_marker(line, pos, true).is_some()
+                })
+                .map(|x| (Inline::default(), 0, x.len())),
+        };
+        let Some((inline, skip, len)) = special else {
+            pos += 1;
+            continue;
+        };
+
+        push(&mut out, &line[start..pos], strong, emphasis);
+        match rest.as_bytes()[0] {
+            // An escaped character starts the next text
+            b'\\' => {
+                start = pos + skip;
+                pos += len;
+                continue;
+            }
+            b'`' | b'[' => out.push(Inline {
+                strong,
+                emphasis,
+                ..inline
+            }),
+            _ if len == 2 => strong = !strong,
+            _ => emphasis = !emphasis,
+        }
+        pos += len;
+        start = pos;
+    }
+    push(&mut out, &line[start..], strong, emphasis);
+    out.retain(|x| !x.text.is_empty());
+    out
+}
+
+/// `line` without a list marker, and the marker
+fn item(line: &str) -> Option<(Marker, &str)> {
+    if let Some(rest) = ["- ", "* ", "+ "]
+        .into_iter()
+        .find_map(|x| line.strip_prefix(x))
+    {
+        return Some((Marker::Bullet, rest));
+    }
+    let (number, rest) = line.split_once(". ")?;
+    Some((Marker::Number(number.parse().ok()?), rest))
+}
+
+/// The blocks of `text`, a subset of Markdown: `#` headings, `-` and `1.`
+/// lists, `**bold**`, `*italic*`, `` `code` `` and `[links](https://..)`.
+/// Anything else is text as it's written
+pub fn parse(text: &str) -> Vec<Block<'_>> {
+    let mut blocks = vec![];
+    // Lines of the paragraph being read
+    let mut paragraph: Vec<Inline> = vec![];
+    let end_paragraph = |blocks: &mut Vec<_>, paragraph: &mut Vec<_>| {
+        if !paragraph.is_empty() {
+            blocks.push(Block::Paragraph(std::mem::take(paragraph)));
+        }
+    };
+
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+        let depth = (line.len() - trimmed.len()) / 2;
+        let hashes = trimmed.len() - trimmed.trim_start_matches('#').len();
+
+        if trimmed.trim_end().is_empty() {
+            end_paragraph(&mut blocks, &mut paragraph);
+        } else if (1..=6).contains(&hashes) && trimmed[hashes..].starts_with(' ') {
+            end_paragraph(&mut blocks, &mut paragraph);
+            let heading = trimmed[hashes..].trim();
+            blocks.push(Block::Heading(hashes, inlines(heading)));
+        } else if let Some((marker, rest)) = item(trimmed) {
+            end_paragraph(&mut blocks, &mut paragraph);
+            blocks.push(Block::Item {
+                depth,
+                marker,
+                content: inlines(rest.trim()),
+            });
+        } else {
+            if !paragraph.is_empty() {
+                paragraph.push(Inline {
+                    text: " ",
+                    ..Default::default()
+                });
+            }
+            paragraph.extend(inlines(trimmed.trim_end()));
+        }
+    }
+    end_paragraph(&mut blocks, &mut paragraph);
+    blocks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn markdown_test() {
+        let text = |text| Inline {
+            text,
+            ..Default::default()
+        };
+        let blocks = parse(
+            "# Widescreen fix\r\n\r\nPatches the **menus** and *HUD*,\r\nnot cutscenes.\r\n\
+             \r\n- Run `game.exe` once\r\n  2. Then see [the forum](https://example.com)\r\n",
+        );
+        assert_eq!(
+            blocks,
+            [
+                Block::Heading(1, vec![text("Widescreen fix")]),
+                Block::Paragraph(vec![
+                    text("Patches the "),
+                    Inline {
+                        strong: true,
+                        ..text("menus")
+                    },
+                    text(" and "),
+                    Inline {
+                        emphasis: true,
+                        ..text("HUD")
+                    },
+                    text(","),
+                    text(" "),
+                    text("not cutscenes."),
+                ]),
+                Block::Item {
+                    depth: 0,
+                    marker: Marker::Bullet,
+                    content: vec![
+                        text("Run "),
+                        Inline {
+                            code: true,
+                            ..text("game.exe")
+                        },
+                        text(" once"),
+                    ],
+                },
+                Block::Item {
+                    depth: 1,
+                    marker: Marker::Number(2),
+                    content: vec![
+                        text("Then see "),
+                        Inline {
+                            link: Some("https://example.com"),
+                            ..text("the forum")
+                        },
+                    ],
+                },
+            ]
+        );
+
+        // Markers that aren't closed, inside words or escaped are text
+        assert_eq!(
+            parse(r"2 * 3 = 6, p1_xoffset, \*not italic\*, #hashtag"),
+            [Block::Paragraph(vec![
+                text("2 * 3 = 6, p1_xoffset, "),
+                text("*not italic"),
+                text("*, #hashtag"),
+            ])]
+        );
+        assert_eq!(
+            parse("See https://example.com/a_b_c"),
+            [Block::Paragraph(vec![
+                text("See "),
+                Inline {
+                    link: Some("https://example.com/a_b_c"),
+                    ..text("https://example.com/a_b_c")
+                },
+            ])]
+        );
+        assert_eq!(
+            parse("[Readme](file:///C:/Games/evil.exe)"),
+            [Block::Paragraph(vec![text(
+                "[Readme](file:///C:/Games/evil.exe)"
+            )])]
+        );
+    }
+}
@@ -0,0 +1,3107 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt::Write as _,
+    fs::File,
+    io::{Read, Write as _},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    activity,
+    backup::{self, BackupEntry, BackupMode, BackupRecord},
+    config::{
+        self, AppSection, Config, DetailsFormat, DuplicatePolicy, Format, Items, LoadOptions,
+        ParseMode,
+    },
+    crash, derive,
+    display::{self, Display, Preset},
+    document::Document,
+    editor::Editor,
+    encoding::{self, Encoding},
+    error::{Error, PatchContext},
+    hexview::HexView,
+    history,
+    i18n::tr,
+    installs,
+    patch_info::{PatchInfo, PatchOutcome, PatchStatus, PatchStrategy, SectionReport},
+    preview::{self, Preview},
+    registry::{self, RegistryBackup},
+    retention::{self, RetentionPolicy},
+    scan::{self, Candidate},
+    settings::{Profile, Settings},
+    share,
+    signature::Signature,
+    validate::{self, Severity},
+    wine,
+};
+
+/// What fills the main area of the window
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum Panel {
+    #[default]
+    Details,
+    Backups,
+    /// A single patch entered by hand or opened from a share link
+    CustomPatch,
+    SignatureBuilder,
+    /// Hex dump of what the next patch will write
+    Preview,
+    /// Whole file hex dump
+    HexView,
+    /// Form for writing or changing a game entry
+    Editor,
+}
+
+/// Order of the game list
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum SortOrder {
+    /// As listed in `[Apps]`
+    #[default]
+    Config,
+    Alphabetical,
+    /// Most recently patched first, then the rest in config order
+    Recent,
+}
+impl SortOrder {
+    pub const ALL: [Self; 3] = [Self::Config, Self::Alphabetical, Self::Recent];
+}
+impl std::fmt::Display for SortOrder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&match self {
+            Self::Config => tr!("sort-config"),
+            Self::Alphabetical => tr!("sort-alphabetical"),
+            Self::Recent => tr!("sort-recent"),
+        })
+    }
+}
+
+/// Entry of the game picker
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct AppEntry {
+    pub name: String,
+    /// In the group of recently patched games at the top
+    pub recent: bool,
+}
+impl std::fmt::Display for AppEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.recent {
+            true => f.write_str(&tr!("recent-game", name = self.name)),
+            false => f.write_str(&self.name),
+        }
+    }
+}
+
+/// Pattern being written in the signature builder
+#[derive(Debug, Default, Clone)]
+pub struct SigBuilder {
+    pub pattern: String,
+    /// Relative to the game directory
+    pub modfile: String,
+    /// Start of every match from the last test, cleared when anything changes
+    pub matches: Option<Vec<usize>>,
+    /// Result of the last scan of `modfile` for default resolutions
+    pub candidates: Option<Vec<Candidate>>,
+    /// Candidate `pattern` was taken from, cleared when it's edited
+    pub candidate: Option<Candidate>,
+    /// Config section derived from a patched copy of `modfile`
+    pub derived: Option<String>,
+}
+impl SigBuilder {
+    pub const SECTION: &str = "Signature builder";
+
+    pub fn signature(&self) -> Result<Signature, Error> {
+        Signature::from_pattern_str(Self::SECTION, &self.pattern)
+    }
+}
+
+/// Files of a section with every patch applied in memory
+struct Patched<'s> {
+    strategies: Vec<PatchStrategy<'s>>,
+    original: HashMap<&'s str, Vec<u8>>,
+    patched: HashMap<&'s str, Vec<u8>>,
+}
+impl Patched<'_> {
+    fn sites(&self) -> Vec<Preview> {
+        self.strategies
+            .iter()
+            .flat_map(|s| preview::previews(s, &self.original[s.modfile], &self.patched[s.modfile]))
+            .collect()
+    }
+}
+
+/// Hex dumps of the writes of a dry run or the last patch
+#[derive(Debug, Default, Clone)]
+pub struct PatchPreview {
+    /// Whether this is the custom patch rather than the selected game
+    pub custom: bool,
+    /// Whether the writes have been made, rather than being a dry run
+    pub applied: bool,
+    pub sites: Vec<Preview>,
+}
+impl PatchPreview {
+    /// Every site as text, for posting when a patch doesn't work
+    pub fn export(&self) -> String {
+        let mut out = match self.applied {
+            true => "Written to disk\n".to_string(),
+            false => "Dry run, nothing written\n".to_string(),
+        };
+        for site in &self.sites {
+            let _ = write!(out, "\n{site}");
+        }
+        out
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub enum ConfigState {
+    #[default]
+    NotLoaded,
+    Loaded(Config),
+    Error(Error),
+}
+
+/// Everything the user can ask for, independent of how it's presented
+#[derive(Debug, Clone)]
+pub enum Action {
+    ConfigLoaded(Result<Config, Error>),
+    /// Config dropped on the window, which replaces the loaded one or is
+    /// merged into it, after asking
+    ConfigDropped(PathBuf, Result<Config, Error>),
+    AppSelected(String),
+    /// Text narrowing the game list
+    FilterChanged(String),
+    ConfigUrlChanged(String),
+    SortSelected(SortOrder),
+    /// Only list the games of one config file, or of all with `None`
+    SourceSelected(Option<PathBuf>),
+    GameDirChanged(Option<String>),
+    /// Folder or file dropped on the window, the game directory is it or
+    /// the folder holding it
+    PathDropped(PathBuf),
+    /// Displays found at startup, the primary one fills in the resolution if
+    /// none is set. `None` if they can't be detected on this system
+    DisplaysFound(Option<Vec<Display>>),
+    /// Fill in the resolution of the primary display
+    UseCurrentResolution,
+    /// Fill in the resolution of a display
+    DisplaySelected(Display),
+    PresetSelected(Preset),
+    /// Keep the current aspect ratio when either side is edited
+    AspectLockToggled(bool),
+    /// Digits, or a whole resolution like `1920x1080` to fill in both sides
+    WidthChanged(String),
+    HeightChanged(String),
+    /// Add to the width, from the spinner buttons
+    WidthStepped(i16),
+    HeightStepped(i16),
+    /// Switch between [`ParseMode`]s, the config has to be loaded again
+    /// for it to count
+    StrictToggled(bool),
+    /// How config files are read from now on, like [`Action::StrictToggled`]
+    EncodingSelected(Encoding),
+    /// What's kept of sections and keys given twice, like
+    /// [`Action::StrictToggled`]
+    DuplicatesSelected(DuplicatePolicy),
+    CentralBackupToggled(bool),
+    /// Enable or disable the optional patches with a label
+    OptionToggled(String, bool),
+    /// Input for the value slot with a name
+    ValueChanged(String, String),
+    ApplyPatch,
+    /// Show what [`Action::ApplyPatch`] would write without writing it
+    PreviewPatch,
+    /// Save the current [`PatchPreview`] as text
+    ExportDiff(PathBuf),
+    ExportBundle(PathBuf),
+    RestoreBundle(PathBuf),
+    /// Switch to a panel, or back to [`Panel::Details`] if it's already shown
+    TogglePanel(Panel),
+    RestoreEverything,
+    RetentionSelected(RetentionPolicy),
+    CleanBackups,
+    RestoreBackup(usize),
+    DeleteBackup(usize),
+    /// Answer to an [`Effect::Confirm`]
+    Confirmed(bool),
+    CustomFieldChanged(&'static str, String),
+    OpenShareLink(String),
+    ApplyCustomPatch,
+    PreviewCustomPatch,
+    SigPatternChanged(String),
+    SigFileChanged(String),
+    /// Search the builder's modfile for its pattern
+    TestSignature,
+    /// Copy the builder's pattern into the custom patch
+    SaveSignature,
+    /// Search the builder's modfile for common default resolutions
+    ScanCandidates,
+    /// Start a pattern from one of the scanned candidates
+    UseCandidate(usize),
+    /// Diff the builder's modfile against a patched copy of it
+    DeriveSignature(PathBuf),
+    /// Open the builder's modfile in the hex viewer at one of its matches
+    ShowInHexView(usize),
+    HexFileChanged(String),
+    /// Read the hex viewer's modfile from the game directory
+    HexLoad,
+    HexGotoChanged(String),
+    HexGoto,
+    HexSearchChanged(String),
+    HexSearch,
+    HexNextHit,
+    /// Scroll by a number of rows
+    HexScroll(isize),
+    /// Move the cursor to a file offset
+    HexSelect(usize),
+    HexExpertToggled(bool),
+    HexByteChanged(String),
+    /// Write the typed byte at the cursor, in expert mode
+    HexEdit,
+    /// Back up the hex viewer's file then write the edits to it
+    HexSave,
+    /// Open the selected game in the editor
+    EditSelected,
+    /// Start a new game in the editor
+    EditNew,
+    EditorNameChanged(String),
+    /// Field of the game, or of an edit set by index
+    EditorFieldChanged(Option<usize>, &'static str, String),
+    EditorAddPatch,
+    EditorRemovePatch(usize),
+    /// File to search for the editor's signatures in
+    EditorBinaryChosen(PathBuf),
+    /// Check each loaded config file, see [`validate::validate`]
+    ValidateConfig,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Level {
+    Info,
+    Warning,
+    Error,
+}
+
+/// What the presentation layer should do after an [`Action`]
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Effect {
+    None,
+    Notify(Level, String),
+    /// Ask a yes/no question, then send the answer back as [`Action::Confirmed`]
+    Confirm(String),
+}
+
+#[derive(Debug, Default)]
+pub struct Model {
+    /// What's been done this session, see [`activity::Log`]
+    pub log: activity::Log,
+    pub config: ConfigState,
+    pub selected_section: Option<String>,
+    /// Narrows the game list, see [`Model::visible_apps`]
+    pub filter: String,
+    pub sort: SortOrder,
+    /// Config file the game list is narrowed to, see [`Config::sources`]
+    pub source: Option<PathBuf>,
+    /// Address typed in to download a config from
+    pub config_url: String,
+    /// Address of the config downloaded last, checked for newer versions
+    pub sync_url: Option<String>,
+    /// Config files loaded, remembered for the next run. Games in later
+    /// files replace those of the same name in earlier ones
+    pub config_paths: Vec<PathBuf>,
+    /// Whether a config with mistakes in it loads what it can or not at all,
+    /// and how its text is read
+    pub load_options: LoadOptions,
+    /// Settings of each game, restored when it's selected again
+    pub profiles: BTreeMap<String, Profile>,
+    /// Games patched, most recent first
+    pub recent: Vec<String>,
+    /// Where [`Self::recent`] is saved, if anywhere
+    pub history_file: Option<PathBuf>,
+    pub game_dir: Option<String>,
+    pub width: Option<u16>,
+    pub height: Option<u16>,
+    /// Ratio kept between width and height, in lowest terms
+    pub aspect_lock: Option<(u16, u16)>,
+    pub backup_mode: BackupMode,
+    pub last_backup: Option<BackupRecord>,
+    pub panel: Panel,
+    pub backups: Vec<BackupEntry>,
+    pub retention: RetentionPolicy,
+    /// Raw config values of the custom patch, keyed by field name
+    pub custom: Items,
+    pub sig_builder: SigBuilder,
+    /// Shown in [`Panel::Preview`]
+    pub preview: PatchPreview,
+    pub hex_view: HexView,
+    pub editor: Editor,
+    /// Where games written in the editor are saved, see [`config::personal_path`]
+    pub personal_config: Option<PathBuf>,
+    /// Labels of the optional patches to apply
+    pub enabled_options: HashSet<String>,
+    /// Raw input of each value slot, keyed by slot name
+    pub values: HashMap<String, String>,
+    /// Games whose `checkfile` is in the game dir, when more than one is
+    /// there to pick from
+    pub detected: Vec<String>,
+    /// Connected displays, see [`Action::DisplaysFound`]
+    pub displays: Vec<Display>,
+    /// Displays can't be detected on this system, as opposed to none being found
+    pub displays_unavailable: bool,
+    /// Dropped config waiting on whether to merge it, see [`Action::ConfigDropped`]
+    pending_config: Option<(PathBuf, Config)>,
+    /// Backups waiting on an answer before they're removed, with the count removed so far
+    pending_clean: Option<(Vec<(PathBuf, String)>, usize)>,
+}
+impl Model {
+    /// [`Config::load_file`] without blocking the UI
+    pub async fn load_config(
+        path: impl AsRef<Path>,
+        options: LoadOptions,
+    ) -> Result<Config, Error> {
+        let path = path.as_ref().to_path_buf();
+        tokio::task::spawn_blocking(move || Config::load_file(&path, options))
+            .await
+            .unwrap_or_else(|e| Err(Error::state_error(e.to_string())))
+    }
+
+    /// Every config in `paths` merged in order, see [`Self::config_paths`]
+    pub async fn load_configs(paths: Vec<PathBuf>, options: LoadOptions) -> Result<Config, Error> {
+        let mut merged: Option<Config> = None;
+        for path in paths {
+            let config = Self::load_config(&path, options)
+                .await
+                .map_err(|e| Error::in_config_file(path.display().to_string(), e))?;
+            match &mut merged {
+                Some(merged) => {
+                    merged.merge(config);
+                }
+                None => merged = Some(config),
+            }
+        }
+        merged.ok_or(Error::state_error(tr!("error-no-config")))
+    }
+
+    /// The files the config is loaded from, or that it's the built in one
+    fn config_files(&self) -> String {
+        match self.config_paths.is_empty() {
+            true => tr!("built-in-config"),
+            false => self
+                .config_paths
+                .iter()
+                .map(|x| x.display().to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+        }
+    }
+
+    /// What of `config` didn't load, games first then lines, if anything
+    fn skipped_message(config: &Config) -> Option<String> {
+        let mut message = vec![];
+        if !config.broken.is_empty() {
+            let broken = config
+                .broken
+                .iter()
+                .map(|x| format!("{}: {}", x.name, x.error))
+                .collect::<Vec<_>>()
+                .join("\n");
+            message.push(tr!("skipped-games", games = broken));
+        }
+        if !config.skipped.is_empty() {
+            let skipped = config
+                .skipped
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join("\n");
+            message.push(tr!("skipped-lines", lines = skipped));
+        }
+        (!message.is_empty()).then(|| message.join("\n\n"))
+    }
+
+    fn read_game_data(path: impl AsRef<Path>) -> Result<Vec<u8>, Error> {
+        let mut file = File::open(path)?;
+        let capacity = file.metadata().map(|m| m.len()).unwrap_or_default();
+        let mut buf = Vec::with_capacity(capacity as usize);
+        file.read_to_end(&mut buf)?;
+        Ok(buf)
+    }
+
+    /// Overwrite an existing file and read it back to check the write stuck
+    fn write_game_data(path: impl AsRef<Path>, data: &[u8]) -> Result<(), Error> {
+        let path = path.as_ref();
+
+        let mut file = File::options().write(true).truncate(true).open(path)?;
+        file.write_all(data)?;
+        drop(file);
+
+        if Self::read_game_data(path)? != data {
+            return Err(Error::WriteVerifyFailed {
+                file: path.display().to_string(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Whether `patch` is applied, optional ones have to be enabled
+    pub fn is_enabled(&self, patch: &PatchInfo) -> bool {
+        !patch.optional
+            || patch
+                .label
+                .as_ref()
+                .is_some_and(|x| self.enabled_options.contains(x))
+    }
+
+    /// Value of each of the section's slots, the input or else its default
+    pub fn slot_values(&self, section: &AppSection) -> Result<Vec<f64>, Error> {
+        section
+            .values
+            .iter()
+            .map(|slot| match self.values.get(&slot.name) {
+                Some(input) if !input.trim().is_empty() => slot.parse(input),
+                _ => slot.default.ok_or_else(|| {
+                    Error::state_error(tr!("error-missing-value", name = slot.name))
+                }),
+            })
+            .collect()
+    }
+
+    /// Find and apply every patch of `section` to copies of its files
+    ///
+    /// Nothing is written to disk. The patched data is only returned if every
+    /// patch succeeded
+    fn patch_in_memory<'s>(
+        &self,
+        section: &'s AppSection,
+    ) -> Result<(SectionReport, Option<Patched<'s>>), Error> {
+        let dir = self
+            .game_dir
+            .as_deref()
+            .ok_or(Error::state_error(tr!("error-missing-game-dir")))?;
+        let game_path = Path::new(dir);
+
+        let width = self
+            .width
+            .ok_or(Error::state_error(tr!("error-missing-width")))?;
+        let height = self
+            .height
+            .ok_or(Error::state_error(tr!("error-missing-height")))?;
+        let in_section = |e: Error| e.in_patch(PatchContext::section(&section.name));
+        let slots = self.slot_values(section).map_err(in_section)?;
+
+        let mut game_data_library = HashMap::new();
+
+        let patch_strategies = section
+            .patches
+            .iter()
+            .enumerate()
+            .map(|(i, p)| {
+                self.is_enabled(p).then(|| -> Result<PatchStrategy, Error> {
+                    let data = game_data_library
+                        .entry(p.modfile.as_str())
+                        .or_insert_with(|| {
+                            Self::read_game_data(wine::resolve(game_path, &p.modfile))
+                        })
+                        .as_ref()
+                        .map_err(|e| e.clone().in_patch(PatchContext::patch(i, &p.modfile)))?;
+
+                    let offsets = p.find_patch_offsets(data, i)?;
+                    Ok(PatchStrategy {
+                        iteration: i,
+                        modfile: &p.modfile,
+                        offsets,
+                        modify: p.modify.as_ref(),
+                        replace: p.replace.as_ref(),
+                        requested: p.occur,
+                        found: p.count_matches(data),
+                        slot: p.valueslot.map(|x| slots[x.get() - 1]),
+                    })
+                })
+            })
+            .collect::<Vec<_>>();
+
+        // Checked up front so a missing line fails before anything is patched
+        let text_results = section
+            .text_patches
+            .iter()
+            .enumerate()
+            .map(|(i, t)| {
+                let data = game_data_library
+                    .entry(t.textfile.as_str())
+                    .or_insert_with(|| Self::read_game_data(wine::resolve(game_path, &t.textfile)))
+                    .as_ref()
+                    .map_err(|e| {
+                        let context = PatchContext::patch(section.patches.len() + i, &t.textfile);
+                        e.clone().in_patch(context)
+                    })?;
+                t.find_lines(section.patches.len() + i, data)
+            })
+            .collect::<Vec<_>>();
+
+        let registry_index = section.patches.len() + section.text_patches.len();
+        let registry_results = section
+            .registry_patches
+            .iter()
+            .enumerate()
+            .map(|(i, r)| {
+                r.data(width, height)
+                    .map(|_| r.describe(width, height))
+                    .map_err(|e| e.in_patch(PatchContext::patch(registry_index + i, "registry")))
+            })
+            .collect::<Vec<_>>();
+
+        let mut report = SectionReport {
+            section: section.name.clone(),
+            patches: section
+                .patches
+                .iter()
+                .zip(&patch_strategies)
+                .enumerate()
+                .map(|(index, (p, s))| PatchOutcome {
+                    index,
+                    file: wine::resolve(game_path, &p.modfile),
+                    status: match s {
+                        Some(Ok(s)) => s.status(),
+                        Some(Err(e)) => PatchStatus::Failed(in_section(e.clone())),
+                        None => PatchStatus::Skipped("optional, not enabled".into()),
+                    },
+                })
+                .chain(
+                    section
+                        .text_patches
+                        .iter()
+                        .zip(&text_results)
+                        .enumerate()
+                        .map(|(i, (t, lines))| PatchOutcome {
+                            index: section.patches.len() + i,
+                            file: wine::resolve(game_path, &t.textfile),
+                            status: match lines {
+                                Ok(lines) => PatchStatus::Patched {
+                                    requested: PatchInfo::ALL,
+                                    found: lines.len(),
+                                    sites: lines.clone(),
+                                    signature: 0,
+                                },
+                                Err(e) => PatchStatus::Failed(in_section(e.clone())),
+                            },
+                        }),
+                )
+                .chain(
+                    registry_results
+                        .iter()
+                        .enumerate()
+                        .map(|(i, written)| PatchOutcome {
+                            index: registry_index + i,
+                            file: PathBuf::from("registry"),
+                            status: match written {
+                                Ok(_) if !cfg!(windows) => {
+                                    PatchStatus::Skipped("registry, Windows only".into())
+                                }
+                                Ok(written) => PatchStatus::Written(written.clone()),
+                                Err(e) => PatchStatus::Failed(in_section(e.clone())),
+                            },
+                        }),
+                )
+                .collect(),
+        };
+
+        if let Some(failed) = patch_strategies
+            .iter()
+            .position(|x| matches!(x, Some(Err(_))))
+            .or_else(|| {
+                let failed = text_results.iter().position(Result::is_err)?;
+                Some(section.patches.len() + failed)
+            })
+            .or_else(|| {
+                let failed = registry_results.iter().position(Result::is_err)?;
+                Some(registry_index + failed)
+            })
+        {
+            report.skip_rest(failed);
+            return Ok((report, None));
+        }
+
+        let strategies = patch_strategies
+            .into_iter()
+            .flatten()
+            .collect::<Result<Vec<_>, _>>()?;
+        let original = game_data_library
+            .into_iter()
+            .map(|(k, v)| v.map(|v| (k, v)))
+            .collect::<Result<HashMap<_, _>, _>>()?;
+        let mut patched = original.clone();
+
+        for s in &strategies {
+            let file_data = patched.get_mut(s.modfile).expect("Missing game data?");
+
+            if let Err(e) = s.patch_data(file_data, width, height) {
+                report.patches[s.iteration].status = PatchStatus::Failed(in_section(e));
+                report.skip_rest(s.iteration);
+                return Ok((report, None));
+            }
+        }
+
+        for (i, t) in section.text_patches.iter().enumerate() {
+            let index = section.patches.len() + i;
+            let file_data = patched
+                .get_mut(t.textfile.as_str())
+                .expect("Missing game data?");
+
+            match t.patch_data(index, file_data, width, height) {
+                Ok((data, _)) => *file_data = data,
+                Err(e) => {
+                    report.patches[index].status = PatchStatus::Failed(in_section(e));
+                    report.skip_rest(index);
+                    return Ok((report, None));
+                }
+            }
+        }
+
+        Ok((
+            report,
+            Some(Patched {
+                strategies,
+                original,
+                patched,
+            }),
+        ))
+    }
+
+    /// Patch every file of `section`, or none of them if any patch fails
+    pub fn apply_patches(
+        &mut self,
+        section: &AppSection,
+        custom: bool,
+    ) -> Result<SectionReport, Error> {
+        tracing::info!(
+            section = section.name,
+            game_dir = self.game_dir,
+            width = self.width,
+            height = self.height,
+            options = ?self.enabled_options,
+            "Patching"
+        );
+        // Checked here rather than only by the patch button, so the command
+        // line refuses the same resolutions
+        if let Some((_, issue)) = self
+            .resolution_issues(Some(section).filter(|_| !custom))
+            .into_iter()
+            .find(|x| x.0 == Level::Error)
+        {
+            return Err(Error::value_error(issue));
+        }
+
+        let (report, patched) = self.patch_in_memory(section)?;
+        if let Some(patched) = &patched {
+            self.log_scanned(patched);
+        }
+        let sites = patched.as_ref().map(Patched::sites).unwrap_or_default();
+        let Some(Patched {
+            strategies: patch_strategies,
+            patched: game_data_library,
+            ..
+        }) = patched
+        else {
+            return Ok(report);
+        };
+
+        let game_path = Path::new(self.game_dir.as_deref().unwrap_or_default());
+        let width = self.width.unwrap_or_default();
+        let height = self.height.unwrap_or_default();
+
+        let mut modfiles = game_data_library.keys().copied().collect::<Vec<_>>();
+        modfiles.sort_unstable();
+
+        let in_section = |e: Error| e.in_patch(PatchContext::section(&section.name));
+        let mut record = backup::backup_files(
+            self.backup_mode,
+            &section.name,
+            game_path,
+            &modfiles,
+            width,
+            height,
+        )
+        .map_err(|e| in_section(Error::BackupFailed(Box::new(e))))?;
+
+        // Only written on Windows, elsewhere they're reported as skipped
+        let registry = match cfg!(windows) {
+            true => section.registry_patches.iter().collect(),
+            false => vec![],
+        };
+
+        let log = patch_strategies
+            .iter()
+            .map(|s| s.log(width, height))
+            .chain(
+                section
+                    .text_patches
+                    .iter()
+                    .enumerate()
+                    .map(|(i, t)| t.log(section.patches.len() + i, width, height)),
+            )
+            .chain(registry.iter().enumerate().map(|(i, r)| {
+                let index = section.patches.len() + section.text_patches.len() + i;
+                format!("patch {index}: {}\n", r.describe(width, height))
+            }))
+            .collect::<String>();
+        record.set_log(self.backup_mode, log).map_err(in_section)?;
+        self.log.push(
+            Level::Info,
+            tr!(
+                "log-backup",
+                count = modfiles.len(),
+                dir = record.dir.display()
+            ),
+        );
+
+        let previous = registry
+            .iter()
+            .map(|r| RegistryBackup::take(r))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| in_section(Error::BackupFailed(Box::new(e))))?;
+        record
+            .set_registry(self.backup_mode, previous)
+            .map_err(in_section)?;
+
+        for modfile in modfiles {
+            let data = &game_data_library[modfile];
+            Self::write_game_data(wine::resolve(game_path, modfile), data)
+                .map_err(|e| in_section(e.in_patch(PatchContext::file(modfile))))?;
+            tracing::debug!(
+                file = modfile,
+                sha256 = backup::sha256_hex(data),
+                "After patching"
+            );
+        }
+        for r in &registry {
+            registry::apply(r, width, height)
+                .map_err(|e| in_section(e.in_patch(PatchContext::file("registry"))))?;
+        }
+
+        self.last_backup = Some(record);
+        self.preview = PatchPreview {
+            custom,
+            applied: true,
+            sites,
+        };
+        self.refresh_backups();
+
+        Ok(report)
+    }
+
+    /// Record the files `patched` was read from
+    fn log_scanned(&mut self, patched: &Patched) {
+        let mut files = patched.original.iter().collect::<Vec<_>>();
+        files.sort_unstable_by_key(|x| x.0);
+        for (file, data) in files {
+            tracing::debug!(file, sha256 = backup::sha256_hex(data), "Before patching");
+            self.log.push(
+                Level::Info,
+                tr!("log-scanned", file = file, count = data.len()),
+            );
+        }
+    }
+
+    /// Fill [`Self::preview`] with what patching `section` would write
+    fn preview_patches(&mut self, section: &AppSection, custom: bool) -> Effect {
+        match self.patch_in_memory(section) {
+            Ok((_, Some(patched))) => {
+                self.log_scanned(&patched);
+                self.preview = PatchPreview {
+                    custom,
+                    applied: false,
+                    sites: patched.sites(),
+                };
+                self.panel = Panel::Preview;
+                Effect::None
+            }
+            Ok((report, None)) => {
+                Effect::Notify(Level::Error, tr!("preview-failed-report", report = report))
+            }
+            Err(e) => Effect::Notify(Level::Error, tr!("preview-failed", error = e)),
+        }
+    }
+
+    /// How well `name` matches `filter`, lower is better: 0 when it contains
+    /// the filter and 1 when its letters appear in order, e.g. `kotor` in
+    /// `Knights of the Old Republic`
+    pub fn filter_score(name: &str, filter: &str) -> Option<u8> {
+        let name = name.to_lowercase();
+        let filter = filter.trim().to_lowercase();
+        if name.contains(&filter) {
+            return Some(0);
+        }
+
+        let mut chars = name.chars();
+        filter
+            .chars()
+            .filter(|x| !x.is_whitespace())
+            .all(|x| chars.any(|c| c == x))
+            .then_some(1)
+    }
+
+    /// Digits only, clamped to [`display::MAX_DIMENSION`]
+    fn parse_dimension(input: &str, current: Option<u16>) -> Option<u16> {
+        let input = input.trim();
+        if input.is_empty() {
+            None
+        } else if input.bytes().all(|x| x.is_ascii_digit()) {
+            let max = display::MAX_DIMENSION;
+            Some(input.parse::<u16>().map_or(max, |x| x.min(max)))
+        } else {
+            current
+        }
+    }
+
+    pub fn update(&mut self, action: Action) -> Effect {
+        match action {
+            Action::ConfigLoaded(config) => {
+                let mut effect = Effect::None;
+                self.config = match config {
+                    Ok(config) => {
+                        // Keep the game when reloading or switching to a config that has it
+                        if !self
+                            .selected_section
+                            .as_ref()
+                            .is_some_and(|x| config.apps.apps.contains(x))
+                        {
+                            self.selected_section = config.apps.apps.first().cloned();
+                        }
+                        if let Some(source) = &self.source
+                            && !config.sources.values().any(|x| x == source)
+                        {
+                            self.source = None;
+                        }
+                        if let Some(skipped) = Self::skipped_message(&config) {
+                            effect = Effect::Notify(Level::Warning, skipped);
+                        }
+                        let files = self.config_files();
+                        crash::set_config(&files);
+                        self.log.push(
+                            Level::Info,
+                            tr!(
+                                "log-config-loaded",
+                                count = config.apps.apps.len(),
+                                files = files
+                            ),
+                        );
+                        ConfigState::Loaded(config)
+                    }
+                    Err(e) => {
+                        self.log
+                            .push(Level::Error, tr!("log-config-failed", error = e));
+                        ConfigState::Error(e)
+                    }
+                };
+                self.detect_game();
+                self.refresh_backups();
+
+                effect
+            }
+            Action::ConfigDropped(path, config) => match (&self.config, config) {
+                (ConfigState::Loaded(_), Ok(config)) => {
+                    self.pending_config = Some((path, config));
+                    Effect::Confirm(tr!("confirm-merge"))
+                }
+                (_, config) => {
+                    if config.is_ok() {
+                        self.config_paths = vec![path];
+                    }
+                    self.update(Action::ConfigLoaded(config))
+                }
+            },
+            Action::Confirmed(answer) if let Some((path, config)) = self.pending_config.take() => {
+                if !answer {
+                    self.config_paths = vec![path];
+                    return self.update(Action::ConfigLoaded(Ok(config)));
+                }
+
+                let added = config.sections.len();
+                self.config_paths.retain(|x| *x != path);
+                self.config_paths.push(path);
+                crash::set_config(&self.config_files());
+                let skipped = Self::skipped_message(&config);
+                let replaced = match &mut self.config {
+                    ConfigState::Loaded(loaded) => loaded.merge(config),
+                    _ => vec![],
+                };
+                self.detect_game();
+                self.refresh_backups();
+
+                let message = match replaced.is_empty() {
+                    true => tr!("merged", count = added),
+                    false => tr!(
+                        "merged-replacing",
+                        count = added,
+                        games = replaced.join("\n")
+                    ),
+                };
+                match skipped {
+                    None => Effect::Notify(Level::Info, message),
+                    Some(skipped) => {
+                        Effect::Notify(Level::Warning, format!("{message}\n\n{skipped}"))
+                    }
+                }
+            }
+            Action::FilterChanged(filter) => {
+                self.filter = filter;
+                Effect::None
+            }
+            Action::ConfigUrlChanged(url) => {
+                self.config_url = url;
+                Effect::None
+            }
+            Action::SortSelected(sort) => {
+                self.sort = sort;
+                Effect::None
+            }
+            Action::SourceSelected(source) => {
+                self.source = source;
+
+                // Like switching tabs, show a game of the file picked
+                let apps = self.visible_apps();
+                match self.selected_section.as_ref() {
+                    Some(x) if apps.contains(x) => Effect::None,
+                    _ => match apps.into_iter().next() {
+                        Some(first) => self.update(Action::AppSelected(first)),
+                        None => Effect::None,
+                    },
+                }
+            }
+            Action::AppSelected(app) => {
+                if let Some((name, profile)) = self.current_profile() {
+                    self.profiles.insert(name, profile);
+                }
+                let switched = self.selected_section.as_ref() != Some(&app);
+                self.selected_section = Some(app);
+                self.detected.clear();
+
+                // Go back to how this game was last set up
+                if switched {
+                    let profile = self
+                        .selected_section
+                        .as_ref()
+                        .and_then(|x| self.profiles.get(x))
+                        .cloned()
+                        .unwrap_or_default();
+                    self.restore_profile(profile);
+                }
+
+                // Fill in the install folder unless one for this game is already picked
+                if let Some(section) = self.get_selected_app_section()
+                    && !self.has_checkfile(section)
+                    && let Some(egsname) = &section.egsname
+                    && let Some(dir) = installs::find_epic(&installs::epic_manifest_dirs(), egsname)
+                {
+                    self.game_dir = Some(dir.to_string_lossy().into_owned());
+                }
+
+                self.refresh_backups();
+                Effect::None
+            }
+            Action::GameDirChanged(dir) => {
+                self.game_dir = dir;
+                self.detect_game();
+                if let Some(section) = self.get_selected_app_section()
+                    && self.has_checkfile(section)
+                    && let Some(dir) = &self.game_dir
+                {
+                    let profile = self.profiles.entry(section.name.clone()).or_default();
+                    profile.game_dir = Some(dir.clone());
+                }
+                self.refresh_backups();
+                Effect::None
+            }
+            Action::PathDropped(path) => {
+                let dir = match path.is_dir() {
+                    true => path.as_path(),
+                    false => path.parent().unwrap_or(&path),
+                };
+                self.update(Action::GameDirChanged(Some(
+                    dir.to_string_lossy().into_owned(),
+                )));
+
+                let found = !self.detected.is_empty()
+                    || self
+                        .get_selected_app_section()
+                        .is_some_and(|x| self.has_checkfile(x));
+                match &self.config {
+                    _ if found => Effect::None,
+                    ConfigState::Loaded(config) => Effect::Notify(
+                        Level::Warning,
+                        tr!(
+                            "detect-none",
+                            count = config.sections.len(),
+                            dir = dir.display()
+                        ),
+                    ),
+                    _ => {
+                        Effect::Notify(Level::Warning, tr!("detect-no-config", dir = dir.display()))
+                    }
+                }
+            }
+            Action::DisplaysFound(displays) => {
+                self.displays_unavailable = displays.is_none();
+                self.displays = displays.unwrap_or_default();
+                match (self.width, self.height) {
+                    (None, None) => self.update(Action::UseCurrentResolution),
+                    _ => Effect::None,
+                }
+            }
+            Action::UseCurrentResolution => match display::primary(&self.displays) {
+                Some(display) => self.update(Action::DisplaySelected(display.clone())),
+                None if self.displays_unavailable => {
+                    Effect::Notify(Level::Warning, tr!("error-display-unavailable"))
+                }
+                None => Effect::Notify(Level::Warning, tr!("error-no-display")),
+            },
+            Action::DisplaySelected(display) => {
+                self.set_resolution(display.width, display.height);
+                Effect::None
+            }
+            Action::PresetSelected(preset) => {
+                self.set_resolution(preset.width, preset.height);
+                Effect::None
+            }
+            Action::AspectLockToggled(locked) => {
+                self.aspect_lock = match (locked, self.width, self.height) {
+                    (true, Some(width), Some(height)) if width > 0 && height > 0 => {
+                        Some(display::reduce(width, height))
+                    }
+                    _ => None,
+                };
+                Effect::None
+            }
+            Action::WidthChanged(width) if let Some((w, h)) = display::parse_mode(&width) => {
+                self.set_resolution(w, h);
+                Effect::None
+            }
+            Action::HeightChanged(height) if let Some((w, h)) = display::parse_mode(&height) => {
+                self.set_resolution(w, h);
+                Effect::None
+            }
+            Action::WidthStepped(step) => {
+                let width = self
+                    .width
+                    .unwrap_or_default()
+                    .saturating_add_signed(step)
+                    .max(1);
+                self.update(Action::WidthChanged(width.to_string()))
+            }
+            Action::HeightStepped(step) => {
+                let height = self
+                    .height
+                    .unwrap_or_default()
+                    .saturating_add_signed(step)
+                    .max(1);
+                self.update(Action::HeightChanged(height.to_string()))
+            }
+            Action::WidthChanged(width) => {
+                self.width = Self::parse_dimension(&width, self.width);
+                if let (Some((w, h)), Some(width)) = (self.aspect_lock, self.width) {
+                    self.height = Some(display::scale(width, w, h));
+                }
+                Effect::None
+            }
+            Action::HeightChanged(height) => {
+                self.height = Self::parse_dimension(&height, self.height);
+                if let (Some((w, h)), Some(height)) = (self.aspect_lock, self.height) {
+                    self.width = Some(display::scale(height, h, w));
+                }
+                Effect::None
+            }
+            Action::StrictToggled(strict) => {
+                self.load_options.mode = match strict {
+                    true => ParseMode::Strict,
+                    false => ParseMode::Lenient,
+                };
+                Effect::None
+            }
+            Action::EncodingSelected(encoding) => {
+                self.load_options.encoding = encoding;
+                Effect::None
+            }
+            Action::DuplicatesSelected(policy) => {
+                self.load_options.duplicates = policy;
+                Effect::None
+            }
+            Action::CentralBackupToggled(enabled) => {
+                self.backup_mode = if enabled {
+                    BackupMode::Central
+                } else {
+                    BackupMode::GameDirectory
+                };
+
+                Effect::None
+            }
+            Action::OptionToggled(label, enabled) => {
+                if enabled {
+                    self.enabled_options.insert(label);
+                } else {
+                    self.enabled_options.remove(&label);
+                }
+                Effect::None
+            }
+            Action::ValueChanged(name, input) => {
+                self.values.insert(name, input);
+                Effect::None
+            }
+            Action::ApplyPatch => {
+                let result = match self.get_selected_app_section().cloned() {
+                    Some(section) => self.apply_patches(&section, false),
+                    None => Ok(SectionReport::default()),
+                };
+
+                if let Ok(report) = &result
+                    && report.succeeded()
+                    && let Some(game) = &self.selected_section
+                {
+                    history::touch(&mut self.recent, game);
+                    if let Some(path) = &self.history_file {
+                        // Losing the history isn't worth failing a patch that worked over
+                        let _ = history::save(path, &self.recent);
+                    }
+                }
+
+                Self::patch_effect(result)
+            }
+            Action::ApplyCustomPatch => {
+                let result = self
+                    .custom_section()
+                    .and_then(|section| self.apply_patches(&section, true));
+
+                Self::patch_effect(result)
+            }
+            Action::ExportDiff(path) => match std::fs::write(&path, self.preview.export()) {
+                Ok(()) => Effect::None,
+                Err(e) => Effect::Notify(
+                    Level::Error,
+                    tr!("error-export-diff", error = Error::from(e)),
+                ),
+            },
+            Action::PreviewPatch => match self.get_selected_app_section().cloned() {
+                Some(section) => self.preview_patches(&section, false),
+                None => Effect::None,
+            },
+            Action::PreviewCustomPatch => match self.custom_section() {
+                Ok(section) => self.preview_patches(&section, true),
+                Err(e) => Effect::Notify(Level::Error, tr!("preview-failed", error = e)),
+            },
+            Action::CustomFieldChanged(field, value) => {
+                self.custom.insert(field.to_string(), value);
+                Effect::None
+            }
+            Action::SigPatternChanged(pattern) => {
+                self.sig_builder.pattern = pattern;
+                self.sig_builder.matches = None;
+                self.sig_builder.candidate = None;
+                Effect::None
+            }
+            Action::SigFileChanged(modfile) => {
+                self.sig_builder = SigBuilder {
+                    pattern: std::mem::take(&mut self.sig_builder.pattern),
+                    modfile,
+                    ..Default::default()
+                };
+                Effect::None
+            }
+            Action::TestSignature => {
+                let result = self.sig_builder.signature().and_then(|sig| {
+                    Ok(sig
+                        .find_iter(&self.read_builder_file()?)
+                        .collect::<Vec<_>>())
+                });
+
+                match result {
+                    Ok(matches) => {
+                        let offsets = matches.iter().map(|x| format!("{x:#X}"));
+                        self.log.push(
+                            Level::Info,
+                            tr!(
+                                "log-signature",
+                                file = self.sig_builder.modfile,
+                                count = matches.len(),
+                                offsets = offsets.collect::<Vec<_>>().join(", ")
+                            ),
+                        );
+                        self.sig_builder.matches = Some(matches);
+                        self.sig_builder.derived = None;
+                        Effect::None
+                    }
+                    Err(e) => Effect::Notify(Level::Error, tr!("error-test-signature", error = e)),
+                }
+            }
+            Action::ScanCandidates => match self.read_builder_file() {
+                Ok(data) => {
+                    let candidates = scan::scan(&data);
+                    self.log.push(
+                        Level::Info,
+                        tr!(
+                            "log-candidates",
+                            file = self.sig_builder.modfile,
+                            count = candidates.len()
+                        ),
+                    );
+                    self.sig_builder.candidates = Some(candidates);
+                    self.sig_builder.derived = None;
+                    Effect::None
+                }
+                Err(e) => Effect::Notify(Level::Error, tr!("error-scan", error = e)),
+            },
+            Action::UseCandidate(index) => {
+                let candidate = self
+                    .sig_builder
+                    .candidates
+                    .as_ref()
+                    .and_then(|x| x.get(index));
+                if let Some(candidate) = candidate.cloned() {
+                    self.sig_builder.pattern = candidate.pattern();
+                    self.sig_builder.matches = None;
+                    self.sig_builder.candidate = Some(candidate);
+                }
+                Effect::None
+            }
+            Action::ShowInHexView(offset) => {
+                let modfile = self.sig_builder.modfile.clone();
+                if (self.hex_view.loaded != modfile || self.hex_view.data.is_empty())
+                    && let Err(e) = self.load_hex_view(modfile)
+                {
+                    return e;
+                }
+
+                let hit_len = self.sig_builder.signature().map_or(1, |x| x.pattern.len());
+                let hits = self.sig_builder.matches.clone().unwrap_or_default();
+                self.hex_view
+                    .show_hits(self.sig_builder.pattern.clone(), hits, hit_len);
+                self.hex_view.jump(offset);
+                self.panel = Panel::HexView;
+                Effect::None
+            }
+            Action::HexFileChanged(modfile) => {
+                self.hex_view.modfile = modfile;
+                Effect::None
+            }
+            Action::HexLoad => match self.load_hex_view(self.hex_view.modfile.clone()) {
+                Ok(()) => Effect::None,
+                Err(e) => e,
+            },
+            Action::HexGotoChanged(goto) => {
+                self.hex_view.goto = goto;
+                Effect::None
+            }
+            Action::HexGoto => match self.hex_view.goto() {
+                Ok(()) => Effect::None,
+                Err(e) => Effect::Notify(Level::Error, e.to_string()),
+            },
+            Action::HexSearchChanged(search) => {
+                self.hex_view.search = search;
+                Effect::None
+            }
+            Action::HexSearch => match self.hex_view.find() {
+                Ok(()) if self.hex_view.hits.is_empty() => {
+                    Effect::Notify(Level::Info, tr!("no-matches"))
+                }
+                Ok(()) => Effect::None,
+                Err(e) => Effect::Notify(Level::Error, e.to_string()),
+            },
+            Action::HexNextHit => {
+                self.hex_view.next_hit();
+                Effect::None
+            }
+            Action::HexScroll(rows) => {
+                self.hex_view.scroll(rows);
+                Effect::None
+            }
+            Action::HexSelect(offset) => {
+                self.hex_view.jump(offset);
+                Effect::None
+            }
+            Action::HexExpertToggled(expert) => {
+                self.hex_view.expert = expert;
+                Effect::None
+            }
+            Action::HexByteChanged(byte) => {
+                self.hex_view.byte = byte;
+                Effect::None
+            }
+            Action::HexEdit => {
+                let byte = std::mem::take(&mut self.hex_view.byte);
+                match self.hex_view.edit(&byte) {
+                    Ok(()) => Effect::None,
+                    Err(e) => Effect::Notify(Level::Error, e.to_string()),
+                }
+            }
+            Action::ValidateConfig => {
+                let results: Vec<_> = match self.config_paths.is_empty() {
+                    true => vec![(
+                        tr!("built-in-config"),
+                        Ok(validate::validate(&Document::parse(config::DEFAULT))),
+                    )],
+                    false => self
+                        .config_paths
+                        .iter()
+                        .map(|x| {
+                            (
+                                x.display().to_string(),
+                                validate::validate_file(x, self.load_options),
+                            )
+                        })
+                        .collect(),
+                };
+
+                let issues = results.iter().flat_map(|x| x.1.iter().flatten());
+                let level = match results.iter().any(|x| x.1.is_err()) {
+                    true => Level::Error,
+                    false if issues.clone().any(|x| x.severity == Severity::Error) => Level::Error,
+                    false if issues.clone().next().is_some() => Level::Warning,
+                    false => Level::Info,
+                };
+                let report = results
+                    .into_iter()
+                    .map(|(name, result)| match result {
+                        Ok(issues) => format!("{name}\n{}", validate::report(&issues)),
+                        Err(e) => format!("{name}\n{e}\n"),
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                Effect::Notify(level, report)
+            }
+            Action::HexSave => match self.save_hex_view() {
+                Ok(()) => {
+                    Effect::Notify(Level::Info, tr!("hex-saved", file = self.hex_view.loaded))
+                }
+                Err(e) => Effect::Notify(Level::Error, tr!("error-hex-save", error = e)),
+            },
+            Action::EditSelected => match self.edit_selected() {
+                Ok(()) => {
+                    self.panel = Panel::Editor;
+                    Effect::None
+                }
+                Err(e) => Effect::Notify(Level::Error, tr!("error-edit-game", error = e)),
+            },
+            Action::EditNew => {
+                self.editor.clear();
+                self.panel = Panel::Editor;
+                Effect::None
+            }
+            Action::EditorNameChanged(name) => {
+                self.editor.set_name(name);
+                Effect::None
+            }
+            Action::EditorFieldChanged(patch, field, value) => {
+                self.editor.set_field(patch, field, value);
+                Effect::None
+            }
+            Action::EditorAddPatch => {
+                self.editor.add_patch();
+                Effect::None
+            }
+            Action::EditorRemovePatch(patch) => {
+                self.editor.remove_patch(patch);
+                Effect::None
+            }
+            Action::EditorBinaryChosen(path) => match Self::read_game_data(&path) {
+                Ok(data) => {
+                    self.editor.set_binary(path, data);
+                    Effect::None
+                }
+                Err(e) => Effect::Notify(
+                    Level::Error,
+                    tr!("error-open", file = path.display(), error = e),
+                ),
+            },
+            Action::DeriveSignature(patched) => {
+                let name = self
+                    .selected_section
+                    .clone()
+                    .unwrap_or_else(|| self.sig_builder.modfile.clone());
+                let resolution = self.width.zip(self.height);
+
+                let result = self.read_builder_file().and_then(|original| {
+                    let patched = std::fs::read(patched)?;
+                    derive::derive(
+                        &name,
+                        &self.sig_builder.modfile,
+                        &original,
+                        &patched,
+                        resolution,
+                    )
+                });
+
+                match result {
+                    Ok(section) => {
+                        self.sig_builder.derived = Some(section);
+                        Effect::None
+                    }
+                    Err(e) => Effect::Notify(Level::Error, tr!("error-derive", error = e)),
+                }
+            }
+            Action::SaveSignature => match self.sig_builder.signature() {
+                Ok(sig) => {
+                    self.custom.shift_remove("sig");
+                    self.custom.shift_remove("sigwild");
+                    self.custom.insert("pattern".into(), sig.to_string());
+                    for (field, value) in self.sig_builder.candidate.iter().flat_map(|x| x.fields())
+                    {
+                        self.custom.insert(field.into(), value);
+                    }
+                    if !self.sig_builder.modfile.is_empty() {
+                        self.custom
+                            .insert("modfile".into(), self.sig_builder.modfile.clone());
+                    }
+                    self.panel = Panel::CustomPatch;
+                    Effect::None
+                }
+                Err(e) => Effect::Notify(Level::Error, tr!("error-save-signature", error = e)),
+            },
+            Action::OpenShareLink(link) => match share::from_link(&link) {
+                Ok(fields) => {
+                    self.custom = fields;
+                    self.panel = Panel::CustomPatch;
+                    Effect::None
+                }
+                Err(e) => Effect::Notify(Level::Error, tr!("error-share-link", error = e)),
+            },
+            Action::ExportBundle(path) => match &self.last_backup {
+                Some(record) => match record.export_bundle(path) {
+                    Ok(()) => Effect::None,
+                    Err(e) => Effect::Notify(Level::Error, tr!("error-export-bundle", error = e)),
+                },
+                None => Effect::None,
+            },
+            Action::RestoreBundle(path) => {
+                let result = match self.game_dir.as_deref() {
+                    Some(dir) => backup::restore_bundle(
+                        path,
+                        Path::new(dir),
+                        self.get_selected_app_section()
+                            .map_or(&[], |x| &x.registry_patches),
+                    ),
+                    None => Err(Error::state_error(tr!("error-no-game-dir"))),
+                };
+                self.refresh_backups();
+
+                match result {
+                    Ok(files) if files.is_empty() => Effect::None,
+                    Ok(files) => {
+                        let files = files
+                            .iter()
+                            .map(|x| x.to_string_lossy())
+                            .collect::<Vec<_>>()
+                            .join("\n");
+
+                        Effect::Notify(Level::Info, tr!("bundle-restored", files = files))
+                    }
+                    Err(e) => Effect::Notify(Level::Error, tr!("error-restore-bundle", error = e)),
+                }
+            }
+            Action::TogglePanel(panel) => {
+                self.panel = if self.panel == panel {
+                    Panel::Details
+                } else {
+                    panel
+                };
+
+                if self.panel == Panel::SignatureBuilder
+                    && self.sig_builder.modfile.is_empty()
+                    && let Some(patch) = self
+                        .get_selected_app_section()
+                        .and_then(|x| x.patches.first())
+                {
+                    self.sig_builder.modfile = patch.modfile.clone();
+                }
+
+                if self.panel == Panel::Editor
+                    && self.editor.original.is_none()
+                    && self.editor.patches.is_empty()
+                    && self.edit_selected().is_err()
+                {
+                    self.editor.clear();
+                }
+
+                if self.panel == Panel::HexView && self.hex_view.modfile.is_empty() {
+                    self.hex_view.modfile = match &self.sig_builder.modfile {
+                        x if !x.is_empty() => x.clone(),
+                        _ => self
+                            .get_selected_app_section()
+                            .and_then(|x| x.patches.first())
+                            .map(|x| x.modfile.clone())
+                            .unwrap_or_default(),
+                    };
+                }
+
+                self.refresh_backups();
+                Effect::None
+            }
+            Action::RestoreEverything => match self.game_dir.as_deref() {
+                Some(dir) => {
+                    let summary = backup::restore_all(Path::new(dir));
+                    let level = if summary.failed.is_empty() {
+                        Level::Info
+                    } else {
+                        Level::Warning
+                    };
+                    Effect::Notify(level, summary.to_string())
+                }
+                None => Effect::None,
+            },
+            Action::RetentionSelected(policy) => {
+                self.retention = policy;
+                Effect::None
+            }
+            Action::CleanBackups => {
+                let game_dir = self.game_dir.as_deref().map(Path::new);
+                let sets = backup::central_manifests()
+                    .into_iter()
+                    .filter(|(_, m)| game_dir.is_none_or(|dir| m.game_dir == dir))
+                    .collect::<Vec<_>>();
+
+                let mut planned = retention::plan(self.retention, &sets)
+                    .into_iter()
+                    .map(|dir| {
+                        let game = sets
+                            .iter()
+                            .find(|(x, _)| *x == dir)
+                            .map(|(_, m)| m.game.clone())
+                            .unwrap_or_default();
+                        (dir, game)
+                    })
+                    .collect::<Vec<_>>();
+                // Answers are handled from the back of the queue
+                planned.reverse();
+
+                if self.retention == RetentionPolicy::Prompt {
+                    self.pending_clean = Some((planned, 0));
+                    self.next_clean_prompt()
+                } else {
+                    let mut removed = 0;
+                    for (dir, _) in planned {
+                        if let Err(e) = std::fs::remove_dir_all(&dir) {
+                            self.refresh_backups();
+                            return Effect::Notify(Level::Error, tr!("error-clean", error = e));
+                        }
+                        removed += 1;
+                    }
+
+                    self.refresh_backups();
+                    Effect::Notify(Level::Info, tr!("cleaned", count = removed))
+                }
+            }
+            Action::Confirmed(answer) => {
+                if let Some((pending, removed)) = &mut self.pending_clean
+                    && let Some((dir, _)) = pending.pop()
+                    && answer
+                {
+                    if let Err(e) = std::fs::remove_dir_all(&dir) {
+                        self.pending_clean = None;
+                        self.refresh_backups();
+                        return Effect::Notify(
+                            Level::Error,
+                            tr!("error-clean", error = Error::from(e)),
+                        );
+                    }
+                    *removed += 1;
+                }
+
+                self.next_clean_prompt()
+            }
+            Action::RestoreBackup(index) => match self.backups.get(index) {
+                Some(entry) => match entry.restore() {
+                    Ok(()) => Effect::Notify(
+                        Level::Info,
+                        tr!("backup-restored", file = entry.target.display()),
+                    ),
+                    Err(e) => Effect::Notify(
+                        Level::Error,
+                        tr!("error-restore-backup", file = entry.name(), error = e),
+                    ),
+                },
+                None => Effect::None,
+            },
+            Action::DeleteBackup(index) => {
+                let effect = match self.backups.get(index).map(|x| (x, x.delete())) {
+                    Some((entry, Err(e))) => Effect::Notify(
+                        Level::Error,
+                        tr!("error-delete-backup", file = entry.name(), error = e),
+                    ),
+                    _ => Effect::None,
+                };
+
+                self.refresh_backups();
+                effect
+            }
+        }
+    }
+
+    /// Open `modfile` from the game directory in the hex viewer
+    fn load_hex_view(&mut self, modfile: String) -> Result<(), Effect> {
+        let result = self
+            .game_dir
+            .as_deref()
+            .ok_or_else(|| Error::state_error(tr!("error-no-game-dir")))
+            .and_then(|dir| Self::read_game_data(wine::resolve(Path::new(dir), &modfile)));
+
+        match result {
+            Ok(data) => {
+                self.hex_view.open(modfile, data);
+                Ok(())
+            }
+            Err(e) => Err(Effect::Notify(
+                Level::Error,
+                tr!("error-open", file = modfile, error = e),
+            )),
+        }
+    }
+
+    fn save_hex_view(&mut self) -> Result<(), Error> {
+        let dir = self
+            .game_dir
+            .as_deref()
+            .ok_or_else(|| Error::state_error(tr!("error-no-game-dir")))?;
+        let game_path = Path::new(dir);
+        // The name typed in may have changed since, so save where it came from
+        let modfile = self.hex_view.loaded.as_str();
+
+        let record = backup::backup_files(
+            self.backup_mode,
+            HexView::SECTION,
+            game_path,
+            &[modfile],
+            self.width.unwrap_or_default(),
+            self.height.unwrap_or_default(),
+        )
+        .map_err(|e| Error::BackupFailed(Box::new(e)))?;
+
+        Self::write_game_data(wine::resolve(game_path, modfile), &self.hex_view.data)?;
+        self.hex_view.dirty = false;
+        self.last_backup = Some(record);
+        Ok(())
+    }
+
+    /// Fill the editor in with the selected game, as written in the file it
+    /// was loaded from
+    fn edit_selected(&mut self) -> Result<(), Error> {
+        let name = self
+            .selected_section
+            .clone()
+            .ok_or_else(|| Error::state_error(tr!("error-no-game")))?;
+        let document = match self.selected_source() {
+            Some(path) => {
+                let text = encoding::read(path, self.load_options.encoding)?;
+                Document::parse_as(&text, Format::from_path(path))?
+            }
+            None => Document::parse(config::DEFAULT),
+        };
+
+        self.editor.open(&name, &document.items(&name));
+        Ok(())
+    }
+
+    /// Write the editor's game to [`Self::personal_config`], replacing the
+    /// one it was opened from if it's there, and select it
+    ///
+    /// Returns the file written, which still has to be loaded
+    pub fn save_editor(&mut self) -> Result<PathBuf, Error> {
+        self.editor.section()?;
+        let path = self
+            .personal_config
+            .clone()
+            .ok_or_else(|| Error::state_error(tr!("error-no-config-dir")))?;
+        let format = Format::from_path(&path);
+
+        let not_found = |e: &Error| {
+            matches!(
+                e,
+                Error::ReadError {
+                    kind: std::io::ErrorKind::NotFound,
+                    ..
+                }
+            )
+        };
+        let mut document = match encoding::read(&path, self.load_options.encoding) {
+            Ok(x) => Document::parse_as(&x, format)?,
+            // Only the built in games are loaded, start from them so they stay listed
+            Err(e) if not_found(&e) && self.config_paths.is_empty() => {
+                Document::parse(config::DEFAULT)
+            }
+            Err(e) if not_found(&e) => Document::default(),
+            Err(e) => return Err(e),
+        };
+        if document.get("Apps", "version").is_none() {
+            document.set("Apps", "version", "1");
+        }
+
+        let name = self.editor.name.clone();
+        if let Some(original) = &self.editor.original
+            && *original != name
+        {
+            document.remove_section(original);
+        }
+        document.set_section(&name, &self.editor.items());
+        document.add_app(&name);
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, document.to_string_as(format))?;
+
+        self.editor.original = Some(name.clone());
+        self.selected_section = Some(name);
+        Ok(path)
+    }
+
+    fn read_builder_file(&self) -> Result<Vec<u8>, Error> {
+        let dir = self
+            .game_dir
+            .as_deref()
+            .ok_or_else(|| Error::state_error(tr!("error-no-game-dir")))?;
+        Self::read_game_data(wine::resolve(Path::new(dir), &self.sig_builder.modfile))
+    }
+
+    fn patch_effect(result: Result<SectionReport, Error>) -> Effect {
+        match result {
+            Ok(report) if report.succeeded() => {
+                Effect::Notify(Level::Info, tr!("patch-applied", report = report))
+            }
+            Ok(report) if report.patches.is_empty() => {
+                Effect::Notify(Level::Error, tr!("patch-failed-empty"))
+            }
+            Ok(report) => Effect::Notify(Level::Error, tr!("patch-failed-report", report = report)),
+            Err(e) => Effect::Notify(Level::Error, tr!("patch-failed", error = e)),
+        }
+    }
+
+    /// The custom patch as a single patch game entry
+    pub fn custom_section(&self) -> Result<AppSection, Error> {
+        let patch = PatchInfo::from_items(share::SECTION, &self.custom, None)?;
+        if !wine::is_inside(&patch.modfile) {
+            return Err(Error::config_field_parse(
+                share::SECTION,
+                "modfile",
+                "must be a file in the game folder",
+            ));
+        }
+
+        Ok(AppSection {
+            name: share::SECTION.to_string(),
+            details: String::new(),
+            details_format: DetailsFormat::Text,
+            image: None,
+            checkfile: patch.modfile.clone(),
+            egsname: None,
+            patches: vec![patch],
+            text_patches: vec![],
+            registry_patches: vec![],
+            maxwidth: None,
+            maxheight: None,
+            values: vec![],
+        })
+    }
+
+    pub fn share_link(&self) -> String {
+        share::to_link(&self.custom)
+    }
+
+    /// Ask about the next backup waiting to be cleaned, or report when done
+    fn next_clean_prompt(&mut self) -> Effect {
+        match self.pending_clean.take() {
+            Some((pending, removed)) => match pending.last() {
+                Some((dir, game)) => {
+                    let question = tr!("confirm-clean", game = game, dir = dir.display());
+                    self.pending_clean = Some((pending, removed));
+                    Effect::Confirm(question)
+                }
+                None => {
+                    self.refresh_backups();
+                    Effect::Notify(Level::Info, tr!("cleaned", count = removed))
+                }
+            },
+            None => Effect::None,
+        }
+    }
+
+    pub fn refresh_backups(&mut self) {
+        self.backups = match (self.game_dir.as_deref(), self.get_selected_app_section()) {
+            (Some(dir), section) if self.panel == Panel::Backups => {
+                let modfiles = section.map(AppSection::files).unwrap_or_default();
+                backup::find_backups(Path::new(dir), &modfiles)
+            }
+            _ => vec![],
+        };
+    }
+
+    /// Select the game whose `checkfile` is in the game dir, unless the
+    /// selected one's already is. Several matches go in [`Self::detected`]
+    fn detect_game(&mut self) {
+        self.detected.clear();
+
+        let ConfigState::Loaded(config) = &self.config else {
+            return;
+        };
+        if self
+            .get_selected_app_section()
+            .is_some_and(|x| self.has_checkfile(x))
+        {
+            return;
+        }
+
+        let found = config
+            .sections
+            .iter()
+            .filter(|x| !x.checkfile.is_empty() && self.has_checkfile(x))
+            .map(|x| x.name.clone())
+            .collect::<Vec<_>>();
+        match found.as_slice() {
+            [] => {}
+            [name] => self.selected_section = Some(name.clone()),
+            _ => self.detected = found,
+        }
+    }
+
+    /// Display with the resolution that's filled in
+    pub fn selected_display(&self) -> Option<&Display> {
+        self.displays
+            .iter()
+            .find(|x| Some(x.width) == self.width && Some(x.height) == self.height)
+    }
+
+    /// Problems with the resolution for `section`, an error stops the patch
+    pub fn resolution_issues(&self, section: Option<&AppSection>) -> Vec<(Level, String)> {
+        let (Some(width), Some(height)) = (self.width, self.height) else {
+            return vec![];
+        };
+
+        let mut issues = Vec::new();
+        for (side, value, min, max) in [
+            (
+                tr!("side-width"),
+                width,
+                320,
+                section.and_then(|x| x.maxwidth),
+            ),
+            (
+                tr!("side-height"),
+                height,
+                200,
+                section.and_then(|x| x.maxheight),
+            ),
+        ] {
+            if !(min..=display::MAX_DIMENSION).contains(&value) {
+                issues.push((
+                    Level::Error,
+                    tr!("resolution-invalid", side = side, value = value),
+                ));
+            } else if let Some(max) = max
+                && value > max
+            {
+                issues.push((Level::Error, tr!("resolution-max", side = side, max = max)));
+            } else if value % 2 == 1 {
+                issues.push((Level::Warning, tr!("resolution-odd", side = side)));
+            }
+        }
+
+        if width < height {
+            issues.push((Level::Warning, tr!("resolution-swapped")));
+        }
+        issues
+    }
+
+    /// What to remember for the next run, apart from the theme, language and
+    /// window
+    pub fn settings(&self) -> Settings {
+        Settings {
+            config_paths: self.config_paths.clone(),
+            sync_url: self.sync_url.clone(),
+            game: self.selected_section.clone(),
+            width: self.width,
+            height: self.height,
+            strict: self.load_options.mode == ParseMode::Strict,
+            encoding: self.load_options.encoding,
+            duplicates: self.load_options.duplicates,
+            profiles: {
+                let mut profiles = self.profiles.clone();
+                profiles.extend(self.current_profile());
+                profiles
+            },
+            ..Default::default()
+        }
+    }
+
+    /// Pick up where the last run left off, before the config is loaded
+    pub fn apply_settings(&mut self, settings: Settings) {
+        self.config_paths = settings.config_paths;
+        self.sync_url = settings.sync_url;
+        self.selected_section = settings.game;
+        self.width = settings.width;
+        self.height = settings.height;
+        self.profiles = settings.profiles;
+        if settings.strict {
+            self.load_options.mode = ParseMode::Strict;
+        }
+        self.load_options.encoding = settings.encoding;
+        self.load_options.duplicates = settings.duplicates;
+
+        let profile = self
+            .selected_section
+            .as_ref()
+            .and_then(|x| self.profiles.get(x))
+            .cloned()
+            .unwrap_or_default();
+        self.restore_profile(profile);
+    }
+
+    /// Profile of the selected game as it's set up now. The folder is only
+    /// kept if it holds the game
+    fn current_profile(&self) -> Option<(String, Profile)> {
+        let name = self.selected_section.clone()?;
+        let mut profile = self.profiles.get(&name).cloned().unwrap_or_default();
+
+        if let Some(section) = self.get_selected_app_section()
+            && self.has_checkfile(section)
+        {
+            profile.game_dir = self.game_dir.clone();
+        }
+        profile.width = self.width;
+        profile.height = self.height;
+        profile.options = self.enabled_options.iter().cloned().collect();
+        profile.options.sort();
+
+        Some((name, profile))
+    }
+
+    /// Switch to the settings of a game. Anything the profile doesn't have is
+    /// kept, apart from the options which belong to each game
+    fn restore_profile(&mut self, profile: Profile) {
+        let has_checkfile = self
+            .get_selected_app_section()
+            .is_some_and(|x| self.has_checkfile(x));
+        if let Some(dir) = profile.game_dir
+            && !has_checkfile
+        {
+            self.game_dir = Some(dir);
+        }
+        if let (Some(width), Some(height)) = (profile.width, profile.height) {
+            self.set_resolution(width, height);
+        }
+        self.enabled_options = profile.options.into_iter().collect();
+    }
+
+    /// Fill in both sides, moving the aspect lock to the new ratio
+    fn set_resolution(&mut self, width: u16, height: u16) {
+        let max = display::MAX_DIMENSION;
+        let (width, height) = (width.min(max), height.min(max));
+        self.width = Some(width);
+        self.height = Some(height);
+        if self.aspect_lock.is_some() {
+            self.aspect_lock = Some(display::reduce(width, height));
+        }
+    }
+
+    /// Preset with the resolution that's filled in
+    pub fn selected_preset(&self) -> Option<Preset> {
+        display::PRESETS
+            .into_iter()
+            .find(|x| Some(x.width) == self.width && Some(x.height) == self.height)
+    }
+
+    /// The selected game's `checkfile` in the game dir, if it's there
+    pub fn checkfile_path(&self) -> Option<PathBuf> {
+        let section = self.get_selected_app_section()?;
+        let dir = self.game_dir.as_deref()?;
+        Some(wine::resolve(Path::new(dir), &section.checkfile)).filter(|x| x.exists())
+    }
+
+    /// Whether the game dir holds the section's `checkfile`
+    fn has_checkfile(&self, selected_section: &AppSection) -> bool {
+        self.game_dir
+            .as_deref()
+            .is_some_and(|x| wine::resolve(Path::new(x), &selected_section.checkfile).exists())
+    }
+
+    pub fn can_patch(&self, selected_section: &AppSection) -> bool {
+        self.has_checkfile(selected_section)
+            && self.width.is_some()
+            && self.height.is_some()
+            && !self
+                .resolution_issues(Some(selected_section))
+                .iter()
+                .any(|x| x.0 == Level::Error)
+            && self.slot_values(selected_section).is_ok()
+    }
+
+    /// Games in the list that match [`Self::filter`], closest first and
+    /// then in [`Self::sort`] order
+    pub fn visible_apps(&self) -> Vec<String> {
+        let ConfigState::Loaded(config) = &self.config else {
+            return vec![];
+        };
+
+        let mut apps = config
+            .apps
+            .apps
+            .iter()
+            .filter(|x| {
+                let source = self.source.as_ref();
+                source.is_none_or(|source| config.sources.get(*x) == Some(source))
+            })
+            .filter_map(|x| Some((Self::filter_score(x, &self.filter)?, x)))
+            .collect::<Vec<_>>();
+        match self.sort {
+            SortOrder::Config => apps.sort_by_key(|x| x.0),
+            SortOrder::Alphabetical => apps.sort_by_cached_key(|x| (x.0, x.1.to_lowercase())),
+            SortOrder::Recent => apps.sort_by_key(|x| {
+                let recent = self.recent.iter().position(|r| r == x.1);
+                (x.0, recent.unwrap_or(usize::MAX))
+            }),
+        }
+        apps.into_iter().map(|x| x.1.clone()).collect()
+    }
+
+    /// [`Self::visible_apps`] after a group of the ones patched most recently
+    pub fn app_entries(&self) -> Vec<AppEntry> {
+        const RECENT_SHOWN: usize = 5;
+
+        let apps = self.visible_apps();
+        let recent = self
+            .recent
+            .iter()
+            .filter(|x| apps.contains(x))
+            .take(RECENT_SHOWN)
+            .map(|name| AppEntry {
+                name: name.clone(),
+                recent: true,
+            });
+        let all = apps.iter().map(|name| AppEntry {
+            name: name.clone(),
+            recent: false,
+        });
+        recent.chain(all).collect()
+    }
+
+    pub fn get_selected_app_section(&self) -> Option<&AppSection> {
+        match &self.config {
+            ConfigState::Loaded(config) => self
+                .selected_section
+                .as_deref()
+                .and_then(|selected| config.sections.iter().find(|x| x.name == selected)),
+            _ => None,
+        }
+    }
+
+    /// File the selected game was loaded from, see [`Config::sources`]
+    pub fn selected_source(&self) -> Option<&Path> {
+        match &self.config {
+            ConfigState::Loaded(config) => self
+                .selected_section
+                .as_ref()
+                .and_then(|x| config.sources.get(x))
+                .map(PathBuf::as_path),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::PatchErrorKind;
+    use crate::testing::TempDir;
+
+    const CONFIG: &str = r#"
+[Apps]
+version=1.03
+a0=Test Game
+a1=Other Game
+
+[Test Game]
+details=Test
+checkfile=game.exe
+modfile=game.exe
+sig=80020000C701E0010000
+sigwild=0000110000
+xoffset=0
+yoffset=6
+occur=1
+
+[Other Game]
+details=Other
+checkfile=other.exe
+modfile=other.exe
+sig=8002
+sigwild=00
+xoffset=0
+occur=1
+"#;
+
+    fn loaded() -> Model {
+        let mut model = Model::default();
+        model.update(Action::ConfigLoaded(Config::new(CONFIG)));
+        model
+    }
+
+    #[test]
+    fn config_loaded_selects_first_app() {
+        let model = loaded();
+        assert_eq!(model.selected_section.as_deref(), Some("Test Game"));
+        assert_eq!(
+            model
+                .get_selected_app_section()
+                .map(|x| x.checkfile.as_str()),
+            Some("game.exe")
+        );
+
+        let mut model = Model::default();
+        model.update(Action::ConfigLoaded(Err(Error::config_missing_section(
+            "Apps",
+        ))));
+        assert!(matches!(model.config, ConfigState::Error(_)));
+        assert_eq!(model.selected_section, None);
+    }
+
+    #[test]
+    fn broken_sections_warn() {
+        let config = CONFIG.replace("a1=Other Game", "a1=Other Game\na2=Nowhere");
+
+        let mut model = Model::default();
+        let effect = model.update(Action::ConfigLoaded(Config::new(&config)));
+        assert_eq!(
+            effect,
+            Effect::Notify(
+                Level::Warning,
+                format!(
+                    "Some games could not be loaded and were skipped:\n\nNowhere: {}",
+                    Error::config_missing_section("Nowhere")
+                )
+            )
+        );
+        assert!(matches!(model.config, ConfigState::Loaded(_)));
+    }
+
+    #[test]
+    fn dimension_input() {
+        let mut model = loaded();
+
+        model.update(Action::WidthChanged("1920".into()));
+        assert_eq!(model.width, Some(1920));
+
+        model.update(Action::WidthChanged("1920x".into()));
+        assert_eq!(model.width, Some(1920));
+
+        model.update(Action::WidthChanged("".into()));
+        assert_eq!(model.width, None);
+
+        // Clamped rather than ignored
+        model.update(Action::HeightChanged("99999".into()));
+        assert_eq!(model.height, Some(display::MAX_DIMENSION));
+
+        // A whole resolution pasted into either side
+        model.update(Action::HeightChanged("2560 x 1080".into()));
+        assert_eq!((model.width, model.height), (Some(2560), Some(1080)));
+
+        model.update(Action::WidthStepped(-1));
+        model.update(Action::HeightStepped(2));
+        assert_eq!((model.width, model.height), (Some(2559), Some(1082)));
+        model.update(Action::WidthChanged(String::new()));
+        model.update(Action::WidthStepped(-1));
+        assert_eq!(model.width, Some(1));
+    }
+
+    #[test]
+    fn apply_patch() {
+        let tmp = TempDir::new("model");
+
+        #[rustfmt::skip]
+        let data = [
+            0x00, 0x00, 0x80, 0x02, 0x00, 0x00, 0xC7, 0x01, 0xE0, 0x01, 0x00, 0x00,
+        ];
+        std::fs::write(tmp.join("game.exe"), data).unwrap();
+
+        let mut model = loaded();
+        assert_eq!(
+            model.update(Action::ApplyPatch),
+            Effect::Notify(
+                Level::Error,
+                format!(
+                    "Patch failed to apply: {}",
+                    Error::state_error("Missing game dir")
+                )
+            )
+        );
+
+        model.update(Action::GameDirChanged(Some(
+            tmp.to_string_lossy().into_owned(),
+        )));
+        model.update(Action::WidthChanged("1920".into()));
+        assert!(!model.can_patch(model.get_selected_app_section().unwrap()));
+        model.update(Action::HeightChanged("1080".into()));
+        assert!(model.can_patch(model.get_selected_app_section().unwrap()));
+
+        assert_eq!(model.update(Action::PreviewPatch), Effect::None);
+        assert_eq!(model.panel, Panel::Preview);
+        let site = &model.preview.sites[0];
+        assert_eq!((site.index, site.start), (2, 0));
+        assert_eq!(site.after[2..4], [0x80, 0x07]);
+        assert!(site.is_written(8) && !site.is_written(6));
+        assert_eq!(std::fs::read(tmp.join("game.exe")).unwrap(), data);
+
+        assert_eq!(
+            model.update(Action::ApplyPatch),
+            Effect::Notify(
+                Level::Info,
+                format!(
+                    "Patch applied successfully\n\n\
+                     patch 0 ({}): patched 1/1 occurrence(s) of 1 found at 0x2\n",
+                    tmp.join("game.exe").display()
+                )
+            )
+        );
+        assert!(model.last_backup.is_some());
+        assert_eq!(model.recent, ["Test Game"]);
+        let log = model.log.to_string();
+        assert!(log.contains("Scanned game.exe, 12 bytes"), "{log}");
+        assert!(log.contains("Backed up 1 file(s) to "), "{log}");
+        assert!(model.preview.applied);
+        assert_eq!(model.preview.sites.len(), 1);
+        assert!(
+            model
+                .preview
+                .export()
+                .starts_with("Written to disk\n\npatch 0")
+        );
+
+        #[rustfmt::skip]
+        assert_eq!(std::fs::read(tmp.join("game.exe")).unwrap(), [
+            0x00, 0x00, 0x80, 0x07, 0x00, 0x00, 0xC7, 0x01, 0x38, 0x04, 0x00, 0x00,
+        ]);
+        assert_eq!(std::fs::read(tmp.join("game.exe.undo")).unwrap(), data);
+
+        model.update(Action::TogglePanel(Panel::Backups));
+        assert_eq!(model.backups.len(), 1);
+    }
+
+    #[test]
+    fn failed_patch_writes_nothing() {
+        let tmp = TempDir::new("model-fail");
+
+        let data = [0x80, 0x02, 0x00, 0x00];
+        std::fs::write(tmp.join("game.exe"), data).unwrap();
+
+        let patch = |occur| PatchInfo {
+            modfile: "game.exe".into(),
+            signature: crate::signature::Signature::from_string("test", "8002", "00").unwrap(),
+            xoffset: vec![0],
+            occur,
+            ..Default::default()
+        };
+        let section = AppSection {
+            name: "Test".into(),
+            details: String::new(),
+            details_format: DetailsFormat::Text,
+            image: None,
+            checkfile: "game.exe".into(),
+            egsname: None,
+            patches: vec![patch(1), patch(2)],
+            text_patches: vec![],
+            registry_patches: vec![],
+            maxwidth: None,
+            maxheight: None,
+            values: vec![],
+        };
+
+        let mut model = Model {
+            game_dir: Some(tmp.to_string_lossy().into_owned()),
+            width: Some(1920),
+            height: Some(1080),
+            ..Default::default()
+        };
+
+        let report = model.apply_patches(&section, false).unwrap();
+        assert!(!report.succeeded());
+        assert_eq!(
+            report.patches[0].status,
+            PatchStatus::Skipped("patch 1 failed".into())
+        );
+        assert_eq!(
+            report.patches[1].status,
+            PatchStatus::Failed(Error::PatchError {
+                context: Box::new(PatchContext {
+                    section: Some(section.name.clone()),
+                    ..PatchContext::patch(1, "game.exe")
+                }),
+                kind: PatchErrorKind::TooFewOccurrences {
+                    found: 1,
+                    requested: 2
+                }
+            })
+        );
+
+        assert_eq!(std::fs::read(tmp.join("game.exe")).unwrap(), data);
+        assert!(!tmp.join("game.exe.undo").exists());
+        assert!(model.last_backup.is_none());
+    }
+
+    #[test]
+    fn optional_patches() {
+        let tmp = TempDir::new("model-opt");
+
+        let data = [0x80, 0x02, 0xE0, 0x01];
+        std::fs::write(tmp.join("game.exe"), data).unwrap();
+
+        let patch = |sig: &str, optional| PatchInfo {
+            modfile: "game.exe".into(),
+            signature: crate::signature::Signature::from_string("test", sig, "00").unwrap(),
+            xoffset: vec![0],
+            occur: 1,
+            optional,
+            label: optional.then(|| "Extra".into()),
+            ..Default::default()
+        };
+        let section = AppSection {
+            name: "Test".into(),
+            details: String::new(),
+            details_format: DetailsFormat::Text,
+            image: None,
+            checkfile: "game.exe".into(),
+            egsname: None,
+            patches: vec![patch("8002", false), patch("E001", true)],
+            text_patches: vec![],
+            registry_patches: vec![],
+            maxwidth: None,
+            maxheight: None,
+            values: vec![],
+        };
+        assert_eq!(section.options(), ["Extra"]);
+
+        let mut model = Model {
+            game_dir: Some(tmp.to_string_lossy().into_owned()),
+            width: Some(1920),
+            height: Some(1080),
+            ..Default::default()
+        };
+
+        let report = model.apply_patches(&section, false).unwrap();
+        assert!(report.succeeded());
+        assert_eq!(
+            report.patches[1].status,
+            PatchStatus::Skipped("optional, not enabled".into())
+        );
+        assert_eq!(
+            std::fs::read(tmp.join("game.exe")).unwrap(),
+            [0x80, 0x07, 0xE0, 0x01]
+        );
+
+        std::fs::write(tmp.join("game.exe"), data).unwrap();
+        model.update(Action::OptionToggled("Extra".into(), true));
+        assert!(model.apply_patches(&section, false).unwrap().succeeded());
+        assert_eq!(
+            std::fs::read(tmp.join("game.exe")).unwrap(),
+            [0x80, 0x07, 0x80, 0x07]
+        );
+    }
+
+    #[test]
+    fn value_slots() {
+        let tmp = TempDir::new("model-slot");
+        std::fs::write(tmp.join("game.exe"), [0x00, 0x00, 0x96, 0x42]).unwrap();
+
+        let section = AppSection {
+            name: "Test".into(),
+            details: String::new(),
+            details_format: DetailsFormat::Text,
+            image: None,
+            checkfile: "game.exe".into(),
+            egsname: None,
+            patches: vec![PatchInfo {
+                modfile: "game.exe".into(),
+                signature: crate::signature::Signature::from_string("test", "00009642", "0000")
+                    .unwrap(),
+                xoffset: vec![0],
+                valuetype: crate::value::ValueType::F32,
+                valueslot: std::num::NonZeroUsize::new(1),
+                occur: 1,
+                ..Default::default()
+            }],
+            text_patches: vec![],
+            registry_patches: vec![],
+            maxwidth: None,
+            maxheight: None,
+            values: vec![crate::config::ValueSlot {
+                name: "FOV".into(),
+                valuetype: crate::value::ValueType::F32,
+                default: None,
+            }],
+        };
+
+        let mut model = Model {
+            game_dir: Some(tmp.to_string_lossy().into_owned()),
+            width: Some(1920),
+            height: Some(1080),
+            ..Default::default()
+        };
+        assert!(!model.can_patch(&section));
+        assert!(model.apply_patches(&section, false).is_err());
+
+        model.update(Action::ValueChanged("FOV".into(), "110".into()));
+        assert!(model.can_patch(&section));
+        assert!(model.apply_patches(&section, false).unwrap().succeeded());
+        assert_eq!(
+            std::fs::read(tmp.join("game.exe")).unwrap(),
+            110f32.to_le_bytes()
+        );
+    }
+
+    #[test]
+    fn text_patches() {
+        let tmp = TempDir::new("model-text");
+        std::fs::write(tmp.join("game.exe"), [0x80, 0x02, 0xE0, 0x01]).unwrap();
+        std::fs::write(tmp.join("game.ini"), "[Video]\nWidth=640\nHeight=480\n").unwrap();
+
+        let config = Config::new(
+            "[Apps]
+version=1.0
+a0=Test
+
+[Test]
+details=Test
+checkfile=game.exe
+modfile=game.exe
+sig=8002E001
+sigwild=0000
+xoffset=0
+yoffset=2
+occur=1
+p1textfile=game.ini
+p1find=Width=
+p1replace=Width={width}
+p2textfile=game.ini
+p2find=Height=
+p2replace=Height={height}
+",
+        )
+        .unwrap();
+        let section = &config.sections[0];
+        assert_eq!(section.files(), ["game.exe", "game.ini", "game.ini"]);
+
+        let mut model = Model {
+            game_dir: Some(tmp.to_string_lossy().into_owned()),
+            width: Some(1920),
+            height: Some(1080),
+            ..Default::default()
+        };
+
+        let report = model.apply_patches(section, false).unwrap();
+        assert!(report.succeeded());
+        assert_eq!(report.patches[2].index, 2);
+        assert_eq!(
+            std::fs::read_to_string(tmp.join("game.ini")).unwrap(),
+            "[Video]\nWidth=1920\nHeight=1080\n"
+        );
+        assert_eq!(
+            std::fs::read(tmp.join("game.exe")).unwrap(),
+            [0x80, 0x07, 0x38, 0x04]
+        );
+
+        // A missing line fails the whole section before anything is written
+        std::fs::write(tmp.join("game.exe"), [0x80, 0x02, 0xE0, 0x01]).unwrap();
+        std::fs::write(tmp.join("game.ini"), "[Video]\nWidth=640\n").unwrap();
+        let report = model.apply_patches(section, false).unwrap();
+        assert!(!report.succeeded());
+        assert_eq!(
+            report.patches[0].status,
+            PatchStatus::Skipped("patch 2 failed".into())
+        );
+        assert_eq!(
+            std::fs::read(tmp.join("game.exe")).unwrap(),
+            [0x80, 0x02, 0xE0, 0x01]
+        );
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn registry_patches_skipped() {
+        let tmp = TempDir::new("model-reg");
+        std::fs::write(tmp.join("game.exe"), [0x80, 0x02]).unwrap();
+
+        let config = Config::new(
+            r"[Apps]
+version=1.0
+a0=Test
+
+[Test]
+details=Test
+checkfile=game.exe
+modfile=game.exe
+sig=8002
+sigwild=00
+xoffset=0
+occur=1
+p1regkey=HKCU\Software\Game
+p1regvalue=Width
+p1regdata={width}
+",
+        )
+        .unwrap();
+        let section = &config.sections[0];
+        assert_eq!(section.registry_patches[0].key, r"HKCU\Software\Game");
+
+        let mut model = Model {
+            game_dir: Some(tmp.to_string_lossy().into_owned()),
+            width: Some(1920),
+            height: Some(1080),
+            ..Default::default()
+        };
+        let report = model.apply_patches(section, false).unwrap();
+        assert!(report.succeeded());
+        assert_eq!(
+            report.patches[1].status,
+            PatchStatus::Skipped("registry, Windows only".into())
+        );
+        assert_eq!(std::fs::read(tmp.join("game.exe")).unwrap(), [0x80, 0x07]);
+    }
+
+    #[tokio::test]
+    async fn configs_loaded() {
+        let tmp = TempDir::new("configs");
+        let (base, extra) = (tmp.join("base.ini"), tmp.join("extra.ini"));
+        std::fs::write(&base, CONFIG).unwrap();
+        let other = CONFIG
+            .replace("Test Game", "Third Game")
+            .replace("details=Other", "details=Mine");
+        std::fs::write(&extra, other).unwrap();
+
+        let config = Model::load_configs(vec![base.clone(), extra.clone()], LoadOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(config.apps.apps, ["Test Game", "Other Game", "Third Game"]);
+        assert_eq!(config.sections[1].details, "Mine");
+        assert_eq!(config.sources["Test Game"], base);
+        assert_eq!(config.sources["Other Game"], extra);
+
+        let mut model = Model::default();
+        model.update(Action::ConfigLoaded(Ok(config.clone())));
+        model.update(Action::AppSelected("Other Game".into()));
+        assert_eq!(model.selected_source(), Some(extra.as_path()));
+
+        // Switching to a file keeps the game if it's from there
+        model.update(Action::SourceSelected(Some(extra.clone())));
+        assert_eq!(model.visible_apps(), ["Other Game", "Third Game"]);
+        assert_eq!(model.selected_section.as_deref(), Some("Other Game"));
+        model.update(Action::SourceSelected(Some(base.clone())));
+        assert_eq!(model.visible_apps(), ["Test Game"]);
+        assert_eq!(model.selected_section.as_deref(), Some("Test Game"));
+        model.update(Action::SourceSelected(None));
+        assert_eq!(model.visible_apps().len(), 3);
+
+        // A file no longer loaded shows every game again
+        model.update(Action::SourceSelected(Some(base.clone())));
+        let mut reloaded = config;
+        reloaded.set_source(&extra);
+        model.update(Action::ConfigLoaded(Ok(reloaded)));
+        assert_eq!(model.source, None);
+
+        // Written by an editor that saves in Windows-1252
+        let legacy = CONFIG.replace("details=Other", "details=Caf\u{e9}");
+        let (legacy, _, _) = encoding_rs::WINDOWS_1252.encode(&legacy);
+        std::fs::write(&extra, legacy).unwrap();
+        let config = Model::load_config(&extra, LoadOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(config.sections[1].details, "Café");
+        let utf8 = LoadOptions {
+            encoding: Encoding::Utf8,
+            ..Default::default()
+        };
+        assert!(Model::load_config(&extra, utf8).await.is_err());
+
+        let missing = tmp.join("missing.ini");
+        let error = Model::load_configs(vec![base, missing.clone()], LoadOptions::default())
+            .await
+            .unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .starts_with(&missing.display().to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn editor_saved() {
+        let tmp = TempDir::new("editor");
+        let (base, personal) = (tmp.join("base.ini"), tmp.join("personal.ini"));
+        std::fs::write(&base, CONFIG).unwrap();
+
+        let mut model = Model {
+            config_paths: vec![base.clone()],
+            personal_config: Some(personal.clone()),
+            ..Default::default()
+        };
+        model.update(Action::ConfigLoaded(
+            Model::load_config(&base, LoadOptions::default()).await,
+        ));
+        model.update(Action::AppSelected("Other Game".into()));
+        model.update(Action::TogglePanel(Panel::Editor));
+        assert_eq!(model.editor.original.as_deref(), Some("Other Game"));
+        assert_eq!(model.editor.field(Some(0), "sig"), "8002");
+
+        model.update(Action::EditorFieldChanged(None, "details", "Mine".into()));
+        assert_eq!(model.save_editor().unwrap(), personal);
+        let config =
+            Model::load_configs(vec![base.clone(), personal.clone()], LoadOptions::default())
+                .await
+                .unwrap();
+        assert_eq!(config.apps.apps, ["Test Game", "Other Game"]);
+        assert_eq!(config.sections[1].details, "Mine");
+        assert_eq!(config.sources["Other Game"], personal);
+
+        // Renaming replaces the game saved before
+        model.update(Action::EditorNameChanged("Renamed Game".into()));
+        model.save_editor().unwrap();
+        let config = Model::load_config(&personal, LoadOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(config.apps.apps, ["Renamed Game"]);
+        assert_eq!(model.selected_section.as_deref(), Some("Renamed Game"));
+
+        model.update(Action::EditNew);
+        assert_eq!(model.editor.original, None);
+        assert!(model.save_editor().is_err());
+    }
+
+    #[test]
+    fn config_dropped() {
+        let other = CONFIG
+            .replace("Test Game", "Third Game")
+            .replace("a1=Other Game\n", "");
+
+        // Nothing to merge with
+        let mut model = Model::default();
+        let effect = model.update(Action::ConfigDropped(
+            PathBuf::from("other.ini"),
+            Config::new(&other),
+        ));
+        assert_eq!(effect, Effect::None);
+        assert_eq!(model.selected_section.as_deref(), Some("Third Game"));
+
+        let mut model = loaded();
+        model.update(Action::AppSelected("Other Game".into()));
+        let effect = model.update(Action::ConfigDropped(
+            PathBuf::from("other.ini"),
+            Config::new(&other),
+        ));
+        assert!(matches!(effect, Effect::Confirm(_)));
+        assert_eq!(
+            model.update(Action::Confirmed(true)),
+            Effect::Notify(Level::Info, "Merged 1 game(s) into the config".into())
+        );
+        let ConfigState::Loaded(config) = &model.config else {
+            panic!("config not loaded");
+        };
+        assert_eq!(config.apps.apps, ["Test Game", "Other Game", "Third Game"]);
+        assert_eq!(model.selected_section.as_deref(), Some("Other Game"));
+        assert_eq!(model.config_paths, [PathBuf::from("other.ini")]);
+
+        model.update(Action::ConfigDropped(
+            PathBuf::from("other.ini"),
+            Config::new(&other),
+        ));
+        assert_eq!(model.update(Action::Confirmed(false)), Effect::None);
+        let ConfigState::Loaded(config) = &model.config else {
+            panic!("config not loaded");
+        };
+        assert_eq!(config.apps.apps, ["Third Game"]);
+        assert_eq!(model.selected_section.as_deref(), Some("Third Game"));
+    }
+
+    #[test]
+    fn display_resolution() {
+        let mut model = Model::default();
+        assert!(matches!(
+            model.update(Action::UseCurrentResolution),
+            Effect::Notify(Level::Warning, x) if x == tr!("error-no-display")
+        ));
+        model.update(Action::DisplaysFound(None));
+        assert!(model.displays_unavailable);
+        assert!(matches!(
+            model.update(Action::UseCurrentResolution),
+            Effect::Notify(Level::Warning, x) if x == tr!("error-display-unavailable")
+        ));
+
+        let displays = vec![
+            Display {
+                name: "DP-1".into(),
+                width: 1920,
+                height: 1080,
+                primary: false,
+            },
+            Display {
+                name: "DP-2".into(),
+                width: 3440,
+                height: 1440,
+                primary: true,
+            },
+        ];
+        model.update(Action::DisplaysFound(Some(displays.clone())));
+        assert_eq!((model.width, model.height), (Some(3440), Some(1440)));
+
+        // Typed in before the displays were found
+        let mut model = Model::default();
+        model.update(Action::WidthChanged("800".into()));
+        model.update(Action::DisplaysFound(Some(displays)));
+        assert_eq!((model.width, model.height), (Some(800), None));
+        model.update(Action::UseCurrentResolution);
+        assert_eq!((model.width, model.height), (Some(3440), Some(1440)));
+        model.update(Action::DisplaySelected(model.displays[0].clone()));
+        assert_eq!((model.width, model.height), (Some(1920), Some(1080)));
+        assert_eq!(model.selected_display(), Some(&model.displays[0]));
+
+        let preset = display::PRESETS[15];
+        assert_eq!(preset.to_string(), "21:9 3440x1440");
+        model.update(Action::PresetSelected(preset));
+        assert_eq!((model.width, model.height), (Some(3440), Some(1440)));
+        assert_eq!(model.selected_preset(), Some(preset));
+        assert_eq!(model.selected_display(), Some(&model.displays[1]));
+
+        model.update(Action::PresetSelected(display::PRESETS[11]));
+        model.update(Action::AspectLockToggled(true));
+        assert_eq!(model.aspect_lock, Some((16, 9)));
+        model.update(Action::WidthChanged("2560".into()));
+        assert_eq!((model.width, model.height), (Some(2560), Some(1440)));
+        model.update(Action::HeightChanged("768".into()));
+        assert_eq!((model.width, model.height), (Some(1365), Some(768)));
+        model.update(Action::PresetSelected(display::PRESETS[15]));
+        assert_eq!(model.aspect_lock, Some((43, 18)));
+        model.update(Action::AspectLockToggled(false));
+        model.update(Action::WidthChanged("1920".into()));
+        assert_eq!((model.width, model.height), (Some(1920), Some(1440)));
+    }
+
+    #[test]
+    fn resolution_checked() {
+        let config = CONFIG.replace("checkfile=game.exe", "checkfile=game.exe\nmaxwidth=2560");
+        let mut model = Model::default();
+        model.update(Action::ConfigLoaded(Config::new(&config)));
+        let section = model.get_selected_app_section().cloned().unwrap();
+        assert_eq!((section.maxwidth, section.maxheight), (Some(2560), None));
+        assert!(model.resolution_issues(Some(&section)).is_empty());
+
+        model.update(Action::WidthChanged("1921".into()));
+        model.update(Action::HeightChanged("1080".into()));
+        assert_eq!(
+            model.resolution_issues(Some(&section)),
+            [(Level::Warning, "An odd width may crash this engine".into())]
+        );
+
+        model.update(Action::WidthChanged("3440".into()));
+        model.update(Action::HeightChanged("100".into()));
+        assert_eq!(
+            model.resolution_issues(Some(&section)),
+            [
+                (
+                    Level::Error,
+                    "This game supports a width of at most 2560".into()
+                ),
+                (
+                    Level::Error,
+                    "A height of 100 isn't a real resolution".into()
+                ),
+            ]
+        );
+        assert_eq!(model.resolution_issues(None).len(), 1);
+        assert_eq!(
+            model.apply_patches(&section, false),
+            Err(Error::value_error(
+                "This game supports a width of at most 2560"
+            ))
+        );
+
+        let bad = CONFIG.replace("checkfile=game.exe", "checkfile=game.exe\nmaxheight=big");
+        let config = Config::new(&bad).unwrap();
+        assert_eq!(config.broken[0].name, "Test Game");
+    }
+
+    #[test]
+    fn filter_apps() {
+        let config = CONFIG.replace(
+            "a1=Other Game",
+            "a1=Other Game\na2=Knights of the Old Republic",
+        ) + "[Knights of the Old Republic]\ndetails=x\ncheckfile=swkotor.exe\n\
+               modfile=swkotor.exe\nsig=8002\nsigwild=00\noccur=1\n";
+        let mut model = Model::default();
+        model.update(Action::ConfigLoaded(Config::new(&config)));
+        assert_eq!(model.visible_apps().len(), 3);
+
+        model.update(Action::FilterChanged("GAME".into()));
+        assert_eq!(model.visible_apps(), ["Test Game", "Other Game"]);
+        model.update(Action::FilterChanged("kotor".into()));
+        assert_eq!(model.visible_apps(), ["Knights of the Old Republic"]);
+        // Exact matches before scattered letters
+        model.update(Action::FilterChanged("ts".into()));
+        assert_eq!(
+            model.visible_apps(),
+            ["Knights of the Old Republic", "Test Game"]
+        );
+        model.update(Action::FilterChanged("zzz".into()));
+        assert!(model.visible_apps().is_empty());
+
+        model.update(Action::FilterChanged(String::new()));
+        model.update(Action::SortSelected(SortOrder::Alphabetical));
+        assert_eq!(
+            model.visible_apps(),
+            ["Knights of the Old Republic", "Other Game", "Test Game"]
+        );
+        model.recent = vec!["Other Game".into(), "Removed Game".into()];
+        model.update(Action::SortSelected(SortOrder::Recent));
+        assert_eq!(
+            model.visible_apps(),
+            ["Other Game", "Test Game", "Knights of the Old Republic"]
+        );
+
+        let entries = model
+            .app_entries()
+            .iter()
+            .map(|x| x.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            entries,
+            [
+                "Recent: Other Game",
+                "Other Game",
+                "Test Game",
+                "Knights of the Old Republic"
+            ]
+        );
+        model.update(Action::FilterChanged("test".into()));
+        assert_eq!(model.app_entries().len(), 1);
+    }
+
+    #[test]
+    fn settings_restored() {
+        let tmp = TempDir::new("model-settings");
+        let (one, two) = (tmp.join("one"), tmp.join("two"));
+        std::fs::create_dir_all(&one).unwrap();
+        std::fs::create_dir_all(&two).unwrap();
+        std::fs::write(one.join("game.exe"), b"").unwrap();
+        std::fs::write(two.join("other.exe"), b"").unwrap();
+        let one = one.to_string_lossy().into_owned();
+        let two = two.to_string_lossy().into_owned();
+
+        let mut model = Model::default();
+        model.apply_settings(Settings {
+            config_paths: vec!["patches.ini".into()],
+            game: Some("Other Game".into()),
+            width: Some(2560),
+            height: None,
+            profiles: BTreeMap::from([(
+                "Other Game".into(),
+                Profile {
+                    game_dir: Some(two.clone()),
+                    ..Default::default()
+                },
+            )]),
+            ..Default::default()
+        });
+        assert_eq!(model.game_dir.as_ref(), Some(&two));
+        model.update(Action::ConfigLoaded(Config::new(CONFIG)));
+        assert_eq!(model.selected_section.as_deref(), Some("Other Game"));
+
+        model.update(Action::HeightChanged("1440".into()));
+        model.update(Action::OptionToggled("Fix HUD".into(), true));
+
+        // Each game goes back to its own folder, resolution and options
+        model.update(Action::AppSelected("Test Game".into()));
+        assert!(model.enabled_options.is_empty());
+        model.update(Action::GameDirChanged(Some(one.clone())));
+        model.update(Action::WidthChanged("1920".into()));
+        model.update(Action::HeightChanged("1080".into()));
+        model.update(Action::AppSelected("Other Game".into()));
+        assert_eq!(model.game_dir.as_ref(), Some(&two));
+        assert_eq!((model.width, model.height), (Some(2560), Some(1440)));
+        assert!(model.enabled_options.contains("Fix HUD"));
+        model.update(Action::AppSelected("Test Game".into()));
+        assert_eq!(model.game_dir.as_ref(), Some(&one));
+        assert_eq!((model.width, model.height), (Some(1920), Some(1080)));
+
+        let settings = model.settings();
+        assert_eq!(settings.game.as_deref(), Some("Test Game"));
+        assert_eq!((settings.width, settings.height), (Some(1920), Some(1080)));
+        assert_eq!(
+            settings.profiles["Other Game"],
+            Profile {
+                game_dir: Some(two.clone()),
+                width: Some(2560),
+                height: Some(1440),
+                options: vec!["Fix HUD".into()],
+            }
+        );
+        assert_eq!(settings.profiles["Test Game"].game_dir, Some(one.clone()));
+    }
+
+    #[test]
+    fn game_detected() {
+        let tmp = TempDir::new("model-detect");
+        std::fs::write(tmp.join("OTHER.EXE"), [0x80, 0x02]).unwrap();
+        let dir = Some(tmp.to_string_lossy().into_owned());
+
+        let mut model = loaded();
+        model.update(Action::GameDirChanged(dir.clone()));
+        assert_eq!(model.selected_section.as_deref(), Some("Other Game"));
+        assert!(model.detected.is_empty());
+
+        // Both games check for the same file
+        let config = CONFIG.replace("checkfile=game.exe", "checkfile=other.exe");
+        let mut model = Model::default();
+        model.update(Action::ConfigLoaded(Config::new(&config)));
+        model.selected_section = None;
+        model.update(Action::GameDirChanged(dir.clone()));
+        assert_eq!(model.selected_section, None);
+        assert_eq!(model.detected, ["Test Game", "Other Game"]);
+
+        model.update(Action::AppSelected("Other Game".into()));
+        assert!(model.detected.is_empty());
+        model.update(Action::GameDirChanged(dir));
+        assert_eq!(model.selected_section.as_deref(), Some("Other Game"));
+        assert!(model.detected.is_empty());
+
+        // Dropping the exe uses its folder
+        let mut model = loaded();
+        let effect = model.update(Action::PathDropped(tmp.join("OTHER.EXE")));
+        assert_eq!(effect, Effect::None);
+        assert_eq!(model.game_dir, Some(tmp.to_string_lossy().into_owned()));
+        assert_eq!(model.selected_section.as_deref(), Some("Other Game"));
+
+        let empty = tmp.join("empty");
+        std::fs::create_dir_all(&empty).unwrap();
+        let effect = model.update(Action::PathDropped(empty.clone()));
+        assert!(matches!(effect, Effect::Notify(Level::Warning, _)));
+        assert_eq!(model.game_dir, Some(empty.to_string_lossy().into_owned()));
+    }
+
+    #[test]
+    fn share_link() {
+        let mut model = Model::default();
+
+        let effect = model.update(Action::OpenShareLink("uniws://patch?sig=80".into()));
+        assert!(matches!(effect, Effect::Notify(Level::Error, _)));
+        assert_eq!(model.panel, Panel::Details);
+
+        let link = "uniws://patch?modfile=game.exe&sig=8002&sigwild=00&xoffset=0&occur=1";
+        assert_eq!(
+            model.update(Action::OpenShareLink(link.into())),
+            Effect::None
+        );
+        assert_eq!(model.panel, Panel::CustomPatch);
+        assert_eq!(model.share_link(), link);
+
+        model.update(Action::CustomFieldChanged("occur", "all".into()));
+        let section = model.custom_section().unwrap();
+        assert_eq!(section.checkfile, "game.exe");
+        assert_eq!(section.patches[0].occur, PatchInfo::ALL);
+
+        model.update(Action::TogglePanel(Panel::CustomPatch));
+        assert_eq!(model.panel, Panel::Details);
+    }
+
+    #[test]
+    fn signature_builder() {
+        let tmp = TempDir::new("model-sig");
+        std::fs::write(
+            tmp.join("game.exe"),
+            [0x00, 0x80, 0x02, 0x11, 0x80, 0x02, 0x22],
+        )
+        .unwrap();
+
+        let mut model = loaded();
+        model.update(Action::GameDirChanged(Some(
+            tmp.to_string_lossy().into_owned(),
+        )));
+        model.update(Action::TogglePanel(Panel::SignatureBuilder));
+        assert_eq!(model.sig_builder.modfile, "game.exe");
+
+        model.update(Action::SigPatternChanged("8002 ?".into()));
+        assert_eq!(model.update(Action::TestSignature), Effect::None);
+        assert_eq!(model.sig_builder.matches, Some(vec![1, 4]));
+
+        assert_eq!(model.update(Action::ShowInHexView(4)), Effect::None);
+        assert_eq!(model.panel, Panel::HexView);
+        assert_eq!(model.hex_view.cursor, Some(4));
+        assert!(model.hex_view.is_hit(1) && model.hex_view.is_hit(6));
+
+        model.update(Action::HexExpertToggled(true));
+        model.update(Action::HexSelect(0));
+        model.update(Action::HexByteChanged("AA".into()));
+        model.update(Action::HexEdit);
+        model.update(Action::HexFileChanged("other.exe".into()));
+        assert!(matches!(
+            model.update(Action::HexSave),
+            Effect::Notify(Level::Info, _)
+        ));
+        assert_eq!(std::fs::read(tmp.join("game.exe")).unwrap()[0], 0xAA);
+        assert_eq!(std::fs::read(tmp.join("game.exe.undo")).unwrap()[0], 0x00);
+
+        model.update(Action::SigPatternChanged("80 0".into()));
+        assert_eq!(model.sig_builder.matches, None);
+        assert!(matches!(
+            model.update(Action::TestSignature),
+            Effect::Notify(Level::Error, _)
+        ));
+
+        model.update(Action::SigPatternChanged("80 02 ??".into()));
+        model.update(Action::SaveSignature);
+        assert_eq!(model.panel, Panel::CustomPatch);
+        assert_eq!(model.custom.get("pattern").unwrap(), "80 02 ??");
+        assert_eq!(model.custom.get("modfile").unwrap(), "game.exe");
+
+        let mut data = (0..32).collect::<Vec<u8>>();
+        data[20..22].copy_from_slice(&640u16.to_le_bytes());
+        data[22..24].copy_from_slice(&480u16.to_le_bytes());
+        std::fs::write(tmp.join("game.exe"), &data).unwrap();
+
+        model.update(Action::ScanCandidates);
+        assert_eq!(model.sig_builder.candidates.as_ref().unwrap().len(), 1);
+        model.update(Action::UseCandidate(0));
+        assert_eq!(model.sig_builder.pattern.len(), 20 * 3 - 1);
+        model.update(Action::SaveSignature);
+        assert_eq!(model.custom.get("xoffset").unwrap(), "8");
+        assert_eq!(model.custom.get("yoffset").unwrap(), "10");
+        model.update(Action::CustomFieldChanged("occur", "1".into()));
+        let section = model.custom_section().unwrap();
+        assert_eq!(
+            section.patches[0].find_patch_offsets(&data, 0).unwrap()[0].xoffset,
+            [20]
+        );
+
+        data[20] = 0x90;
+        std::fs::write(tmp.join("patched.exe"), &data).unwrap();
+        model.update(Action::DeriveSignature(tmp.join("patched.exe")));
+        let derived = model.sig_builder.derived.as_deref().unwrap();
+        assert!(derived.contains("patchbytes=90"));
+    }
+
+    #[test]
+    fn clean_prompts_for_each_backup() {
+        let mut model = Model {
+            pending_clean: Some((
+                vec![
+                    ("/nonexistent/2".into(), "Game".into()),
+                    ("/nonexistent/1".into(), "Game".into()),
+                ],
+                0,
+            )),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            model.next_clean_prompt(),
+            Effect::Confirm("Remove backup of Game (/nonexistent/1)?".into())
+        );
+        assert_eq!(
+            model.update(Action::Confirmed(false)),
+            Effect::Confirm("Remove backup of Game (/nonexistent/2)?".into())
+        );
+        assert_eq!(
+            model.update(Action::Confirmed(false)),
+            Effect::Notify(Level::Info, "Removed 0 old backup(s)".into())
+        );
+        assert_eq!(model.update(Action::Confirmed(true)), Effect::None);
+    }
+}
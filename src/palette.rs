@@ -0,0 +1,146 @@
+use crate::{i18n::tr, model::Model};
+
+/// Most matches listed at once
+pub const SHOWN: usize = 10;
+
+/// Something the command palette can do besides selecting a game
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Command {
+    LoadConfig,
+    AddConfig,
+    SelectGameDir,
+    Apply,
+    /// Preview the patch without writing it
+    DryRun,
+    Restore,
+    OpenBackups,
+    Backups,
+    CustomPatch,
+    SignatureBuilder,
+    HexView,
+    Editor,
+    Validate,
+    CheckForUpdates,
+    Shortcuts,
+}
+impl Command {
+    pub const ALL: [Self; 15] = [
+        Self::LoadConfig,
+        Self::AddConfig,
+        Self::SelectGameDir,
+        Self::Apply,
+        Self::DryRun,
+        Self::Restore,
+        Self::OpenBackups,
+        Self::Backups,
+        Self::CustomPatch,
+        Self::SignatureBuilder,
+        Self::HexView,
+        Self::Editor,
+        Self::Validate,
+        Self::CheckForUpdates,
+        Self::Shortcuts,
+    ];
+}
+impl std::fmt::Display for Command {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&match self {
+            Self::LoadConfig => tr!("load-config"),
+            Self::AddConfig => tr!("add-config"),
+            Self::SelectGameDir => tr!("shortcut-game-dir"),
+            Self::Apply => tr!("palette-apply"),
+            Self::DryRun => tr!("palette-dry-run"),
+            Self::Restore => tr!("restore-everything"),
+            Self::OpenBackups => tr!("palette-open-backups"),
+            Self::Backups => tr!("panel-backups"),
+            Self::CustomPatch => tr!("panel-custom"),
+            Self::SignatureBuilder => tr!("panel-signature"),
+            Self::HexView => tr!("panel-hex"),
+            Self::Editor => tr!("panel-editor"),
+            Self::Validate => tr!("validate"),
+            Self::CheckForUpdates => tr!("check-updates"),
+            Self::Shortcuts => tr!("shortcuts"),
+        })
+    }
+}
+
+/// A match in the palette
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Entry {
+    Command(Command),
+    Game(String),
+}
+impl std::fmt::Display for Entry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Command(x) => x.fmt(f),
+            Self::Game(x) => f.write_str(x),
+        }
+    }
+}
+
+/// What's typed in the palette while it's open
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct Palette {
+    pub query: String,
+    /// Index of the match Enter runs
+    pub selected: usize,
+}
+impl Palette {
+    /// The commands and `games` matching the query like the game list's
+    /// search does, closest first and commands before games, at most
+    /// [`SHOWN`]
+    pub fn matches(&self, games: &[String]) -> Vec<Entry> {
+        let mut matches = Command::ALL
+            .into_iter()
+            .map(Entry::Command)
+            .chain(games.iter().cloned().map(Entry::Game))
+            .filter_map(|x| Some((Model::filter_score(&x.to_string(), &self.query)?, x)))
+            .collect::<Vec<_>>();
+        matches.sort_by_key(|x| x.0);
+        matches.into_iter().take(SHOWN).map(|x| x.1).collect()
+    }
+
+    /// Move the selection by `delta`, staying on one of `count` matches
+    pub fn select(&mut self, delta: isize, count: usize) {
+        self.selected = self
+            .selected
+            .saturating_add_signed(delta)
+            .min(count.saturating_sub(1));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_test() {
+        let games = ["Need for Speed II".to_string(), "Dry Dock".to_string()];
+        let mut palette = Palette {
+            query: "dry run".into(),
+            ..Default::default()
+        };
+        assert_eq!(
+            palette.matches(&games).first(),
+            Some(&Entry::Command(Command::DryRun))
+        );
+
+        palette.query = "nfs".into();
+        assert_eq!(
+            palette.matches(&games),
+            [Entry::Game("Need for Speed II".into())]
+        );
+
+        palette.query = "restore".into();
+        assert_eq!(palette.matches(&games), [Entry::Command(Command::Restore)]);
+
+        palette.query.clear();
+        assert_eq!(palette.matches(&games).len(), SHOWN);
+
+        palette.select(-1, 3);
+        assert_eq!(palette.selected, 0);
+        palette.select(5, 3);
+        assert_eq!(palette.selected, 2);
+    }
+}
@@ -1,63 +1,404 @@
-use std::{fs::File, io::Write as _, path::Path};
+use std::{fmt::Write as _, num::NonZeroUsize, ops::Range, path::PathBuf};
 
-use crate::{config::Items, error::Error, signature::Signature};
+use crate::{
+    config::Items,
+    error::{Error, PatchContext, PatchErrorKind},
+    expr::{Expr, Vars},
+    signature::Signature,
+    template::Template,
+    value::{Aspect, Endian, FilePosition, ValueSpec, ValueType, WordSize},
+    wine,
+};
 
 /// Index into blob data where patch should be applied
-#[derive(Debug, Default, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+#[derive(Debug, Default, PartialEq, Eq, PartialOrd, Ord, Clone)]
 pub struct PatchOffsets {
-    pub xoffset: Option<usize>,
-    pub yoffset: Option<usize>,
+    /// Start of the signature match
+    pub index: usize,
+    /// Which match of the signature it is, counting from 1
+    pub occurrence: usize,
+    /// Signature that matched, 0 for `sig` or `n` for `sigaltn`
+    pub signature: usize,
+    /// Every place the width is written
+    pub xoffset: Vec<usize>,
+    pub yoffset: Vec<usize>,
     pub setx: Option<u16>,
     pub sety: Option<u16>,
+    /// Size of the width and height values
+    pub wordsize: WordSize,
+    pub endian: Endian,
+    pub valuetype: ValueType,
+    /// Written at `xoffset` instead of the width
+    pub aspect: Option<Aspect>,
+    /// Start of the `replace` template
+    pub replace: Option<usize>,
+    /// Target of a [`Modify`] patch
+    pub opoffset: Option<usize>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ModifyOp {
+    Add,
+    Mul,
+    Or,
+    And,
+    /// Replace the value with `operand`
+    Set,
+}
+
+/// Read the value already in the file, combine it with `operand` and write it back
+#[derive(Debug, PartialEq, Clone)]
+pub struct Modify {
+    pub op: ModifyOp,
+    pub operand: Expr,
+    pub offset: u64,
+    pub size: WordSize,
+}
+impl Modify {
+    /// Bytes read and written, bitwise operations always work on integers
+    fn len(&self, valuetype: ValueType) -> usize {
+        match self.op {
+            ModifyOp::Or | ModifyOp::And => self.size.len(),
+            _ => valuetype.len(self.size),
+        }
+    }
+
+    /// Fail if `operand` can't be used as it is: bitwise operands must fit in
+    /// `size`, and so must the value [`ModifyOp::Set`] writes
+    fn check(&self, operand: f64, valuetype: ValueType) -> Result<(), PatchErrorKind> {
+        match self.op {
+            ModifyOp::Or | ModifyOp::And => ValueType::Int.check(self.size, operand.round()),
+            ModifyOp::Set => match valuetype {
+                ValueType::Int => valuetype.check(self.size, operand.round()),
+                _ => valuetype.check(self.size, operand),
+            },
+            ModifyOp::Add | ModifyOp::Mul => Ok(()),
+        }
+    }
+
+    /// Modify the value at `offset`, which is bounds checked by the caller
+    fn apply(
+        &self,
+        data: &mut [u8],
+        offset: usize,
+        operand: f64,
+        valuetype: ValueType,
+        endian: Endian,
+    ) -> Result<(), Error> {
+        let current = || valuetype.read(self.size, data, offset, endian);
+        let value = match self.op {
+            ModifyOp::Add => current() + operand,
+            ModifyOp::Mul => current() * operand,
+            ModifyOp::Set => operand,
+            ModifyOp::Or | ModifyOp::And => {
+                self.check(operand, valuetype)?;
+                let operand = operand.round() as u64;
+                let current = self.size.read(data, offset, endian);
+                let value = match self.op {
+                    ModifyOp::Or => current | operand,
+                    _ => current & operand,
+                };
+                self.size.write(data, offset, value, endian);
+                return Ok(());
+            }
+        };
+
+        let value = match valuetype {
+            ValueType::Int => value.round(),
+            _ => value,
+        };
+        valuetype.check(self.size, value)?;
+        valuetype.write(self.size, data, offset, value, endian);
+        Ok(())
+    }
+}
+
+/// What happened to one patch of a section
+#[derive(Debug, PartialEq, Clone)]
+pub enum PatchStatus {
+    Patched {
+        /// `occur` from the config, may be [`PatchInfo::ALL`]
+        requested: u32,
+        /// Matches of the signature anywhere in the file
+        found: usize,
+        /// Start of every match that was patched
+        sites: Vec<usize>,
+        /// Signature that matched, 0 for `sig` or `n` for `sigaltn`
+        signature: usize,
+    },
+    /// Written somewhere other than a file, e.g. a registry value
+    Written(String),
+    Failed(Error),
+    /// Not written, for the given reason
+    Skipped(String),
+}
+impl std::fmt::Display for PatchStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Patched {
+                requested,
+                found,
+                sites,
+                signature,
+            } => {
+                let patched = sites.len();
+                let requested = match *requested {
+                    PatchInfo::ALL => patched,
+                    n => n as usize,
+                };
+                let sites = sites
+                    .iter()
+                    .map(|x| format!("0x{x:X}"))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+
+                write!(
+                    f,
+                    "patched {patched}/{requested} occurrence(s) of {found} found at {sites}"
+                )?;
+                match signature {
+                    0 => Ok(()),
+                    n => write!(f, " using sigalt{n}"),
+                }
+            }
+            Self::Written(what) => write!(f, "wrote {what}"),
+            Self::Failed(e) => write!(f, "failed: {e}"),
+            Self::Skipped(reason) => write!(f, "skipped: {reason}"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct PatchOutcome {
+    /// Position of the patch in its section
+    pub index: usize,
+    pub file: PathBuf,
+    pub status: PatchStatus,
+}
+impl std::fmt::Display for PatchOutcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "patch {} ({}): {}",
+            self.index,
+            self.file.display(),
+            self.status
+        )
+    }
+}
+
+/// Outcome of every patch in a section
+///
+/// Patches are all or nothing: if one fails, the others are skipped and no
+/// file is written
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct SectionReport {
+    pub section: String,
+    pub patches: Vec<PatchOutcome>,
+}
+impl SectionReport {
+    /// Whether anything was patched and nothing failed, optional patches that
+    /// weren't enabled are skipped
+    pub fn succeeded(&self) -> bool {
+        let any = |f: fn(&PatchStatus) -> bool| self.patches.iter().any(|x| f(&x.status));
+
+        any(|x| matches!(x, PatchStatus::Patched { .. } | PatchStatus::Written(_)))
+            && !any(|x| matches!(x, PatchStatus::Failed(_)))
+    }
+
+    /// Skip every patch that hasn't failed, since patch `failed` did
+    pub fn skip_rest(&mut self, failed: usize) {
+        for patch in &mut self.patches {
+            if !matches!(patch.status, PatchStatus::Failed(_)) {
+                patch.status = PatchStatus::Skipped(format!("patch {failed} failed"));
+            }
+        }
+    }
+}
+impl std::fmt::Display for SectionReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for patch in &self.patches {
+            writeln!(f, "{patch}")?;
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug)]
-pub struct PatchStrategy<'a, 'b> {
-    pub base_directory: &'a Path,
+pub struct PatchStrategy<'b> {
+    /// Position of the patch in its section
+    pub iteration: usize,
     pub modfile: &'b str,
     pub offsets: Vec<PatchOffsets>,
+    pub modify: Option<&'b Modify>,
+    /// Written at the start of every match
+    pub replace: Option<&'b Template>,
+    /// Value of the patch's value slot, written at every `xoffset` instead of the width
+    pub slot: Option<f64>,
+    pub requested: u32,
+    pub found: usize,
 }
-impl<'a, 'b> PatchStrategy<'a, 'b> {
-    fn patch_data(&self, file_data: &mut [u8], x_res: u16, y_res: u16) {
-        apply_patches(file_data, &self.offsets, x_res, y_res);
+impl<'b> PatchStrategy<'b> {
+    /// Status to report once this strategy has been written
+    pub fn status(&self) -> PatchStatus {
+        PatchStatus::Patched {
+            requested: self.requested,
+            found: self.found,
+            sites: self.offsets.iter().map(|x| x.index).collect(),
+            signature: self.offsets.first().map_or(0, |x| x.signature),
+        }
     }
 
-    pub fn apply(&self, file_data: &mut [u8], width: u16, height: u16) -> Result<(), Error> {
-        let mod_file_path = self.base_directory.join(self.modfile);
-        let undo_file_path = {
-            let undo_file = format!("{}.undo", mod_file_path.to_string_lossy());
-            self.base_directory.join(&undo_file)
+    /// Patch `file_data` in memory
+    pub fn patch_data(&self, file_data: &mut [u8], x_res: u16, y_res: u16) -> Result<(), Error> {
+        let context = PatchContext::patch(self.iteration, self.modfile);
+        let each = |apply: &mut dyn FnMut(&[PatchOffsets]) -> Result<(), Error>| {
+            each_match(&self.offsets, &context, apply)
         };
 
-        self.patch_data(file_data, width, height);
+        if let Some(replace) = self.replace {
+            each(&mut |at| Ok(apply_replace(file_data, at, replace, x_res, y_res)?))?;
+        }
 
-        if !undo_file_path.exists() {
-            std::fs::copy(&mod_file_path, &undo_file_path)?;
+        match self.slot {
+            Some(value) => each(&mut |at| Ok(apply_value(file_data, at, value)?))?,
+            None => each(&mut |at| Ok(apply_patches(file_data, at, x_res, y_res)?))?,
         }
 
-        let mut file = File::options()
-            .write(true)
-            .truncate(true)
-            .open(mod_file_path)?;
-        file.write_all(file_data)?;
+        if let Some(modify) = self.modify {
+            each(&mut |at| {
+                for offsets in at {
+                    if let Some(offset) = offsets.opoffset {
+                        check_bounds(file_data.len(), offset, modify.len(offsets.valuetype))?;
+                    }
+                }
+                Ok(())
+            })?;
+            each(&mut |at| apply_modify(file_data, at, modify, x_res, y_res))?;
+        }
 
         Ok(())
     }
+
+    /// Every byte range written at one match
+    pub fn writes(&self, offsets: &PatchOffsets) -> Vec<Range<usize>> {
+        let value_len = offsets.valuetype.len(offsets.wordsize);
+        let values = offsets
+            .xoffset
+            .iter()
+            .chain(&offsets.yoffset)
+            .map(|&x| x..x + value_len);
+
+        let others = [
+            offsets.replace.zip(self.replace.map(|r| r.len())),
+            offsets
+                .opoffset
+                .zip(self.modify.map(|m| m.len(offsets.valuetype))),
+        ];
+
+        values
+            .chain(others.into_iter().flatten().map(|(x, len)| x..x + len))
+            .collect()
+    }
+
+    /// Human readable description of every write this strategy makes
+    pub fn log(&self, width: u16, height: u16) -> String {
+        let index = self.iteration;
+        let mut out = String::new();
+
+        for offsets in &self.offsets {
+            if let (Some(offset), Some(replace)) = (offsets.replace, self.replace) {
+                let _ = writeln!(
+                    out,
+                    "patch {index}: {} 0x{offset:X} = template of {} byte(s)",
+                    self.modfile,
+                    replace.len()
+                );
+            }
+            for x in &offsets.xoffset {
+                let value = offsets.setx.unwrap_or(width);
+                let value = match (self.slot, offsets.aspect) {
+                    (Some(slot), _) => slot,
+                    (None, Some(aspect)) => aspect.value(value, offsets.sety.unwrap_or(height)),
+                    (None, None) => value.into(),
+                };
+                let _ = writeln!(out, "patch {index}: {} 0x{x:X} = {value}", self.modfile);
+            }
+            for y in &offsets.yoffset {
+                let value = offsets.sety.unwrap_or(height);
+                let _ = writeln!(out, "patch {index}: {} 0x{y:X} = {value}", self.modfile);
+            }
+            if let (Some(offset), Some(modify)) = (offsets.opoffset, self.modify) {
+                let operand = modify.operand.eval(&Vars::new(width, height));
+                let _ = writeln!(
+                    out,
+                    "patch {index}: {} 0x{offset:X} {:?} {operand}",
+                    self.modfile, modify.op
+                );
+            }
+        }
+
+        out
+    }
 }
 
-#[derive(Debug, Default, PartialEq, Eq, Clone)]
+#[derive(Debug, Default, PartialEq, Clone)]
 pub struct PatchInfo {
     pub modfile: String,
     pub undofile: Option<String>,
     pub signature: Signature,
-    pub xoffset: Option<u64>,
-    pub yoffset: Option<u64>,
+    /// Tried in order when `signature` isn't found, from `sigalt1`/`sigaltwild1`
+    pub alternatives: Vec<Signature>,
+    /// Relative to the start of the match, may be negative. Each match writes
+    /// the width at every offset in the list
+    pub xoffset: Vec<i64>,
+    pub yoffset: Vec<i64>,
+    /// Only search for the signature from here
+    pub searchstart: Option<FilePosition>,
+    /// Only search for the signature up to here, matches must end before it
+    pub searchend: Option<FilePosition>,
+    /// Only accept matches starting at a multiple of this file offset
+    pub align: Option<NonZeroUsize>,
+    /// Number of matches to patch, or [`Self::ALL`]
     pub occur: u32,
+    /// 1-based index of the first occurrence to patch
+    pub occurfirst: Option<u32>,
 
     pub setx: Option<u16>,
     pub sety: Option<u16>,
+    /// Size of the width and height values, 2 bytes unless configured
+    pub wordsize: WordSize,
+    /// Byte order of every value this patch reads or writes
+    pub endian: Endian,
+    /// Integer or float encoding of the width, height and `op` values
+    pub valuetype: ValueType,
+    /// Write an aspect ratio at `xoffset` rather than the width
+    pub aspect: Option<Aspect>,
+    /// 1-based index of the section's value slot written at `xoffset` rather
+    /// than the width
+    pub valueslot: Option<NonZeroUsize>,
+
+    /// Only applied when the user enables `label`
+    pub optional: bool,
+    /// Name of the option an optional patch belongs to, shared by every patch
+    /// of the option
+    pub label: Option<String>,
+
+    pub modify: Option<Modify>,
+    /// Template written over every match, from `replace` or `patchbytes`
+    pub replace: Option<Template>,
+    /// Where `replace` is written, relative to the start of the match
+    pub offset: i64,
+    /// Bytes that must already be at `offset` before anything is written
+    pub expect: Option<Template>,
 }
 impl PatchInfo {
+    /// `occur` value meaning every match of the signature
+    pub const ALL: u32 = 0;
+
+    /// Furthest a write can be from the start of the match
+    pub const MAX_OFFSET: u64 = 0x10000;
+
     pub fn from_items(section: &str, items: &Items, index: Option<u8>) -> Result<Self, Error> {
         struct Field<'a> {
             section: &'a str,
@@ -88,6 +429,41 @@ impl PatchInfo {
                     })
                 })
             }
+
+            /// Comma separated list like `1,17,24`, empty if the field is missing
+            fn parse_list<T>(&self) -> Result<Vec<T>, Error>
+            where
+                T: std::str::FromStr,
+                T::Err: std::error::Error,
+            {
+                match self.get() {
+                    Ok(list) => list
+                        .split(',')
+                        .map(|x| {
+                            x.trim().parse().map_err(|x: T::Err| {
+                                Error::config_field_parse(
+                                    self.section,
+                                    self.field_name,
+                                    x.to_string(),
+                                )
+                            })
+                        })
+                        .collect(),
+                    Err(_) => Ok(Vec::new()),
+                }
+            }
+
+            /// Like [`Self::parse`], but a missing field isn't an error
+            fn parse_opt<T>(&self) -> Result<Option<T>, Error>
+            where
+                T: std::str::FromStr,
+                T::Err: std::error::Error,
+            {
+                match self.get() {
+                    Ok(_) => self.parse().map(Some),
+                    Err(_) => Ok(None),
+                }
+            }
         }
 
         let field_name = |base_name: &'static str| Field {
@@ -97,21 +473,279 @@ impl PatchInfo {
             index,
         };
 
-        let sig = field_name("sig");
-        let sigwild = field_name("sigwild");
+        let signature = match field_name("pattern").get() {
+            Ok(pattern) => Signature::from_pattern_str(section, pattern)?,
+            Err(_) => Signature::from_string(
+                section,
+                field_name("sig").get()?,
+                field_name("sigwild").get()?,
+            )?,
+        };
+
+        let prefix = index.map_or(String::new(), |x| format!("p{x}"));
+        let mut alternatives = Vec::new();
+        for n in 1.. {
+            let Some(sig) = items.get(&format!("{prefix}sigalt{n}")) else {
+                break;
+            };
+            let sigwild = items
+                .get(&format!("{prefix}sigaltwild{n}"))
+                .ok_or(Error::config_missing_field(section, "sigaltwild"))?;
+            alternatives.push(Signature::from_string(section, sig, sigwild)?);
+        }
+
+        let modify = match field_name("op").get() {
+            Ok(op) => {
+                let op = match op.to_ascii_lowercase().as_str() {
+                    "add" => ModifyOp::Add,
+                    "mul" => ModifyOp::Mul,
+                    "or" => ModifyOp::Or,
+                    "and" => ModifyOp::And,
+                    "set" => ModifyOp::Set,
+                    x => {
+                        return Err(Error::config_field_parse(
+                            section,
+                            "op",
+                            format!("Unknown operation: {x}"),
+                        ));
+                    }
+                };
+
+                let operand = Expr::parse(field_name("operand").get()?)
+                    .map_err(|e| Error::config_field_parse(section, "operand", e.to_string()))?;
 
-        let signature = Signature::from_string(section, sig.get()?, sigwild.get()?)?;
+                Some(Modify {
+                    op,
+                    operand,
+                    offset: field_name("opoffset").parse()?,
+                    size: field_name("opsize").parse_opt()?.unwrap_or_default(),
+                })
+            }
+            Err(_) => None,
+        };
+
+        let ValueSpec(aspect, valuetype) = field_name("valuetype").parse_opt()?.unwrap_or_default();
+        let yoffset = field_name("yoffset").parse_list()?;
+        if aspect.is_some() && !yoffset.is_empty() {
+            return Err(Error::config_field_parse(
+                section,
+                "yoffset",
+                "Aspect values are only written at xoffset".to_string(),
+            ));
+        }
+        let valueslot = field_name("valueslot").parse_opt::<NonZeroUsize>()?;
+        if valueslot.is_some() && (aspect.is_some() || !yoffset.is_empty()) {
+            return Err(Error::config_field_parse(
+                section,
+                "valueslot",
+                "Slot values are only written at xoffset, without an aspect valuetype",
+            ));
+        }
+        if valuetype != ValueType::Int
+            && let Some(Modify {
+                op: op @ (ModifyOp::Or | ModifyOp::And),
+                ..
+            }) = &modify
+        {
+            return Err(Error::config_field_parse(
+                section,
+                "op",
+                format!("{op:?} only works on int values"),
+            ));
+        }
+        // Operands using the resolution are checked when they're applied
+        if let Some(modify) = &modify
+            && let Some(operand) = modify.operand.constant()
+        {
+            modify
+                .check(operand, valuetype)
+                .map_err(|e| Error::config_field_parse(section, "operand", e.to_string()))?;
+        }
 
-        Ok(Self {
+        let replace = match (field_name("replace").get(), field_name("patchbytes").get()) {
+            (Ok(_), Ok(_)) => {
+                return Err(Error::config_field_parse(
+                    section,
+                    "patchbytes",
+                    "Can't be used together with replace".to_string(),
+                ));
+            }
+            (Ok(replace), _) => Some(Template::from_string(section, "replace", replace)?),
+            (_, Ok(bytes)) => {
+                let bytes = Template::from_string(section, "patchbytes", bytes)?;
+                if bytes.value_sizes().next().is_some() {
+                    return Err(Error::config_field_parse(
+                        section,
+                        "patchbytes",
+                        "Only hex bytes and .. are allowed, use replace for WW/HH".to_string(),
+                    ));
+                }
+                Some(bytes)
+            }
+            _ => None,
+        };
+        let expect = field_name("expect")
+            .get()
+            .ok()
+            .map(|x| Template::from_string(section, "expect", x))
+            .transpose()?;
+        if expect
+            .as_ref()
+            .is_some_and(|x| x.value_sizes().next().is_some())
+        {
+            return Err(Error::config_field_parse(
+                section,
+                "expect",
+                "Only hex bytes and .. are allowed".to_string(),
+            ));
+        }
+
+        if let Some(size) = replace
+            .iter()
+            .flat_map(|x| x.value_sizes())
+            .find(|&size| valuetype.len(size) != size.len())
+        {
+            return Err(Error::config_field_parse(
+                section,
+                "replace",
+                format!("{} byte runs can't hold {valuetype:?} values", size.len()),
+            ));
+        }
+
+        // `occurindex=N` and `skip=N` are shorthands for `occurfirst`
+        let first_fields = [
+            field_name("occurfirst").parse_opt::<u32>()?,
+            field_name("occurindex").parse_opt::<u32>()?,
+            field_name("skip")
+                .parse_opt::<u32>()?
+                .map(|x| x.saturating_add(1)),
+        ];
+        let occurfirst = match first_fields.iter().flatten().collect::<Vec<_>>()[..] {
+            [] => None,
+            [&first] => Some(first),
+            _ => {
+                return Err(Error::config_field_parse(
+                    section,
+                    "occurfirst",
+                    "Only one of occurfirst, occurindex and skip can be used",
+                ));
+            }
+        };
+
+        let occur = field_name("occur");
+        let occur = match occur.get() {
+            Ok(x) if x.trim().eq_ignore_ascii_case("all") => Self::ALL,
+            // Patch just the chosen occurrence
+            Err(_) if first_fields[1].is_some() => 1,
+            _ => occur.parse()?,
+        };
+
+        let optional = field_name("optional")
+            .parse_opt::<u8>()?
+            .is_some_and(|x| x != 0);
+        let label = field_name("label").get().cloned().ok();
+        if optional && label.is_none() {
+            return Err(Error::config_field_parse(
+                section,
+                "label",
+                "Optional patches need a label",
+            ));
+        }
+
+        let info = Self {
             signature,
-            modfile: field_name("modfile").get().cloned()?,
-            undofile: field_name("undofile").get().cloned().ok(),
-            xoffset: field_name("xoffset").parse().ok(),
-            yoffset: field_name("yoffset").parse().ok(),
-            occur: field_name("occur").parse()?,
-            setx: field_name("setx").parse().ok(),
-            sety: field_name("sety").parse().ok(),
-        })
+            alternatives,
+            modfile: wine::config_path(field_name("modfile").get()?),
+            undofile: field_name("undofile")
+                .get()
+                .ok()
+                .map(|x| wine::config_path(x)),
+            xoffset: field_name("xoffset").parse_list()?,
+            yoffset,
+            searchstart: field_name("searchstart").parse_opt()?,
+            searchend: field_name("searchend").parse_opt()?,
+            align: field_name("align").parse_opt()?,
+            occur,
+            occurfirst,
+            setx: field_name("setx").parse_opt()?,
+            sety: field_name("sety").parse_opt()?,
+            wordsize: field_name("wordsize").parse_opt()?.unwrap_or_default(),
+            endian: field_name("endian").parse_opt()?.unwrap_or_default(),
+            valuetype,
+            aspect,
+            valueslot,
+            optional,
+            label,
+            modify,
+            replace,
+            offset: field_name("offset").parse_opt()?.unwrap_or_default(),
+            expect,
+        };
+        info.check_offsets(section)?;
+
+        Ok(info)
+    }
+
+    /// Check every write is within [`Self::MAX_OFFSET`] of the match
+    fn check_offsets(&self, section: &str) -> Result<(), Error> {
+        let value_len = self.valuetype.len(self.wordsize);
+        let writes = (self.xoffset.iter().map(|&x| ("xoffset", x, value_len)))
+            .chain(self.yoffset.iter().map(|&y| ("yoffset", y, value_len)))
+            .chain(self.modify.iter().map(|m| {
+                let offset = i64::try_from(m.offset).unwrap_or(i64::MAX);
+                ("opoffset", offset, m.len(self.valuetype))
+            }))
+            .chain(
+                self.replace
+                    .iter()
+                    .map(|r| ("offset", self.offset, r.len())),
+            );
+
+        for (field, offset, len) in writes {
+            let end = offset.checked_add(len as i64);
+            if offset.unsigned_abs() > Self::MAX_OFFSET
+                || end.is_none_or(|x| x.unsigned_abs() > Self::MAX_OFFSET)
+            {
+                return Err(Error::config_field_parse(
+                    section,
+                    field,
+                    format!(
+                        "{offset} + {len} byte(s) is more than {} bytes from the sig",
+                        Self::MAX_OFFSET
+                    ),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Start of every aligned match in the search window of `data`, using the
+    /// first of `sig` and the `sigalt`s found, and the index of that signature
+    fn matches<'a>(&'a self, data: &'a [u8]) -> (usize, impl Iterator<Item = usize> + 'a) {
+        let start = self.searchstart.map_or(0, |x| x.resolve(data.len()));
+        let end = self.searchend.map_or(data.len(), |x| x.resolve(data.len()));
+        let window = data.get(start..end).unwrap_or_default();
+        let align = self.align.map_or(1, NonZeroUsize::get);
+
+        let hits = move |sig: &'a Signature| {
+            sig.find_iter(window)
+                .map(move |x| x + start)
+                .filter(move |x| x % align == 0)
+        };
+
+        let (index, signature) = std::iter::once(&self.signature)
+            .chain(&self.alternatives)
+            .enumerate()
+            .find(|(_, sig)| hits(sig).next().is_some())
+            .unwrap_or((0, &self.signature));
+
+        (index, hits(signature))
+    }
+
+    /// Number of times the matching signature appears in `data`
+    pub fn count_matches(&self, data: &[u8]) -> usize {
+        self.matches(data).1.count()
     }
 
     /// **NOTE**: find all offsets before doing any patching
@@ -120,60 +754,253 @@ impl PatchInfo {
         data: &[u8],
         iteration: usize,
     ) -> Result<Vec<PatchOffsets>, Error> {
-        (0..self.occur as usize)
-            .map(|_| {
-                self.signature
-                    .try_find(data)
-                    .map(|index| PatchOffsets {
-                        xoffset: self.xoffset.map(|x| index + x as usize),
-                        yoffset: self.yoffset.map(|y| index + y as usize),
-                        setx: self.setx,
-                        sety: self.sety,
-                    })
-                    .ok_or(Error::PatchError { iteration })
+        let skip = self.occurfirst.map_or(0, |x| x.saturating_sub(1)) as usize;
+        let take = match self.occur {
+            Self::ALL => usize::MAX,
+            n => n as usize,
+        };
+
+        let context = PatchContext::patch(iteration, &self.modfile);
+        let error = |kind: PatchErrorKind| Error::from(kind).in_patch(context.clone());
+        // At the `occurrence`th match, starting at `index`
+        let error_at = |kind: PatchErrorKind, index: usize, occurrence: usize| {
+            Error::from(kind).in_patch(context.clone().at(index, occurrence))
+        };
+
+        let resolve = |index: usize, offset: i64| match index.checked_add_signed(offset as isize) {
+            Some(x) => Ok(x),
+            None if offset < 0 => Err(PatchErrorKind::OffsetBeforeStart { index, offset }),
+            // Caught by the bounds check below
+            None => Ok(usize::MAX),
+        };
+
+        let (signature_index, matches) = self.matches(data);
+        let offsets = matches
+            .enumerate()
+            .skip(skip)
+            .take(take)
+            .map(|(i, index)| -> Result<PatchOffsets, Error> {
+                let resolve =
+                    |offset| resolve(index, offset).map_err(|e| error_at(e, index, i + 1));
+                Ok(PatchOffsets {
+                    index,
+                    occurrence: i + 1,
+                    signature: signature_index,
+                    xoffset: self
+                        .xoffset
+                        .iter()
+                        .map(|&x| resolve(x))
+                        .collect::<Result<_, _>>()?,
+                    yoffset: self
+                        .yoffset
+                        .iter()
+                        .map(|&y| resolve(y))
+                        .collect::<Result<_, _>>()?,
+                    setx: self.setx,
+                    sety: self.sety,
+                    wordsize: self.wordsize,
+                    endian: self.endian,
+                    valuetype: self.valuetype,
+                    aspect: self.aspect,
+                    replace: self
+                        .replace
+                        .as_ref()
+                        .map(|_| resolve(self.offset))
+                        .transpose()?,
+                    opoffset: self
+                        .modify
+                        .as_ref()
+                        .map(|m| index.saturating_add(m.offset as usize)),
+                })
             })
-            .collect::<Result<Vec<_>, _>>()
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if offsets.is_empty() && skip == 0 {
+            return Err(error(PatchErrorKind::SignatureNotFound));
+        }
+
+        if offsets.is_empty() || offsets.len() < self.occur as usize {
+            return Err(error(PatchErrorKind::TooFewOccurrences {
+                found: offsets.len(),
+                requested: self.occur,
+            }));
+        }
+
+        for offsets in &offsets {
+            let value_len = self.valuetype.len(self.wordsize);
+            let values = offsets.xoffset.iter().chain(&offsets.yoffset);
+            for &offset in values {
+                check_bounds(data.len(), offset, value_len)
+                    .map_err(|e| error_at(e, offsets.index, offsets.occurrence))?;
+            }
+
+            let writes = [
+                offsets
+                    .opoffset
+                    .zip(self.modify.as_ref().map(|m| m.len(self.valuetype))),
+                offsets.replace.zip(self.replace.as_ref().map(|r| r.len())),
+            ];
+
+            for (offset, len) in writes.into_iter().flatten() {
+                check_bounds(data.len(), offset, len)
+                    .map_err(|e| error_at(e, offsets.index, offsets.occurrence))?;
+            }
+
+            if let Some(expect) = &self.expect {
+                let at = |e| error_at(e, offsets.index, offsets.occurrence);
+                let offset = resolve(offsets.index, self.offset).map_err(at)?;
+                check_bounds(data.len(), offset, expect.len()).map_err(at)?;
+                if !expect.matches(data, offset) {
+                    let found = data[offset..offset + expect.len()]
+                        .iter()
+                        .map(|x| format!("{x:02X}"))
+                        .collect();
+                    return Err(at(PatchErrorKind::ExpectMismatch { offset, found }));
+                }
+            }
+        }
+
+        Ok(offsets)
     }
 }
 
-pub fn apply_patches(data: &mut [u8], patch_offsets: &[PatchOffsets], x_res: u16, y_res: u16) {
+/// Run `apply` on one match at a time, so a failure says which match it was at
+fn each_match(
+    offsets: &[PatchOffsets],
+    context: &PatchContext,
+    apply: &mut dyn FnMut(&[PatchOffsets]) -> Result<(), Error>,
+) -> Result<(), Error> {
+    offsets.iter().try_for_each(|x| {
+        apply(std::slice::from_ref(x))
+            .map_err(|e| e.in_patch(context.clone().at(x.index, x.occurrence)))
+    })
+}
+
+pub fn apply_modify(
+    data: &mut [u8],
+    patch_offsets: &[PatchOffsets],
+    modify: &Modify,
+    x_res: u16,
+    y_res: u16,
+) -> Result<(), Error> {
+    let operand = modify.operand.eval(&Vars::new(x_res, y_res));
+    if !operand.is_finite() {
+        return Err(Error::value_error(format!(
+            "Operand evaluated to {operand}"
+        )));
+    }
+
+    for offsets in patch_offsets {
+        if let Some(offset) = offsets.opoffset {
+            modify.apply(data, offset, operand, offsets.valuetype, offsets.endian)?;
+        }
+    }
+
+    Ok(())
+}
+
+pub fn apply_replace(
+    data: &mut [u8],
+    patch_offsets: &[PatchOffsets],
+    replace: &Template,
+    x_res: u16,
+    y_res: u16,
+) -> Result<(), PatchErrorKind> {
+    for offsets in patch_offsets {
+        let Some(offset) = offsets.replace else {
+            continue;
+        };
+
+        check_bounds(data.len(), offset, replace.len())?;
+        replace.write(
+            data,
+            offset,
+            offsets.setx.unwrap_or(x_res),
+            offsets.sety.unwrap_or(y_res),
+            offsets.valuetype,
+            offsets.endian,
+        )?;
+    }
+
+    Ok(())
+}
+
+fn check_bounds(file_len: usize, offset: usize, len: usize) -> Result<(), PatchErrorKind> {
+    if offset.checked_add(len).is_some_and(|end| end <= file_len) {
+        Ok(())
+    } else {
+        Err(PatchErrorKind::OffsetOutOfBounds {
+            offset,
+            len,
+            file_len,
+        })
+    }
+}
+
+/// Write `value` at every `xoffset`, for patches of a value slot
+pub fn apply_value(
+    data: &mut [u8],
+    patch_offsets: &[PatchOffsets],
+    value: f64,
+) -> Result<(), PatchErrorKind> {
+    for offsets in patch_offsets {
+        for &offset in &offsets.xoffset {
+            check_bounds(data.len(), offset, offsets.valuetype.len(offsets.wordsize))?;
+            offsets.valuetype.check(offsets.wordsize, value)?;
+            offsets
+                .valuetype
+                .write(offsets.wordsize, data, offset, value, offsets.endian);
+        }
+    }
+
+    Ok(())
+}
+
+pub fn apply_patches(
+    data: &mut [u8],
+    patch_offsets: &[PatchOffsets],
+    x_res: u16,
+    y_res: u16,
+) -> Result<(), PatchErrorKind> {
     for PatchOffsets {
         xoffset,
         yoffset,
         setx,
         sety,
+        wordsize,
+        endian,
+        valuetype,
+        aspect,
+        ..
     } in patch_offsets
     {
-        let x_bytes = match setx {
-            Some(x) => x.to_le_bytes(),
-            None => x_res.to_le_bytes(),
+        let x = setx.unwrap_or(x_res);
+        let y = sety.unwrap_or(y_res);
+        let x_value = match aspect {
+            Some(aspect) => aspect.value(x, y),
+            None => x.into(),
         };
-        let y_bytes = match sety {
-            Some(y) => y.to_le_bytes(),
-            None => y_res.to_le_bytes(),
-        };
-
-        if let Some(x_offset) = xoffset {
-            data[*x_offset] = x_bytes[0];
-            data[*x_offset + 1] = x_bytes[1];
-        }
 
-        if let Some(y_offset) = yoffset {
-            data[*y_offset] = y_bytes[0];
-            data[*y_offset + 1] = y_bytes[1];
+        for (offsets, value) in [(xoffset, x_value), (yoffset, y.into())] {
+            for &offset in offsets {
+                check_bounds(data.len(), offset, valuetype.len(*wordsize))?;
+                valuetype.check(*wordsize, value)?;
+                valuetype.write(*wordsize, data, offset, value, *endian);
+            }
         }
     }
+
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::collections::HashMap;
+    use crate::error::ConfigError;
 
-    #[test]
-    fn parse_multiple_patches() {
-        let section = "test";
-        let items = HashMap::from_iter(
+    /// Star Wars: KOTOR (800x600 interface) from the original patches.ini
+    fn kotor_items() -> Items {
+        Items::from_iter(
             [
                 ("details", ""),
                 ("checkfile", "swkotor.exe"),
@@ -230,7 +1057,13 @@ mod tests {
                 ("p5occur", "1"),
             ]
             .map(|(a, b)| (a.to_string(), b.to_string())),
-        );
+        )
+    }
+
+    #[test]
+    fn parse_multiple_patches() {
+        let section = "test";
+        let items = kotor_items();
 
         PatchInfo::from_items(section, &items, None).unwrap();
         PatchInfo::from_items(section, &items, Some(1)).unwrap();
@@ -240,13 +1073,92 @@ mod tests {
         PatchInfo::from_items(section, &items, Some(5)).unwrap();
     }
 
+    #[test]
+    fn setx_test() {
+        let items = kotor_items();
+
+        let interface = PatchInfo::from_items("test", &items, Some(1)).unwrap();
+        assert_eq!(interface.setx, Some(0));
+        assert_eq!(interface.sety, None);
+
+        #[rustfmt::skip]
+        let mut data = [
+            0x3D, 0x00, 0x04, 0x00, 0x00, 0xB3, 0x29, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77,
+            0x88, 0x99, 0x3D, 0x00, 0x05, 0x00, 0x00, 0xAA, 0xBB, 0x3D, 0x40, 0x06, 0x00, 0x00,
+        ];
+        let offsets = interface.find_patch_offsets(&data, 1).unwrap();
+        apply_patches(&mut data, &offsets, 1920, 1080).unwrap();
+        assert_eq!(data[1..3], [0x00, 0x00]);
+        assert_eq!(data[16..19], [0x3D, 0x00, 0x05]);
+
+        let strategy = PatchStrategy {
+            iteration: 1,
+            modfile: "swkotor.exe",
+            offsets,
+            modify: None,
+            replace: None,
+            requested: 1,
+            found: 1,
+            slot: None,
+        };
+        assert_eq!(strategy.log(1920, 1080), "patch 1: swkotor.exe 0x1 = 0\n");
+
+        let mut fixed = items.clone();
+        fixed.insert("sety".into(), "600".into());
+        let main = PatchInfo::from_items("test", &fixed, None).unwrap();
+
+        #[rustfmt::skip]
+        let mut data = [
+            0x3D, 0x20, 0x03, 0x00, 0x00, 0xEF, 0xEF, 0xEF, 0xEF, 0xEF, 0xEF, 0x58, 0x02, 0x00,
+            0x00,
+        ];
+        let offsets = main.find_patch_offsets(&data, 0).unwrap();
+        apply_patches(&mut data, &offsets, 1920, 1080).unwrap();
+        assert_eq!(data[1..3], [0x80, 0x07]);
+        assert_eq!(data[11..13], [0x58, 0x02]);
+
+        fixed.insert("p1setx".into(), "wide".into());
+        assert!(PatchInfo::from_items("test", &fixed, Some(1)).is_err());
+    }
+
+    #[test]
+    fn offset_list_test() {
+        let mut items = kotor_items();
+        items.insert("p1xoffset".into(), "1, 17,24".into());
+
+        // One edit set instead of p1, p2 and p3
+        let interface = PatchInfo::from_items("test", &items, Some(1)).unwrap();
+        assert_eq!(interface.xoffset, [1, 17, 24]);
+
+        #[rustfmt::skip]
+        let mut data = [
+            0x3D, 0x00, 0x04, 0x00, 0x00, 0xB3, 0x29, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77,
+            0x88, 0x99, 0x3D, 0x00, 0x05, 0x00, 0x00, 0xAA, 0xBB, 0x3D, 0x40, 0x06, 0x00, 0x00,
+        ];
+        let offsets = interface.find_patch_offsets(&data, 1).unwrap();
+        assert_eq!(offsets.len(), 1);
+        assert_eq!(offsets[0].xoffset, [1, 17, 24]);
+
+        apply_patches(&mut data, &offsets, 1920, 1080).unwrap();
+        assert_eq!(data[1..3], [0x00, 0x00]);
+        assert_eq!(data[17..19], [0x00, 0x00]);
+        assert_eq!(data[24..26], [0x00, 0x00]);
+
+        items.insert("p1xoffset".into(), "1,,24".into());
+        assert!(PatchInfo::from_items("test", &items, Some(1)).is_err());
+
+        items.insert("p1xoffset".into(), "1,27".into());
+        let interface = PatchInfo::from_items("test", &items, Some(1)).unwrap();
+        assert!(interface.find_patch_offsets(&data, 1).is_err());
+    }
+
     #[test]
     fn apply_test() {
         let info = PatchInfo {
             signature: Signature::from_string("test", "80020000C701E0010000", "0000110000")
                 .unwrap(),
-            xoffset: Some(0),
-            yoffset: Some(6),
+            xoffset: vec![0],
+            yoffset: vec![6],
             occur: 2,
             ..Default::default()
         };
@@ -265,7 +1177,7 @@ mod tests {
         ];
 
         let offsets = info.find_patch_offsets(&data, 0).unwrap();
-        apply_patches(&mut data, &offsets, 1920, 1080);
+        apply_patches(&mut data, &offsets, 1920, 1080).unwrap();
 
         #[rustfmt::skip]
         assert_eq!(data.as_slice(), [
@@ -279,5 +1191,806 @@ mod tests {
 
             0x80, 0x07, 0x00, 0x00, 0xC7, 0x01, 0x38, 0x04, 0x00, 0x00,
         ]);
+
+        let offsets = [PatchOffsets {
+            xoffset: vec![0],
+            wordsize: WordSize::Byte,
+            ..Default::default()
+        }];
+        assert_eq!(
+            apply_patches(&mut data, &offsets, 1920, 1080),
+            Err(PatchErrorKind::ValueOutOfRange {
+                value: 1920.0,
+                len: 1
+            })
+        );
+    }
+
+    #[test]
+    fn modify_test() {
+        let items = Items::from_iter(
+            [
+                ("modfile", "game.exe"),
+                ("sig", "AABB0000CCDD"),
+                ("sigwild", "000100"),
+                ("occur", "1"),
+                ("op", "mul"),
+                ("operand", "aspect / (4 / 3)"),
+                ("opoffset", "3"),
+                ("opsize", "1"),
+                ("p1modfile", "game.exe"),
+                ("p1sig", "AABB0000CCDD"),
+                ("p1sigwild", "000100"),
+                ("p1occur", "1"),
+                ("p1op", "or"),
+                ("p1operand", "0x01"),
+                ("p1opoffset", "4"),
+                ("p1opsize", "1"),
+            ]
+            .map(|(a, b)| (a.to_string(), b.to_string())),
+        );
+
+        let scale = PatchInfo::from_items("test", &items, None).unwrap();
+        let flag = PatchInfo::from_items("test", &items, Some(1)).unwrap();
+
+        let mut data = [0x00, 0xAA, 0xBB, 0x00, 0x03, 0xCC, 0xDD, 0x00];
+        let offsets = scale.find_patch_offsets(&data, 0).unwrap();
+        apply_modify(
+            &mut data,
+            &offsets,
+            scale.modify.as_ref().unwrap(),
+            1920,
+            1080,
+        )
+        .unwrap();
+        assert_eq!(data, [0x00, 0xAA, 0xBB, 0x00, 0x04, 0xCC, 0xDD, 0x00]);
+
+        let offsets = flag.find_patch_offsets(&data, 1).unwrap();
+        apply_modify(
+            &mut data,
+            &offsets,
+            flag.modify.as_ref().unwrap(),
+            1920,
+            1080,
+        )
+        .unwrap();
+        assert_eq!(data, [0x00, 0xAA, 0xBB, 0x00, 0x04, 0xCD, 0xDD, 0x00]);
+
+        let bad_op = {
+            let mut items = items.clone();
+            items.insert("op".into(), "pow".into());
+            items
+        };
+        assert!(PatchInfo::from_items("test", &bad_op, None).is_err());
+
+        // Bitwise operands must fit in opsize, instead of being truncated
+        for operand in ["-1", "0x100", "0.5 - 1"] {
+            let mut items = items.clone();
+            items.insert("p1operand".into(), operand.into());
+            assert!(
+                matches!(
+                    PatchInfo::from_items("test", &items, Some(1)),
+                    Err(Error::ConfigError(ConfigError::FieldParseError {
+                        field: "operand",
+                        ..
+                    }))
+                ),
+                "{operand}"
+            );
+        }
+        let mut items = items.clone();
+        items.insert("p1operand".into(), "width".into());
+        let flag = PatchInfo::from_items("test", &items, Some(1)).unwrap();
+        let mut data = [0x00, 0xAA, 0xBB, 0x00, 0x03, 0xCC, 0xDD, 0x00];
+        let offsets = flag.find_patch_offsets(&data, 1).unwrap();
+        assert_eq!(
+            apply_modify(
+                &mut data,
+                &offsets,
+                flag.modify.as_ref().unwrap(),
+                1920,
+                1080
+            ),
+            Err(PatchErrorKind::ValueOutOfRange {
+                value: 1920.0,
+                len: 1
+            }
+            .into())
+        );
+        assert_eq!(data, [0x00, 0xAA, 0xBB, 0x00, 0x03, 0xCC, 0xDD, 0x00]);
+    }
+
+    #[test]
+    fn occurfirst_test() {
+        let info = PatchInfo {
+            signature: Signature::from_string("test", "80020000C701E0010000", "0000110000")
+                .unwrap(),
+            xoffset: vec![0],
+            yoffset: vec![6],
+            occur: 2,
+            occurfirst: Some(2),
+            ..Default::default()
+        };
+
+        #[rustfmt::skip]
+        let mut data = [
+            0x80, 0x02, 0x00, 0x00, 0xC7, 0x01, 0xE0, 0x01, 0x00, 0x00,
+            0x80, 0x02, 0x00, 0x00, 0xC7, 0x01, 0xE0, 0x01, 0x00, 0x00,
+            0x80, 0x02, 0x00, 0x00, 0xC7, 0x01, 0xE0, 0x01, 0x00, 0x00,
+        ];
+
+        let offsets = info.find_patch_offsets(&data, 0).unwrap();
+        // Counted from the first match, not the first patched
+        assert_eq!(
+            offsets.iter().map(|x| x.occurrence).collect::<Vec<_>>(),
+            [2, 3]
+        );
+        apply_patches(&mut data, &offsets, 1920, 1080).unwrap();
+
+        #[rustfmt::skip]
+        assert_eq!(data.as_slice(), [
+            0x80, 0x02, 0x00, 0x00, 0xC7, 0x01, 0xE0, 0x01, 0x00, 0x00,
+            0x80, 0x07, 0x00, 0x00, 0xC7, 0x01, 0x38, 0x04, 0x00, 0x00,
+            0x80, 0x07, 0x00, 0x00, 0xC7, 0x01, 0x38, 0x04, 0x00, 0x00,
+        ]);
+
+        let too_many = PatchInfo { occur: 3, ..info };
+        assert!(too_many.find_patch_offsets(&data, 0).is_err());
+    }
+
+    #[test]
+    fn out_of_bounds_test() {
+        let info = PatchInfo {
+            modfile: "game.exe".into(),
+            signature: Signature::from_string("test", "8002", "00").unwrap(),
+            xoffset: vec![0],
+            yoffset: vec![3],
+            occur: 1,
+            ..Default::default()
+        };
+
+        let data = [0x00, 0x80, 0x02, 0x00, 0x00];
+        assert_eq!(
+            info.find_patch_offsets(&data, 2),
+            Err(Error::PatchError {
+                context: Box::new(PatchContext::patch(2, "game.exe").at(1, 1)),
+                kind: PatchErrorKind::OffsetOutOfBounds {
+                    offset: 4,
+                    len: 2,
+                    file_len: 5
+                }
+            })
+        );
+
+        let huge = PatchInfo {
+            yoffset: vec![i64::MAX],
+            ..info.clone()
+        };
+        assert!(huge.find_patch_offsets(&data, 0).is_err());
+
+        let before = PatchInfo {
+            xoffset: vec![-2],
+            ..info.clone()
+        };
+        assert_eq!(
+            before.find_patch_offsets(&data, 0),
+            Err(Error::PatchError {
+                context: Box::new(PatchContext::patch(0, "game.exe").at(1, 1)),
+                kind: PatchErrorKind::OffsetBeforeStart {
+                    index: 1,
+                    offset: -2
+                }
+            })
+        );
+
+        let mut data = [0x80, 0x02];
+        let offsets = [PatchOffsets {
+            xoffset: vec![1],
+            ..Default::default()
+        }];
+        assert!(apply_patches(&mut data, &offsets, 1920, 1080).is_err());
+    }
+
+    #[test]
+    fn wordsize_test() {
+        let items = Items::from_iter(
+            [
+                ("modfile", "game.exe"),
+                ("sig", "8002000000000000"),
+                ("sigwild", "00001111"),
+                ("xoffset", "0"),
+                ("yoffset", "4"),
+                ("occur", "1"),
+                ("wordsize", "4"),
+            ]
+            .map(|(a, b)| (a.to_string(), b.to_string())),
+        );
+        let info = PatchInfo::from_items("test", &items, None).unwrap();
+        assert_eq!(info.wordsize, WordSize::DWord);
+
+        let mut data = [0x80, 0x02, 0x00, 0x00, 0xFF, 0xFF, 0xFF, 0xFF];
+        let offsets = info.find_patch_offsets(&data, 0).unwrap();
+        apply_patches(&mut data, &offsets, 1920, 1080).unwrap();
+        assert_eq!(data, [0x80, 0x07, 0x00, 0x00, 0x38, 0x04, 0x00, 0x00]);
+
+        let truncated = [0x80, 0x02, 0x00, 0x00, 0xFF, 0xFF];
+        assert!(info.find_patch_offsets(&truncated, 0).is_err());
+
+        let mut bad = items.clone();
+        bad.insert("wordsize".into(), "3".into());
+        assert!(PatchInfo::from_items("test", &bad, None).is_err());
+    }
+
+    #[test]
+    fn endian_test() {
+        let info = PatchInfo {
+            signature: Signature::from_string("test", "028001E0", "0000").unwrap(),
+            xoffset: vec![0],
+            yoffset: vec![2],
+            occur: 1,
+            endian: Endian::Big,
+            ..Default::default()
+        };
+
+        let mut data = [0x02, 0x80, 0x01, 0xE0];
+        let offsets = info.find_patch_offsets(&data, 0).unwrap();
+        apply_patches(&mut data, &offsets, 1920, 1080).unwrap();
+        assert_eq!(data, [0x07, 0x80, 0x04, 0x38]);
+    }
+
+    #[test]
+    fn float_test() {
+        let items = Items::from_iter(
+            [
+                ("modfile", "game.exe"),
+                ("sig", "D90500000000803F"),
+                ("sigwild", "00000000"),
+                ("occur", "1"),
+                ("valuetype", "f32"),
+                ("op", "set"),
+                ("operand", "90 * aspect / (4 / 3)"),
+                ("opoffset", "4"),
+                ("p1modfile", "game.exe"),
+                ("p1sig", "D905"),
+                ("p1sigwild", "00"),
+                ("p1xoffset", "2"),
+                ("p1occur", "1"),
+                ("p1valuetype", "f32"),
+                ("p1endian", "be"),
+            ]
+            .map(|(a, b)| (a.to_string(), b.to_string())),
+        );
+
+        let fov = PatchInfo::from_items("test", &items, None).unwrap();
+        assert_eq!(fov.valuetype, ValueType::F32);
+
+        let mut data = [0xD9, 0x05, 0x00, 0x00, 0x00, 0x00, 0x80, 0x3F];
+        let offsets = fov.find_patch_offsets(&data, 0).unwrap();
+        apply_modify(
+            &mut data,
+            &offsets,
+            fov.modify.as_ref().unwrap(),
+            1920,
+            1080,
+        )
+        .unwrap();
+        assert_eq!(data[4..], 120.0f32.to_le_bytes());
+
+        let mul = Modify {
+            op: ModifyOp::Mul,
+            ..fov.modify.clone().unwrap()
+        };
+        apply_modify(&mut data, &offsets, &mul, 800, 600).unwrap();
+        assert_eq!(data[4..], 10800.0f32.to_le_bytes());
+
+        let width = PatchInfo::from_items("test", &items, Some(1)).unwrap();
+        let offsets = width.find_patch_offsets(&data, 1).unwrap();
+        apply_patches(&mut data, &offsets, 1920, 1080).unwrap();
+        assert_eq!(data[2..6], 1920.0f32.to_be_bytes());
+
+        let mut bitwise = items.clone();
+        bitwise.insert("op".into(), "or".into());
+        assert!(PatchInfo::from_items("test", &bitwise, None).is_err());
+
+        let mut bad = items.clone();
+        bad.insert("valuetype".into(), "f16".into());
+        assert!(PatchInfo::from_items("test", &bad, None).is_err());
+    }
+
+    #[test]
+    fn aspect_test() {
+        let items = Items::from_iter(
+            [
+                ("modfile", "game.exe"),
+                ("sig", "ABAAAA3F"),
+                ("sigwild", "0000"),
+                ("xoffset", "0"),
+                ("occur", "1"),
+                ("valuetype", "aspect_f32"),
+                ("p1modfile", "game.exe"),
+                ("p1sig", "0000803F"),
+                ("p1sigwild", "0000"),
+                ("p1xoffset", "0"),
+                ("p1occur", "1"),
+                ("p1valuetype", "aspect43_f32"),
+            ]
+            .map(|(a, b)| (a.to_string(), b.to_string())),
+        );
+
+        let ratio = PatchInfo::from_items("test", &items, None).unwrap();
+        assert_eq!(ratio.aspect, Some(Aspect::Ratio));
+        assert_eq!(ratio.valuetype, ValueType::F32);
+        let relative = PatchInfo::from_items("test", &items, Some(1)).unwrap();
+
+        let mut data = [0xAB, 0xAA, 0xAA, 0x3F, 0x00, 0x00, 0x80, 0x3F];
+        let offsets = ratio.find_patch_offsets(&data, 0).unwrap();
+        let relative_offsets = relative.find_patch_offsets(&data, 1).unwrap();
+        apply_patches(&mut data, &offsets, 1920, 1080).unwrap();
+        apply_patches(&mut data, &relative_offsets, 1920, 1080).unwrap();
+
+        assert_eq!(data[..4], (16.0f32 / 9.0).to_le_bytes());
+        assert_eq!(data[4..], (4.0f32 / 3.0).to_le_bytes());
+
+        let mut both = items.clone();
+        both.insert("yoffset".into(), "4".into());
+        assert!(PatchInfo::from_items("test", &both, None).is_err());
+    }
+
+    #[test]
+    fn replace_test() {
+        let items = Items::from_iter(
+            [
+                ("modfile", "game.exe"),
+                ("sig", "C705000000008002E001"),
+                ("sigwild", "0011110000"),
+                ("occur", "1"),
+                ("replace", "C705....WWWWWWWWHHHHHHHH"),
+                ("p1modfile", "game.exe"),
+                ("p1sig", "C705"),
+                ("p1sigwild", "00"),
+                ("p1occur", "1"),
+                ("p1replace", "WWWW"),
+                ("p1valuetype", "f32"),
+            ]
+            .map(|(a, b)| (a.to_string(), b.to_string())),
+        );
+        let info = PatchInfo::from_items("test", &items, None).unwrap();
+        assert!(PatchInfo::from_items("test", &items, Some(1)).is_err());
+
+        let mut data = [
+            0xC7, 0x05, 0xAA, 0xBB, 0x00, 0x00, 0x80, 0x02, 0xE0, 0x01, 0x00, 0x00,
+        ];
+        assert!(info.find_patch_offsets(&data[..10], 0).is_err());
+
+        let strategy = PatchStrategy {
+            iteration: 0,
+            modfile: "game.exe",
+            offsets: info.find_patch_offsets(&data, 0).unwrap(),
+            modify: None,
+            replace: info.replace.as_ref(),
+            requested: info.occur,
+            found: 1,
+            slot: None,
+        };
+        strategy.patch_data(&mut data, 1920, 1080).unwrap();
+        assert_eq!(
+            data,
+            [
+                0xC7, 0x05, 0xAA, 0xBB, 0x80, 0x07, 0x00, 0x00, 0x38, 0x04, 0x00, 0x00
+            ]
+        );
+    }
+
+    #[test]
+    fn patchbytes_test() {
+        let items = Items::from_iter(
+            [
+                ("modfile", "game.exe"),
+                ("sig", "D9E8D8F17602"),
+                ("sigwild", "000000"),
+                ("occur", "1"),
+                ("patchbytes", "..9090"),
+                ("offset", "3"),
+            ]
+            .map(|(a, b)| (a.to_string(), b.to_string())),
+        );
+        let info = PatchInfo::from_items("test", &items, None).unwrap();
+        assert_eq!(info.offset, 3);
+
+        let mut data = [0xD9, 0xE8, 0xD8, 0xF1, 0x76, 0x02];
+        let offsets = info.find_patch_offsets(&data, 0).unwrap();
+        apply_replace(
+            &mut data,
+            &offsets,
+            info.replace.as_ref().unwrap(),
+            1920,
+            1080,
+        )
+        .unwrap();
+        assert_eq!(data, [0xD9, 0xE8, 0xD8, 0xF1, 0x90, 0x90]);
+
+        let mut too_far = items.clone();
+        too_far.insert("offset".into(), "4".into());
+        let info = PatchInfo::from_items("test", &too_far, None).unwrap();
+        assert!(info.find_patch_offsets(&data, 0).is_err());
+
+        let mut expect = items.clone();
+        expect.insert("expect".into(), "..7602".into());
+        let info = PatchInfo::from_items("test", &expect, None).unwrap();
+        let original = [0xD9, 0xE8, 0xD8, 0xF1, 0x76, 0x02];
+        assert!(info.find_patch_offsets(&original, 0).is_ok());
+        expect.insert("expect".into(), "..9090".into());
+        let info = PatchInfo::from_items("test", &expect, None).unwrap();
+        assert_eq!(
+            info.find_patch_offsets(&original, 0)
+                .map_err(|e| e.to_string()),
+            Err(
+                "patch 0, game.exe, match 1 at 0x0: found F17602 at 0x3 rather than the bytes in expect"
+                    .into()
+            )
+        );
+        expect.insert("expect".into(), "HH".into());
+        assert!(PatchInfo::from_items("test", &expect, None).is_err());
+
+        let mut placeholder = items.clone();
+        placeholder.insert("patchbytes".into(), "WWWW".into());
+        assert!(PatchInfo::from_items("test", &placeholder, None).is_err());
+
+        let mut both = items.clone();
+        both.insert("replace".into(), "90".into());
+        assert!(PatchInfo::from_items("test", &both, None).is_err());
+    }
+
+    #[test]
+    fn sigalt_test() {
+        let items = Items::from_iter(
+            [
+                ("modfile", "game.exe"),
+                ("sig", "80020000C701E001"),
+                ("sigwild", "00001100"),
+                ("sigalt1", "8002E001"),
+                ("sigaltwild1", "0000"),
+                ("sigalt2", "80025802"),
+                ("sigaltwild2", "0000"),
+                ("xoffset", "0"),
+                ("occur", "1"),
+            ]
+            .map(|(a, b)| (a.to_string(), b.to_string())),
+        );
+        let info = PatchInfo::from_items("test", &items, None).unwrap();
+        assert_eq!(info.alternatives.len(), 2);
+
+        let mut data = [0x00, 0x80, 0x02, 0x58, 0x02];
+        let offsets = info.find_patch_offsets(&data, 0).unwrap();
+        assert_eq!(offsets[0].signature, 2);
+        assert_eq!(offsets[0].index, 1);
+        assert_eq!(info.count_matches(&data), 1);
+
+        apply_patches(&mut data, &offsets, 1920, 1080).unwrap();
+        assert_eq!(data, [0x00, 0x80, 0x07, 0x58, 0x02]);
+
+        let strategy = PatchStrategy {
+            iteration: 0,
+            modfile: "game.exe",
+            offsets,
+            modify: None,
+            replace: None,
+            requested: 1,
+            found: 1,
+            slot: None,
+        };
+        assert_eq!(
+            strategy.status().to_string(),
+            "patched 1/1 occurrence(s) of 1 found at 0x1 using sigalt2"
+        );
+
+        // `sig` wins when it's found, even if an alternative is too
+        let data = [
+            0x80, 0x02, 0xE0, 0x01, 0x80, 0x02, 0x00, 0x00, 0xC7, 0x01, 0xE0, 0x01,
+        ];
+        let offsets = info.find_patch_offsets(&data, 0).unwrap();
+        assert_eq!((offsets[0].signature, offsets[0].index), (0, 4));
+
+        assert!(info.find_patch_offsets(&[0x00; 8], 0).is_err());
+
+        let mut missing_wild = items.clone();
+        missing_wild.shift_remove("sigaltwild2");
+        assert!(PatchInfo::from_items("test", &missing_wild, None).is_err());
+    }
+
+    #[test]
+    fn pattern_test() {
+        let items = Items::from_iter(
+            [
+                ("modfile", "game.exe"),
+                ("pattern", "80 02 ?? ?? C7 01 E0 01"),
+                ("xoffset", "0"),
+                ("yoffset", "6"),
+                ("occur", "1"),
+            ]
+            .map(|(a, b)| (a.to_string(), b.to_string())),
+        );
+        let info = PatchInfo::from_items("test", &items, None).unwrap();
+        assert_eq!(
+            info.signature,
+            Signature::from_string("test", "80020000C701E001", "00110000").unwrap()
+        );
+
+        let mut bad = items.clone();
+        bad.insert("pattern".into(), "80 02 0".into());
+        assert!(PatchInfo::from_items("test", &bad, None).is_err());
+
+        let mut neither = items.clone();
+        neither.shift_remove("pattern");
+        assert!(PatchInfo::from_items("test", &neither, None).is_err());
+    }
+
+    #[test]
+    fn search_window_test() {
+        let items = Items::from_iter(
+            [
+                ("modfile", "game.exe"),
+                ("sig", "8002"),
+                ("sigwild", "00"),
+                ("xoffset", "0"),
+                ("occur", "all"),
+                ("searchstart", "0x2"),
+                ("searchend", "75%"),
+            ]
+            .map(|(a, b)| (a.to_string(), b.to_string())),
+        );
+        let info = PatchInfo::from_items("test", &items, None).unwrap();
+        assert_eq!(info.searchstart, Some(FilePosition::Offset(2)));
+        assert_eq!(info.searchend, Some(FilePosition::Percent(75.0)));
+
+        let data = [0x80, 0x02, 0x80, 0x02, 0x80, 0x02, 0x80, 0x02];
+        let offsets = info.find_patch_offsets(&data, 0).unwrap();
+        assert_eq!(offsets.iter().map(|x| x.index).collect::<Vec<_>>(), [2, 4]);
+        assert_eq!(info.count_matches(&data), 2);
+
+        let empty = PatchInfo {
+            searchstart: Some(FilePosition::Percent(90.0)),
+            ..info.clone()
+        };
+        assert!(empty.find_patch_offsets(&data, 0).is_err());
+
+        let mut bad = items.clone();
+        bad.insert("searchend".into(), "most".into());
+        assert!(PatchInfo::from_items("test", &bad, None).is_err());
+    }
+
+    #[test]
+    fn align_test() {
+        let items = Items::from_iter(
+            [
+                ("modfile", "game.exe"),
+                ("sig", "8002"),
+                ("sigwild", "00"),
+                ("xoffset", "0"),
+                ("occur", "1"),
+                ("align", "4"),
+            ]
+            .map(|(a, b)| (a.to_string(), b.to_string())),
+        );
+        let info = PatchInfo::from_items("test", &items, None).unwrap();
+        assert_eq!(info.align, NonZeroUsize::new(4));
+
+        let data = [0x00, 0x80, 0x02, 0x00, 0x80, 0x02, 0x80, 0x02];
+        let offsets = info.find_patch_offsets(&data, 0).unwrap();
+        assert_eq!(offsets[0].index, 4);
+        assert_eq!(info.count_matches(&data), 1);
+
+        let unaligned = [0x00, 0x80, 0x02, 0x00, 0x00, 0x00, 0x80, 0x02];
+        assert!(info.find_patch_offsets(&unaligned, 0).is_err());
+
+        let mut zero = items.clone();
+        zero.insert("align".into(), "0".into());
+        assert!(PatchInfo::from_items("test", &zero, None).is_err());
+    }
+
+    #[test]
+    fn occurindex_test() {
+        let items = Items::from_iter(
+            [
+                ("modfile", "game.exe"),
+                ("sig", "8002"),
+                ("sigwild", "00"),
+                ("xoffset", "0"),
+                ("occurindex", "3"),
+            ]
+            .map(|(a, b)| (a.to_string(), b.to_string())),
+        );
+        let info = PatchInfo::from_items("test", &items, None).unwrap();
+        assert_eq!((info.occur, info.occurfirst), (1, Some(3)));
+
+        let data = [0x80, 0x02, 0x80, 0x02, 0x80, 0x02, 0x80, 0x02];
+        let offsets = info.find_patch_offsets(&data, 0).unwrap();
+        assert_eq!(offsets.iter().map(|x| x.index).collect::<Vec<_>>(), [4]);
+
+        let mut skip = items.clone();
+        skip.shift_remove("occurindex");
+        skip.insert("skip".into(), "1".into());
+        skip.insert("occur".into(), "2".into());
+        let info = PatchInfo::from_items("test", &skip, None).unwrap();
+        assert_eq!((info.occur, info.occurfirst), (2, Some(2)));
+
+        // `occur` is still required without occurindex
+        skip.shift_remove("occur");
+        assert!(PatchInfo::from_items("test", &skip, None).is_err());
+
+        let mut both = items.clone();
+        both.insert("occurfirst".into(), "2".into());
+        assert!(PatchInfo::from_items("test", &both, None).is_err());
+    }
+
+    #[test]
+    fn optional_test() {
+        let items = Items::from_iter(
+            [
+                ("modfile", "game.exe"),
+                ("sig", "8002"),
+                ("sigwild", "00"),
+                ("xoffset", "0"),
+                ("occur", "1"),
+                ("optional", "1"),
+                ("label", "Remove letterboxing"),
+            ]
+            .map(|(a, b)| (a.to_string(), b.to_string())),
+        );
+        let info = PatchInfo::from_items("test", &items, None).unwrap();
+        assert!(info.optional);
+        assert_eq!(info.label.as_deref(), Some("Remove letterboxing"));
+
+        let mut unlabelled = items.clone();
+        unlabelled.shift_remove("label");
+        assert!(PatchInfo::from_items("test", &unlabelled, None).is_err());
+
+        unlabelled.insert("optional".into(), "0".into());
+        assert!(
+            !PatchInfo::from_items("test", &unlabelled, None)
+                .unwrap()
+                .optional
+        );
+    }
+
+    #[test]
+    fn valueslot_test() {
+        let items = Items::from_iter(
+            [
+                ("modfile", "game.exe"),
+                ("sig", "0000B442"),
+                ("sigwild", "0000"),
+                ("xoffset", "0"),
+                ("valuetype", "f32"),
+                ("valueslot", "1"),
+                ("occur", "1"),
+            ]
+            .map(|(a, b)| (a.to_string(), b.to_string())),
+        );
+        let info = PatchInfo::from_items("test", &items, None).unwrap();
+        assert_eq!(info.valueslot, NonZeroUsize::new(1));
+
+        let mut data = 90f32.to_le_bytes();
+        let strategy = PatchStrategy {
+            iteration: 0,
+            modfile: "game.exe",
+            offsets: info.find_patch_offsets(&data, 0).unwrap(),
+            modify: None,
+            replace: None,
+            requested: info.occur,
+            found: 1,
+            slot: Some(110.0),
+        };
+        strategy.patch_data(&mut data, 1920, 1080).unwrap();
+        assert_eq!(data, 110f32.to_le_bytes());
+
+        let mut both = items.clone();
+        both.insert("yoffset".into(), "0".into());
+        assert!(PatchInfo::from_items("test", &both, None).is_err());
+        both.insert("valueslot".into(), "0".into());
+        assert!(PatchInfo::from_items("test", &both, None).is_err());
+    }
+
+    #[test]
+    fn offset_range_test() {
+        let items = Items::from_iter(
+            [
+                ("modfile", "game.exe"),
+                ("sig", "8002"),
+                ("sigwild", "00"),
+                ("xoffset", "-65536"),
+                ("yoffset", "65534"),
+                ("occur", "1"),
+            ]
+            .map(|(a, b)| (a.to_string(), b.to_string())),
+        );
+        assert!(PatchInfo::from_items("test", &items, None).is_ok());
+
+        let mut far = items.clone();
+        far.insert("yoffset".into(), "65535".into());
+        assert_eq!(
+            PatchInfo::from_items("test", &far, None),
+            Err(Error::config_field_parse(
+                "test",
+                "yoffset",
+                "65535 + 2 byte(s) is more than 65536 bytes from the sig"
+            ))
+        );
+
+        let mut huge = items.clone();
+        huge.insert("xoffset".into(), i64::MAX.to_string());
+        assert!(PatchInfo::from_items("test", &huge, None).is_err());
+
+        let mut mismatch = items.clone();
+        mismatch.insert("sigwild".into(), "0".into());
+        assert!(PatchInfo::from_items("test", &mismatch, None).is_err());
+    }
+
+    #[test]
+    fn negative_offset_test() {
+        let items = Items::from_iter(
+            [
+                ("modfile", "game.exe"),
+                ("sig", "C701"),
+                ("sigwild", "00"),
+                ("xoffset", "-4"),
+                ("yoffset", "-2"),
+                ("occur", "1"),
+            ]
+            .map(|(a, b)| (a.to_string(), b.to_string())),
+        );
+        let info = PatchInfo::from_items("test", &items, None).unwrap();
+        assert_eq!(info.xoffset, [-4]);
+
+        let mut data = [0x80, 0x02, 0xE0, 0x01, 0xC7, 0x01];
+        let offsets = info.find_patch_offsets(&data, 0).unwrap();
+        apply_patches(&mut data, &offsets, 1920, 1080).unwrap();
+        assert_eq!(data, [0x80, 0x07, 0x38, 0x04, 0xC7, 0x01]);
+    }
+
+    #[test]
+    fn occur_all_test() {
+        let items = Items::from_iter(
+            [
+                ("modfile", "game.exe"),
+                ("sig", "8002"),
+                ("sigwild", "00"),
+                ("xoffset", "0"),
+                ("occur", "All"),
+            ]
+            .map(|(a, b)| (a.to_string(), b.to_string())),
+        );
+        let info = PatchInfo::from_items("test", &items, None).unwrap();
+        assert_eq!(info.occur, PatchInfo::ALL);
+
+        let mut data = [0x80, 0x02, 0x00, 0x80, 0x02, 0x00, 0x80, 0x02];
+        let offsets = info.find_patch_offsets(&data, 0).unwrap();
+        assert_eq!(offsets.len(), 3);
+
+        let strategy = PatchStrategy {
+            iteration: 0,
+            modfile: "game.exe",
+            offsets: offsets.clone(),
+            modify: None,
+            replace: None,
+            requested: info.occur,
+            found: info.count_matches(&data),
+            slot: None,
+        };
+        assert_eq!(
+            strategy.status().to_string(),
+            "patched 3/3 occurrence(s) of 3 found at 0x0, 0x3, 0x6"
+        );
+
+        let with_first = PatchInfo {
+            occurfirst: Some(3),
+            ..info.clone()
+        };
+        assert_eq!(with_first.find_patch_offsets(&data, 0).unwrap().len(), 1);
+
+        apply_patches(&mut data, &offsets, 1920, 1080).unwrap();
+        assert_eq!(data, [0x80, 0x07, 0x00, 0x80, 0x07, 0x00, 0x80, 0x07]);
+
+        assert!(info.find_patch_offsets(&[0x00; 8], 0).is_err());
     }
 }
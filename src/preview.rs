@@ -0,0 +1,169 @@
+use std::ops::Range;
+
+use crate::patch_info::PatchStrategy;
+
+/// Bytes shown either side of the writes at a match
+pub const CONTEXT: usize = 8;
+
+/// Bytes per row of a hex dump
+pub const ROW: usize = 16;
+
+/// Bytes around one match, before and after patching
+///
+/// Displays as a side by side hex dump, with `^^` under every byte written
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Preview {
+    /// Position of the patch in its section
+    pub iteration: usize,
+    pub modfile: String,
+    /// Start of the signature match
+    pub index: usize,
+    /// File offset of the first byte shown
+    pub start: usize,
+    pub before: Vec<u8>,
+    pub after: Vec<u8>,
+    /// File offsets written by the patch
+    pub writes: Vec<Range<usize>>,
+}
+impl Preview {
+    pub fn is_written(&self, offset: usize) -> bool {
+        self.writes.iter().any(|x| x.contains(&offset))
+    }
+
+    /// File offset, old bytes and new bytes of each row of the dump
+    pub fn rows(&self) -> impl Iterator<Item = (usize, &[u8], &[u8])> {
+        self.before
+            .chunks(ROW)
+            .zip(self.after.chunks(ROW))
+            .enumerate()
+            .map(|(i, (before, after))| (self.start + i * ROW, before, after))
+    }
+}
+impl std::fmt::Display for Preview {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "patch {} ({}): match at 0x{:X}",
+            self.iteration, self.modfile, self.index
+        )?;
+
+        let hex = |bytes: &[u8]| {
+            bytes
+                .iter()
+                .map(|x| format!("{x:02X}"))
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+
+        for (offset, before, after) in self.rows() {
+            writeln!(f, "{offset:08X}  {:47}  |  {}", hex(before), hex(after))?;
+
+            if (offset..offset + before.len()).any(|x| self.is_written(x)) {
+                let marks = (0..before.len())
+                    .map(|i| match self.is_written(offset + i) {
+                        true => "^^",
+                        false => "  ",
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                writeln!(f, "{:8}  {marks:47}  |  {}", "", marks.trim_end())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Dump of every match of `strategy`, `before` and `after` being the whole
+/// file before and after every patch of the section is written
+pub fn previews(strategy: &PatchStrategy, before: &[u8], after: &[u8]) -> Vec<Preview> {
+    strategy
+        .offsets
+        .iter()
+        .map(|offsets| {
+            let writes = strategy.writes(offsets);
+
+            let first = writes
+                .iter()
+                .map(|x| x.start)
+                .fold(offsets.index, usize::min);
+            let last = writes
+                .iter()
+                .map(|x| x.end)
+                .fold(offsets.index + 1, usize::max);
+
+            let start = first.saturating_sub(CONTEXT) / ROW * ROW;
+            let end = (last + CONTEXT).next_multiple_of(ROW).min(before.len());
+
+            Preview {
+                iteration: strategy.iteration,
+                modfile: strategy.modfile.to_string(),
+                index: offsets.index,
+                start,
+                before: before[start..end].to_vec(),
+                after: after[start..end].to_vec(),
+                writes,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{config::Items, patch_info::PatchInfo};
+
+    #[test]
+    fn preview_test() {
+        let items = Items::from_iter(
+            [
+                ("modfile", "game.exe"),
+                ("sig", "80020000E0010000"),
+                ("sigwild", "00000000"),
+                ("xoffset", "0"),
+                ("yoffset", "4"),
+                ("occur", "1"),
+            ]
+            .map(|(k, v)| (k.to_string(), v.to_string())),
+        );
+        let patch = PatchInfo::from_items("test", &items, None).unwrap();
+
+        let mut before = vec![0xCC; 64];
+        before[20..28].copy_from_slice(&[0x80, 0x02, 0x00, 0x00, 0xE0, 0x01, 0x00, 0x00]);
+
+        let strategy = PatchStrategy {
+            iteration: 0,
+            modfile: &patch.modfile,
+            offsets: patch.find_patch_offsets(&before, 0).unwrap(),
+            modify: None,
+            replace: None,
+            requested: patch.occur,
+            found: 1,
+            slot: None,
+        };
+        let mut after = before.clone();
+        strategy.patch_data(&mut after, 1920, 1080).unwrap();
+
+        let previews = previews(&strategy, &before, &after);
+        assert_eq!(previews.len(), 1);
+
+        let preview = &previews[0];
+        assert_eq!(
+            (preview.index, preview.start, preview.before.len()),
+            (20, 0, 48)
+        );
+        assert!(preview.is_written(21) && !preview.is_written(22));
+        assert_eq!(
+            preview.to_string().lines().collect::<Vec<_>>()[2..4],
+            [
+                concat!(
+                    "00000010  CC CC CC CC 80 02 00 00 E0 01 00 00 CC CC CC CC",
+                    "  |  CC CC CC CC 80 07 00 00 38 04 00 00 CC CC CC CC"
+                ),
+                concat!(
+                    "                      ^^ ^^       ^^ ^^                    ",
+                    "|              ^^ ^^       ^^ ^^"
+                ),
+            ]
+        );
+    }
+}
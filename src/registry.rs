@@ -0,0 +1,536 @@
+use crate::{config::Items, error::Error};
+
+/// Kind of registry value written, from `regtype`
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum RegType {
+    #[default]
+    Dword,
+    Qword,
+    /// Plain string
+    Sz,
+}
+impl RegType {
+    /// Name Windows uses
+    pub fn reg_name(self) -> &'static str {
+        match self {
+            Self::Dword => "REG_DWORD",
+            Self::Qword => "REG_QWORD",
+            Self::Sz => "REG_SZ",
+        }
+    }
+}
+impl std::str::FromStr for RegType {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().to_ascii_lowercase();
+        match s.strip_prefix("reg_").unwrap_or(&s) {
+            "dword" => Ok(Self::Dword),
+            "qword" => Ok(Self::Qword),
+            "sz" | "string" => Ok(Self::Sz),
+            _ => Err(Error::value_error(format!("Unknown registry type: {s}"))),
+        }
+    }
+}
+impl std::fmt::Display for RegType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Dword => "dword",
+            Self::Qword => "qword",
+            Self::Sz => "sz",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Which registry of 64-bit Windows is used, from the section's `regview`.
+/// 32-bit programs see their own copy of `HKLM\Software`, under `WOW6432Node`
+#[derive(Debug, Default, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum RegView {
+    /// The one 32-bit games read, like most that need patching
+    #[default]
+    Bits32,
+    Bits64,
+}
+impl std::str::FromStr for RegView {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "32" => Ok(Self::Bits32),
+            "64" => Ok(Self::Bits64),
+            x => Err(Error::value_error(format!(
+                "Unknown registry view: {x}, expected 32 or 64"
+            ))),
+        }
+    }
+}
+impl std::fmt::Display for RegView {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let bits = match self {
+            Self::Bits32 => "32",
+            Self::Bits64 => "64",
+        };
+        f.write_str(bits)
+    }
+}
+
+/// Value as the registry stores it, so any type is put back as it was
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct RawValue {
+    /// Number of the type, named in [`RawValue::TYPES`]
+    pub regtype: u32,
+    pub bytes: Vec<u8>,
+}
+impl RawValue {
+    /// Name of each type, by number
+    pub const TYPES: [&str; 12] = [
+        "REG_NONE",
+        "REG_SZ",
+        "REG_EXPAND_SZ",
+        "REG_BINARY",
+        "REG_DWORD",
+        "REG_DWORD_BIG_ENDIAN",
+        "REG_LINK",
+        "REG_MULTI_SZ",
+        "REG_RESOURCE_LIST",
+        "REG_FULL_RESOURCE_DESCRIPTOR",
+        "REG_RESOURCE_REQUIREMENTS_LIST",
+        "REG_QWORD",
+    ];
+
+    /// e.g. `REG_DWORD`
+    pub fn type_name(&self) -> &'static str {
+        Self::TYPES
+            .get(self.regtype as usize)
+            .copied()
+            .unwrap_or("REG_NONE")
+    }
+
+    /// Number of the type named `name`
+    pub fn parse_type(name: &str) -> Option<u32> {
+        Self::TYPES
+            .iter()
+            .position(|x| x.eq_ignore_ascii_case(name.trim()))
+            .map(|x| x as u32)
+    }
+
+    fn new(name: &str, bytes: Vec<u8>) -> Self {
+        Self {
+            regtype: Self::parse_type(name).unwrap_or_default(),
+            bytes,
+        }
+    }
+
+    /// `data` stored as `regtype`, `None` if it isn't a number that fits
+    pub fn encode(regtype: RegType, data: &str) -> Option<Self> {
+        let bytes = match regtype {
+            RegType::Dword => u32::try_from(number(data)?).ok()?.to_le_bytes().to_vec(),
+            RegType::Qword => number(data)?.to_le_bytes().to_vec(),
+            RegType::Sz => wide(data),
+        };
+        Some(Self::new(regtype.reg_name(), bytes))
+    }
+
+    /// Value as `reg query` printed it, e.g. `REG_DWORD` and `0x780`, which
+    /// manifests kept before values were kept as they're stored. `regtype`
+    /// may also be the short name of a [`RegType`], like `dword`
+    pub fn from_text(regtype: &str, data: &str) -> Option<Self> {
+        let name = match regtype.parse::<RegType>() {
+            Ok(x) => x.reg_name().to_string(),
+            Err(_) => regtype.trim().to_ascii_uppercase(),
+        };
+        let bytes = match name.as_str() {
+            "REG_DWORD" | "REG_QWORD" | "REG_SZ" => {
+                return Self::encode(name.parse().ok()?, data);
+            }
+            "REG_EXPAND_SZ" => wide(data),
+            "REG_MULTI_SZ" => {
+                let mut bytes = data.split(r"\0").flat_map(wide).collect::<Vec<_>>();
+                bytes.extend([0, 0]);
+                bytes
+            }
+            "REG_BINARY" => parse_hex(data)?,
+            _ => return None,
+        };
+        Some(Self::new(&name, bytes))
+    }
+}
+
+/// Decimal or `0x` hex number
+fn number(x: &str) -> Option<u64> {
+    let x = x.trim();
+    match x.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+        None => x.parse().ok(),
+    }
+}
+
+/// `text` as a string value is stored, UTF-16 ending with a nul
+fn wide(text: &str) -> Vec<u8> {
+    text.encode_utf16()
+        .chain([0])
+        .flat_map(u16::to_le_bytes)
+        .collect()
+}
+
+/// Bytes of `text` like `01a0ff`
+pub fn parse_hex(text: &str) -> Option<Vec<u8>> {
+    let text = text.trim().as_bytes();
+    if !text.len().is_multiple_of(2) {
+        return None;
+    }
+    text.chunks(2)
+        .map(|x| u8::from_str_radix(std::str::from_utf8(x).ok()?, 16).ok())
+        .collect()
+}
+
+/// Registry value set alongside the file patches of a section, on Windows only
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct RegistryPatch {
+    /// Full key including the root, e.g. `HKCU\Software\Game\Video`
+    pub key: String,
+    /// Name of the value under `key`
+    pub value: String,
+    pub regtype: RegType,
+    /// `{width}` and `{height}` are replaced with the resolution
+    pub data: String,
+    /// From the section's `regview`, the same for each of its registry patches
+    pub view: RegView,
+}
+impl RegistryPatch {
+    const ROOTS: [&str; 6] = [
+        "HKCU",
+        "HKLM",
+        "HKCR",
+        "HKEY_CURRENT_USER",
+        "HKEY_LOCAL_MACHINE",
+        "HKEY_CLASSES_ROOT",
+    ];
+
+    pub fn from_items(section: &str, items: &Items, index: Option<u8>) -> Result<Self, Error> {
+        let prefix = index.map_or(String::new(), |x| format!("p{x}"));
+        let get = |field: &'static str| {
+            items
+                .get(&format!("{prefix}{field}"))
+                .cloned()
+                .ok_or(Error::config_missing_field(section, field))
+        };
+
+        let key = get("regkey")?;
+        let root = key.split('\\').next().unwrap_or_default();
+        if !Self::ROOTS.iter().any(|x| x.eq_ignore_ascii_case(root)) {
+            return Err(Error::config_field_parse(
+                section,
+                "regkey",
+                format!("Unknown root key: {root}"),
+            ));
+        }
+
+        let regtype = match get("regtype") {
+            Ok(x) => x
+                .parse()
+                .map_err(|e: Error| Error::config_field_parse(section, "regtype", e.to_string()))?,
+            Err(_) => RegType::default(),
+        };
+        let view = match items.get("regview") {
+            Some(x) => x
+                .parse()
+                .map_err(|e: Error| Error::config_field_parse(section, "regview", e.to_string()))?,
+            None => RegView::default(),
+        };
+
+        Ok(Self {
+            key,
+            value: get("regvalue")?,
+            regtype,
+            data: get("regdata")?,
+            view,
+        })
+    }
+
+    /// `data` with the resolution filled in, checked against `regtype`
+    pub fn data(&self, width: u16, height: u16) -> Result<String, Error> {
+        self.encode(width, height).map(|x| x.0)
+    }
+
+    /// [`Self::data`] and the value it's stored as
+    fn encode(&self, width: u16, height: u16) -> Result<(String, RawValue), Error> {
+        let data = self
+            .data
+            .replace("{width}", &width.to_string())
+            .replace("{height}", &height.to_string());
+
+        match RawValue::encode(self.regtype, &data) {
+            Some(raw) => Ok((data, raw)),
+            None => Err(Error::value_error(format!(
+                "{data} isn't a valid {} for {}",
+                self.regtype.reg_name(),
+                self.value
+            ))),
+        }
+    }
+
+    /// Human readable description of the write
+    pub fn describe(&self, width: u16, height: u16) -> String {
+        let data = self
+            .data(width, height)
+            .unwrap_or_else(|_| self.data.clone());
+        format!("{}\\{} = {data}", self.key, self.value)
+    }
+}
+
+/// Value a registry patch replaced, to put back on restore
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct RegistryBackup {
+    pub key: String,
+    pub value: String,
+    pub view: RegView,
+    /// What was there, kept as it was stored even for types patches can't
+    /// write. `None` if the value didn't exist, so restoring deletes it
+    pub previous: Option<RawValue>,
+}
+impl RegistryBackup {
+    /// Full path of the value, e.g. `HKCU\Software\Game\Video\Width`
+    pub fn name(&self) -> String {
+        format!("{}\\{}", self.key, self.value)
+    }
+
+    /// Whether this is the value `patch` writes
+    pub fn is_for(&self, patch: &RegistryPatch) -> bool {
+        self.key.eq_ignore_ascii_case(&patch.key)
+            && self.value.eq_ignore_ascii_case(&patch.value)
+            && self.view == patch.view
+    }
+
+    /// Read the current value of `patch`
+    pub fn take(patch: &RegistryPatch) -> Result<Self, Error> {
+        Ok(Self {
+            key: patch.key.clone(),
+            value: patch.value.clone(),
+            view: patch.view,
+            previous: read(&patch.key, &patch.value, patch.view)?,
+        })
+    }
+
+    pub fn restore(&self) -> Result<(), Error> {
+        match &self.previous {
+            Some(raw) => write(&self.key, &self.value, self.view, raw),
+            None => delete(&self.key, &self.value, self.view),
+        }
+    }
+}
+
+/// Write `patch`, with the resolution filled in
+pub fn apply(patch: &RegistryPatch, width: u16, height: u16) -> Result<(), Error> {
+    let (_, raw) = patch.encode(width, height)?;
+    write(&patch.key, &patch.value, patch.view, &raw)
+}
+
+#[cfg(windows)]
+mod native {
+    use std::io::ErrorKind;
+
+    use winreg::{RegKey, RegValue, enums::*};
+
+    use super::{RawValue, RegView};
+    use crate::error::Error;
+
+    /// Types as winreg has them, by number
+    const TYPES: [RegType; 12] = [
+        REG_NONE,
+        REG_SZ,
+        REG_EXPAND_SZ,
+        REG_BINARY,
+        REG_DWORD,
+        REG_DWORD_BIG_ENDIAN,
+        REG_LINK,
+        REG_MULTI_SZ,
+        REG_RESOURCE_LIST,
+        REG_FULL_RESOURCE_DESCRIPTOR,
+        REG_RESOURCE_REQUIREMENTS_LIST,
+        REG_QWORD,
+    ];
+
+    /// Open `key` with `access`, creating it if `create`. `None` if it
+    /// doesn't exist
+    fn open(key: &str, view: RegView, access: u32, create: bool) -> Result<Option<RegKey>, Error> {
+        let (root, path) = key.split_once('\\').unwrap_or((key, ""));
+        let root = match root.to_ascii_uppercase().as_str() {
+            "HKCU" | "HKEY_CURRENT_USER" => HKEY_CURRENT_USER,
+            "HKLM" | "HKEY_LOCAL_MACHINE" => HKEY_LOCAL_MACHINE,
+            "HKCR" | "HKEY_CLASSES_ROOT" => HKEY_CLASSES_ROOT,
+            _ => return Err(Error::value_error(format!("Unknown root key: {root}"))),
+        };
+        let access = access
+            | match view {
+                RegView::Bits32 => KEY_WOW64_32KEY,
+                RegView::Bits64 => KEY_WOW64_64KEY,
+            };
+
+        let root = RegKey::predef(root);
+        let opened = match create {
+            true => root.create_subkey_with_flags(path, access).map(|x| x.0),
+            false => root.open_subkey_with_flags(path, access),
+        };
+        match opened {
+            Ok(x) => Ok(Some(x)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(failed(key, e)),
+        }
+    }
+
+    fn failed(name: &str, e: std::io::Error) -> Error {
+        Error::state_error(format!("{name}: {e}"))
+    }
+
+    pub fn read(key: &str, value: &str, view: RegView) -> Result<Option<RawValue>, Error> {
+        let Some(opened) = open(key, view, KEY_QUERY_VALUE, false)? else {
+            return Ok(None);
+        };
+        match opened.get_raw_value(value) {
+            Ok(x) => Ok(Some(RawValue {
+                regtype: x.vtype as u32,
+                bytes: x.bytes,
+            })),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(failed(&format!("{key}\\{value}"), e)),
+        }
+    }
+
+    pub fn write(key: &str, value: &str, view: RegView, raw: &RawValue) -> Result<(), Error> {
+        let name = format!("{key}\\{value}");
+        let vtype = TYPES
+            .get(raw.regtype as usize)
+            .cloned()
+            .ok_or_else(|| Error::value_error(format!("{name}: Unknown type {}", raw.regtype)))?;
+        let opened = open(key, view, KEY_SET_VALUE, true)?
+            .ok_or_else(|| Error::state_error(format!("Couldn't create {key}")))?;
+        let raw = RegValue {
+            bytes: raw.bytes.clone(),
+            vtype,
+        };
+        opened
+            .set_raw_value(value, &raw)
+            .map_err(|e| failed(&name, e))
+    }
+
+    pub fn delete(key: &str, value: &str, view: RegView) -> Result<(), Error> {
+        let Some(opened) = open(key, view, KEY_SET_VALUE, false)? else {
+            return Ok(());
+        };
+        match opened.delete_value(value) {
+            Err(e) if e.kind() != ErrorKind::NotFound => Err(failed(&format!("{key}\\{value}"), e)),
+            _ => Ok(()),
+        }
+    }
+}
+#[cfg(windows)]
+use native::{delete, read, write};
+
+#[cfg(not(windows))]
+fn unsupported() -> Error {
+    Error::value_error("Registry patches only apply on Windows")
+}
+#[cfg(not(windows))]
+fn read(_key: &str, _value: &str, _view: RegView) -> Result<Option<RawValue>, Error> {
+    Err(unsupported())
+}
+#[cfg(not(windows))]
+fn write(_key: &str, _value: &str, _view: RegView, _raw: &RawValue) -> Result<(), Error> {
+    Err(unsupported())
+}
+#[cfg(not(windows))]
+fn delete(_key: &str, _value: &str, _view: RegView) -> Result<(), Error> {
+    Err(unsupported())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registry_test() {
+        let items = Items::from_iter(
+            [
+                ("p2regkey", r"HKCU\Software\Game\Video"),
+                ("p2regvalue", "Width"),
+                ("p2regdata", "{width}"),
+            ]
+            .map(|(k, v)| (k.to_string(), v.to_string())),
+        );
+        let patch = RegistryPatch::from_items("test", &items, Some(2)).unwrap();
+        assert_eq!(patch.regtype, RegType::Dword);
+        assert_eq!(patch.data(1920, 1080).unwrap(), "1920");
+        assert_eq!(
+            patch.describe(1920, 1080),
+            r"HKCU\Software\Game\Video\Width = 1920"
+        );
+
+        let mut bad = items.clone();
+        bad.insert("p2regkey".into(), r"HKXX\Software".into());
+        assert!(RegistryPatch::from_items("test", &bad, Some(2)).is_err());
+        bad.insert("p2regkey".into(), r"HKCU\Software".into());
+        bad.insert("p2regtype".into(), "binary".into());
+        assert!(RegistryPatch::from_items("test", &bad, Some(2)).is_err());
+
+        let text = RegistryPatch {
+            data: "{width}x{height}".into(),
+            ..patch.clone()
+        };
+        assert!(text.data(1920, 1080).is_err());
+        let text = RegistryPatch {
+            regtype: RegType::Sz,
+            ..text
+        };
+        assert_eq!(text.data(1920, 1080).unwrap(), "1920x1080");
+
+        assert_eq!(
+            RegistryPatch::from_items("test", &items, Some(2))
+                .unwrap()
+                .view,
+            RegView::Bits32
+        );
+        let mut wide = items.clone();
+        wide.insert("regview".into(), "64".into());
+        assert_eq!(
+            RegistryPatch::from_items("test", &wide, Some(2))
+                .unwrap()
+                .view,
+            RegView::Bits64
+        );
+        wide.insert("regview".into(), "16".into());
+        assert!(RegistryPatch::from_items("test", &wide, Some(2)).is_err());
+    }
+
+    #[test]
+    fn raw_value_test() {
+        let raw = |regtype: &str, data: &str| RawValue::from_text(regtype, data).unwrap();
+        assert_eq!(raw("REG_DWORD", "0x780").bytes, [0x80, 0x07, 0, 0]);
+        assert_eq!(raw("qword", "1920").bytes, [0x80, 0x07, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(raw("dword", "0x780").type_name(), "REG_DWORD");
+        assert_eq!(RawValue::from_text("REG_DWORD", "0x100000000"), None);
+
+        // Strings are UTF-16 ending with a nul, so non-ASCII text and runs
+        // of spaces are kept as they are
+        let path = raw("REG_EXPAND_SZ", "%ProgramFiles%\\Jeu    é");
+        assert_eq!(path.type_name(), "REG_EXPAND_SZ");
+        assert_eq!(
+            path.bytes,
+            "%ProgramFiles%\\Jeu    é\0"
+                .encode_utf16()
+                .flat_map(u16::to_le_bytes)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            raw("REG_MULTI_SZ", r"a\0b").bytes,
+            [b'a', 0, 0, 0, b'b', 0, 0, 0, 0, 0]
+        );
+        assert_eq!(raw("REG_BINARY", "01a0FF").bytes, [0x01, 0xA0, 0xFF]);
+        assert_eq!(RawValue::from_text("REG_BINARY", "0"), None);
+        assert_eq!(RawValue::from_text("REG_LINK", ""), None);
+
+        assert_eq!(RawValue::parse_type("reg_qword"), Some(11));
+        assert_eq!(RawValue::parse_type("REG_WORD"), None);
+    }
+}
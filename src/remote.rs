@@ -0,0 +1,317 @@
+use std::{
+    cmp::Ordering,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    backup,
+    config::{Config, Format, LoadOptions},
+    encoding::Encoding,
+    error::Error,
+};
+
+/// `~/.local/share/uniws/configs` (or the platform equivalent)
+pub fn cache_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|x| x.join("uniws").join("configs"))
+}
+
+/// Format of the config at `url`, by the extension of its path
+fn format(url: &str) -> Format {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    Format::from_path(Path::new(path))
+}
+
+/// Where the config at `url` is kept in `dir`, named after the URL so
+/// each one has its own copy, with the extension of its format
+pub fn cache_path(dir: &Path, url: &str) -> PathBuf {
+    let hash = backup::sha256_hex(url.as_bytes());
+    dir.join(format!("{}.{}", &hash[..16], format(url).extension()))
+}
+
+/// The copy `path` replaced, see [`save`]
+pub fn previous_path(path: &Path) -> PathBuf {
+    path.with_extension(format!("previous.{}", Format::from_path(path).extension()))
+}
+
+/// Order of two `Apps.version` values, comparing each dotted part as a number
+/// when it is one, so `1.10` is newer than `1.9`
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let mut a = a.trim().split('.');
+    let mut b = b.trim().split('.');
+    loop {
+        let ordering = match (a.next(), b.next()) {
+            (None, None) => return Ordering::Equal,
+            (Some(_), None) => Ordering::Greater,
+            (None, Some(_)) => Ordering::Less,
+            (Some(a), Some(b)) => match (a.parse::<u64>(), b.parse::<u64>()) {
+                (Ok(a), Ok(b)) => a.cmp(&b),
+                _ => a.cmp(b),
+            },
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+}
+
+/// Contents of the file at `url`, which must be `https://`, in whatever
+/// encoding [`Encoding::detect`] finds
+pub async fn fetch(url: &str) -> Result<String, Error> {
+    Encoding::Auto.decode(&fetch_bytes(url).await?)
+}
+
+/// Bytes of the file at `url`, which must be `https://`
+pub async fn fetch_bytes(url: &str) -> Result<Vec<u8>, Error> {
+    if !url.starts_with("https://") {
+        return Err(Error::DownloadError(format!(
+            "{url} isn't an https:// address"
+        )));
+    }
+
+    let error = |e: reqwest::Error| Error::DownloadError(e.to_string());
+    let bytes = reqwest::get(url)
+        .await
+        .and_then(|x| x.error_for_status())
+        .map_err(error)?
+        .bytes()
+        .await
+        .map_err(error)?;
+    Ok(bytes.to_vec())
+}
+
+/// A config downloaded and checked to load
+#[derive(Debug, Clone)]
+pub struct Fetched {
+    pub url: String,
+    pub text: String,
+    pub config: Config,
+}
+
+/// Download the config at `url`, failing if it doesn't load
+pub async fn fetch_config(url: String) -> Result<Fetched, Error> {
+    let url = url.trim().to_string();
+    let text = fetch(&url).await?;
+    let config = Config::load(&text, format(&url), LoadOptions::default())?;
+    Ok(Fetched { url, text, config })
+}
+
+/// Save `fetched` in `dir`, keeping the copy it replaces for [`rollback`]
+pub fn save(dir: &Path, fetched: &Fetched) -> Result<PathBuf, Error> {
+    let path = cache_path(dir, &fetched.url);
+    std::fs::create_dir_all(dir)?;
+    if path.exists() {
+        std::fs::rename(&path, previous_path(&path))?;
+    }
+    std::fs::write(&path, &fetched.text)?;
+    Ok(path)
+}
+
+/// Put back the copy of the config at `url` that the last [`save`] replaced.
+/// The two swap places, so rolling back again undoes it
+pub fn rollback(dir: &Path, url: &str) -> Result<PathBuf, Error> {
+    let path = cache_path(dir, url.trim());
+    let previous = previous_path(&path);
+    if !previous.exists() {
+        return Err(Error::state_error(
+            "There is no previous version to go back to",
+        ));
+    }
+
+    let swap = path.with_extension(format!("swap.{}", Format::from_path(&path).extension()));
+    std::fs::rename(&path, &swap)?;
+    std::fs::rename(&previous, &path)?;
+    std::fs::rename(&swap, &previous)?;
+    Ok(path)
+}
+
+/// Fetch the config at `url` into [`cache_dir`], returning where it was saved
+///
+/// Nothing is saved unless it loads as a config, so a broken download doesn't
+/// replace a good copy
+pub async fn download_config(url: String) -> Result<PathBuf, Error> {
+    let dir = cache_dir().ok_or(Error::state_error("No folder to save downloads in"))?;
+    let fetched = fetch_config(url).await?;
+    save(&dir, &fetched)
+}
+
+/// Outcome of [`sync`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Synced {
+    /// The saved copy is as new as the remote one, at this version
+    UpToDate(String),
+    Updated {
+        path: PathBuf,
+        /// Version of the copy replaced, if there was one that loaded
+        from: Option<String>,
+        to: String,
+    },
+}
+
+/// The copy of the config at `url` saved in `dir`, if it loads
+fn cached(dir: &Path, url: &str) -> Option<Config> {
+    let text = std::fs::read_to_string(cache_path(dir, url)).ok()?;
+    Config::load(&text, format(url), LoadOptions::default()).ok()
+}
+
+/// Save `fetched` in `dir` if it's newer than the copy already there
+pub fn update(dir: &Path, fetched: &Fetched) -> Result<Synced, Error> {
+    let local = cached(dir, &fetched.url).map(|x| x.apps.version);
+    let remote = &fetched.config.apps.version;
+
+    match &local {
+        Some(local) if compare_versions(remote, local).is_le() => {
+            Ok(Synced::UpToDate(local.clone()))
+        }
+        _ => Ok(Synced::Updated {
+            path: save(dir, fetched)?,
+            from: local,
+            to: remote.clone(),
+        }),
+    }
+}
+
+/// Check the config at `url` for a newer version, and save it if there is one
+pub async fn sync(url: String) -> Result<Synced, Error> {
+    let dir = cache_dir().ok_or(Error::state_error("No folder to save downloads in"))?;
+    let fetched = fetch_config(url).await?;
+    update(&dir, &fetched)
+}
+
+/// A newer version of a downloaded config, not saved yet
+#[derive(Debug, Clone)]
+pub struct Available {
+    pub fetched: Fetched,
+    /// Version of the saved copy
+    pub from: Option<String>,
+    /// Games that the saved copy doesn't have, in config order
+    pub added: Vec<String>,
+}
+
+/// What `fetched` has to offer over the copy in `dir`, if it's newer
+pub fn compare(dir: &Path, fetched: Fetched) -> Option<Available> {
+    let local = cached(dir, &fetched.url);
+    let from = local.as_ref().map(|x| x.apps.version.clone());
+    if let Some(from) = &from
+        && compare_versions(&fetched.config.apps.version, from).is_le()
+    {
+        return None;
+    }
+
+    let added = fetched
+        .config
+        .apps
+        .apps
+        .iter()
+        .filter(|x| {
+            local
+                .as_ref()
+                .is_none_or(|local| !local.apps.apps.contains(x))
+        })
+        .cloned()
+        .collect();
+    Some(Available {
+        fetched,
+        from,
+        added,
+    })
+}
+
+/// Look for a newer version of the config at `url` without saving it
+pub async fn check(url: String) -> Result<Option<Available>, Error> {
+    let dir = cache_dir().ok_or(Error::state_error("No folder to save downloads in"))?;
+    let fetched = fetch_config(url).await?;
+    Ok(compare(&dir, fetched))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+
+    #[tokio::test]
+    async fn remote_test() {
+        let dir = Path::new("/cache");
+        let path = cache_path(dir, "https://example.com/patches.ini");
+        assert_eq!(path.parent(), Some(dir));
+        assert_eq!(path.extension().unwrap(), "ini");
+        assert_ne!(path, cache_path(dir, "https://example.com/other.ini"));
+        let path = cache_path(dir, "https://example.com/patches.toml?raw=1");
+        assert_eq!(path.extension().unwrap(), "toml");
+        assert!(previous_path(&path).ends_with(format!(
+            "{}.previous.toml",
+            path.file_stem().unwrap().to_string_lossy()
+        )));
+        assert_eq!(format("https://example.com/patches.json#top"), Format::Json);
+
+        assert!(matches!(
+            fetch("http://example.com/patches.ini").await,
+            Err(Error::DownloadError(_))
+        ));
+
+        assert_eq!(compare_versions("1.10", "1.9"), Ordering::Greater);
+        assert_eq!(compare_versions("1.03", "1.3"), Ordering::Equal);
+        assert_eq!(compare_versions("1.03", "1.03.1"), Ordering::Less);
+        assert_eq!(compare_versions("2024-06", "2024-05"), Ordering::Greater);
+    }
+
+    #[test]
+    fn sync_test() {
+        let dir = TempDir::new("remote");
+        let fetched = |version: &str| {
+            let text = format!(
+                "[Apps]\nversion={version}\na0=Game\n\n[Game]\ndetails={version}\n\
+                 checkfile=game.exe\nmodfile=game.exe\nsig=8002\nsigwild=00\noccur=1\n"
+            );
+            Fetched {
+                url: "https://example.com/patches.ini".into(),
+                config: Config::new(&text).unwrap(),
+                text,
+            }
+        };
+        let version = |path: &Path| {
+            let text = std::fs::read_to_string(path).unwrap();
+            Config::new(&text).unwrap().apps.version
+        };
+
+        let path = cache_path(&dir, "https://example.com/patches.ini");
+        assert_eq!(
+            update(&dir, &fetched("1.0")).unwrap(),
+            Synced::Updated {
+                path: path.clone(),
+                from: None,
+                to: "1.0".into(),
+            }
+        );
+        assert!(rollback(&dir, "https://example.com/patches.ini").is_err());
+
+        assert_eq!(
+            update(&dir, &fetched("1.0")).unwrap(),
+            Synced::UpToDate("1.0".into())
+        );
+        assert!(compare(&dir, fetched("1.0")).is_none());
+        let mut newer = fetched("1.1");
+        newer.config.apps.apps.push("New Game".into());
+        let available = compare(&dir, newer).unwrap();
+        assert_eq!(available.from.as_deref(), Some("1.0"));
+        assert_eq!(available.added, ["New Game"]);
+
+        assert_eq!(
+            update(&dir, &fetched("1.1")).unwrap(),
+            Synced::Updated {
+                path: path.clone(),
+                from: Some("1.0".into()),
+                to: "1.1".into(),
+            }
+        );
+        assert_eq!(version(&path), "1.1");
+
+        assert_eq!(
+            rollback(&dir, "https://example.com/patches.ini").unwrap(),
+            path
+        );
+        assert_eq!(version(&path), "1.0");
+        assert_eq!(version(&previous_path(&path)), "1.1");
+        rollback(&dir, "https://example.com/patches.ini").unwrap();
+        assert_eq!(version(&path), "1.1");
+    }
+}
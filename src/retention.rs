@@ -0,0 +1,256 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    backup::{self, Manifest},
+    error::Error,
+    i18n::tr,
+    registry::RegView,
+};
+
+/// Which central backups to remove when cleaning up
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum RetentionPolicy {
+    /// Keep the N most recent backups of each game directory
+    KeepLast(usize),
+    /// Keep the most recent backup of each patched file
+    KeepNewestPerFile,
+    /// Ask before removing each backup
+    Prompt,
+}
+impl RetentionPolicy {
+    pub const PRESETS: [Self; 5] = [
+        Self::KeepLast(1),
+        Self::KeepLast(3),
+        Self::KeepLast(5),
+        Self::KeepNewestPerFile,
+        Self::Prompt,
+    ];
+}
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self::KeepLast(3)
+    }
+}
+impl std::fmt::Display for RetentionPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::KeepLast(n) => f.write_str(&tr!("retention-keep-last", count = n)),
+            Self::KeepNewestPerFile => f.write_str(&tr!("retention-newest")),
+            Self::Prompt => f.write_str(&tr!("retention-prompt")),
+        }
+    }
+}
+
+/// A file or registry value a backup holds the previous contents of
+#[derive(Debug, PartialEq, Eq, Hash)]
+enum Saved<'a> {
+    File(&'a Path),
+    /// The view and lowercase name, since the registry ignores case
+    Registry(RegView, String),
+}
+
+fn saved(manifest: &Manifest) -> impl Iterator<Item = Saved<'_>> {
+    let files = manifest.files.iter().map(|x| Saved::File(&x.source));
+    let registry = manifest
+        .registry
+        .iter()
+        .map(|x| Saved::Registry(x.view, x.name().to_lowercase()));
+    files.chain(registry)
+}
+
+/// Backup directories that `policy` would remove
+///
+/// The oldest backup of every file and registry value is always kept, since
+/// it is the only copy of the unpatched original
+pub fn plan(policy: RetentionPolicy, sets: &[(PathBuf, Manifest)]) -> Vec<PathBuf> {
+    let mut by_age = sets.iter().collect::<Vec<_>>();
+    by_age.sort_by_key(|(_, m)| m.timestamp);
+
+    let mut originals = HashSet::new();
+    let mut seen = HashSet::new();
+    for (dir, manifest) in &by_age {
+        for saved in saved(manifest) {
+            if seen.insert(saved) {
+                originals.insert(dir);
+            }
+        }
+    }
+
+    let keep = match policy {
+        RetentionPolicy::KeepLast(n) => {
+            let mut by_game: HashMap<&Path, Vec<&PathBuf>> = HashMap::new();
+            for (dir, manifest) in by_age.iter().rev() {
+                by_game.entry(&manifest.game_dir).or_default().push(dir);
+            }
+
+            by_game
+                .into_values()
+                .flat_map(|dirs| dirs.into_iter().take(n))
+                .collect::<HashSet<_>>()
+        }
+        RetentionPolicy::KeepNewestPerFile => {
+            let mut seen = HashSet::new();
+            let mut keep = HashSet::new();
+            for (dir, manifest) in by_age.iter().rev() {
+                for saved in saved(manifest) {
+                    if seen.insert(saved) {
+                        keep.insert(dir);
+                    }
+                }
+            }
+            keep
+        }
+        RetentionPolicy::Prompt => HashSet::new(),
+    };
+
+    by_age
+        .into_iter()
+        .map(|(dir, _)| dir)
+        .filter(|dir| !originals.contains(dir) && !keep.contains(dir))
+        .cloned()
+        .collect()
+}
+
+/// Remove old central backups, optionally only those taken from `game_dir`
+///
+/// `confirm` is asked about each backup when the policy is
+/// [`RetentionPolicy::Prompt`]. Returns the removed directories
+pub fn clean(
+    policy: RetentionPolicy,
+    game_dir: Option<&Path>,
+    mut confirm: impl FnMut(&Path, &Manifest) -> bool,
+) -> Result<Vec<PathBuf>, Error> {
+    let sets = backup::central_manifests()
+        .into_iter()
+        .filter(|(_, m)| game_dir.is_none_or(|dir| m.game_dir == dir))
+        .collect::<Vec<_>>();
+
+    let mut removed = vec![];
+    for dir in plan(policy, &sets) {
+        if policy == RetentionPolicy::Prompt {
+            let manifest = sets
+                .iter()
+                .find(|(x, _)| *x == dir)
+                .map(|(_, m)| m)
+                .expect("Planned backup not in set");
+
+            if !confirm(&dir, manifest) {
+                continue;
+            }
+        }
+
+        std::fs::remove_dir_all(&dir)?;
+        removed.push(dir);
+    }
+
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backup::ManifestFile;
+    use crate::registry::RegistryBackup;
+
+    fn set(timestamp: u64, game_dir: &str, files: &[&str]) -> (PathBuf, Manifest) {
+        let dir = PathBuf::from(format!("/backups/{timestamp}"));
+        let manifest = Manifest {
+            game: "Game".into(),
+            game_dir: game_dir.into(),
+            timestamp,
+            width: 1920,
+            height: 1080,
+            files: files
+                .iter()
+                .map(|x| ManifestFile {
+                    source: Path::new(game_dir).join(x),
+                    backup: x.to_string(),
+                    sha256: String::new(),
+                })
+                .collect(),
+            registry: vec![],
+        };
+        (dir, manifest)
+    }
+
+    #[test]
+    fn keep_last() {
+        let sets = [
+            set(1, "/game", &["game.exe"]),
+            set(2, "/game", &["game.exe"]),
+            set(3, "/game", &["game.exe"]),
+            set(4, "/game", &["game.exe"]),
+            set(5, "/other", &["other.exe"]),
+        ];
+
+        assert_eq!(
+            plan(RetentionPolicy::KeepLast(2), &sets),
+            [PathBuf::from("/backups/2")]
+        );
+        assert_eq!(
+            plan(RetentionPolicy::KeepLast(1), &sets),
+            [PathBuf::from("/backups/2"), PathBuf::from("/backups/3")]
+        );
+    }
+
+    #[test]
+    fn keep_newest_per_file() {
+        let sets = [
+            set(1, "/game", &["game.exe", "data.dll"]),
+            set(2, "/game", &["game.exe"]),
+            set(3, "/game", &["data.dll"]),
+            set(4, "/game", &["game.exe"]),
+        ];
+
+        assert_eq!(
+            plan(RetentionPolicy::KeepNewestPerFile, &sets),
+            [PathBuf::from("/backups/2")]
+        );
+    }
+
+    #[test]
+    fn registry_originals() {
+        let value = |name: &str| RegistryBackup {
+            key: r"HKCU\Software\Game".into(),
+            value: name.into(),
+            view: RegView::Bits32,
+            previous: None,
+        };
+        let mut sets = [
+            set(1, "/game", &["game.exe"]),
+            set(2, "/game", &[]),
+            set(3, "/game", &["game.exe"]),
+            set(4, "/game", &[]),
+            set(5, "/game", &["game.exe"]),
+        ];
+        sets[1].1.registry = vec![value("Width")];
+        sets[3].1.registry = vec![value("width")];
+
+        // The first backup of the registry value is its only original
+        assert_eq!(
+            plan(RetentionPolicy::KeepLast(1), &sets),
+            [PathBuf::from("/backups/3"), PathBuf::from("/backups/4")]
+        );
+        assert_eq!(
+            plan(RetentionPolicy::KeepNewestPerFile, &sets),
+            [PathBuf::from("/backups/3")]
+        );
+    }
+
+    #[test]
+    fn prompt_offers_everything_but_originals() {
+        let sets = [
+            set(1, "/game", &["game.exe"]),
+            set(2, "/game", &["game.exe"]),
+            set(3, "/game", &["game.exe"]),
+        ];
+
+        assert_eq!(
+            plan(RetentionPolicy::Prompt, &sets),
+            [PathBuf::from("/backups/2"), PathBuf::from("/backups/3")]
+        );
+    }
+}
@@ -0,0 +1,227 @@
+use crate::value::{Endian, ValueType, WordSize};
+
+/// Default resolutions games commonly hardcode
+pub const RESOLUTIONS: [(u16, u16); 4] = [(640, 480), (800, 600), (1024, 768), (1280, 720)];
+
+/// Furthest the height can be after the width
+pub const MAX_GAP: usize = 16;
+
+/// Bytes of context shown either side of a candidate
+pub const CONTEXT: usize = 8;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Encoding {
+    Word,
+    DWord,
+    F32,
+}
+impl Encoding {
+    pub const ALL: [Self; 3] = [Self::Word, Self::DWord, Self::F32];
+
+    fn value_type(self) -> (ValueType, WordSize) {
+        match self {
+            Self::Word => (ValueType::Int, WordSize::Word),
+            Self::DWord => (ValueType::Int, WordSize::DWord),
+            Self::F32 => (ValueType::F32, WordSize::DWord),
+        }
+    }
+
+    fn encode(self, value: u16) -> Vec<u8> {
+        let (valuetype, size) = self.value_type();
+        let mut bytes = vec![0; valuetype.len(size)];
+        valuetype.write(size, &mut bytes, 0, value.into(), Endian::Little);
+        bytes
+    }
+}
+impl std::fmt::Display for Encoding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Word => "16-bit",
+            Self::DWord => "32-bit",
+            Self::F32 => "float",
+        })
+    }
+}
+
+/// A width followed closely by its height
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Candidate {
+    pub width: u16,
+    pub height: u16,
+    pub encoding: Encoding,
+    /// File offset of the width
+    pub xoffset: usize,
+    /// File offset of the height
+    pub yoffset: usize,
+    /// File offset of the first byte of `context`
+    pub context_start: usize,
+    pub context: Vec<u8>,
+}
+impl Candidate {
+    /// Config fields for a patch of this candidate, using `context` as the sig
+    pub fn fields(&self) -> [(&'static str, String); 4] {
+        let (valuetype, size) = self.encoding.value_type();
+        let valuetype = match valuetype {
+            ValueType::Int => "int",
+            ValueType::F32 => "f32",
+            ValueType::F64 => "f64",
+        };
+
+        [
+            ("xoffset", (self.xoffset - self.context_start).to_string()),
+            ("yoffset", (self.yoffset - self.context_start).to_string()),
+            ("wordsize", size.len().to_string()),
+            ("valuetype", valuetype.to_string()),
+        ]
+    }
+
+    /// `context` as a pattern with the width and height as wildcards, so it
+    /// still matches once patched, e.g. `00 ?? ?? 00 00 ?? ??`
+    pub fn pattern(&self) -> String {
+        let len = self.encoding.encode(0).len();
+        let values = [self.xoffset, self.yoffset].map(|x| x - self.context_start);
+
+        self.context
+            .iter()
+            .enumerate()
+            .map(
+                |(i, x)| match values.iter().any(|&v| (v..v + len).contains(&i)) {
+                    true => "??".to_string(),
+                    false => format!("{x:02X}"),
+                },
+            )
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+impl std::fmt::Display for Candidate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}x{} ({}) at 0x{:X}/0x{:X}: {}",
+            self.width,
+            self.height,
+            self.encoding,
+            self.xoffset,
+            self.yoffset,
+            self.context
+                .iter()
+                .map(|x| format!("{x:02X}"))
+                .collect::<Vec<_>>()
+                .join(" ")
+        )
+    }
+}
+
+/// Every place in `data` that looks like one of [`RESOLUTIONS`], in file order
+pub fn scan(data: &[u8]) -> Vec<Candidate> {
+    let mut candidates = Vec::new();
+
+    for encoding in Encoding::ALL {
+        for (width, height) in RESOLUTIONS {
+            let width_bytes = encoding.encode(width);
+            let height_bytes = encoding.encode(height);
+            let len = width_bytes.len();
+
+            let widths = data
+                .windows(len)
+                .enumerate()
+                .filter(|(_, x)| *x == width_bytes)
+                .map(|(i, _)| i);
+
+            for xoffset in widths {
+                let search_start = xoffset + len;
+                let search_end = (search_start + MAX_GAP + len).min(data.len());
+                let Some(gap) = data
+                    .get(search_start..search_end)
+                    .and_then(|x| x.windows(len).position(|x| x == height_bytes))
+                else {
+                    continue;
+                };
+
+                let yoffset = search_start + gap;
+                let context_start = xoffset.saturating_sub(CONTEXT);
+                let context_end = (yoffset + len + CONTEXT).min(data.len());
+
+                candidates.push(Candidate {
+                    width,
+                    height,
+                    encoding,
+                    xoffset,
+                    yoffset,
+                    context_start,
+                    context: data[context_start..context_end].to_vec(),
+                });
+            }
+        }
+    }
+
+    // A 32-bit value also looks like a 16-bit one followed by zeros
+    let dwords = candidates
+        .iter()
+        .filter(|x| x.encoding == Encoding::DWord)
+        .map(|x| x.xoffset)
+        .collect::<Vec<_>>();
+    candidates.retain(|x| x.encoding != Encoding::Word || !dwords.contains(&x.xoffset));
+
+    candidates.sort_by_key(|x| x.xoffset);
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scan_test() {
+        let mut data = vec![0xCC; 64];
+        // 16-bit 800x600 with a 2 byte gap
+        data[10..12].copy_from_slice(&800u16.to_le_bytes());
+        data[14..16].copy_from_slice(&600u16.to_le_bytes());
+        // float 1280x720
+        data[40..44].copy_from_slice(&1280f32.to_le_bytes());
+        data[44..48].copy_from_slice(&720f32.to_le_bytes());
+
+        let candidates = scan(&data);
+        assert_eq!(candidates.len(), 2);
+
+        let word = &candidates[0];
+        assert_eq!(
+            (word.width, word.height, word.encoding),
+            (800, 600, Encoding::Word)
+        );
+        assert_eq!(
+            (word.xoffset, word.yoffset, word.context_start),
+            (10, 14, 2)
+        );
+        assert_eq!(word.context.len(), 8 + 6 + 8);
+        assert!(
+            word.pattern()
+                .starts_with("CC CC CC CC CC CC CC CC ?? ?? CC CC ?? ?? CC")
+        );
+        assert_eq!(
+            word.fields().map(|(_, x)| x),
+            ["8", "12", "2", "int"].map(String::from)
+        );
+
+        let float = &candidates[1];
+        assert_eq!(
+            (float.encoding, float.xoffset, float.yoffset),
+            (Encoding::F32, 40, 44)
+        );
+        assert_eq!(float.context.len(), 8 + 8 + 8);
+
+        let mut dword = vec![0x00; 16];
+        dword[0..4].copy_from_slice(&1024u32.to_le_bytes());
+        dword[4..8].copy_from_slice(&768u32.to_le_bytes());
+        let candidates = scan(&dword);
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].encoding, Encoding::DWord);
+
+        // Height too far after the width
+        let mut far = vec![0x00; 64];
+        far[0..2].copy_from_slice(&640u16.to_le_bytes());
+        far[40..42].copy_from_slice(&480u16.to_le_bytes());
+        assert!(scan(&far).is_empty());
+    }
+}
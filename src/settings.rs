@@ -0,0 +1,259 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Write as _,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    config::{self, DuplicatePolicy, Items},
+    encoding::Encoding,
+    error::Error,
+};
+
+/// What a game was last patched with, restored when it's selected again
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct Profile {
+    pub game_dir: Option<String>,
+    pub width: Option<u16>,
+    pub height: Option<u16>,
+    /// Labels of the optional patches enabled
+    pub options: Vec<String>,
+}
+
+/// What the GUI remembers between runs
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct Settings {
+    /// Config files loaded, in the order they're merged
+    pub config_paths: Vec<PathBuf>,
+    /// Where a config is downloaded from to keep up to date
+    pub sync_url: Option<String>,
+    /// Game selected when the app was closed
+    pub game: Option<String>,
+    pub width: Option<u16>,
+    pub height: Option<u16>,
+    /// Name of the theme, see [`crate::theme::ThemeMode`]
+    pub theme: Option<String>,
+    /// Code of the language picked, see [`crate::i18n::languages`]
+    pub language: Option<String>,
+    /// Profile of each game, by name
+    pub profiles: BTreeMap<String, Profile>,
+    /// Logical size of the window
+    pub window_size: Option<(u32, u32)>,
+    /// Not known on every platform, e.g. Wayland
+    pub window_position: Option<(i32, i32)>,
+    /// Size of the interface in percent, 100 when it's not set
+    pub scale: Option<u16>,
+    /// Fail to load a config with any mistake in it, see [`config::ParseMode`]
+    pub strict: bool,
+    /// How config files are read, see [`Encoding`]
+    pub encoding: Encoding,
+    /// What's kept of sections and keys given twice in a config
+    pub duplicates: DuplicatePolicy,
+}
+impl Settings {
+    pub fn to_ini(&self) -> String {
+        let mut out = String::new();
+
+        let _ = writeln!(out, "[Settings]");
+        let _ = writeln!(out, "version=1");
+        for (i, path) in self.config_paths.iter().enumerate() {
+            let _ = writeln!(
+                out,
+                "config{i}={}",
+                config::quote(&path.display().to_string())
+            );
+        }
+        if let Some(url) = &self.sync_url {
+            let _ = writeln!(out, "syncurl={}", config::quote(url));
+        }
+        if let Some(game) = &self.game {
+            let _ = writeln!(out, "game={}", config::quote(game));
+        }
+        if let Some(width) = self.width {
+            let _ = writeln!(out, "width={width}");
+        }
+        if let Some(height) = self.height {
+            let _ = writeln!(out, "height={height}");
+        }
+        if let Some(theme) = &self.theme {
+            let _ = writeln!(out, "theme={}", config::quote(theme));
+        }
+        if let Some(language) = &self.language {
+            let _ = writeln!(out, "language={}", config::quote(language));
+        }
+        if let Some((width, height)) = self.window_size {
+            let _ = writeln!(out, "windowwidth={width}");
+            let _ = writeln!(out, "windowheight={height}");
+        }
+        if let Some((x, y)) = self.window_position {
+            let _ = writeln!(out, "windowx={x}");
+            let _ = writeln!(out, "windowy={y}");
+        }
+        if let Some(scale) = self.scale {
+            let _ = writeln!(out, "scale={scale}");
+        }
+        if self.strict {
+            let _ = writeln!(out, "strict=1");
+        }
+        if self.encoding != Encoding::Auto {
+            let _ = writeln!(out, "encoding={}", self.encoding.name());
+        }
+        if self.duplicates != DuplicatePolicy::default() {
+            let _ = writeln!(out, "duplicates={}", self.duplicates.name());
+        }
+
+        for (i, (game, profile)) in self.profiles.iter().enumerate() {
+            let _ = writeln!(out);
+            let _ = writeln!(out, "[Game{i}]");
+            let _ = writeln!(out, "name={}", config::quote(game));
+            if let Some(dir) = &profile.game_dir {
+                let _ = writeln!(out, "dir={}", config::quote(dir));
+            }
+            if let Some(width) = profile.width {
+                let _ = writeln!(out, "width={width}");
+            }
+            if let Some(height) = profile.height {
+                let _ = writeln!(out, "height={height}");
+            }
+            for (i, option) in profile.options.iter().enumerate() {
+                let _ = writeln!(out, "option{i}={}", config::quote(option));
+            }
+        }
+
+        out
+    }
+
+    /// Anything missing or unreadable is left at its default
+    pub fn from_ini(input: &str) -> Result<Self, Error> {
+        let raw = config::parse(input)?;
+        let items = raw
+            .get("Settings")
+            .ok_or(Error::config_missing_section("Settings"))?;
+        let text = |name: &str| items.get(name).cloned();
+        let number = |name: &str| items.get(name).and_then(|x| x.parse().ok());
+        fn pair<T: std::str::FromStr>(items: &Items, x: &str, y: &str) -> Option<(T, T)> {
+            let parse = |name| items.get(name).and_then(|x: &String| x.parse().ok());
+            Some((parse(x)?, parse(y)?))
+        }
+
+        let mut profiles = BTreeMap::new();
+        let games = (0..).map_while(|i| raw.get(&format!("Game{i}")));
+        for game in games {
+            let Some(name) = game.get("name") else {
+                continue;
+            };
+            let number = |name: &str| game.get(name).and_then(|x| x.parse().ok());
+            let options = (0..).map_while(|i| game.get(&format!("option{i}")).cloned());
+
+            let profile = Profile {
+                game_dir: game.get("dir").cloned(),
+                width: number("width"),
+                height: number("height"),
+                options: options.collect(),
+            };
+            profiles.insert(name.clone(), profile);
+        }
+
+        Ok(Self {
+            config_paths: (0..)
+                .map_while(|i| text(&format!("config{i}")))
+                .map(PathBuf::from)
+                .collect(),
+            sync_url: text("syncurl"),
+            game: text("game"),
+            width: number("width"),
+            height: number("height"),
+            theme: text("theme"),
+            language: text("language"),
+            profiles,
+            window_size: pair(items, "windowwidth", "windowheight"),
+            window_position: pair(items, "windowx", "windowy"),
+            scale: number("scale"),
+            strict: text("strict").is_some_and(|x| x == "1"),
+            encoding: text("encoding")
+                .and_then(|x| Encoding::from_name(&x))
+                .unwrap_or_default(),
+            duplicates: text("duplicates")
+                .and_then(|x| DuplicatePolicy::from_name(&x))
+                .unwrap_or_default(),
+        })
+    }
+}
+
+/// `~/.config/uniws/settings.ini` (or the platform equivalent)
+pub fn path() -> Option<PathBuf> {
+    dirs::config_dir().map(|x| x.join("uniws").join("settings.ini"))
+}
+
+/// Settings saved at `path`, or the defaults on the first run
+pub fn load(path: &Path) -> Settings {
+    std::fs::read_to_string(path)
+        .map_err(Error::from)
+        .and_then(|x| Settings::from_ini(&x))
+        .unwrap_or_default()
+}
+
+pub fn save(path: &Path, settings: &Settings) -> Result<(), Error> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, settings.to_ini())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+
+    #[test]
+    fn settings_test() {
+        let settings = Settings {
+            config_paths: vec![
+                PathBuf::from("/games/patches.ini"),
+                PathBuf::from("/games/mine.ini"),
+            ],
+            sync_url: Some("https://example.com/patches.ini".into()),
+            game: Some("Test Game".into()),
+            width: Some(2560),
+            height: Some(1440),
+            theme: Some("Tokyo Night".into()),
+            language: Some("de".into()),
+            profiles: BTreeMap::from([
+                (
+                    "Test Game".into(),
+                    Profile {
+                        game_dir: Some("/games/test".into()),
+                        width: Some(1920),
+                        height: Some(1080),
+                        options: vec!["Fix HUD".into(), "Widescreen FOV".into()],
+                    },
+                ),
+                (
+                    "Other Game".into(),
+                    Profile {
+                        game_dir: Some(r"C:\Games\Other".into()),
+                        ..Default::default()
+                    },
+                ),
+            ]),
+            window_size: Some((1600, 900)),
+            window_position: Some((-1920, 40)),
+            scale: Some(150),
+            strict: true,
+            encoding: Encoding::Windows1252,
+            duplicates: DuplicatePolicy::Error,
+        };
+        assert_eq!(Settings::from_ini(&settings.to_ini()).unwrap(), settings);
+        assert_eq!(
+            Settings::from_ini("[Settings]\nversion=1\n").unwrap(),
+            Settings::default()
+        );
+
+        let tmp = TempDir::new("settings");
+        let path = tmp.join("settings.ini");
+        assert_eq!(load(&path), Settings::default());
+        save(&path, &settings).unwrap();
+        assert_eq!(load(&path), settings);
+    }
+}
@@ -0,0 +1,189 @@
+use std::fmt::Write as _;
+
+use crate::{
+    config::Items,
+    error::{ConfigError, Error},
+    patch_info::PatchInfo,
+    wine,
+};
+
+/// Links look like `uniws://patch?modfile=game.exe&sig=8002&sigwild=00&xoffset=0&occur=1`
+pub const PREFIX: &str = "uniws://patch?";
+
+/// Name used for a shared patch in error messages and backups
+pub const SECTION: &str = "Shared patch";
+
+/// Patch fields a link can carry, in the order they're written
+pub const FIELDS: [&str; 28] = [
+    "modfile",
+    "pattern",
+    "sig",
+    "sigwild",
+    "sigalt1",
+    "sigaltwild1",
+    "xoffset",
+    "yoffset",
+    "searchstart",
+    "searchend",
+    "align",
+    "occur",
+    "occurfirst",
+    "occurindex",
+    "skip",
+    "setx",
+    "sety",
+    "wordsize",
+    "endian",
+    "valuetype",
+    "op",
+    "operand",
+    "opoffset",
+    "opsize",
+    "replace",
+    "patchbytes",
+    "offset",
+    "expect",
+];
+
+fn encode_component(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for byte in s.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                out.push(byte as char)
+            }
+            _ => {
+                let _ = write!(out, "%{byte:02X}");
+            }
+        }
+    }
+    out
+}
+
+fn decode_component(s: &str) -> Result<String, Error> {
+    let invalid = || Error::InvalidLink(format!("invalid escape in {s}"));
+
+    let mut bytes = Vec::with_capacity(s.len());
+    let mut iter = s.bytes();
+    while let Some(byte) = iter.next() {
+        match byte {
+            b'%' => {
+                let hex = [
+                    iter.next().ok_or_else(invalid)?,
+                    iter.next().ok_or_else(invalid)?,
+                ];
+                let hex = std::str::from_utf8(&hex).map_err(|_| invalid())?;
+                bytes.push(u8::from_str_radix(hex, 16).map_err(|_| invalid())?);
+            }
+            b'+' => bytes.push(b' '),
+            x => bytes.push(x),
+        }
+    }
+
+    String::from_utf8(bytes).map_err(|_| invalid())
+}
+
+/// Build a link from raw config values, skipping empty fields
+pub fn to_link(fields: &Items) -> String {
+    let query = FIELDS
+        .iter()
+        .filter_map(|&field| {
+            let value = fields.get(field).filter(|x| !x.trim().is_empty())?;
+            Some(format!("{field}={}", encode_component(value.trim())))
+        })
+        .collect::<Vec<_>>()
+        .join("&");
+
+    format!("{PREFIX}{query}")
+}
+
+/// Read the raw config values out of a link, checking they form a valid patch
+pub fn from_link(link: &str) -> Result<Items, Error> {
+    let query = link
+        .trim()
+        .strip_prefix(PREFIX)
+        .ok_or_else(|| Error::InvalidLink(format!("not a {PREFIX} link")))?;
+
+    let mut fields = Items::new();
+    for pair in query.split('&').filter(|x| !x.is_empty()) {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        let key = decode_component(key)?;
+
+        if !FIELDS.contains(&key.as_str()) {
+            return Err(Error::ConfigError(ConfigError::UnknownField {
+                section: SECTION.to_string(),
+                field: key,
+            }));
+        }
+
+        let value = decode_component(value)?;
+        // The file written to has to be in the game folder
+        if key == "modfile" && !wine::is_inside(&value) {
+            return Err(Error::InvalidLink(format!(
+                "{value} isn't a file in the game folder"
+            )));
+        }
+        fields.insert(key, value);
+    }
+
+    PatchInfo::from_items(SECTION, &fields, None)?;
+    Ok(fields)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let fields = Items::from_iter(
+            [
+                ("modfile", "My Game.exe"),
+                ("sig", "D9050000A03F"),
+                ("sigwild", "001111"),
+                ("occur", "1"),
+                ("op", "mul"),
+                ("operand", "aspect / (4 / 3)"),
+                ("opoffset", "2"),
+                ("xoffset", ""),
+            ]
+            .map(|(a, b)| (a.to_string(), b.to_string())),
+        );
+
+        let link = to_link(&fields);
+        assert_eq!(
+            link,
+            "uniws://patch?modfile=My%20Game.exe&sig=D9050000A03F&sigwild=001111&occur=1\
+             &op=mul&operand=aspect%20%2F%20%284%20%2F%203%29&opoffset=2"
+        );
+
+        let mut expected = fields.clone();
+        expected.shift_remove("xoffset");
+        assert_eq!(from_link(&link).unwrap(), expected);
+    }
+
+    #[test]
+    fn bad_links() {
+        assert!(from_link("https://example.com").is_err());
+        assert!(from_link("uniws://patch?modfile=game.exe&sig=8002").is_err());
+        assert!(
+            from_link("uniws://patch?modfile=game.exe&sig=8002&sigwild=00&occur=1&checkfile=x")
+                .is_err()
+        );
+        assert!(from_link("uniws://patch?modfile=game%2").is_err());
+        assert!(from_link("uniws://patch?modfile=a+b.exe&sig=8002&sigwild=00&occur=1").is_ok());
+
+        // Files outside the game folder
+        for modfile in [
+            "..%2F..%2F.bashrc",
+            "%2Fetc%2Fpasswd",
+            "C%3A%5CWindows%5Cexplorer.exe",
+        ] {
+            let link = format!("uniws://patch?modfile={modfile}&sig=8002&sigwild=00&occur=1");
+            assert!(
+                matches!(from_link(&link), Err(Error::InvalidLink(_))),
+                "{link}"
+            );
+        }
+    }
+}
@@ -0,0 +1,132 @@
+use iced::keyboard::{Key, Modifiers, key::Named};
+
+use crate::i18n::tr;
+
+/// Something done from the keyboard, listed in the help overlay
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Shortcut {
+    NextField,
+    PreviousField,
+    LoadConfig,
+    SelectGameDir,
+    /// Press the patch button, if it can be
+    Apply,
+    /// Restore every backup of the game directory
+    Restore,
+    /// Show or hide the help overlay
+    Help,
+    /// Search games and commands
+    Palette,
+    /// Hide the help overlay or the palette
+    Close,
+}
+impl Shortcut {
+    pub const ALL: [Self; 9] = [
+        Self::NextField,
+        Self::PreviousField,
+        Self::LoadConfig,
+        Self::SelectGameDir,
+        Self::Apply,
+        Self::Restore,
+        Self::Help,
+        Self::Palette,
+        Self::Close,
+    ];
+
+    /// The shortcut of `key` pressed with `modifiers`. Ctrl is Cmd on macOS
+    pub fn from_key(key: &Key, modifiers: Modifiers) -> Option<Self> {
+        let command = modifiers.command();
+        match key.as_ref() {
+            Key::Named(Named::Tab) if modifiers.shift() => Some(Self::PreviousField),
+            Key::Named(Named::Tab) => Some(Self::NextField),
+            Key::Named(Named::Enter) if modifiers.is_empty() => Some(Self::Apply),
+            Key::Named(Named::F1) => Some(Self::Help),
+            Key::Named(Named::Escape) => Some(Self::Close),
+            Key::Character(x) if command && modifiers.shift() && x.eq_ignore_ascii_case("p") => {
+                Some(Self::Palette)
+            }
+            Key::Character(x) if command && x.eq_ignore_ascii_case("o") => Some(Self::LoadConfig),
+            Key::Character(x) if command && x.eq_ignore_ascii_case("d") => {
+                Some(Self::SelectGameDir)
+            }
+            Key::Character(x) if command && x.eq_ignore_ascii_case("z") => Some(Self::Restore),
+            _ => None,
+        }
+    }
+
+    /// Keys to press, as they're shown in the help overlay
+    pub fn keys(self) -> String {
+        let command = match cfg!(target_os = "macos") {
+            true => "Cmd",
+            false => "Ctrl",
+        };
+        match self {
+            Self::NextField => "Tab".into(),
+            Self::PreviousField => "Shift+Tab".into(),
+            Self::LoadConfig => format!("{command}+O"),
+            Self::SelectGameDir => format!("{command}+D"),
+            Self::Apply => "Enter".into(),
+            Self::Restore => format!("{command}+Z"),
+            Self::Help => "F1".into(),
+            Self::Palette => format!("{command}+Shift+P"),
+            Self::Close => "Esc".into(),
+        }
+    }
+}
+impl std::fmt::Display for Shortcut {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&match self {
+            Self::NextField => tr!("shortcut-next-field"),
+            Self::PreviousField => tr!("shortcut-previous-field"),
+            Self::LoadConfig => tr!("load-config"),
+            Self::SelectGameDir => tr!("shortcut-game-dir"),
+            Self::Apply => tr!("shortcut-apply"),
+            Self::Restore => tr!("restore-everything"),
+            Self::Help => tr!("shortcut-help"),
+            Self::Palette => tr!("shortcut-palette"),
+            Self::Close => tr!("shortcut-close"),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_key_test() {
+        let ctrl = Modifiers::COMMAND;
+        let key = |x: &str| Key::Character(x.into());
+        assert_eq!(
+            Shortcut::from_key(&key("o"), ctrl),
+            Some(Shortcut::LoadConfig)
+        );
+        // Shift makes it upper case
+        assert_eq!(
+            Shortcut::from_key(&key("D"), ctrl | Modifiers::SHIFT),
+            Some(Shortcut::SelectGameDir)
+        );
+        assert_eq!(Shortcut::from_key(&key("z"), ctrl), Some(Shortcut::Restore));
+        assert_eq!(
+            Shortcut::from_key(&key("P"), ctrl | Modifiers::SHIFT),
+            Some(Shortcut::Palette)
+        );
+        assert_eq!(Shortcut::from_key(&key("p"), ctrl), None);
+        assert_eq!(Shortcut::from_key(&key("o"), Modifiers::empty()), None);
+        assert_eq!(
+            Shortcut::from_key(&Key::Named(Named::Tab), Modifiers::SHIFT),
+            Some(Shortcut::PreviousField)
+        );
+        assert_eq!(
+            Shortcut::from_key(&Key::Named(Named::Enter), Modifiers::empty()),
+            Some(Shortcut::Apply)
+        );
+        assert_eq!(Shortcut::from_key(&Key::Named(Named::Enter), ctrl), None);
+
+        // Each one listed has a key of its own
+        for shortcut in Shortcut::ALL {
+            let others = Shortcut::ALL.iter().filter(|x| x.keys() == shortcut.keys());
+            assert_eq!(others.count(), 1, "{shortcut:?}");
+        }
+    }
+}
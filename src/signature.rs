@@ -13,26 +13,27 @@ pub struct Signature {
 impl Signature {
     pub fn from_string(section: &str, signature: &str, sigwild: &str) -> Result<Self, Error> {
         fn read_sig(section: &str, sig: &str) -> Result<Vec<u8>, Error> {
-            (0..sig.len())
-                .step_by(2)
-                .map(|x| {
-                    if x + 1 >= sig.len() {
-                        return Err(Error::config_field_parse(
-                            section,
-                            "sig",
-                            "Invalid hex string length",
-                        ));
-                    }
-
-                    let byte_pair = &sig[x..=x + 1];
+            if !sig.len().is_multiple_of(2) {
+                return Err(Error::config_field_parse(
+                    section,
+                    "sig",
+                    "Invalid hex string length",
+                ));
+            }
 
-                    u8::from_str_radix(byte_pair, 16).map_err(|_| {
-                        Error::config_field_parse(
-                            section,
-                            "sig",
-                            format!("Invalid hex byte pair: {byte_pair}"),
-                        )
-                    })
+            sig.as_bytes()
+                .chunks(2)
+                .map(|pair| {
+                    std::str::from_utf8(pair)
+                        .ok()
+                        .and_then(|x| u8::from_str_radix(x, 16).ok())
+                        .ok_or_else(|| {
+                            Error::config_field_parse(
+                                section,
+                                "sig",
+                                format!("Invalid hex byte pair: {}", String::from_utf8_lossy(pair)),
+                            )
+                        })
                 })
                 .collect()
         }
@@ -53,12 +54,64 @@ impl Signature {
         }
 
         let sig = read_sig(section, signature)?;
+        // It would match at every offset
+        if sig.is_empty() {
+            return Err(Error::config_field_parse(section, "sig", "Empty signature"));
+        }
         let sigwild = read_sigwild(section, sigwild)?;
+        if sig.len() != sigwild.len() {
+            return Err(Error::config_field_parse(
+                section,
+                "sigwild",
+                format!(
+                    "Has {} flag(s) but sig has {} byte(s)",
+                    sigwild.len(),
+                    sig.len()
+                ),
+            ));
+        }
+
         Ok(Self::new(&sig, &sigwild))
     }
 
+    /// Parse an IDA/Cheat Engine style pattern like `80 02 ?? ?? C7 01`, where
+    /// `??` (or `?`) is a wildcard byte. Spaces between bytes are optional
+    pub fn from_pattern_str(section: &str, pattern: &str) -> Result<Self, Error> {
+        let error = |msg: String| Error::config_field_parse(section, "pattern", msg);
+
+        let mut bytes = Vec::new();
+        for token in pattern.split_whitespace() {
+            if token == "?" {
+                bytes.push(None);
+                continue;
+            }
+
+            if token.len() % 2 != 0 {
+                return Err(error(format!("Invalid byte: {token}")));
+            }
+            for pair in token.as_bytes().chunks(2) {
+                let pair =
+                    std::str::from_utf8(pair).map_err(|_| error("Invalid pattern".into()))?;
+                bytes.push(match pair {
+                    "??" => None,
+                    x => Some(
+                        u8::from_str_radix(x, 16)
+                            .map_err(|_| error(format!("Invalid hex byte pair: {x}")))?,
+                    ),
+                });
+            }
+        }
+
+        if bytes.is_empty() {
+            return Err(error("Empty pattern".into()));
+        }
+
+        Ok(Self { pattern: bytes })
+    }
+
+    /// `signature` and `sigwild` must be the same length
     pub fn new(signature: &[u8], sigwild: &[MatchType]) -> Self {
-        assert_eq!(signature.len(), sigwild.len());
+        debug_assert_eq!(signature.len(), sigwild.len());
 
         let pattern = signature
             .iter()
@@ -107,6 +160,32 @@ impl Signature {
 
         None
     }
+
+    /// Every match in `haystack`, from the start of the file onwards
+    pub fn find_iter<'a>(&'a self, haystack: &'a [u8]) -> impl Iterator<Item = usize> + 'a {
+        let mut start = 0;
+        std::iter::from_fn(move || {
+            let index = start + self.try_find(&haystack[start..])?;
+            start = index + 1;
+            Some(index)
+        })
+    }
+}
+
+/// Pattern syntax, e.g. `80 02 ?? ?? C7 01`
+impl std::fmt::Display for Signature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, byte) in self.pattern.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            match byte {
+                Some(byte) => write!(f, "{byte:02X}")?,
+                None => f.write_str("??")?,
+            }
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -145,6 +224,32 @@ mod tests {
             Signature::from_string("test", "80020000C701E0010000", "0000110000").unwrap();
 
         assert_eq!(sig, sig_from_string);
+
+        assert_eq!(
+            Signature::from_string("Game", "80020000", "001"),
+            Err(Error::config_field_parse(
+                "Game",
+                "sigwild",
+                "Has 3 flag(s) but sig has 4 byte(s)"
+            ))
+        );
+
+        assert!(Signature::from_string("Game", "8é02", "0000").is_err());
+        assert!(Signature::from_string("Game", "éé", "00").is_err());
+        assert!(Signature::from_string("Game", "800", "00").is_err());
+        assert_eq!(
+            Signature::from_string("Game", "", ""),
+            Err(Error::config_field_parse("Game", "sig", "Empty signature"))
+        );
+
+        let sig_from_pattern =
+            Signature::from_pattern_str("test", "80 02 00 00 ?? ? E0010000").unwrap();
+        assert_eq!(sig, sig_from_pattern);
+        assert_eq!(sig.to_string(), "80 02 00 00 ?? ?? E0 01 00 00");
+
+        assert!(Signature::from_pattern_str("test", "80 2").is_err());
+        assert!(Signature::from_pattern_str("test", "80 GG").is_err());
+        assert!(Signature::from_pattern_str("test", "  ").is_err());
     }
 
     #[test]
@@ -188,4 +293,20 @@ mod tests {
         ];
         assert_eq!(sig.try_find(&sig_only), Some(0));
     }
+
+    #[test]
+    fn find_iter_test() {
+        let sig = get_signature();
+
+        #[rustfmt::skip]
+        let data = [
+            0x80, 0x02, 0x00, 0x00, 0xC7, 0x01, 0xE0, 0x01, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x80, 0x02, 0x00, 0x00, 0x11, 0x22, 0xE0, 0x01, 0x00, 0x00,
+            0x80, 0x02, 0x00, 0x00, 0xC7, 0x01, 0xE0, 0x01, 0x00, 0x00,
+        ];
+
+        assert_eq!(sig.find_iter(&data).collect::<Vec<_>>(), [0, 20, 30]);
+        assert_eq!(sig.find_iter(&data[..5]).count(), 0);
+    }
 }
@@ -0,0 +1,106 @@
+use std::path::PathBuf;
+
+/// Steam installs relative to the home folder, the Flatpak one last
+const ROOTS: [&str; 4] = [
+    ".steam/steam",
+    ".local/share/Steam",
+    ".var/app/com.valvesoftware.Steam/.local/share/Steam",
+    ".var/app/com.valvesoftware.Steam/data/Steam",
+];
+
+/// Every Steam install on Linux, native or Flatpak
+pub fn roots() -> Vec<PathBuf> {
+    let Some(home) = dirs::home_dir().filter(|_| cfg!(target_os = "linux")) else {
+        return vec![];
+    };
+
+    let mut roots = Vec::new();
+    for root in ROOTS.map(|x| home.join(x)) {
+        // ~/.steam/steam is usually a link to ~/.local/share/Steam
+        let root = root.canonicalize().unwrap_or(root);
+        if root.is_dir() && !roots.contains(&root) {
+            roots.push(root);
+        }
+    }
+    roots
+}
+
+/// `path` values of a `libraryfolders.vdf`
+fn library_paths(vdf: &str) -> Vec<PathBuf> {
+    vdf.lines()
+        .filter_map(|line| {
+            let value = line.trim().strip_prefix("\"path\"")?.trim();
+            let value = value.strip_prefix('"')?.strip_suffix('"')?;
+            Some(value.replace("\\\\", "\\").into())
+        })
+        .collect()
+}
+
+/// Every Steam library folder, each install's own and any added in Steam
+pub fn libraries() -> Vec<PathBuf> {
+    let mut libraries = Vec::new();
+    for root in roots() {
+        let vdf = std::fs::read_to_string(root.join("steamapps/libraryfolders.vdf"));
+        let paths = vdf.map(|x| library_paths(&x)).unwrap_or_default();
+
+        for library in std::iter::once(root).chain(paths) {
+            let library = library.canonicalize().unwrap_or(library);
+            if library.join("steamapps").is_dir() && !libraries.contains(&library) {
+                libraries.push(library);
+            }
+        }
+    }
+    libraries
+}
+
+/// Folders that games are installed in
+pub fn common_dirs() -> Vec<PathBuf> {
+    libraries()
+        .into_iter()
+        .map(|x| x.join("steamapps/common"))
+        .filter(|x| x.is_dir())
+        .collect()
+}
+
+/// Folders holding a Proton prefix per Steam game
+pub fn compatdata_dirs() -> Vec<PathBuf> {
+    libraries()
+        .into_iter()
+        .map(|x| x.join("steamapps/compatdata"))
+        .filter(|x| x.is_dir())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn library_test() {
+        let vdf = r#"
+"libraryfolders"
+{
+	"0"
+	{
+		"path"		"/home/user/.var/app/com.valvesoftware.Steam/.local/share/Steam"
+		"label"		""
+		"apps"
+		{
+			"2420"		"15238459"
+		}
+	}
+	"1"
+	{
+		"path"		"/mnt/games/SteamLibrary"
+	}
+}
+"#;
+        assert_eq!(
+            library_paths(vdf),
+            [
+                PathBuf::from("/home/user/.var/app/com.valvesoftware.Steam/.local/share/Steam"),
+                PathBuf::from("/mnt/games/SteamLibrary"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,330 @@
+use serde_json::{Map, Value};
+
+use crate::{document::Document, error::Error};
+
+/// Fields of a game that aren't copied to its section as they are
+const NAME: &str = "name";
+const PATCHES: &str = "patches";
+const VALUES: &str = "values";
+/// Files merged in before the config, `[Include]` in a patches.ini
+const INCLUDE: &str = "include";
+
+/// Fields of a section about the game rather than one of its edit sets
+pub const GAME_FIELDS: [&str; 7] = [
+    "details",
+    "detailsformat",
+    "image",
+    "checkfile",
+    "egsname",
+    "maxwidth",
+    "maxheight",
+];
+
+/// A number, string or boolean as it would be written in a patches.ini
+fn ini_value(game: &str, field: &str, value: &Value) -> Result<String, Error> {
+    match value {
+        Value::String(x) => Ok(x.clone()),
+        Value::Number(x) => Ok(x.to_string()),
+        Value::Bool(x) => Ok(if *x { "1" } else { "0" }.into()),
+        _ => Err(Error::SyntaxError(format!(
+            "{game}: {field} must be a string, number or boolean"
+        ))),
+    }
+}
+
+/// Copy each field of `table` to `items` as `{prefix}{field}`
+fn flatten<'a>(
+    game: &str,
+    prefix: &str,
+    table: impl IntoIterator<Item = (&'a String, &'a Value)>,
+    items: &mut Vec<(String, String)>,
+) -> Result<(), Error> {
+    for (field, value) in table {
+        let key = format!("{prefix}{field}");
+        if items.iter().any(|x| x.0 == key) {
+            return Err(Error::SyntaxError(format!("{game}: {key} is given twice")));
+        }
+        let value = ini_value(game, &key, value)?;
+        items.push((key, value));
+    }
+    Ok(())
+}
+
+/// Tables in the array `field` of `game`, if it has one
+fn tables<'a>(
+    name: &str,
+    game: &'a Map<String, Value>,
+    field: &str,
+) -> Result<Vec<&'a Map<String, Value>>, Error> {
+    let error = || Error::SyntaxError(format!("{name}: {field} must be a list of tables"));
+    match game.get(field) {
+        None => Ok(vec![]),
+        Some(Value::Array(x)) => x.iter().map(|x| x.as_object().ok_or_else(error)).collect(),
+        Some(_) => Err(error()),
+    }
+}
+
+/// The patches.ini a structured config stands for:
+///
+/// ```toml
+/// version = "1.03"
+///
+/// [[games]]
+/// name = "Need for Speed: Underground 2"
+/// details = "Point to the folder containing speed2.exe"
+/// checkfile = "speed2.exe"
+///
+/// [[games.patches]]
+/// modfile = "speed2.exe"
+/// sig = "80020000C701E0010000"
+/// sigwild = "0000110000"
+/// xoffset = 0
+/// yoffset = 6
+/// ```
+///
+/// The first of `patches` is the unprefixed edit set and the rest are `p1`,
+/// `p2` and so on. `values` are the value slots, `value1name` and on. An
+/// `include = ["racing.toml"]` list is the `[Include]` section
+fn from_value(value: Value) -> Result<Document, Error> {
+    let root = value
+        .as_object()
+        .ok_or(Error::SyntaxError("A config must be a table".into()))?;
+    let version = root
+        .get("version")
+        .ok_or(Error::config_missing_field("Apps", "version"))
+        .and_then(|x| ini_value("Apps", "version", x))?;
+
+    let mut document = Document::default();
+    document.set("Apps", "version", &version);
+    let includes = match root.get(INCLUDE) {
+        Some(Value::Array(includes)) => includes.as_slice(),
+        Some(_) => return Err(Error::SyntaxError("include must be a list of files".into())),
+        None => &[],
+    };
+    for (i, include) in includes.iter().enumerate() {
+        let include = include
+            .as_str()
+            .ok_or(Error::SyntaxError("include must be a list of files".into()))?;
+        document.set("Include", &format!("file{i}"), include);
+    }
+    let games = match root.get("games") {
+        Some(Value::Array(games)) => games.as_slice(),
+        Some(_) => return Err(Error::SyntaxError("games must be a list of tables".into())),
+        None => &[],
+    };
+    for (i, game) in games.iter().enumerate() {
+        let game = game
+            .as_object()
+            .ok_or(Error::SyntaxError("games must be a list of tables".into()))?;
+        let name = match game.get(NAME) {
+            Some(Value::String(name)) => name.clone(),
+            _ => return Err(Error::SyntaxError(format!("Game {} has no name", i + 1))),
+        };
+
+        let mut items = Vec::new();
+        let fields = game
+            .iter()
+            .filter(|(k, _)| ![NAME, PATCHES, VALUES].contains(&k.as_str()));
+        flatten(&name, "", fields, &mut items)?;
+        for (i, patch) in tables(&name, game, PATCHES)?.into_iter().enumerate() {
+            let prefix = match i {
+                0 => String::new(),
+                i => format!("p{i}"),
+            };
+            flatten(&name, &prefix, patch, &mut items)?;
+        }
+        for (i, value) in tables(&name, game, VALUES)?.into_iter().enumerate() {
+            flatten(&name, &format!("value{}", i + 1), value, &mut items)?;
+        }
+
+        document.add_app(&name);
+        document.set_section(&name, &items);
+    }
+
+    Ok(document)
+}
+
+/// `key` without a leading `{prefix}{n}`, and `n`
+pub fn numbered<'a>(key: &'a str, prefix: &str) -> Option<(usize, &'a str)> {
+    let rest = key.strip_prefix(prefix)?;
+    let digits = rest.len() - rest.trim_start_matches(|x: char| x.is_ascii_digit()).len();
+    let n = rest[..digits].parse().ok()?;
+    Some((n, &rest[digits..])).filter(|x| !x.1.is_empty())
+}
+
+/// The structured form of the games listed in `document`, see [`from_value`].
+/// Every value is kept as the string it was written as
+fn to_value(document: &Document) -> Value {
+    let mut games = Vec::new();
+    for name in document.apps() {
+        let mut game = Map::new();
+        game.insert(NAME.into(), name.into());
+        let mut patches: Vec<Map<String, Value>> = vec![];
+        let mut values: Vec<Map<String, Value>> = vec![];
+
+        for (key, value) in document.items(name) {
+            let (list, n, field) = if GAME_FIELDS.contains(&key) {
+                game.insert(key.into(), value.into());
+                continue;
+            } else if let Some((n, field)) = numbered(key, "value") {
+                (&mut values, n.saturating_sub(1), field)
+            } else if let Some((n, field)) = numbered(key, "p") {
+                (&mut patches, n, field)
+            } else {
+                (&mut patches, 0, key)
+            };
+
+            if list.len() <= n {
+                list.resize_with(n + 1, Map::new);
+            }
+            list[n].insert(field.into(), value.into());
+        }
+
+        let tables =
+            |x: Vec<Map<String, Value>>| Value::Array(x.into_iter().map(Value::Object).collect());
+        if !patches.is_empty() {
+            game.insert(PATCHES.into(), tables(patches));
+        }
+        if !values.is_empty() {
+            game.insert(VALUES.into(), tables(values));
+        }
+        games.push(Value::Object(game));
+    }
+
+    let mut root = Map::new();
+    root.insert(
+        "version".into(),
+        document.get("Apps", "version").unwrap_or_default().into(),
+    );
+    let includes = (0..).map_while(|i| document.get("Include", &format!("file{i}")));
+    let includes: Vec<Value> = includes.map(Value::from).collect();
+    if !includes.is_empty() {
+        root.insert(INCLUDE.into(), Value::Array(includes));
+    }
+    root.insert("games".into(), Value::Array(games));
+    Value::Object(root)
+}
+
+pub fn to_toml(document: &Document) -> String {
+    toml::to_string_pretty(&to_value(document)).unwrap_or_default()
+}
+
+pub fn to_json(document: &Document) -> String {
+    serde_json::to_string_pretty(&to_value(document)).unwrap_or_default() + "\n"
+}
+
+pub fn parse_toml(input: &str) -> Result<Document, Error> {
+    let table =
+        toml::from_str::<toml::Table>(input).map_err(|e| Error::SyntaxError(e.to_string()))?;
+    let value = serde_json::to_value(table).map_err(|e| Error::SyntaxError(e.to_string()))?;
+    from_value(value)
+}
+
+pub fn parse_json(input: &str) -> Result<Document, Error> {
+    let value = serde_json::from_str(input).map_err(|e| Error::SyntaxError(e.to_string()))?;
+    from_value(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{Config, Format, LoadOptions};
+
+    #[test]
+    fn structured_test() {
+        let ini = r#"
+[Apps]
+version=1.03
+a0=Test Game
+
+[Test Game]
+details=Test
+checkfile=game.exe
+modfile=game.exe
+sig=80020000C701E0010000
+sigwild=0000110000
+xoffset=0
+yoffset=6
+occur=1
+optional=1
+label=Fix HUD
+p1modfile=game.exe
+p1sig=8002
+p1sigwild=00
+p1xoffset=0
+value1name=FOV
+value1type=float
+"#;
+        let toml = r#"
+version = "1.03"
+
+[[games]]
+name = "Test Game"
+details = "Test"
+checkfile = "game.exe"
+
+# Resolution
+[[games.patches]]
+modfile = "game.exe"
+sig = "80020000C701E0010000"
+sigwild = "0000110000"
+xoffset = 0
+yoffset = 6
+occur = 1
+optional = true
+label = "Fix HUD"
+
+[[games.patches]]
+modfile = "game.exe"
+sig = "8002"
+sigwild = "00"
+xoffset = 0
+
+[[games.values]]
+name = "FOV"
+type = "float"
+"#;
+        let json = r#"{
+    "version": "1.03",
+    "games": [{
+        "name": "Test Game",
+        "details": "Test",
+        "checkfile": "game.exe",
+        "patches": [
+            {
+                "modfile": "game.exe", "sig": "80020000C701E0010000", "sigwild": "0000110000",
+                "xoffset": 0, "yoffset": 6, "occur": 1, "optional": true, "label": "Fix HUD"
+            },
+            { "modfile": "game.exe", "sig": "8002", "sigwild": "00", "xoffset": 0 }
+        ],
+        "values": [{ "name": "FOV", "type": "float" }]
+    }]
+}"#;
+
+        let expected = Config::new(ini).unwrap();
+        assert_eq!(
+            Config::load(toml, Format::Toml, LoadOptions::default()).unwrap(),
+            expected
+        );
+        assert_eq!(
+            Config::load(json, Format::Json, LoadOptions::default()).unwrap(),
+            expected
+        );
+
+        assert!(matches!(
+            parse_toml("version = \"1.0\"\n[[games]]\ndetails = \"x\"\n"),
+            Err(Error::SyntaxError(_))
+        ));
+        assert!(matches!(
+            parse_json(r#"{"version": "1.0", "games": [{"name": "x", "sig": [1]}]}"#),
+            Err(Error::SyntaxError(_))
+        ));
+        assert!(matches!(
+            parse_json(
+                r#"{"version": "1.0", "games": [{"name": "x", "p1sig": "80", "patches": [{}, {"sig": "80"}]}]}"#
+            ),
+            Err(Error::SyntaxError(_))
+        ));
+        assert!(matches!(parse_json("{"), Err(Error::SyntaxError(_))));
+    }
+}
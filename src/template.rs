@@ -0,0 +1,194 @@
+use crate::{
+    error::{Error, PatchErrorKind},
+    value::{Endian, ValueType, WordSize},
+};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Part {
+    Byte(u8),
+    /// `..`, leave the byte in the file alone
+    Keep,
+    /// Run of `WW`, one per byte
+    Width(WordSize),
+    /// Run of `HH`, one per byte
+    Height(WordSize),
+}
+impl Part {
+    fn len(self) -> usize {
+        match self {
+            Self::Byte(_) | Self::Keep => 1,
+            Self::Width(size) | Self::Height(size) => size.len(),
+        }
+    }
+}
+
+/// Bytes written over a match, e.g. `C705....WWWWHHHH`
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct Template {
+    pub parts: Vec<Part>,
+}
+impl Template {
+    pub fn from_string(section: &str, field: &'static str, template: &str) -> Result<Self, Error> {
+        let error = |msg: String| Error::config_field_parse(section, field, msg);
+
+        let template = template.trim();
+        if !template.len().is_multiple_of(2) {
+            return Err(error("Invalid template length".to_string()));
+        }
+
+        let mut parts: Vec<Part> = Vec::new();
+        let mut run: Option<(char, usize)> = None;
+
+        let end_run =
+            |parts: &mut Vec<Part>, run: &mut Option<(char, usize)>| -> Result<(), Error> {
+                let Some((c, len)) = run.take() else {
+                    return Ok(());
+                };
+                let size = len.to_string().parse::<WordSize>().map_err(|_| {
+                    error(format!(
+                        "{c}{c} runs must be 1, 2, 4 or 8 bytes long, not {len}"
+                    ))
+                })?;
+                parts.push(match c {
+                    'W' => Part::Width(size),
+                    _ => Part::Height(size),
+                });
+                Ok(())
+            };
+
+        for pair in template.as_bytes().chunks(2) {
+            let pair = std::str::from_utf8(pair).map_err(|_| error("Invalid template".into()))?;
+
+            let placeholder = match pair {
+                "WW" | "ww" => Some('W'),
+                "HH" | "hh" => Some('H'),
+                _ => None,
+            };
+            match (placeholder, run) {
+                (Some(c), Some((r, len))) if c == r => {
+                    run = Some((c, len + 1));
+                    continue;
+                }
+                _ => end_run(&mut parts, &mut run)?,
+            }
+
+            if let Some(c) = placeholder {
+                run = Some((c, 1));
+                continue;
+            }
+
+            parts.push(match pair {
+                ".." => Part::Keep,
+                x => Part::Byte(
+                    u8::from_str_radix(x, 16)
+                        .map_err(|_| error(format!("Invalid template byte: {x}")))?,
+                ),
+            });
+        }
+        end_run(&mut parts, &mut run)?;
+
+        Ok(Self { parts })
+    }
+
+    /// Number of bytes covered
+    pub fn len(&self) -> usize {
+        self.parts.iter().map(|x| x.len()).sum()
+    }
+
+    /// Sizes of every width and height run
+    pub fn value_sizes(&self) -> impl Iterator<Item = WordSize> + '_ {
+        self.parts.iter().filter_map(|x| match x {
+            Part::Width(size) | Part::Height(size) => Some(*size),
+            _ => None,
+        })
+    }
+
+    /// Whether `data` from `offset` has every hex byte of the template, which
+    /// is bounds checked by the caller
+    pub fn matches(&self, data: &[u8], mut offset: usize) -> bool {
+        self.parts.iter().all(|part| {
+            let matches = !matches!(*part, Part::Byte(byte) if data[offset] != byte);
+            offset += part.len();
+            matches
+        })
+    }
+
+    /// Write over `data` from `offset`, which is bounds checked by the caller.
+    /// Nothing is written if the width or height doesn't fit in its run
+    pub fn write(
+        &self,
+        data: &mut [u8],
+        mut offset: usize,
+        width: u16,
+        height: u16,
+        valuetype: ValueType,
+        endian: Endian,
+    ) -> Result<(), PatchErrorKind> {
+        for part in &self.parts {
+            match *part {
+                Part::Width(size) => valuetype.check(size, width.into())?,
+                Part::Height(size) => valuetype.check(size, height.into())?,
+                _ => {}
+            }
+        }
+
+        for part in &self.parts {
+            match *part {
+                Part::Byte(byte) => data[offset] = byte,
+                Part::Keep => {}
+                Part::Width(size) => valuetype.write(size, data, offset, width.into(), endian),
+                Part::Height(size) => valuetype.write(size, data, offset, height.into(), endian),
+            }
+            offset += part.len();
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn template_test() {
+        let template = Template::from_string("test", "replace", "C705....WWWWHHHH").unwrap();
+        assert_eq!(
+            template.parts,
+            [
+                Part::Byte(0xC7),
+                Part::Byte(0x05),
+                Part::Keep,
+                Part::Keep,
+                Part::Width(WordSize::Word),
+                Part::Height(WordSize::Word),
+            ]
+        );
+        assert_eq!(template.len(), 8);
+
+        let mut data = [0xFF; 10];
+        template
+            .write(&mut data, 1, 1920, 1080, ValueType::Int, Endian::Little)
+            .unwrap();
+        assert_eq!(
+            data,
+            [0xFF, 0xC7, 0x05, 0xFF, 0xFF, 0x80, 0x07, 0x38, 0x04, 0xFF]
+        );
+
+        assert!(template.matches(&data, 1) && !template.matches(&data, 0));
+
+        let template = Template::from_string("test", "replace", "WWHHHH").unwrap();
+        let mut data = [0xFF; 3];
+        assert_eq!(
+            template.write(&mut data, 0, 1920, 1080, ValueType::Int, Endian::Little),
+            Err(PatchErrorKind::ValueOutOfRange {
+                value: 1920.0,
+                len: 1
+            })
+        );
+        assert_eq!(data, [0xFF; 3]);
+
+        assert!(Template::from_string("test", "replace", "C70").is_err());
+        assert!(Template::from_string("test", "replace", "WWWWWW").is_err());
+        assert!(Template::from_string("test", "replace", "XX").is_err());
+    }
+}
@@ -0,0 +1,306 @@
+use std::{
+    fmt::Write as _,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    backup,
+    config::{AppSection, Config},
+    error::{Error, PatchContext, PatchErrorKind},
+    patch_info::{PatchInfo, PatchOutcome, PatchStatus, SectionReport},
+    wine,
+};
+use serde_json::{Value, json};
+
+/// Sample of `modfile` for `section`: `<samples>/<section name>/<modfile>`
+///
+/// The sample may be the whole file or just excerpts containing each
+/// signature. A `<modfile>.sha256` file next to it is checked if present
+fn sample_path(samples: &Path, section: &AppSection, modfile: &str) -> PathBuf {
+    let dir = samples.join(backup::sanitize_name(&section.name));
+    wine::resolve(&dir, modfile)
+}
+
+fn read_sample(path: &Path) -> Result<Vec<u8>, Error> {
+    let data = std::fs::read(path)?;
+
+    let mut hash_path = path.as_os_str().to_owned();
+    hash_path.push(".sha256");
+    if let Ok(expected) = std::fs::read_to_string(hash_path) {
+        let expected = expected.split_whitespace().next().unwrap_or_default();
+        let actual = backup::sha256_hex(&data);
+        if !expected.eq_ignore_ascii_case(&actual) {
+            return Err(Error::ChecksumMismatch {
+                file: path.display().to_string(),
+            });
+        }
+    }
+
+    Ok(data)
+}
+
+/// Check every patch of `section` finds its expected matches in the samples,
+/// and no more than that unless it patches them all
+pub fn verify_section(section: &AppSection, samples: &Path) -> SectionReport {
+    let patches = section
+        .patches
+        .iter()
+        .enumerate()
+        .map(|(index, p)| {
+            let file = sample_path(samples, section, &p.modfile);
+
+            let status = if !file.exists() {
+                PatchStatus::Skipped("no sample".into())
+            } else {
+                match read_sample(&file).and_then(|data| {
+                    let offsets = p.find_patch_offsets(&data, index)?;
+                    let found = p.count_matches(&data);
+                    // Matches skipped with `occurfirst` are expected too
+                    let expected = p
+                        .occurfirst
+                        .unwrap_or(1)
+                        .saturating_sub(1)
+                        .saturating_add(p.occur);
+                    if p.occur != PatchInfo::ALL && found > expected as usize {
+                        let kind = PatchErrorKind::TooManyOccurrences {
+                            found,
+                            requested: expected,
+                        };
+                        return Err(
+                            Error::from(kind).in_patch(PatchContext::patch(index, &p.modfile))
+                        );
+                    }
+                    Ok((found, offsets))
+                }) {
+                    Ok((found, offsets)) => PatchStatus::Patched {
+                        requested: p.occur,
+                        found,
+                        sites: offsets.iter().map(|x| x.index).collect(),
+                        signature: offsets.first().map_or(0, |x| x.signature),
+                    },
+                    Err(e) => PatchStatus::Failed(e),
+                }
+            };
+
+            PatchOutcome {
+                index,
+                file,
+                status,
+            }
+        })
+        .collect();
+
+    SectionReport {
+        section: section.name.clone(),
+        patches,
+    }
+}
+
+pub fn verify(config: &Config, samples: &Path) -> Vec<SectionReport> {
+    config
+        .sections
+        .iter()
+        .map(|section| verify_section(section, samples))
+        .collect()
+}
+
+/// Pass/fail table with one row per game and one column per patch,
+/// followed by the reason for every failure
+pub fn matrix(config: &Config, reports: &[SectionReport]) -> String {
+    let name_width = reports
+        .iter()
+        .map(|x| x.section.chars().count())
+        .chain(config.broken.iter().map(|x| x.name.chars().count()))
+        .max()
+        .unwrap_or_default();
+    let columns = reports.iter().map(|x| x.patches.len()).max().unwrap_or(0);
+
+    let mut out = format!("{:name_width$}", "");
+    for i in 0..columns {
+        let _ = write!(out, " {:>4}", format!("p{i}"));
+    }
+    out.push('\n');
+
+    for report in reports {
+        let _ = write!(out, "{:name_width$}", report.section);
+        for patch in &report.patches {
+            let cell = match patch.status {
+                PatchStatus::Patched { .. } | PatchStatus::Written(_) => "PASS",
+                PatchStatus::Failed(_) => "FAIL",
+                PatchStatus::Skipped(_) => "-",
+            };
+            let _ = write!(out, " {cell:>4}");
+        }
+        out.push('\n');
+    }
+
+    for broken in &config.broken {
+        let _ = writeln!(out, "{:name_width$} BROKEN", broken.name);
+    }
+
+    let failures = reports
+        .iter()
+        .flat_map(|r| r.patches.iter().map(move |p| (r, p)))
+        .filter(|(_, p)| matches!(p.status, PatchStatus::Failed(_)));
+    for (report, patch) in failures {
+        let _ = writeln!(out, "\n{}: {patch}", report.section);
+    }
+    for broken in &config.broken {
+        let _ = writeln!(out, "\n{}: {}", broken.name, broken.error);
+    }
+    for error in &config.skipped {
+        let _ = writeln!(out, "\n{error}");
+    }
+
+    out
+}
+
+/// One JSON object per patch, as a JSON array
+pub fn json(config: &Config, reports: &[SectionReport]) -> String {
+    let patches = reports.iter().flat_map(|report| {
+        report.patches.iter().map(|patch| {
+            let mut fields = json!({
+                "section": report.section,
+                "patch": patch.index,
+                "file": patch.file.to_string_lossy(),
+            });
+            let status = match &patch.status {
+                PatchStatus::Patched {
+                    found, signature, ..
+                } => json!({"status": "pass", "found": found, "signature": signature}),
+                PatchStatus::Written(what) => json!({"status": "pass", "written": what}),
+                PatchStatus::Failed(e) => json!({"status": "fail", "error": e.to_json()}),
+                PatchStatus::Skipped(reason) => json!({"status": "skip", "reason": reason}),
+            };
+            if let (Some(fields), Value::Object(status)) = (fields.as_object_mut(), status) {
+                fields.extend(status);
+            }
+            fields
+        })
+    });
+    let broken = config.broken.iter().map(|x| {
+        json!({
+            "section": x.name,
+            "status": "broken",
+            "error": x.error.to_json(),
+        })
+    });
+    let skipped = config
+        .skipped
+        .iter()
+        .map(|x| json!({"status": "unreadable", "error": x.to_json()}));
+
+    Value::Array(patches.chain(broken).chain(skipped).collect()).to_string()
+}
+
+/// First error found, in database order
+pub fn first_error<'a>(config: &'a Config, reports: &'a [SectionReport]) -> Option<&'a Error> {
+    reports
+        .iter()
+        .flat_map(|x| &x.patches)
+        .find_map(|x| match &x.status {
+            PatchStatus::Failed(e) => Some(e),
+            _ => None,
+        })
+        .or(config.broken.first().map(|x| &x.error))
+        .or(config.skipped.first())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+
+    const CONFIG: &str = r#"
+[Apps]
+version=1.03
+a0=Game: One
+a1=Game Two
+
+[Game: One]
+details=Test
+checkfile=one.exe
+modfile=one.exe
+sig=8002
+sigwild=00
+xoffset=0
+occur=2
+p1modfile=one.exe
+p1sig=E001
+p1sigwild=00
+p1yoffset=0
+p1occur=1
+
+[Game Two]
+details=Test
+checkfile=two.exe
+modfile=two.exe
+sig=8002
+sigwild=00
+xoffset=0
+occur=1
+"#;
+
+    #[test]
+    fn verify_samples() {
+        let tmp = TempDir::new("testdb");
+        let dir = tmp.join("Game_ One");
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let data = [0x80, 0x02, 0x00, 0x80, 0x02, 0xE0, 0x00];
+        std::fs::write(dir.join("one.exe"), data).unwrap();
+
+        let config = Config::new(CONFIG).unwrap();
+        let reports = verify(&config, &tmp);
+
+        assert!(matches!(
+            reports[0].patches[0].status,
+            PatchStatus::Patched { found: 2, .. }
+        ));
+        assert!(matches!(
+            reports[0].patches[1].status,
+            PatchStatus::Failed(_)
+        ));
+        assert!(matches!(
+            reports[1].patches[0].status,
+            PatchStatus::Skipped(_)
+        ));
+        assert_eq!(
+            first_error(&config, &reports).map(Error::code),
+            Some("signature_not_found")
+        );
+        let json: Value = serde_json::from_str(&json(&config, &reports)).unwrap();
+        assert_eq!(
+            json[0],
+            json!({
+                "section": "Game: One",
+                "patch": 0,
+                "file": dir.join("one.exe").to_string_lossy(),
+                "status": "pass",
+                "found": 2,
+                "signature": 0,
+            })
+        );
+
+        let table = matrix(&config, &reports);
+        assert!(table.starts_with(
+            "            p0   p1\n\
+             Game: One PASS FAIL\n\
+             Game Two     -\n"
+        ));
+
+        let config = Config::new(&CONFIG.replace("occur=2", "occur=1")).unwrap();
+        let reports = verify(&config, &tmp);
+        assert_eq!(
+            first_error(&config, &reports).map(Error::code),
+            Some("too_many_occurrences")
+        );
+
+        std::fs::write(dir.join("one.exe.sha256"), "00").unwrap();
+        let reports = verify(&config, &tmp);
+        assert!(matches!(
+            reports[0].patches[0].status,
+            PatchStatus::Failed(Error::ChecksumMismatch { .. })
+        ));
+    }
+}
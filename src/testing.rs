@@ -0,0 +1,36 @@
+use std::{
+    ops::Deref,
+    path::{Path, PathBuf},
+};
+
+/// Folder of a test's files in the temp folder, removed with everything in it
+/// when dropped, so a failing test doesn't leave it behind
+pub struct TempDir(PathBuf);
+impl TempDir {
+    /// `uniws-<name>-<process id>`, `name` telling apart tests that run at
+    /// the same time
+    pub fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("uniws-{name}-{}", std::process::id()));
+        // Left over from a run that was killed
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        Self(dir)
+    }
+}
+impl Deref for TempDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+impl AsRef<Path> for TempDir {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
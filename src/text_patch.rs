@@ -0,0 +1,159 @@
+use std::fmt::Write as _;
+
+use crate::{
+    config::Items,
+    error::{Error, PatchContext, PatchErrorKind},
+    wine,
+};
+
+/// Line based edit of a text file, e.g. a game's own `.ini` or `.cfg`
+///
+/// Every line starting with `find`, ignoring case and indentation, is replaced
+/// with `replace`. The indentation and line ending are kept
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct TextPatch {
+    /// Relative to the game directory
+    pub textfile: String,
+    pub find: String,
+    /// New line, `{width}` and `{height}` are replaced with the resolution
+    pub replace: String,
+}
+impl TextPatch {
+    pub fn from_items(section: &str, items: &Items, index: Option<u8>) -> Result<Self, Error> {
+        let prefix = index.map_or(String::new(), |x| format!("p{x}"));
+        let get = |field: &'static str| {
+            items
+                .get(&format!("{prefix}{field}"))
+                .cloned()
+                .ok_or(Error::config_missing_field(section, field))
+        };
+
+        let patch = Self {
+            textfile: wine::config_path(&get("textfile")?),
+            find: get("find")?,
+            replace: get("replace")?,
+        };
+        if patch.find.trim().is_empty() {
+            return Err(Error::config_field_parse(section, "find", "Can't be empty"));
+        }
+
+        Ok(patch)
+    }
+
+    /// `replace` with the resolution filled in
+    pub fn line(&self, width: u16, height: u16) -> String {
+        self.replace
+            .replace("{width}", &width.to_string())
+            .replace("{height}", &height.to_string())
+    }
+
+    /// Start of every line that's replaced, at least one
+    pub fn find_lines(&self, iteration: usize, data: &[u8]) -> Result<Vec<usize>, Error> {
+        let find = self.find.trim_start().as_bytes();
+
+        let mut start = 0;
+        let mut lines = Vec::new();
+        for line in data.split_inclusive(|&x| x == b'\n') {
+            let text = line.trim_ascii_start();
+            if text.len() >= find.len() && text[..find.len()].eq_ignore_ascii_case(find) {
+                lines.push(start);
+            }
+            start += line.len();
+        }
+
+        match lines.is_empty() {
+            true => Err(Error::PatchError {
+                context: Box::new(PatchContext::patch(iteration, &self.textfile)),
+                kind: PatchErrorKind::TextNotFound {
+                    find: self.find.clone(),
+                },
+            }),
+            false => Ok(lines),
+        }
+    }
+
+    /// Copy of `data` with every matching line replaced, and the start of each
+    /// line in the original
+    pub fn patch_data(
+        &self,
+        iteration: usize,
+        data: &[u8],
+        width: u16,
+        height: u16,
+    ) -> Result<(Vec<u8>, Vec<usize>), Error> {
+        let lines = self.find_lines(iteration, data)?;
+
+        let replace = self.line(width, height);
+        let mut out = Vec::with_capacity(data.len());
+        let mut start = 0;
+        for line in data.split_inclusive(|&x| x == b'\n') {
+            if lines.binary_search(&start).is_ok() {
+                let indent = line.len() - line.trim_ascii_start().len();
+                let ending: &[u8] = match line {
+                    [.., b'\r', b'\n'] => b"\r\n",
+                    [.., b'\n'] => b"\n",
+                    _ => b"",
+                };
+                out.extend_from_slice(&line[..indent]);
+                out.extend_from_slice(replace.as_bytes());
+                out.extend_from_slice(ending);
+            } else {
+                out.extend_from_slice(line);
+            }
+            start += line.len();
+        }
+
+        Ok((out, lines))
+    }
+
+    /// Human readable description of the edit
+    pub fn log(&self, iteration: usize, width: u16, height: u16) -> String {
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "patch {iteration}: {} lines starting with \"{}\" = \"{}\"",
+            self.textfile,
+            self.find,
+            self.line(width, height)
+        );
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_patch_test() {
+        let items = Items::from_iter(
+            [
+                ("p1textfile", "Game.ini"),
+                ("p1find", "ResX="),
+                ("p1replace", "ResX={width} ; was {height}"),
+            ]
+            .map(|(k, v)| (k.to_string(), v.to_string())),
+        );
+        let patch = TextPatch::from_items("test", &items, Some(1)).unwrap();
+        assert_eq!(patch.textfile, "Game.ini");
+        assert!(TextPatch::from_items("test", &items, None).is_err());
+
+        let data = b"[Video]\r\nResX=640\r\n  resx = 1\r\nResY=480\r\n\tRESX=800";
+        assert_eq!(patch.find_lines(1, data).unwrap(), [9, 41]);
+
+        let (patched, lines) = patch.patch_data(1, data, 1920, 1080).unwrap();
+        assert_eq!(lines, [9, 41]);
+        assert_eq!(
+            String::from_utf8(patched).unwrap(),
+            "[Video]\r\nResX=1920 ; was 1080\r\n  resx = 1\r\nResY=480\r\n\tResX=1920 ; was 1080"
+        );
+
+        assert!(matches!(
+            patch.patch_data(1, b"ResY=480\n", 1920, 1080),
+            Err(Error::PatchError {
+                kind: PatchErrorKind::TextNotFound { .. },
+                ..
+            })
+        ));
+    }
+}
@@ -0,0 +1,397 @@
+use std::path::{Path, PathBuf};
+
+use iced::{
+    Background, Border, Color, Element, Theme,
+    theme::Palette,
+    widget::{
+        Button,
+        button::{self},
+    },
+};
+
+use crate::{
+    error::{ConfigError, Error},
+    i18n::tr,
+};
+
+/// Colours of a palette, as they're written in a theme file
+const FIELDS: [&str; 5] = ["background", "text", "primary", "success", "danger"];
+
+/// A theme of the user's, from a file in [`dir`]
+#[derive(Debug, PartialEq, Clone)]
+pub struct Custom {
+    pub name: String,
+    pub palette: Palette,
+}
+impl Custom {
+    /// Read a theme file named `file`:
+    ///
+    /// ```toml
+    /// name = "Nord"
+    /// base = "dark"
+    /// background = "#2E3440"
+    /// text = "#ECEFF4"
+    /// primary = "#88C0D0"
+    /// success = "#A3BE8C"
+    /// danger = "#BF616A"
+    /// ```
+    ///
+    /// Colours are `#rgb`, `#rrggbb` or `#rrggbbaa`. Any left out are those
+    /// of the built in `base` theme, `dark` or `light`, and `name` defaults
+    /// to `file`
+    pub fn parse(file: &str, input: &str) -> Result<Self, Error> {
+        let table =
+            toml::from_str::<toml::Table>(input).map_err(|e| Error::SyntaxError(e.to_string()))?;
+        let string = |field: &'static str| match table.get(field) {
+            None => Ok(None),
+            Some(toml::Value::String(x)) => Ok(Some(x.as_str())),
+            Some(_) => Err(Error::config_field_parse(
+                file,
+                field,
+                tr!("error-not-string"),
+            )),
+        };
+
+        let mut palette = match string("base")? {
+            None | Some("dark") => Palette::DARK,
+            Some("light") => Palette::LIGHT,
+            Some(x) => {
+                return Err(Error::config_field_parse(
+                    file,
+                    "base",
+                    tr!("error-theme-base", value = x),
+                ));
+            }
+        };
+        let colors = [
+            &mut palette.background,
+            &mut palette.text,
+            &mut palette.primary,
+            &mut palette.success,
+            &mut palette.danger,
+        ];
+        for (field, color) in FIELDS.into_iter().zip(colors) {
+            if let Some(x) = string(field)? {
+                *color = Color::parse(x).ok_or_else(|| {
+                    Error::config_field_parse(file, field, tr!("error-theme-colour", value = x))
+                })?;
+            }
+        }
+        if let Some(field) = table
+            .keys()
+            .find(|x| !["name", "base"].contains(&x.as_str()) && !FIELDS.contains(&x.as_str()))
+        {
+            return Err(Error::ConfigError(ConfigError::UnknownField {
+                section: file.to_string(),
+                field: field.clone(),
+            }));
+        }
+
+        Ok(Self {
+            name: string("name")?.unwrap_or(file).to_string(),
+            palette,
+        })
+    }
+}
+
+/// White on black, with yellow for what can be clicked. Every colour drawn on
+/// the background has a contrast ratio of at least 7:1, WCAG's AAA level
+const HIGH_CONTRAST: Palette = Palette {
+    background: Color::BLACK,
+    text: Color::WHITE,
+    primary: Color::from_rgb(1.0, 0.83, 0.0),
+    success: Color::from_rgb(0.4, 1.0, 0.4),
+    danger: Color::from_rgb(1.0, 0.42, 0.42),
+};
+
+/// Text and border of a disabled button in [`ThemeMode::HighContrast`],
+/// 7.4:1 on black, where iced's half transparent look would be too faint
+const DISABLED: Color = Color::from_rgb(0.6, 0.6, 0.6);
+
+/// `~/.config/uniws/themes` (or the platform equivalent), where each
+/// `.toml` file is a [`Custom`] theme
+pub fn dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|x| x.join("uniws").join("themes"))
+}
+
+/// The themes in `dir` by file name, and what's wrong with any that can't
+/// be read
+pub fn load(dir: Option<&Path>) -> (Vec<Custom>, Vec<Error>) {
+    let mut paths: Vec<PathBuf> = dir
+        .and_then(|x| std::fs::read_dir(x).ok())
+        .into_iter()
+        .flatten()
+        .filter_map(|x| Some(x.ok()?.path()))
+        .filter(|x| x.extension().is_some_and(|x| x == "toml"))
+        .collect();
+    paths.sort();
+
+    let (mut themes, mut errors) = (vec![], vec![]);
+    for path in paths {
+        let file = path
+            .file_stem()
+            .and_then(|x| x.to_str())
+            .unwrap_or_default();
+        let theme = std::fs::read_to_string(&path)
+            .map_err(Error::from)
+            .and_then(|x| Custom::parse(file, &x));
+        match theme {
+            Ok(theme) => themes.push(theme),
+            Err(e) => errors.push(Error::in_config_file(path.display().to_string(), e)),
+        }
+    }
+    (themes, errors)
+}
+
+/// Look of the window, picked at the top of it
+#[derive(Debug, Default, PartialEq, Clone)]
+pub enum ThemeMode {
+    Light,
+    Dark,
+    /// Light or dark like the desktop, checked again when the window is
+    /// focused
+    #[default]
+    System,
+    /// White on black with bright colours, for low vision
+    HighContrast,
+    Custom(Custom),
+}
+impl ThemeMode {
+    pub const BUILT_IN: [Self; 4] = [Self::Light, Self::Dark, Self::System, Self::HighContrast];
+
+    /// Name as it's saved in the settings
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Light => "light",
+            Self::Dark => "dark",
+            Self::System => "system",
+            Self::HighContrast => "high-contrast",
+            Self::Custom(x) => &x.name,
+        }
+    }
+
+    /// The built in theme or one of `custom` called `name`
+    pub fn from_name(name: &str, custom: &[Custom]) -> Option<Self> {
+        Self::BUILT_IN
+            .into_iter()
+            .chain(custom.iter().cloned().map(Self::Custom))
+            .find(|x| x.name().eq_ignore_ascii_case(name))
+    }
+
+    /// The iced theme to draw with, asking the desktop for
+    /// [`Self::System`]. Dark when it doesn't say
+    pub fn resolve(&self) -> Theme {
+        match self {
+            Self::Custom(x) => Theme::custom(x.name.clone(), x.palette),
+            Self::Light => Theme::Light,
+            Self::Dark => Theme::Dark,
+            Self::System => match dark_light::detect() {
+                dark_light::Mode::Light => Theme::Light,
+                dark_light::Mode::Dark | dark_light::Mode::Default => Theme::Dark,
+            },
+            Self::HighContrast => Theme::custom(tr!("theme-high-contrast"), HIGH_CONTRAST),
+        }
+    }
+}
+impl std::fmt::Display for ThemeMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&match self {
+            Self::Light => tr!("theme-light"),
+            Self::Dark => tr!("theme-dark"),
+            Self::System => tr!("theme-system"),
+            Self::HighContrast => tr!("theme-high-contrast"),
+            Self::Custom(x) => x.name.clone(),
+        })
+    }
+}
+
+/// Colours drawn with beyond iced's palette, dark enough to read on a
+/// light background
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Colors {
+    pub error: Color,
+    pub warning: Color,
+    /// Bytes before a patch, and after it
+    pub removed: Color,
+    pub added: Color,
+    /// Byte selected in the hex viewer, and the matches of a search
+    pub cursor: Color,
+    pub hit: Color,
+}
+
+pub fn colors(theme: &Theme) -> Colors {
+    if is_high_contrast(theme) {
+        return Colors {
+            error: HIGH_CONTRAST.danger,
+            warning: HIGH_CONTRAST.primary,
+            removed: HIGH_CONTRAST.danger,
+            added: HIGH_CONTRAST.success,
+            cursor: HIGH_CONTRAST.primary,
+            hit: Color::from_rgb(0.0, 0.9, 1.0),
+        };
+    }
+    match theme.extended_palette().is_dark {
+        true => Colors {
+            error: Color::from_rgb(1.0, 0.4, 0.4),
+            warning: Color::from_rgb(1.0, 0.8, 0.0),
+            removed: Color::from_rgb(1.0, 0.4, 0.4),
+            added: Color::from_rgb(0.4, 1.0, 0.4),
+            cursor: Color::from_rgb(1.0, 0.9, 0.3),
+            hit: Color::from_rgb(0.4, 0.8, 1.0),
+        },
+        false => Colors {
+            error: Color::from_rgb(0.75, 0.0, 0.0),
+            warning: Color::from_rgb(0.6, 0.4, 0.0),
+            removed: Color::from_rgb(0.75, 0.1, 0.1),
+            added: Color::from_rgb(0.0, 0.5, 0.0),
+            cursor: Color::from_rgb(0.6, 0.35, 0.0),
+            hit: Color::from_rgb(0.0, 0.4, 0.8),
+        },
+    }
+}
+
+fn is_high_contrast(theme: &Theme) -> bool {
+    theme.palette() == HIGH_CONTRAST
+}
+
+/// iced's button, drawn with [`primary`]
+pub fn button<'a, Message>(content: impl Into<Element<'a, Message>>) -> Button<'a, Message> {
+    iced::widget::button(content).style(primary)
+}
+
+/// iced's primary button style, outlined in grey when it's disabled in
+/// [`ThemeMode::HighContrast`]
+pub fn primary(theme: &Theme, status: button::Status) -> button::Style {
+    let style = button::primary(theme, status);
+    match is_high_contrast(theme) && status == button::Status::Disabled {
+        true => outlined(DISABLED, status, style),
+        false => style,
+    }
+}
+
+/// iced's secondary button style. [`ThemeMode::HighContrast`] outlines it
+/// in white instead, as its grey is too close to the text
+pub fn secondary(theme: &Theme, status: button::Status) -> button::Style {
+    let style = button::secondary(theme, status);
+    match (is_high_contrast(theme), status) {
+        (false, _) => style,
+        (true, button::Status::Disabled) => outlined(DISABLED, status, style),
+        (true, _) => outlined(HIGH_CONTRAST.text, status, style),
+    }
+}
+
+/// `color` text in a `color` border on the background, thicker when
+/// hovered
+fn outlined(color: Color, status: button::Status, style: button::Style) -> button::Style {
+    button::Style {
+        background: Some(Background::Color(HIGH_CONTRAST.background)),
+        text_color: color,
+        border: Border {
+            color,
+            width: match status {
+                button::Status::Hovered => 2.0,
+                _ => 1.0,
+            },
+            ..style.border
+        },
+        ..style
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn theme_mode_test() {
+        let custom = Custom::parse(
+            "nord",
+            "name = \"Nord\"\nbackground = \"#2E3440\"\ntext = \"#ECEFF4\"\n",
+        )
+        .unwrap();
+        assert_eq!(
+            custom.palette.background,
+            Color::from_rgb8(0x2E, 0x34, 0x40)
+        );
+        assert_eq!(custom.palette.primary, Palette::DARK.primary);
+        assert_eq!(
+            colors(&ThemeMode::Custom(custom.clone()).resolve()),
+            colors(&Theme::Dark)
+        );
+        assert_eq!(
+            Custom::parse("light", "base = \"light\"").unwrap().palette,
+            Palette::LIGHT
+        );
+        assert!(Custom::parse("x", "text = \"blue\"").is_err());
+        assert!(Custom::parse("x", "foreground = \"#fff\"").is_err());
+
+        let custom = [custom];
+        for mode in ThemeMode::BUILT_IN {
+            assert_eq!(ThemeMode::from_name(mode.name(), &custom), Some(mode));
+        }
+        assert_eq!(
+            ThemeMode::from_name("nord", &custom),
+            Some(ThemeMode::Custom(custom[0].clone()))
+        );
+        // Saved by earlier versions as the name of the iced theme
+        assert_eq!(ThemeMode::from_name("Dark", &custom), Some(ThemeMode::Dark));
+        assert_eq!(ThemeMode::from_name("Tokyo Night", &custom), None);
+
+        assert_eq!(ThemeMode::Light.resolve(), Theme::Light);
+        assert_ne!(colors(&Theme::Light), colors(&Theme::Dark));
+    }
+
+    /// WCAG contrast ratio of two opaque colours, 1 to 21
+    fn contrast(a: Color, b: Color) -> f32 {
+        let luminance = |x: Color| {
+            let [r, g, b] = [x.r, x.g, x.b].map(|x| match x <= 0.04045 {
+                true => x / 12.92,
+                false => ((x + 0.055) / 1.055).powf(2.4),
+            });
+            0.2126 * r + 0.7152 * g + 0.0722 * b
+        };
+        let (a, b) = (luminance(a), luminance(b));
+        (a.max(b) + 0.05) / (a.min(b) + 0.05)
+    }
+
+    #[test]
+    fn contrast_test() {
+        // AA for every theme, AAA for high contrast
+        for (theme, least) in [
+            (Theme::Light, 4.5),
+            (Theme::Dark, 4.5),
+            (ThemeMode::HighContrast.resolve(), 7.0),
+        ] {
+            let background = theme.palette().background;
+            let Colors {
+                error,
+                warning,
+                removed,
+                added,
+                cursor,
+                hit,
+            } = colors(&theme);
+            for color in [error, warning, removed, added, cursor, hit] {
+                assert!(contrast(color, background) >= least, "{color:?} on {theme}");
+            }
+        }
+
+        let theme = ThemeMode::HighContrast.resolve();
+        let background = theme.palette().background;
+        assert!(contrast(theme.palette().text, background) >= 7.0);
+        assert!(contrast(theme.palette().danger, background) >= 7.0);
+        for style in [primary, secondary] {
+            let disabled = style(&theme, button::Status::Disabled);
+            assert!(contrast(disabled.text_color, background) >= 7.0);
+            let active = style(&theme, button::Status::Active);
+            let Some(Background::Color(fill)) = active.background else {
+                panic!("{active:?}");
+            };
+            assert!(contrast(active.text_color, fill) >= 7.0);
+        }
+        assert_eq!(
+            primary(&Theme::Dark, button::Status::Disabled),
+            button::primary(&Theme::Dark, button::Status::Disabled)
+        );
+    }
+}
@@ -0,0 +1,404 @@
+use std::{fmt::Write as _, path::Path};
+
+use crate::{
+    config::{AppSection, Config, Format, Items, LoadOptions, ParseMode},
+    document::{self, Document},
+    encoding,
+    error::Error,
+    signature::Signature,
+    structured,
+};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Severity {
+    /// The config doesn't load as written
+    Error,
+    /// Loads, but likely isn't what was meant
+    Warning,
+}
+
+/// A problem found by [`validate`]
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Issue {
+    pub severity: Severity,
+    /// Section it's in, if it's about one
+    pub section: Option<String>,
+    /// Line it's on, counting from 1, when that's known
+    pub line: Option<usize>,
+    pub message: String,
+}
+impl std::fmt::Display for Issue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.severity {
+            Severity::Error => f.write_str("error")?,
+            Severity::Warning => f.write_str("warning")?,
+        }
+        if let Some(line) = self.line {
+            write!(f, ", line {line}")?;
+        }
+        if let Some(section) = &self.section {
+            write!(f, " in [{section}]")?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+/// Bytes in a hex string like a `sig`
+fn hex_len(hex: &str) -> Result<usize, String> {
+    if let Some(x) = hex.chars().find(|x| !x.is_ascii_hexdigit()) {
+        return Err(format!("isn't hex, it has '{x}'"));
+    }
+    match hex.len() % 2 {
+        0 => Ok(hex.len() / 2),
+        _ => Err("has an odd number of hex digits".into()),
+    }
+}
+
+struct Checker<'a> {
+    document: &'a Document,
+    issues: Vec<Issue>,
+}
+impl Checker<'_> {
+    fn push(&mut self, severity: Severity, section: &str, key: Option<&str>, message: String) {
+        self.issues.push(Issue {
+            severity,
+            section: Some(section.to_string()),
+            line: self.document.line(section, key),
+            message,
+        });
+    }
+
+    fn errors(&self) -> usize {
+        self.issues
+            .iter()
+            .filter(|x| x.severity == Severity::Error)
+            .count()
+    }
+
+    /// Every game listed has a section, and every section is listed
+    fn apps(&mut self) {
+        let sections = self.document.sections();
+        if !sections.contains(&"Apps") {
+            self.issues.push(Issue {
+                severity: Severity::Error,
+                section: None,
+                line: None,
+                message: "There's no [Apps] section".into(),
+            });
+            return;
+        }
+        if self.document.get("Apps", "version").is_none() {
+            self.push(Severity::Error, "Apps", None, "version is missing".into());
+        }
+
+        let apps = self.document.items("Apps");
+        for (key, name) in &apps {
+            if document::is_app_key(key) && !sections.contains(name) {
+                let message = format!("{key} lists {name}, which has no section");
+                self.push(Severity::Error, "Apps", Some(key), message);
+            }
+        }
+
+        let listed = |x: &&str| apps.iter().any(|(k, v)| document::is_app_key(k) && v == x);
+        let special = |x: &str| matches!(x, "Apps" | "Include");
+        for section in sections.iter().filter(|&&x| !special(x) && !listed(&x)) {
+            let message = "Isn't listed in [Apps], so it's never loaded".into();
+            self.push(Severity::Warning, section, None, message);
+        }
+    }
+
+    /// `sig_field` is hex and `wild_field` has a flag per byte of it,
+    /// returning its length in bytes
+    fn signature(
+        &mut self,
+        section: &str,
+        prefix: &str,
+        fields: &[(&str, &str)],
+        (sig_field, wild_field): (&str, &str),
+    ) -> Option<usize> {
+        let get = |field: &str| fields.iter().find(|x| x.0 == field).map(|x| x.1.trim());
+        let sig = get(sig_field)?;
+        let wild = get(wild_field);
+        let (sig_key, wild_key) = (
+            format!("{prefix}{sig_field}"),
+            format!("{prefix}{wild_field}"),
+        );
+
+        let len = match hex_len(sig) {
+            Ok(len) => len,
+            Err(msg) => {
+                self.push(
+                    Severity::Error,
+                    section,
+                    Some(&sig_key),
+                    format!("{sig_key} {msg}"),
+                );
+                return None;
+            }
+        };
+
+        let message = match wild {
+            None => Some(format!("{sig_key} has no {wild_key}")),
+            Some(wild) => match wild.chars().find(|x| !matches!(x, '0' | '1')) {
+                Some(x) => Some(format!("{wild_key} has '{x}', only 0 and 1 are allowed")),
+                None if wild.len() != len => Some(format!(
+                    "{wild_key} has {} flag(s) but {sig_key} has {len} byte(s)",
+                    wild.len()
+                )),
+                None => None,
+            },
+        };
+        if let Some(message) = message {
+            let key = if wild.is_some() { &wild_key } else { &sig_key };
+            self.push(Severity::Error, section, Some(key), message);
+        }
+        Some(len)
+    }
+
+    /// Signatures of the edit set with `prefix`, and that its offsets write
+    /// inside them
+    fn edit_set(&mut self, section: &str, prefix: &str, fields: &[(&str, &str)]) {
+        let get = |field: &str| fields.iter().find(|x| x.0 == field).map(|x| x.1.trim());
+        if get("textfile").is_some() || get("regkey").is_some() {
+            return;
+        }
+
+        let mut length = None;
+        if let Some(pattern) = get("pattern") {
+            let key = format!("{prefix}pattern");
+            match Signature::from_pattern_str(section, pattern) {
+                Ok(sig) => length = Some(sig.pattern.len()),
+                Err(e) => self.push(Severity::Error, section, Some(&key), e.to_string()),
+            }
+        }
+        length = length.or(self.signature(section, prefix, fields, ("sig", "sigwild")));
+        for n in 1.. {
+            let (sig, wild) = (format!("sigalt{n}"), format!("sigaltwild{n}"));
+            if get(&sig).is_none() {
+                break;
+            }
+            self.signature(section, prefix, fields, (&sig, &wild));
+        }
+
+        let Some(length) = length else {
+            return;
+        };
+        let size = get("wordsize").and_then(|x| x.parse::<i64>().ok());
+        let size = size.unwrap_or(2);
+        for field in ["xoffset", "yoffset"] {
+            let key = format!("{prefix}{field}");
+            let offsets = get(field).into_iter().flat_map(|x| x.split(','));
+            for offset in offsets.filter_map(|x| x.trim().parse::<i64>().ok()) {
+                if offset < 0 || offset + size > length as i64 {
+                    let message =
+                        format!("{key} {offset} writes outside the {length} byte signature");
+                    self.push(Severity::Warning, section, Some(&key), message);
+                }
+            }
+        }
+    }
+
+    fn section(&mut self, section: &str) {
+        let items = self.document.items(section);
+        let mut sets: Vec<Vec<(&str, &str)>> = vec![vec![]];
+        for &(key, value) in &items {
+            if structured::GAME_FIELDS.contains(&key)
+                || structured::numbered(key, "value").is_some()
+            {
+                continue;
+            }
+            let (n, field) = structured::numbered(key, "p").unwrap_or((0, key));
+            if sets.len() <= n {
+                sets.resize_with(n + 1, Vec::new);
+            }
+            sets[n].push((field, value));
+        }
+
+        let errors = self.errors();
+        for (i, fields) in sets.iter().enumerate().filter(|x| !x.1.is_empty()) {
+            let prefix = match i {
+                0 => String::new(),
+                i => format!("p{i}"),
+            };
+            self.edit_set(section, &prefix, fields);
+        }
+
+        // Anything else the loader turns down, unless it's been reported already
+        let items: Items = items
+            .into_iter()
+            .map(|(k, v)| (k.into(), v.into()))
+            .collect();
+        if self.errors() == errors
+            && let Err(e) = AppSection::from_items(section, &items)
+        {
+            self.push(Severity::Error, section, None, e.to_string());
+        }
+    }
+}
+
+/// Everything wrong with a config, by line
+pub fn validate(document: &Document) -> Vec<Issue> {
+    let mut checker = Checker {
+        document,
+        issues: vec![],
+    };
+    checker.apps();
+
+    let sections = document.sections();
+    for name in document.apps() {
+        if sections.contains(&name) {
+            checker.section(name);
+        }
+    }
+
+    let mut issues = checker.issues;
+    issues.sort_by_key(|x| x.line);
+    issues
+}
+
+/// [`validate`] the config at `path`, which has to parse first, along with
+/// the lines that can't be read, keys given twice when `options` don't allow
+/// it and files included that don't exist. Lines are only given for INI
+/// files, the others are converted to one to be checked
+pub fn validate_file(path: &Path, options: LoadOptions) -> Result<Vec<Issue>, Error> {
+    let input = encoding::read(path, options.encoding)?;
+    let format = Format::from_path(path);
+    let lenient = LoadOptions {
+        mode: ParseMode::Lenient,
+        ..options
+    };
+    let config = Config::load(&input, format, lenient)?;
+
+    let document = Document::parse_as(&input, format)?;
+    let mut issues = validate(&document);
+    let dir = path.parent().unwrap_or(Path::new(""));
+    for (key, file) in document.items("Include") {
+        if !dir.join(file).exists() {
+            issues.push(Issue {
+                severity: Severity::Error,
+                section: Some("Include".into()),
+                line: document.line("Include", Some(key)),
+                message: format!("{key} includes {file}, which doesn't exist"),
+            });
+        }
+    }
+    if format != Format::Ini {
+        issues.iter_mut().for_each(|x| x.line = None);
+    }
+    for error in config.skipped {
+        let issue = match error {
+            Error::ParseError {
+                line,
+                column,
+                near,
+                section,
+                ..
+            } => Issue {
+                severity: Severity::Error,
+                section,
+                line: Some(line),
+                message: match near {
+                    Some(near) => format!("Can't be read past column {column}, near `{near}`"),
+                    None => format!("Can't be read past column {column}"),
+                },
+            },
+            Error::Duplicate {
+                line, ref section, ..
+            } => Issue {
+                severity: Severity::Error,
+                section: Some(section.clone()),
+                line: Some(line),
+                message: error.to_string(),
+            },
+            e => Issue {
+                severity: Severity::Error,
+                section: None,
+                line: None,
+                message: e.to_string(),
+            },
+        };
+        issues.push(issue);
+    }
+    issues.sort_by_key(|x| x.line);
+    Ok(issues)
+}
+
+/// One line per issue and a count of each kind
+pub fn report(issues: &[Issue]) -> String {
+    let mut out = String::new();
+    for issue in issues {
+        let _ = writeln!(out, "{issue}");
+    }
+
+    let count = |severity| issues.iter().filter(|x| x.severity == severity).count();
+    match (count(Severity::Error), count(Severity::Warning)) {
+        (0, 0) => out.push_str("No problems found\n"),
+        (errors, warnings) => {
+            let _ = writeln!(out, "{errors} error(s), {warnings} warning(s)");
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::DEFAULT;
+
+    #[test]
+    fn validate_test() {
+        let input = "\
+[Apps]
+version=1.03
+a0=Test Game
+a1=Missing Game
+
+[Test Game]
+details=Test
+checkfile=game.exe
+modfile=game.exe
+sig=80020000C701E0010000
+sigwild=000011000
+xoffset=0
+yoffset=6,9
+occur=1
+p1modfile=game.exe
+p1sig=800G
+p1sigwild=00
+p1occur=1
+p2modfile=game.exe
+p2pattern=80 02 ?? ??
+p2xoffset=2
+p2occur=1
+
+[Unused]
+details=Nothing
+";
+        let issues = validate(&Document::parse(input));
+        let lines: Vec<_> = issues.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            lines,
+            [
+                "error, line 4 in [Apps]: a1 lists Missing Game, which has no section",
+                "error, line 11 in [Test Game]: sigwild has 9 flag(s) but sig has 10 byte(s)",
+                "warning, line 13 in [Test Game]: yoffset 9 writes outside the 10 byte signature",
+                "error, line 16 in [Test Game]: p1sig isn't hex, it has 'G'",
+                "warning, line 24 in [Unused]: Isn't listed in [Apps], so it's never loaded",
+            ]
+        );
+        assert!(report(&issues).ends_with("3 error(s), 2 warning(s)\n"));
+
+        // Errors the checks above don't cover come from the loader
+        let issues = validate(&Document::parse(
+            "[Apps]\nversion=1\na0=Game\n\n[Game]\ndetails=x\nmodfile=game.exe\n",
+        ));
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].line, Some(5));
+        assert!(issues[0].message.contains("checkfile"));
+
+        // Only warnings for the 1 byte writes of setxlen, which isn't supported
+        let issues = validate(&Document::parse(DEFAULT));
+        assert!(issues.iter().all(|x| x.severity == Severity::Warning));
+        assert_eq!(report(&[]), "No problems found\n");
+    }
+}
@@ -0,0 +1,376 @@
+use std::str::FromStr;
+
+use crate::error::PatchErrorKind;
+
+/// Number of bytes an integer value occupies in the patched file
+#[derive(Debug, Default, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub enum WordSize {
+    Byte,
+    #[default]
+    Word,
+    DWord,
+    QWord,
+}
+impl WordSize {
+    pub fn len(self) -> usize {
+        match self {
+            Self::Byte => 1,
+            Self::Word => 2,
+            Self::DWord => 4,
+            Self::QWord => 8,
+        }
+    }
+
+    /// Largest value that fits
+    pub fn max(self) -> u64 {
+        u64::MAX >> (64 - 8 * self.len())
+    }
+
+    /// Read a value starting at `offset`
+    pub fn read(self, data: &[u8], offset: usize, endian: Endian) -> u64 {
+        let src = &data[offset..offset + self.len()];
+        let mut bytes = [0; 8];
+        match endian {
+            Endian::Little => {
+                bytes[..self.len()].copy_from_slice(src);
+                u64::from_le_bytes(bytes)
+            }
+            Endian::Big => {
+                bytes[8 - self.len()..].copy_from_slice(src);
+                u64::from_be_bytes(bytes)
+            }
+        }
+    }
+
+    /// Write `value` starting at `offset`, truncated to fit
+    pub fn write(self, data: &mut [u8], offset: usize, value: u64, endian: Endian) {
+        let dst = &mut data[offset..offset + self.len()];
+        match endian {
+            Endian::Little => dst.copy_from_slice(&value.to_le_bytes()[..self.len()]),
+            Endian::Big => dst.copy_from_slice(&value.to_be_bytes()[8 - self.len()..]),
+        }
+    }
+}
+impl FromStr for WordSize {
+    type Err = ParseWordSizeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "1" => Ok(Self::Byte),
+            "2" => Ok(Self::Word),
+            "4" => Ok(Self::DWord),
+            "8" => Ok(Self::QWord),
+            x => Err(ParseWordSizeError(x.to_string())),
+        }
+    }
+}
+
+/// How a number is stored in the patched file
+#[derive(Debug, Default, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub enum ValueType {
+    /// Unsigned integer of the configured [`WordSize`]
+    #[default]
+    Int,
+    /// IEEE-754 single precision float
+    F32,
+    /// IEEE-754 double precision float
+    F64,
+}
+impl ValueType {
+    /// Bytes occupied by a value, `int_size` only applies to [`Self::Int`]
+    pub fn len(self, int_size: WordSize) -> usize {
+        match self {
+            Self::Int => int_size.len(),
+            Self::F32 => 4,
+            Self::F64 => 8,
+        }
+    }
+
+    /// Fail if `value` would be truncated when written
+    pub fn check(self, int_size: WordSize, value: f64) -> Result<(), PatchErrorKind> {
+        let fits = match self {
+            Self::Int => value >= 0.0 && value <= int_size.max() as f64,
+            Self::F32 => (value as f32).is_finite(),
+            Self::F64 => value.is_finite(),
+        };
+        match fits {
+            true => Ok(()),
+            false => Err(PatchErrorKind::ValueOutOfRange {
+                value,
+                len: self.len(int_size),
+            }),
+        }
+    }
+
+    pub fn read(self, int_size: WordSize, data: &[u8], offset: usize, endian: Endian) -> f64 {
+        match self {
+            Self::Int => int_size.read(data, offset, endian) as f64,
+            Self::F32 => f32::from_bits(WordSize::DWord.read(data, offset, endian) as u32).into(),
+            Self::F64 => f64::from_bits(WordSize::QWord.read(data, offset, endian)),
+        }
+    }
+
+    /// Write `value`, truncating it for [`Self::Int`]
+    pub fn write(
+        self,
+        int_size: WordSize,
+        data: &mut [u8],
+        offset: usize,
+        value: f64,
+        endian: Endian,
+    ) {
+        match self {
+            Self::Int => int_size.write(data, offset, value as u64, endian),
+            Self::F32 => {
+                let bits = (value as f32).to_bits();
+                WordSize::DWord.write(data, offset, bits.into(), endian);
+            }
+            Self::F64 => WordSize::QWord.write(data, offset, value.to_bits(), endian),
+        }
+    }
+}
+impl FromStr for ValueType {
+    type Err = ParseValueTypeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "int" => Ok(Self::Int),
+            "f32" | "float" => Ok(Self::F32),
+            "f64" | "double" => Ok(Self::F64),
+            x => Err(ParseValueTypeError(x.to_string())),
+        }
+    }
+}
+
+/// Value derived from the chosen resolution, written instead of the width
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub enum Aspect {
+    /// `width / height`
+    Ratio,
+    /// `width / height` relative to 4:3, i.e. 1.0 at 4:3 and 1.333 at 16:9
+    Relative43,
+}
+impl Aspect {
+    pub fn value(self, width: u16, height: u16) -> f64 {
+        let ratio = f64::from(width) / f64::from(height);
+        match self {
+            Self::Ratio => ratio,
+            Self::Relative43 => ratio / (4.0 / 3.0),
+        }
+    }
+}
+
+/// `valuetype` field: a [`ValueType`], optionally prefixed with `aspect_` or
+/// `aspect43_` to write a derived [`Aspect`] float
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub struct ValueSpec(pub Option<Aspect>, pub ValueType);
+impl FromStr for ValueSpec {
+    type Err = ParseValueTypeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().to_ascii_lowercase();
+        let (aspect, valuetype) = match s.split_once('_') {
+            Some(("aspect", x)) => (Some(Aspect::Ratio), x),
+            Some(("aspect43", x)) => (Some(Aspect::Relative43), x),
+            _ => (None, s.as_str()),
+        };
+
+        let spec = Self(
+            aspect,
+            valuetype
+                .parse()
+                .map_err(|_| ParseValueTypeError(s.clone()))?,
+        );
+        match spec {
+            Self(Some(_), ValueType::Int) => Err(ParseValueTypeError(s)),
+            spec => Ok(spec),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ParseValueTypeError(String);
+impl std::fmt::Display for ParseValueTypeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Invalid value type '{}', expected int, f32, f64, aspect_f32, aspect_f64, aspect43_f32 or aspect43_f64",
+            self.0
+        )
+    }
+}
+impl std::error::Error for ParseValueTypeError {}
+
+/// Byte order of values in the patched file
+#[derive(Debug, Default, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub enum Endian {
+    #[default]
+    Little,
+    Big,
+}
+impl FromStr for Endian {
+    type Err = ParseEndianError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "le" | "little" => Ok(Self::Little),
+            "be" | "big" => Ok(Self::Big),
+            x => Err(ParseEndianError(x.to_string())),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ParseEndianError(String);
+impl std::fmt::Display for ParseEndianError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid byte order '{}', expected le or be", self.0)
+    }
+}
+impl std::error::Error for ParseEndianError {}
+
+/// Position in a file, either absolute or relative to its length
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum FilePosition {
+    /// `4096` or `0x1000`
+    Offset(usize),
+    /// `50%`
+    Percent(f64),
+}
+impl FilePosition {
+    /// Resolve against a file of `len` bytes, clamped to the end of the file
+    pub fn resolve(self, len: usize) -> usize {
+        match self {
+            Self::Offset(x) => x.min(len),
+            Self::Percent(x) => ((len as f64 * x / 100.0) as usize).min(len),
+        }
+    }
+}
+impl FromStr for FilePosition {
+    type Err = ParseFilePositionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || ParseFilePositionError(s.to_string());
+        let s = s.trim();
+
+        if let Some(percent) = s.strip_suffix('%') {
+            let percent: f64 = percent.trim().parse().map_err(|_| error())?;
+            return match percent {
+                0.0..=100.0 => Ok(Self::Percent(percent)),
+                _ => Err(error()),
+            };
+        }
+
+        match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+            Some(hex) => usize::from_str_radix(hex, 16),
+            None => s.parse(),
+        }
+        .map(Self::Offset)
+        .map_err(|_| error())
+    }
+}
+
+#[derive(Debug)]
+pub struct ParseFilePositionError(String);
+impl std::fmt::Display for ParseFilePositionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Invalid file position '{}', expected an offset like 4096 or 0x1000, or a percentage like 50%",
+            self.0
+        )
+    }
+}
+impl std::error::Error for ParseFilePositionError {}
+
+#[derive(Debug)]
+pub struct ParseWordSizeError(String);
+impl std::fmt::Display for ParseWordSizeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid size '{}', expected 1, 2, 4 or 8", self.0)
+    }
+}
+impl std::error::Error for ParseWordSizeError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_write() {
+        let mut data = [0x11, 0x22, 0x33, 0x44, 0x55];
+
+        assert_eq!(WordSize::Byte.read(&data, 1, Endian::Little), 0x22);
+        assert_eq!(WordSize::Word.read(&data, 1, Endian::Little), 0x3322);
+        assert_eq!(WordSize::DWord.read(&data, 1, Endian::Little), 0x55443322);
+        assert_eq!(WordSize::Word.read(&data, 1, Endian::Big), 0x2233);
+        assert_eq!(WordSize::DWord.read(&data, 1, Endian::Big), 0x22334455);
+
+        WordSize::Word.write(&mut data, 0, 0xABCDEF, Endian::Little);
+        assert_eq!(data, [0xEF, 0xCD, 0x33, 0x44, 0x55]);
+
+        WordSize::DWord.write(&mut data, 1, 0x780, Endian::Big);
+        assert_eq!(data, [0xEF, 0x00, 0x00, 0x07, 0x80]);
+
+        assert_eq!(WordSize::Byte.max(), 0xFF);
+        assert_eq!(WordSize::QWord.max(), u64::MAX);
+    }
+
+    #[test]
+    fn value_types() {
+        let mut data = [0; 8];
+
+        ValueType::F32.write(WordSize::Word, &mut data, 0, 90.0, Endian::Little);
+        assert_eq!(data[..4], 90.0f32.to_le_bytes());
+        assert_eq!(
+            ValueType::F32.read(WordSize::Word, &data, 0, Endian::Little),
+            90.0
+        );
+
+        ValueType::F64.write(WordSize::Word, &mut data, 0, 1.5, Endian::Big);
+        assert_eq!(data, 1.5f64.to_be_bytes());
+
+        ValueType::Int.write(WordSize::Word, &mut data, 0, 1920.0, Endian::Little);
+        assert_eq!(data[..2], [0x80, 0x07]);
+        assert_eq!(
+            ValueType::Int.read(WordSize::Word, &data, 0, Endian::Little),
+            1920.0
+        );
+
+        assert_eq!(ValueType::F64.len(WordSize::Word), 8);
+        assert_eq!(ValueType::Int.len(WordSize::DWord), 4);
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!("4".parse::<WordSize>().unwrap(), WordSize::DWord);
+        assert!("3".parse::<WordSize>().is_err());
+        assert_eq!("BE".parse::<Endian>().unwrap(), Endian::Big);
+        assert!("middle".parse::<Endian>().is_err());
+        assert_eq!("F32".parse::<ValueType>().unwrap(), ValueType::F32);
+        assert!("f16".parse::<ValueType>().is_err());
+        assert_eq!(
+            "aspect43_f32".parse::<ValueSpec>().unwrap(),
+            ValueSpec(Some(Aspect::Relative43), ValueType::F32)
+        );
+        assert_eq!(
+            "f64".parse::<ValueSpec>().unwrap(),
+            ValueSpec(None, ValueType::F64)
+        );
+        assert!("aspect_int".parse::<ValueSpec>().is_err());
+        assert!("aspect_f16".parse::<ValueSpec>().is_err());
+
+        assert_eq!(
+            "0x1000".parse::<FilePosition>().unwrap(),
+            FilePosition::Offset(4096)
+        );
+        assert_eq!(
+            " 25 %".parse::<FilePosition>().unwrap(),
+            FilePosition::Percent(25.0)
+        );
+        assert_eq!(FilePosition::Percent(25.0).resolve(1000), 250);
+        assert_eq!(FilePosition::Offset(4096).resolve(1000), 1000);
+        assert!("150%".parse::<FilePosition>().is_err());
+        assert!("-4".parse::<FilePosition>().is_err());
+    }
+}
@@ -0,0 +1,81 @@
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use futures::{SinkExt, Stream, StreamExt, channel::mpsc};
+use notify::{RecursiveMode, Watcher};
+
+/// Time to wait for more changes after one, editors often save in several writes
+const DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// Yields `path` each time the file is written or replaced
+///
+/// Its folder is watched rather than the file itself, as many editors save by
+/// replacing the file, which would end a watch on the old one
+pub fn changes(path: PathBuf) -> impl Stream<Item = PathBuf> {
+    iced::stream::channel(1, move |mut output| async move {
+        let (sender, mut receiver) = mpsc::unbounded();
+        let name = path.file_name().map(ToOwned::to_owned);
+        let watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+            let Ok(event) = event else {
+                return;
+            };
+            let changed = event.kind.is_create() || event.kind.is_modify();
+            if changed && event.paths.iter().any(|x| x.file_name() == name.as_deref()) {
+                let _ = sender.unbounded_send(());
+            }
+        });
+
+        let dir = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+        let Ok(mut watcher) = watcher else {
+            return;
+        };
+        if watcher.watch(dir, RecursiveMode::NonRecursive).is_err() {
+            return;
+        }
+
+        while receiver.next().await.is_some() {
+            tokio::time::sleep(DEBOUNCE).await;
+            while let Ok(Some(())) = receiver.try_next() {}
+
+            if output.send(path.clone()).await.is_err() {
+                break;
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+
+    #[tokio::test]
+    async fn changes_test() {
+        let tmp = TempDir::new("watch");
+        let path = tmp.join("patches.ini");
+        std::fs::write(&path, "[Apps]\nversion=1.03\n").unwrap();
+
+        let mut changes = Box::pin(changes(path.clone()));
+        let mut next = tokio::spawn(async move { changes.next().await });
+
+        // There's no telling when the watcher has started, so keep writing
+        // to a neighbour and the file until it sees a change
+        let changed = tokio::time::timeout(Duration::from_secs(10), async {
+            loop {
+                std::fs::write(tmp.join("other.ini"), "").unwrap();
+                std::fs::write(&path, "[Apps]\nversion=1.03\na0=Test\n").unwrap();
+                tokio::select! {
+                    changed = &mut next => return changed,
+                    () = tokio::time::sleep(Duration::from_millis(100)) => {}
+                }
+            }
+        })
+        .await;
+        assert_eq!(changed.unwrap().unwrap(), Some(path));
+    }
+}
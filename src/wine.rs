@@ -0,0 +1,183 @@
+use std::path::{Path, PathBuf};
+
+use crate::steam;
+
+/// A Wine prefix, such as the one Proton makes for each Steam game
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Prefix {
+    /// Folder holding `drive_c` and `dosdevices`
+    pub root: PathBuf,
+}
+impl Prefix {
+    pub fn drive_c(&self) -> PathBuf {
+        self.root.join("drive_c")
+    }
+
+    /// The prefix `path` is inside of, if any
+    pub fn containing(path: &Path) -> Option<Self> {
+        path.ancestors()
+            .find(|x| x.join("drive_c").is_dir() && x.join("dosdevices").is_dir())
+            .map(|x| Self { root: x.into() })
+    }
+
+    /// Where an absolute Windows path like `C:\Games\Example` is inside the prefix
+    pub fn to_native(&self, path: &str) -> Option<PathBuf> {
+        let (drive, rest) = split_drive(path)?;
+        let drive = match drive {
+            'c' => self.drive_c(),
+            x => self.root.join("dosdevices").join(format!("{x}:")),
+        };
+
+        Some(
+            rest.split(['\\', '/'])
+                .filter(|x| !x.is_empty())
+                .fold(drive, |path, x| path.join(x)),
+        )
+    }
+}
+
+/// Lowercase drive letter and the rest of an absolute Windows path
+fn split_drive(path: &str) -> Option<(char, &str)> {
+    let mut chars = path.chars();
+    let (drive, ':') = (chars.next()?, chars.next()?) else {
+        return None;
+    };
+    let rest = chars.as_str();
+
+    (drive.is_ascii_alphabetic() && (rest.is_empty() || rest.starts_with(['\\', '/'])))
+        .then(|| (drive.to_ascii_lowercase(), rest))
+}
+
+/// A relative path from a config, which are written for Windows, with the
+/// separators of this platform
+pub fn config_path(path: &str) -> String {
+    match cfg!(windows) {
+        true => path.to_string(),
+        false => path.replace('\\', "/"),
+    }
+}
+
+/// Whether `path`, relative to a folder, stays inside it: it can't be absolute,
+/// start with a drive like `C:`, or go up with `..`
+pub fn is_inside(path: &str) -> bool {
+    let mut chars = path.chars();
+    let drive = chars.next().is_some_and(|x| x.is_ascii_alphabetic()) && chars.next() == Some(':');
+    !path.is_empty()
+        && !drive
+        && !path.starts_with(['\\', '/'])
+        && !path.split(['\\', '/']).any(|x| x == "..")
+}
+
+/// `path` from a config inside `dir`
+///
+/// Windows doesn't care about case, so configs often don't match the files of
+/// a Linux install, e.g. `SWKOTOR.EXE` for `swkotor.exe`. When the exact path
+/// doesn't exist each name is matched ignoring case instead
+pub fn resolve(dir: &Path, path: &str) -> PathBuf {
+    let exact = dir.join(config_path(path));
+    if exact.exists() {
+        return exact;
+    }
+
+    let mut resolved = dir.to_path_buf();
+    for name in path.split(['\\', '/']).filter(|x| !x.is_empty()) {
+        let next = resolved.join(name);
+        if next.exists() {
+            resolved = next;
+            continue;
+        }
+
+        let entries = resolved.read_dir().into_iter().flatten().flatten();
+        resolved = entries
+            .map(|x| x.path())
+            .find(|x| {
+                x.file_name()
+                    .and_then(|x| x.to_str())
+                    .is_some_and(|x| x.eq_ignore_ascii_case(name))
+            })
+            .unwrap_or(next);
+    }
+    resolved
+}
+
+/// Every Wine prefix found: `WINEPREFIX`, `~/.wine` and each Proton prefix
+pub fn prefixes() -> Vec<Prefix> {
+    if !cfg!(target_os = "linux") {
+        return vec![];
+    }
+
+    let wine = std::env::var_os("WINEPREFIX")
+        .map(PathBuf::from)
+        .into_iter()
+        .chain(dirs::home_dir().map(|x| x.join(".wine")));
+    let proton = steam::compatdata_dirs()
+        .into_iter()
+        .filter_map(|x| x.read_dir().ok())
+        .flatten()
+        .flatten()
+        .map(|x| x.path().join("pfx"));
+
+    let mut prefixes: Vec<Prefix> = Vec::new();
+    for prefix in wine.chain(proton).filter_map(|x| Prefix::containing(&x)) {
+        if !prefixes.contains(&prefix) {
+            prefixes.push(prefix);
+        }
+    }
+    prefixes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+
+    #[test]
+    fn prefix_test() {
+        let tmp = TempDir::new("wine");
+        let root = tmp.join("compatdata/2420/pfx");
+        std::fs::create_dir_all(root.join("drive_c/Games")).unwrap();
+        std::fs::create_dir_all(root.join("dosdevices")).unwrap();
+
+        let prefix = Prefix::containing(&root.join("drive_c/Games")).unwrap();
+        assert_eq!(prefix.root, root);
+        assert_eq!(Prefix::containing(&tmp), None);
+
+        assert_eq!(
+            prefix.to_native(r"C:\Games\Example\game.exe"),
+            Some(root.join("drive_c/Games/Example/game.exe"))
+        );
+        assert_eq!(
+            prefix.to_native("d:/Steam"),
+            Some(root.join("dosdevices/d:/Steam"))
+        );
+        assert_eq!(prefix.to_native(r"Games\Example"), None);
+        assert_eq!(prefix.to_native("C:Example"), None);
+
+        assert!(is_inside(r"Bin\DXWrap.dll"));
+        assert!(is_inside("..game.exe"));
+        assert!(!is_inside(r"..\..\Windows\notepad.exe"));
+        assert!(!is_inside("Bin/../../game.exe"));
+        assert!(!is_inside("/etc/passwd"));
+        assert!(!is_inside(r"\\server\share\game.exe"));
+        assert!(!is_inside(r"C:\Windows\notepad.exe"));
+        assert!(!is_inside("c:game.exe"));
+        assert!(!is_inside(""));
+
+        let game_dir = root.join("drive_c/Games");
+        std::fs::create_dir_all(game_dir.join("Bin")).unwrap();
+        std::fs::write(game_dir.join("swkotor.exe"), b"").unwrap();
+        std::fs::write(game_dir.join("Bin/DXWrap.dll"), b"").unwrap();
+        assert_eq!(
+            resolve(&game_dir, "SWKOTOR.EXE"),
+            game_dir.join("swkotor.exe")
+        );
+        assert_eq!(
+            resolve(&game_dir, r"bin\dxwrap.dll"),
+            game_dir.join("Bin/DXWrap.dll")
+        );
+        assert_eq!(
+            resolve(&game_dir, r"bin\Missing.dll"),
+            game_dir.join("Bin/Missing.dll")
+        );
+    }
+}